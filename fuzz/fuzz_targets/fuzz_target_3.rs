@@ -0,0 +1,35 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+
+use bernardo::text::buffer_state_fuzz::{history_fuzz_call, HistoryFuzzOp};
+use libfuzzer_sys::arbitrary::Arbitrary;
+use libfuzzer_sys::arbitrary::Unstructured;
+use libfuzzer_sys::fuzz_target;
+use log::error;
+
+// compares operation-log history with one keeping full snapshots of every state
+fuzz_target!(|data: &[u8]| {
+    let mut unstructured = Unstructured::new(data);
+
+    fn generate_data(unstructured: &mut Unstructured) -> Option<(String, Vec<HistoryFuzzOp>)> {
+        let text = String::arbitrary(unstructured).ok()?;
+
+        let mut ops = Vec::new();
+        while let Ok(op) = HistoryFuzzOp::arbitrary(unstructured) {
+            ops.push(op);
+
+            if unstructured.is_empty() {
+                break;
+            }
+        }
+
+        Some((text, ops))
+    }
+
+    if let Some((text, ops)) = generate_data(&mut unstructured) {
+        history_fuzz_call(text, ops);
+    } else {
+        error!("not enough data to generate text and history operations, skipping fuzzing");
+    }
+});
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::input_event::InputEvent;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::w7e::navcomp_provider::{NavCompWorkspaceEdit, StupidSubstituteMessage};
+
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(3);
+
+fn get_full_setup() -> FullSetup {
+    FullSetup::new("./test_envs/apply_edit_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        .build()
+}
+
+fn rename_counter_edit(url: String) -> NavCompWorkspaceEdit {
+    NavCompWorkspaceEdit {
+        file_edits: vec![(
+            url,
+            vec![
+                StupidSubstituteMessage {
+                    substitute: "total".to_string(),
+                    stupid_range: (StupidCursor::new(12, 1), StupidCursor::new(19, 1)),
+                },
+                StupidSubstituteMessage {
+                    substitute: "total".to_string(),
+                    stupid_range: (StupidCursor::new(4, 2), StupidCursor::new(11, 2)),
+                },
+            ],
+        )],
+    }
+}
+
+#[test]
+fn apply_edit_test_1_requested_edit_is_applied_to_open_buffer() {
+    let mut full_setup = get_full_setup();
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    let main_url = format!("file://{}/src/main.rs", full_setup.fsf().root_path_buf().to_string_lossy());
+    let answer = full_setup
+        .navcomp_pilot()
+        .unwrap()
+        .request_apply_edit(rename_counter_edit(main_url))
+        .unwrap();
+
+    // nothing else happens, so I need a frame for request to be picked up
+    assert!(full_setup.send_input(InputEvent::Tick));
+
+    assert!(full_setup.wait_for(|f| {
+        let lines: Vec<String> = f
+            .get_first_editor()
+            .unwrap()
+            .get_all_visible_lines()
+            .map(|l| l.contents.text)
+            .collect();
+        lines.iter().any(|line| line.starts_with("    let mut total = 0;")) && lines.iter().any(|line| line.starts_with("    total += 1;"))
+    }));
+
+    assert_eq!(answer.recv_timeout(ANSWER_TIMEOUT), Ok(true));
+}
+
+#[test]
+fn apply_edit_test_2_edit_outside_workspace_is_refused() {
+    let mut full_setup = get_full_setup();
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    let answer = full_setup
+        .navcomp_pilot()
+        .unwrap()
+        .request_apply_edit(rename_counter_edit("file:///somewhere/else/main.rs".to_string()))
+        .unwrap();
+
+    assert!(full_setup.send_input(InputEvent::Tick));
+
+    assert_eq!(answer.recv_timeout(ANSWER_TIMEOUT), Ok(false));
+    assert!(full_setup.wait_for(|f| f
+        .get_first_editor()
+        .unwrap()
+        .get_all_visible_lines()
+        .any(|line| line.contents.text.starts_with("    let mut counter = 0;"))));
+}
@@ -0,0 +1 @@
+mod apply_edit_test_1;
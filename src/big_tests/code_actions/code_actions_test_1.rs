@@ -0,0 +1,115 @@
+use crate::big_tests::test_utils::{open_context_and_select, MOCK_NAVCOMP_SHARED_ENV};
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockSymbolMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{
+    NavCompCodeAction, NavCompCommand, NavCompSymbol, NavCompWorkspaceEdit, StupidSubstituteMessage, SymbolType,
+};
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/code_actions_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        // .with_frame_based_wait()
+        .build();
+
+    {
+        let mut symbols = full_setup.navcomp_pilot().unwrap().symbols().unwrap();
+        let mockfs = full_setup.fsf();
+
+        let main_url = format!("file://{}/src/main.rs", mockfs.root_path_buf().to_string_lossy());
+
+        // "match direction {}" line
+        symbols.push(MockSymbolMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            symbol: NavCompSymbol {
+                symbol_type: SymbolType::Variable,
+                stupid_range: (StupidCursor::new(4, 10), StupidCursor::new(22, 10)),
+            },
+            code_actions: vec![
+                NavCompCodeAction {
+                    title: "Fill match arms".to_string(),
+                    kind: Some("quickfix".to_string()),
+                    edit: Some(NavCompWorkspaceEdit {
+                        file_edits: vec![(
+                            main_url,
+                            vec![StupidSubstituteMessage {
+                                substitute: "{\n        Direction::Left => todo!(),\n        Direction::Right => todo!(),\n    }"
+                                    .to_string(),
+                                stupid_range: (StupidCursor::new(20, 10), StupidCursor::new(22, 10)),
+                            }],
+                        )],
+                    }),
+                    command: None,
+                },
+                NavCompCodeAction {
+                    title: "Run some command".to_string(),
+                    kind: None,
+                    edit: None,
+                    command: Some(NavCompCommand {
+                        title: "Run some command".to_string(),
+                        command: "mock.someCommand".to_string(),
+                        arguments: vec![],
+                    }),
+                },
+            ],
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn go_to_match(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    for _ in 0..10 {
+        assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    }
+    assert!(full_setup.send_key(Keycode::ArrowRight.to_key().with_ctrl()));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_visible_cursor_lines()
+            .find(|line| line.contents.text.trim() == "match direction {}⏎")
+            .is_some()
+    }));
+}
+
+#[test]
+fn code_actions_test_1_apply_edit() {
+    let mut full_setup = get_full_setup();
+    go_to_match(&mut full_setup);
+
+    open_context_and_select(&mut full_setup, "Fill match arms");
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_all_visible_lines()
+            .find(|line| line.contents.text.contains("Direction::Right => todo!(),"))
+            .is_some()
+    }));
+}
+
+#[test]
+fn code_actions_test_2_execute_command() {
+    let mut full_setup = get_full_setup();
+    go_to_match(&mut full_setup);
+
+    open_context_and_select(&mut full_setup, "Run some command");
+
+    assert!(full_setup.navcomp_pilot().unwrap().wait_for_command("mock.someCommand"));
+
+    // command-only action does not touch the buffer
+    assert!(full_setup
+        .get_first_editor()
+        .unwrap()
+        .get_all_visible_lines()
+        .find(|line| line.contents.text.contains("match direction {}"))
+        .is_some());
+}
@@ -0,0 +1,120 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockSymbolMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{DocumentHighlightKind, NavCompDocumentHighlight, NavCompSymbol, SymbolType};
+
+fn occurrence(line: u32, begin: u32, kind: DocumentHighlightKind) -> NavCompDocumentHighlight {
+    NavCompDocumentHighlight {
+        stupid_range: (StupidCursor::new(begin, line), StupidCursor::new(begin + 7, line)),
+        kind,
+    }
+}
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/document_highlight_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        .build();
+
+    {
+        let mut symbols = full_setup.navcomp_pilot().unwrap().symbols().unwrap();
+        let mockfs = full_setup.fsf();
+
+        symbols.push(MockSymbolMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            symbol: NavCompSymbol {
+                symbol_type: SymbolType::Variable,
+                stupid_range: (StupidCursor::new(12, 1), StupidCursor::new(19, 1)),
+            },
+            document_highlights: vec![
+                occurrence(1, 12, DocumentHighlightKind::Write),
+                occurrence(2, 4, DocumentHighlightKind::Write),
+                occurrence(3, 19, DocumentHighlightKind::Read),
+            ],
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn cursor_line(full_setup: &FullSetup) -> Option<String> {
+    full_setup
+        .get_first_editor()
+        .unwrap()
+        .get_visible_cursor_lines_with_coded_cursors()
+        .next()
+        .map(|line| line.contents.text)
+}
+
+fn go_to_counter(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    for _ in 0..12 {
+        assert!(full_setup.send_key(Keycode::ArrowRight.to_key()));
+    }
+
+    assert!(full_setup.wait_for(|f| cursor_line(f) == Some("    let mut #counter = 0;⏎".to_string())));
+}
+
+fn occurrences_of_background(full_setup: &FullSetup, kind: DocumentHighlightKind) -> usize {
+    let background = full_setup.get_theme().occurrence_background(kind);
+
+    full_setup
+        .get_first_editor()
+        .unwrap()
+        .consistent_items_iter()
+        .filter(|item| item.text_style.background == background && item.text.ends_with("ounter"))
+        .count()
+}
+
+#[test]
+fn document_highlight_test_1_occurrences_are_highlighted() {
+    let mut full_setup = get_full_setup();
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert_eq!(occurrences_of_background(&full_setup, DocumentHighlightKind::Write), 0);
+
+    go_to_counter(&mut full_setup);
+
+    // the one under cursor has its first letter covered by cursor
+    assert!(
+        full_setup.wait_for(|f| occurrences_of_background(f, DocumentHighlightKind::Write) == 2
+            && occurrences_of_background(f, DocumentHighlightKind::Read) == 1)
+    );
+
+    // leaving the symbol clears highlights
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    assert!(
+        full_setup.wait_for(|f| occurrences_of_background(f, DocumentHighlightKind::Write) == 0
+            && occurrences_of_background(f, DocumentHighlightKind::Read) == 0)
+    );
+}
+
+#[test]
+fn document_highlight_test_2_next_and_prev_usage() {
+    let mut full_setup = get_full_setup();
+    go_to_counter(&mut full_setup);
+    assert!(full_setup.wait_for(|f| occurrences_of_background(f, DocumentHighlightKind::Read) == 1));
+
+    let next_usage = full_setup.config().keyboard_config.editor.next_usage;
+    let prev_usage = full_setup.config().keyboard_config.editor.prev_usage;
+
+    assert!(full_setup.send_key(next_usage));
+    assert!(full_setup.wait_for(|f| cursor_line(f) == Some("    #counter += 1;⏎".to_string())));
+
+    assert!(full_setup.send_key(next_usage));
+    assert!(full_setup.wait_for(|f| cursor_line(f) == Some("    println!(\"{}\", #counter);⏎".to_string())));
+
+    // wraps around
+    assert!(full_setup.send_key(next_usage));
+    assert!(full_setup.wait_for(|f| cursor_line(f) == Some("    let mut #counter = 0;⏎".to_string())));
+
+    assert!(full_setup.send_key(prev_usage));
+    assert!(full_setup.wait_for(|f| cursor_line(f) == Some("    println!(\"{}\", #counter);⏎".to_string())));
+}
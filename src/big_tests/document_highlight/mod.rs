@@ -0,0 +1 @@
+mod document_highlight_test_1;
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::config::config::Config;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockFileMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::tsw::lang_id::LangId;
+use crate::w7e::navcomp_provider::StupidSubstituteMessage;
+use crate::widgets::editor_view::editor_view::EditorView;
+
+const FORMATTED: &str = "fn main() {\n    println!(\"hello\");\n}\n";
+
+fn get_full_setup(format_on_save: Vec<LangId>, formatting_delay: Option<Duration>) -> FullSetup {
+    let mut config = Config::default();
+    config.global.format_on_save = format_on_save;
+
+    let mut full_setup: FullSetup = FullSetup::new("./test_envs/format_on_save_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        .with_config(config)
+        .build();
+
+    {
+        let mut files = full_setup.navcomp_pilot().unwrap().files().unwrap();
+        let mockfs = full_setup.fsf();
+
+        files.push(MockFileMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            formatting: Some(vec![StupidSubstituteMessage {
+                substitute: "    ".to_string(),
+                stupid_range: (StupidCursor::new(0, 1), StupidCursor::new(0, 1)),
+            }]),
+            formatting_delay,
+            ..Default::default()
+        });
+    }
+
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    full_setup
+}
+
+fn save_and_read_back(full_setup: &mut FullSetup) -> String {
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.editor.save));
+    full_setup.wait_frame();
+
+    spath!(full_setup.fsf(), "src", "main.rs")
+        .unwrap()
+        .read_entire_file_to_string()
+        .unwrap()
+}
+
+#[test]
+fn format_on_save_test_1_formats_before_save() {
+    let mut full_setup = get_full_setup(vec![LangId::RUST], None);
+
+    assert_eq!(save_and_read_back(&mut full_setup), FORMATTED);
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_all_visible_lines()
+            .any(|line| line.contents.text.starts_with("    println!"))
+    }));
+}
+
+#[test]
+fn format_on_save_test_2_other_languages_are_not_formatted() {
+    let mut full_setup = get_full_setup(vec![LangId::GO], None);
+
+    assert_ne!(save_and_read_back(&mut full_setup), FORMATTED);
+}
+
+#[test]
+fn format_on_save_test_3_stuck_formatter_does_not_block_save() {
+    let mut full_setup = get_full_setup(vec![LangId::RUST], Some(EditorView::FORMAT_ON_SAVE_TIMEOUT * 5));
+
+    assert!(full_setup.type_in("x"));
+    assert!(full_setup.wait_for(|f| f
+        .get_first_editor()
+        .unwrap()
+        .get_visible_cursor_lines()
+        .any(|line| line.contents.text.starts_with("xfn"))));
+
+    let saved = save_and_read_back(&mut full_setup);
+    assert!(saved.starts_with("xfn main() {\nprintln!"));
+}
@@ -0,0 +1 @@
+mod format_on_save_test_1;
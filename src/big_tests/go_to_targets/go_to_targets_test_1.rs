@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::{MockFileMatcher, MockSymbolMatcher};
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{NavCompSemanticToken, NavCompSymbol, StupidSymbolUsage, SymbolType};
+
+// "some_function" in the call in main.rs
+const CALL: (StupidCursor, StupidCursor) = (
+    StupidCursor {
+        char_idx_0b: 4,
+        line_0b: 3,
+    },
+    StupidCursor {
+        char_idx_0b: 17,
+        line_0b: 3,
+    },
+);
+
+/*
+Targets are given as line numbers in some_other_file.rs. Semantic token makes the call a "function",
+so the go-to options are in the context menu even without tree-sitter.
+ */
+fn get_full_setup(target_lines: &[u32]) -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/go_to_targets_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        .build();
+
+    {
+        let mockfs = full_setup.fsf();
+        let url = format!("file://{}/src/some_other_file.rs", mockfs.root_path_buf().to_string_lossy());
+
+        let targets: Vec<StupidSymbolUsage> = target_lines
+            .iter()
+            .map(|line| StupidSymbolUsage {
+                path: url.clone(),
+                stupid_range: (StupidCursor::new(7, *line), StupidCursor::new(20, *line)),
+            })
+            .collect();
+
+        let mut symbols = full_setup.navcomp_pilot().unwrap().symbols().unwrap();
+        symbols.push(MockSymbolMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            symbol: NavCompSymbol {
+                symbol_type: SymbolType::Function,
+                stupid_range: CALL,
+            },
+            usages: Some(targets),
+            ..Default::default()
+        });
+
+        let mut files = full_setup.navcomp_pilot().unwrap().files().unwrap();
+        files.push(MockFileMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            semantic_tokens: Some(vec![NavCompSemanticToken {
+                stupid_range: CALL,
+                token_type: Arc::new("function".to_string()),
+                modifiers: vec![],
+            }]),
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn cursor_line(full_setup: &FullSetup) -> Option<String> {
+    full_setup
+        .get_first_editor()?
+        .get_visible_cursor_lines()
+        .next()
+        .map(|line| line.contents.text.trim().to_string())
+}
+
+// puts cursor on the call and picks option from context menu
+fn choose_option(full_setup: &mut FullSetup, option: &str) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    for _ in 0..3 {
+        assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    }
+    for _ in 0..4 {
+        assert!(full_setup.send_key(Keycode::ArrowRight.to_key()));
+    }
+    assert!(full_setup.wait_for(|f| cursor_line(f) == Some("some_function(\"a\");⏎".to_string())));
+
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.global.everything_bar));
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().context_bar_op().is_some()));
+
+    for _ in 0..10 {
+        let selected = full_setup
+            .get_first_editor()
+            .unwrap()
+            .context_bar_op()
+            .and_then(|c| c.selected_option())
+            .unwrap_or_default();
+
+        if selected.trim().starts_with(option) {
+            break;
+        }
+
+        assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+        assert!(full_setup.wait_for(|f| {
+            f.get_first_editor()
+                .unwrap()
+                .context_bar_op()
+                .and_then(|c| c.selected_option())
+                .map(|s| s != selected)
+                .unwrap_or(false)
+        }));
+    }
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .context_bar_op()
+            .and_then(|c| c.selected_option())
+            .map(|s| s.trim().starts_with(option))
+            .unwrap_or(false)
+    }));
+
+    assert!(full_setup.send_key(Keycode::Enter.to_key()));
+}
+
+#[test]
+fn go_to_targets_test_1_single_target_jumps() {
+    let mut full_setup = get_full_setup(&[4]);
+    choose_option(&mut full_setup, "go to declaration");
+
+    assert!(full_setup.wait_for(|f| cursor_line(f) == Some("pub fn other_function(x: &str) {⏎".to_string())));
+    assert!(full_setup.get_code_results_view().is_none());
+}
+
+#[test]
+fn go_to_targets_test_2_many_targets_are_listed() {
+    let mut full_setup = get_full_setup(&[0, 4]);
+    choose_option(&mut full_setup, "show implementations");
+
+    assert!(full_setup.wait_for(|f| f.get_code_results_view().is_some()));
+}
+
+#[test]
+fn go_to_targets_test_3_no_targets_stays_put() {
+    let mut full_setup = get_full_setup(&[]);
+    choose_option(&mut full_setup, "go to declaration");
+
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().context_bar_op().is_none()));
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().is_editor_focused()));
+
+    assert!(full_setup.get_code_results_view().is_none());
+    assert_eq!(cursor_line(&full_setup), Some("some_function(\"a\");⏎".to_string()));
+}
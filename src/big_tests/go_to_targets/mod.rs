@@ -0,0 +1 @@
+mod go_to_targets_test_1;
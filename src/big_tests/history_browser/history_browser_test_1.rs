@@ -0,0 +1,109 @@
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::with_wait_for::WithWaitFor;
+
+fn get_full_setup() -> FullSetup {
+    FullSetup::new("./test_envs/history_browser_test_1")
+        .with_files(["file_to_edit.txt"])
+        .build()
+}
+
+fn first_line(full_setup: &FullSetup) -> Option<String> {
+    full_setup
+        .get_first_editor()?
+        .get_visible_cursor_lines()
+        .next()
+        .map(|line| line.contents.text.trim().to_string())
+}
+
+// types "x", undoes it and types "y" instead, so there is one abandoned branch
+fn make_branch(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    assert!(full_setup.type_in("x"));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("xfirst line⏎".to_string())));
+
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.edit_msgs.undo));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("first line⏎".to_string())));
+
+    assert!(full_setup.type_in("y"));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("yfirst line⏎".to_string())));
+}
+
+fn open_history(full_setup: &mut FullSetup) {
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.editor.history));
+    assert!(full_setup.wait_for(|f| f.get_history_browser().map(|hb| hb.is_focused()).unwrap_or(false)));
+}
+
+#[test]
+fn history_browser_test_1_shows_abandoned_branch() {
+    let mut full_setup = get_full_setup();
+    make_branch(&mut full_setup);
+    open_history(&mut full_setup);
+
+    let history = full_setup.get_history_browser().unwrap();
+    let labels: Vec<String> = history.visible_items().into_iter().map(|item| item.label).collect();
+
+    assert_eq!(labels.len(), 3);
+    assert_eq!(labels[0], "file_to_edit.txt");
+    assert!(labels[1].starts_with("#0 ") && labels[1].ends_with("[saved]"), "{:?}", labels);
+    assert!(
+        labels[2].starts_with("#2 ") && labels[2].ends_with("+1 chars [current]"),
+        "{:?}",
+        labels
+    );
+
+    // current state is highlighted, preview points at the change
+    assert_eq!(history.selected_option(), Some(labels[2].clone()));
+    assert_eq!(history.preview_lines()[0], ">    1 yfirst line");
+}
+
+#[test]
+fn history_browser_test_2_jump_to_abandoned_state() {
+    let mut full_setup = get_full_setup();
+    make_branch(&mut full_setup);
+    open_history(&mut full_setup);
+
+    // up to the fork, unfold it and go down to the branch and its only state
+    assert!(full_setup.send_key(Keycode::ArrowUp.to_key()));
+    assert!(full_setup.send_key(Keycode::ArrowRight.to_key()));
+    assert!(full_setup.wait_for(|f| f.get_history_browser().unwrap().visible_items().len() == 4));
+
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    assert!(full_setup.send_key(Keycode::ArrowRight.to_key()));
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_history_browser()
+            .unwrap()
+            .selected_option()
+            .map(|label| label.starts_with("#1 "))
+            .unwrap_or(false)
+    }));
+    assert!(full_setup.wait_for(|f| f.get_history_browser().unwrap().preview_lines().first() == Some(&">    1 xfirst line".to_string())));
+
+    assert!(full_setup.send_key(Keycode::Enter.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_history_browser().is_none()));
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().is_editor_focused()));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("xfirst line⏎".to_string())));
+
+    // and the branch we left is still one undo + redo away
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.edit_msgs.undo));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("first line⏎".to_string())));
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.edit_msgs.redo));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("xfirst line⏎".to_string())));
+}
+
+#[test]
+fn history_browser_test_3_esc_closes() {
+    let mut full_setup = get_full_setup();
+    make_branch(&mut full_setup);
+    open_history(&mut full_setup);
+
+    assert!(full_setup.send_key(Keycode::Esc.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_history_browser().is_none()));
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().is_editor_focused()));
+    assert_eq!(first_line(&full_setup), Some("yfirst line⏎".to_string()));
+}
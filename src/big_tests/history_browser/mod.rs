@@ -0,0 +1 @@
+mod history_browser_test_1;
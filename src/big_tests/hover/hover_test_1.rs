@@ -0,0 +1,100 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockSymbolMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{NavCompSymbol, SymbolType};
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/hover_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        // .with_frame_based_wait()
+        .build();
+
+    {
+        let mut symbols = full_setup.navcomp_pilot().unwrap().symbols().unwrap();
+        let mockfs = full_setup.fsf();
+
+        symbols.push(MockSymbolMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            symbol: NavCompSymbol {
+                symbol_type: SymbolType::Function,
+                stupid_range: (StupidCursor::new(4, 7), StupidCursor::new(17, 7)),
+            },
+            usages: Some(vec![]),
+            hover: Some("fn some_function(s: &str)\n\nDoes something.".to_string()),
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn go_to_some_function(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    for _ in 0..7 {
+        assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    }
+
+    assert!(full_setup.wait_for(|f| f
+        .get_first_editor()
+        .unwrap()
+        .get_visible_cursor_lines()
+        .find(|line| line.visible_idx == 8)
+        .is_some()));
+
+    assert!(full_setup.send_key(Keycode::ArrowRight.to_key().with_ctrl()));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_visible_cursor_lines()
+            .find(|line| line.contents.text.trim() == "some_function(\"a\");⏎")
+            .is_some()
+    }));
+}
+
+#[test]
+fn hover_test_1_shows_and_closes() {
+    let mut full_setup = get_full_setup();
+    go_to_some_function(&mut full_setup);
+
+    assert!(full_setup.get_first_editor().unwrap().hover().is_none());
+
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.editor.show_hover));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .hover()
+            .map(|hover| hover.lines().any(|line| line == "fn some_function(s: &str)"))
+            .unwrap_or(false)
+    }));
+
+    assert!(full_setup.send_key(Keycode::Esc.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().hover().is_none()));
+}
+
+#[test]
+fn hover_test_2_nothing_to_show() {
+    let mut full_setup = get_full_setup();
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    // cursor is at the beginning of file, where mock provides no hover
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.editor.show_hover));
+
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    assert!(full_setup.wait_for(|f| f
+        .get_first_editor()
+        .unwrap()
+        .get_visible_cursor_lines()
+        .find(|line| line.visible_idx == 2)
+        .is_some()));
+
+    assert!(full_setup.get_first_editor().unwrap().hover().is_none());
+}
@@ -0,0 +1 @@
+mod hover_test_1;
@@ -0,0 +1,88 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::config::config::Config;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockFileMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::widgets::editor_widget::label::label::{Label, LabelPos, LabelStyle};
+
+fn hint(line_0b: u32, char_idx_0b: u32, text: &str) -> Label {
+    Label::new(
+        LabelPos::InlineStupid {
+            stupid_cursor: StupidCursor::new(char_idx_0b, line_0b),
+        },
+        LabelStyle::TypeAnnotation,
+        Box::new(text.to_string()),
+    )
+}
+
+fn get_full_setup(config: Config) -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/inlay_hints_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        .with_config(config)
+        // .with_frame_based_wait()
+        .build();
+
+    {
+        let mut files = full_setup.navcomp_pilot().unwrap().files().unwrap();
+        let mockfs = full_setup.fsf();
+
+        // "    let sum = add(1, 2);" line
+        files.push(MockFileMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            inlay_hints: vec![hint(5, 11, ": i32"), hint(5, 18, "first: "), hint(5, 21, "second: ")],
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn sum_line(full_setup: &FullSetup) -> Option<String> {
+    full_setup
+        .get_first_editor()?
+        .get_all_visible_lines()
+        .find(|line| line.contents.text.contains("let sum"))
+        .map(|line| line.contents.text.trim().to_string())
+}
+
+#[test]
+fn inlay_hints_test_1_hints_are_drawn_inline() {
+    let mut full_setup = get_full_setup(Config::default());
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    assert!(full_setup.wait_for(|f| sum_line(f).map(|line| line.starts_with("let sum: i32 = add(first: 1, second: 2);")) == Some(true)));
+}
+
+#[test]
+fn inlay_hints_test_2_refreshed_on_edit() {
+    let mut full_setup = get_full_setup(Config::default());
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert!(full_setup.wait_for(|f| sum_line(f).map(|line| line.contains("first: 1")) == Some(true)));
+
+    {
+        let mut files = full_setup.navcomp_pilot().unwrap().files().unwrap();
+        files[0].inlay_hints = vec![hint(5, 11, ": u64")];
+    }
+
+    // typing at the beginning of the file, line with hints is not moved
+    assert!(full_setup.type_in("//"));
+
+    assert!(full_setup.wait_for(|f| sum_line(f).map(|line| line.starts_with("let sum: u64 = add(1, 2);")) == Some(true)));
+}
+
+#[test]
+fn inlay_hints_test_3_can_be_disabled() {
+    let mut config = Config::default();
+    config.global.inlay_hints = false;
+
+    let mut full_setup = get_full_setup(config);
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert!(full_setup.wait_for(|f| sum_line(f).is_some()));
+    assert!(sum_line(&full_setup).unwrap().starts_with("let sum = add(1, 2);"));
+
+    // nobody asks navcomp for hints, so they never show up
+    assert!(!full_setup.wait_for(|f| sum_line(f).map(|line| line.contains(": i32")) == Some(true)));
+}
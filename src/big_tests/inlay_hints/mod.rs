@@ -0,0 +1 @@
+mod inlay_hints_test_1;
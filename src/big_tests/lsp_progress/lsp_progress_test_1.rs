@@ -0,0 +1,47 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::input_event::InputEvent;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::w7e::navcomp_provider::NavCompProgress;
+
+fn get_full_setup() -> FullSetup {
+    FullSetup::new("./test_envs/lsp_progress_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        .build()
+}
+
+fn status_bar(full_setup: &FullSetup) -> String {
+    full_setup
+        .get_frame()
+        .and_then(|frame| frame.get_status_bar_text())
+        .unwrap_or_default()
+}
+
+fn indexing(percentage: u32) -> NavCompProgress {
+    NavCompProgress {
+        title: "Indexing".to_string(),
+        message: None,
+        percentage: Some(percentage),
+    }
+}
+
+#[test]
+fn lsp_progress_test_1_status_bar_shows_progress() {
+    let mut full_setup = get_full_setup();
+
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert!(!status_bar(&full_setup).contains("indexing"));
+
+    full_setup.navcomp_pilot().unwrap().set_progress(vec![indexing(0)]);
+    assert!(full_setup.send_input(InputEvent::Tick));
+    assert!(full_setup.wait_for(|f| status_bar(f).contains("gladius (indexing 0%)")));
+
+    full_setup.navcomp_pilot().unwrap().set_progress(vec![indexing(47)]);
+    assert!(full_setup.send_input(InputEvent::Tick));
+    assert!(full_setup.wait_for(|f| status_bar(f).contains("gladius (indexing 47%)")));
+
+    full_setup.navcomp_pilot().unwrap().set_progress(vec![]);
+    assert!(full_setup.send_input(InputEvent::Tick));
+    assert!(full_setup.wait_for(|f| !status_bar(f).contains("indexing")));
+}
@@ -0,0 +1 @@
+mod lsp_progress_test_1;
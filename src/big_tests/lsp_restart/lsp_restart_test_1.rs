@@ -0,0 +1,53 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::spath;
+
+fn get_full_setup() -> FullSetup {
+    FullSetup::new("./test_envs/lsp_restart_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        // .with_frame_based_wait()
+        .build()
+}
+
+fn status_bar(full_setup: &FullSetup) -> String {
+    full_setup
+        .get_frame()
+        .and_then(|frame| frame.get_status_bar_text())
+        .unwrap_or_default()
+}
+
+#[test]
+fn lsp_restart_test_1_file_reopened_after_restart() {
+    let mut full_setup = get_full_setup();
+    let main_rs = spath!(full_setup.fsf(), "src", "main.rs").unwrap();
+
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert!(full_setup.navcomp_pilot().unwrap().wait_for_load(&main_rs).is_some());
+
+    full_setup.navcomp_pilot().unwrap().crash();
+
+    let contents = full_setup.navcomp_pilot().unwrap().wait_for_load(&main_rs).unwrap();
+    assert!(contents.contains("Hello, world!"));
+}
+
+#[test]
+fn lsp_restart_test_2_status_bar_shows_restart() {
+    let mut full_setup = get_full_setup();
+    let main_rs = spath!(full_setup.fsf(), "src", "main.rs").unwrap();
+
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert!(full_setup.navcomp_pilot().unwrap().wait_for_load(&main_rs).is_some());
+    assert!(!status_bar(&full_setup).contains("lsp"));
+
+    full_setup.navcomp_pilot().unwrap().set_refuse_restart(true);
+    full_setup.navcomp_pilot().unwrap().crash();
+
+    assert!(full_setup.wait_for(|f| status_bar(f).contains("rust lsp restarting")));
+
+    full_setup.navcomp_pilot().unwrap().set_refuse_restart(false);
+
+    assert!(full_setup.navcomp_pilot().unwrap().wait_for_load(&main_rs).is_some());
+    assert!(full_setup.wait_for(|f| !status_bar(f).contains("lsp")));
+}
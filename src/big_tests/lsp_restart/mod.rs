@@ -0,0 +1 @@
+mod lsp_restart_test_1;
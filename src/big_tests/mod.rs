@@ -1,20 +1,35 @@
+mod apply_edit;
 mod buffer_list;
+mod code_actions;
 mod completion;
 mod context_kite_and_scroll;
+mod document_highlight;
 mod dropping_cursor;
 mod file_tree_view;
 mod find_in_files;
 mod find_replace;
+mod format_on_save;
 mod fuzzy_file_open;
+mod go_to_targets;
+mod history_browser;
+mod hover;
+mod inlay_hints;
 mod lsp_clangd_cpp;
+mod lsp_progress;
+mod lsp_restart;
 mod lsp_rust_integ;
 mod lsp_rust_integ_labels;
 
 mod main_basic;
+mod outline;
+mod recovery;
 mod save;
 mod save_file_dialog;
+mod semantic_tokens;
 mod show_usages;
+mod signature_help;
 mod syntax_highlighting;
+mod tasks;
 mod test_utils;
 
 mod golang;
@@ -32,5 +47,7 @@ mod lsp_rust_integ_format;
 
 mod java;
 mod quit_unsaved_dialog;
+mod rename;
 mod toml;
+mod workspace_symbols;
 mod yaml;
@@ -0,0 +1 @@
+mod outline_test_1;
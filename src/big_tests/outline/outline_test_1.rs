@@ -0,0 +1,137 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockFileMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{NavCompDocumentSymbol, SymbolType};
+
+fn symbol(
+    name: &str,
+    symbol_type: SymbolType,
+    lines: (u32, u32),
+    name_start: StupidCursor,
+    children: Vec<NavCompDocumentSymbol>,
+) -> NavCompDocumentSymbol {
+    NavCompDocumentSymbol {
+        name: name.to_string(),
+        symbol_type,
+        stupid_range: (StupidCursor::new(0, lines.0), StupidCursor::new(1, lines.1)),
+        name_stupid_range: (
+            name_start,
+            StupidCursor::new(name_start.char_idx_0b + name.len() as u32, name_start.line_0b),
+        ),
+        children,
+    }
+}
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/outline_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        // .with_frame_based_wait()
+        .build();
+
+    {
+        let mut files = full_setup.navcomp_pilot().unwrap().files().unwrap();
+        let mockfs = full_setup.fsf();
+
+        files.push(MockFileMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            document_symbols: Some(vec![
+                symbol(
+                    "Point",
+                    SymbolType::Struct,
+                    (0, 3),
+                    StupidCursor::new(7, 0),
+                    vec![
+                        symbol("x", SymbolType::Field, (1, 1), StupidCursor::new(4, 1), vec![]),
+                        symbol("y", SymbolType::Field, (2, 2), StupidCursor::new(4, 2), vec![]),
+                    ],
+                ),
+                symbol(
+                    "impl Point",
+                    SymbolType::Object,
+                    (5, 13),
+                    StupidCursor::new(5, 5),
+                    vec![
+                        symbol("new", SymbolType::Method, (6, 8), StupidCursor::new(7, 6), vec![]),
+                        symbol("manhattan_length", SymbolType::Method, (10, 12), StupidCursor::new(7, 10), vec![]),
+                    ],
+                ),
+                symbol("main", SymbolType::Function, (15, 18), StupidCursor::new(3, 15), vec![]),
+            ]),
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn open_outline(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.editor.outline));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_context_menu()
+            .map(|menu| menu.is_focused() && menu.visible_items().len() == 8)
+            .unwrap_or(false)
+    }));
+}
+
+#[test]
+fn outline_test_1_shows_nested_symbols() {
+    let mut full_setup = get_full_setup();
+    open_outline(&mut full_setup);
+
+    let items = full_setup.get_first_context_menu().unwrap().visible_items();
+
+    let labels: Vec<(&str, u16)> = items.iter().map(|item| (item.label.as_str(), item.depth)).collect();
+    assert_eq!(
+        labels[1..],
+        [
+            ("Point: struct", 1),
+            ("x: field", 2),
+            ("y: field", 2),
+            ("impl Point: object", 1),
+            ("new: method", 2),
+            ("manhattan_length: method", 2),
+            ("main: function", 1),
+        ]
+    );
+}
+
+#[test]
+fn outline_test_2_filter_and_jump() {
+    let mut full_setup = get_full_setup();
+    open_outline(&mut full_setup);
+
+    assert!(full_setup.type_in("manh"));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_context_menu()
+            .map(|menu| menu.selected_option() == Some("manhattan_length: method".to_string()))
+            .unwrap_or(false)
+    }));
+    assert!(full_setup.send_key(Keycode::Enter.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().is_none()));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_visible_cursor_lines()
+            .find(|line| line.contents.text.contains("fn manhattan_length(&self)"))
+            .is_some()
+    }));
+}
+
+#[test]
+fn outline_test_3_esc_closes_outline() {
+    let mut full_setup = get_full_setup();
+    open_outline(&mut full_setup);
+
+    assert!(full_setup.send_key(Keycode::Esc.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().is_none()));
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().is_editor_focused()));
+}
@@ -0,0 +1 @@
+mod recovery_test_1;
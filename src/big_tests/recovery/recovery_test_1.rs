@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+
+use crate::config::config::Config;
+use crate::cursor::cursor_set::CursorSet;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::text::recovery;
+use crate::text::recovery::{RecoveryEntry, RecoverySession};
+
+const DISK_TEXT: &str = "first line\nsecond line\n";
+const RECOVERED_TEXT: &str = "recovered line\nsecond line\n";
+
+/*
+Pretends a previous session left three buffers behind: file.txt with changes, file.txt exactly as it
+is on disk (nothing to recover) and a never saved one. If the session is dropped, it looks like a
+crash, otherwise like another instance still running.
+ */
+fn get_session(name: &str) -> (Config, RecoverySession) {
+    let mut config = Config::default();
+    config.config_dir = std::env::temp_dir().join(format!("gladius_recovery_test_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&config.config_dir);
+
+    let workspace_root = PathBuf::from("./test_envs/recovery_test_1").canonicalize().unwrap();
+    let session = RecoverySession::new(&recovery::recovery_dir(&config, &workspace_root).unwrap()).unwrap();
+
+    let entries = [
+        RecoveryEntry {
+            buffer_id: "modified".to_string(),
+            path: Some(PathBuf::from("file.txt")),
+            text: RECOVERED_TEXT.to_string(),
+            cursor_set: Some(CursorSet::single()),
+        },
+        RecoveryEntry {
+            buffer_id: "same_as_disk".to_string(),
+            path: Some(PathBuf::from("file.txt")),
+            text: DISK_TEXT.to_string(),
+            cursor_set: None,
+        },
+        RecoveryEntry {
+            buffer_id: "unnamed".to_string(),
+            path: None,
+            text: "scratch notes\n".to_string(),
+            cursor_set: None,
+        },
+    ];
+
+    for entry in entries.iter() {
+        recovery::store(session.dir(), entry).unwrap();
+    }
+
+    (config, session)
+}
+
+fn get_full_setup(name: &str) -> (FullSetup, PathBuf) {
+    let (config, session) = get_session(name);
+    let dir = session.dir().to_path_buf();
+    drop(session);
+
+    let full_setup = FullSetup::new("./test_envs/recovery_test_1").with_config(config).build();
+
+    (full_setup, dir)
+}
+
+fn first_line(full_setup: &FullSetup) -> Option<String> {
+    full_setup
+        .get_first_editor()?
+        .get_visible_cursor_lines()
+        .next()
+        .map(|line| line.contents.text.trim().to_string())
+}
+
+fn labels(full_setup: &FullSetup) -> Vec<String> {
+    full_setup
+        .get_recovery_view()
+        .map(|rv| rv.visible_items().into_iter().map(|item| item.label).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn recovery_test_1_lists_buffers_that_differ_from_disk() {
+    let (mut full_setup, dir) = get_full_setup("list");
+
+    assert!(full_setup.wait_for(|f| f.get_recovery_view().map(|rv| rv.is_focused()).unwrap_or(false)));
+
+    let labels = labels(&full_setup);
+    assert_eq!(labels.len(), 3, "{:?}", labels);
+    assert!(labels[0].starts_with("unsaved buffers left by previous session"));
+    assert_eq!(labels[1], "[unnamed] scratch notes");
+    assert_eq!(labels[2], "file.txt (modified)");
+
+    // the one matching disk is gone for good
+    assert!(!recovery::entry_file_path(&dir, "same_as_disk").exists());
+
+    let recovery_view = full_setup.get_recovery_view().unwrap();
+    assert_eq!(recovery_view.selected_option(), Some(labels[1].clone()));
+    assert!(!recovery_view.preview_lines().is_empty());
+
+    let _ = std::fs::remove_dir_all(&full_setup.config().config_dir);
+}
+
+#[test]
+fn recovery_test_2_restore_is_undoable() {
+    let (mut full_setup, dir) = get_full_setup("restore");
+
+    assert!(full_setup.wait_for(|f| f.get_recovery_view().map(|rv| rv.is_focused()).unwrap_or(false)));
+
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    assert!(full_setup.wait_for(|f| f.get_recovery_view().unwrap().selected_option() == Some("file.txt (modified)".to_string())));
+
+    assert!(full_setup.send_key(Keycode::Enter.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_editor().map(|e| e.is_editor_focused()).unwrap_or(false)));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("recovered line⏎".to_string())));
+    assert!(!recovery::entry_file_path(&dir, "modified").exists());
+
+    // the unnamed one is still listed, in a display behind the editor
+    assert!(full_setup.get_recovery_view().is_none());
+
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.edit_msgs.undo));
+    assert!(full_setup.wait_for(|f| first_line(f) == Some("first line⏎".to_string())));
+
+    let _ = std::fs::remove_dir_all(&full_setup.config().config_dir);
+}
+
+#[test]
+fn recovery_test_3_discard() {
+    let (mut full_setup, dir) = get_full_setup("discard");
+
+    assert!(full_setup.wait_for(|f| f.get_recovery_view().map(|rv| rv.is_focused()).unwrap_or(false)));
+
+    assert!(full_setup.send_key(Keycode::Delete.to_key()));
+
+    assert!(full_setup.wait_for(|f| labels(f).len() == 2));
+    assert_eq!(labels(&full_setup)[1], "file.txt (modified)");
+    assert!(!recovery::entry_file_path(&dir, "unnamed").exists());
+
+    assert!(full_setup.send_key(Keycode::Delete.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_recovery_view().is_none()));
+    assert!(full_setup.wait_for(|f| f.is_no_editor_opened()));
+    assert!(!recovery::entry_file_path(&dir, "modified").exists());
+
+    // nothing left there, so the crashed session is gone too
+    assert!(!dir.exists());
+
+    let _ = std::fs::remove_dir_all(&full_setup.config().config_dir);
+}
+
+#[test]
+fn recovery_test_4_buffers_of_running_instance_are_not_offered() {
+    let (config, session) = get_session("running");
+    let config_dir = config.config_dir.clone();
+
+    let mut full_setup = FullSetup::new("./test_envs/recovery_test_1").with_config(config).build();
+
+    assert!(full_setup.wait_for(|f| f.is_no_editor_opened()));
+    assert!(full_setup.get_recovery_view().is_none());
+    assert!(recovery::entry_file_path(session.dir(), "modified").exists());
+
+    full_setup.finish();
+    session.remove();
+    let _ = std::fs::remove_dir_all(&config_dir);
+}
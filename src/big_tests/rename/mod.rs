@@ -0,0 +1 @@
+mod rename_test_1;
@@ -0,0 +1,155 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockSymbolMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{NavCompSymbol, StupidSymbolUsage, SymbolType};
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/rename_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        // .with_frame_based_wait()
+        .build();
+
+    {
+        let mut symbols = full_setup.navcomp_pilot().unwrap().symbols().unwrap();
+        let mockfs = full_setup.fsf();
+
+        let root = mockfs.root_path_buf().to_string_lossy().to_string();
+        let main_url = format!("file://{}/src/main.rs", root);
+        let other_url = format!("file://{}/src/some_other_file.rs", root);
+
+        let first_occ = (StupidCursor::new(4, 7), StupidCursor::new(17, 7));
+        let second_occ = (StupidCursor::new(4, 11), StupidCursor::new(17, 11));
+        let definition = (StupidCursor::new(7, 0), StupidCursor::new(20, 0));
+
+        symbols.push(MockSymbolMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            symbol: NavCompSymbol {
+                symbol_type: SymbolType::Function,
+                stupid_range: first_occ,
+            },
+            usages: Some(vec![
+                StupidSymbolUsage {
+                    path: main_url.clone(),
+                    stupid_range: first_occ,
+                },
+                StupidSymbolUsage {
+                    path: main_url,
+                    stupid_range: second_occ,
+                },
+                StupidSymbolUsage {
+                    path: other_url,
+                    stupid_range: definition,
+                },
+            ]),
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn go_to_some_function(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    for _ in 0..7 {
+        assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    }
+    assert!(full_setup.send_key(Keycode::ArrowRight.to_key().with_ctrl()));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_visible_cursor_lines()
+            .find(|line| line.contents.text.trim() == "some_function(\"a\");⏎")
+            .is_some()
+    }));
+}
+
+fn open_rename(full_setup: &mut FullSetup) {
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.editor.rename));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .rename()
+            .map(|rename| rename.is_focused() && rename.new_name() == "some_function")
+            .unwrap_or(false)
+    }));
+}
+
+#[test]
+fn rename_test_1_preview_and_apply() {
+    let mut full_setup = get_full_setup();
+    go_to_some_function(&mut full_setup);
+    open_rename(&mut full_setup);
+
+    assert!(full_setup.type_in("_renamed"));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .rename()
+            .map(|rename| rename.new_name() == "some_function_renamed")
+            .unwrap_or(false)
+    }));
+    assert!(full_setup.send_key(Keycode::Enter.to_key()));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_code_results_view()
+            .map(|crv| crv.description().contains("3 changes in 2 files"))
+            .unwrap_or(false)
+    }));
+
+    // nothing is changed before preview is accepted
+    let other_file = spath!(full_setup.fsf(), "src", "some_other_file.rs").unwrap();
+    assert!(other_file
+        .read_entire_file_to_string()
+        .unwrap()
+        .starts_with("pub fn some_function("));
+
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.global.apply));
+
+    assert!(full_setup.wait_for(|f| f.get_code_results_view().is_none()));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_all_visible_lines()
+            .filter(|line| line.contents.text.contains("some_function_renamed("))
+            .count()
+            == 2
+    }));
+
+    // file that was not open is changed on disk
+    assert!(other_file
+        .read_entire_file_to_string()
+        .unwrap()
+        .starts_with("pub fn some_function_renamed("));
+    assert!(full_setup.navcomp_pilot().unwrap().wait_for_file_changed_on_disk(&other_file));
+
+    // while the open one got an edit that can be undone
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.edit_msgs.undo));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_all_visible_lines()
+            .filter(|line| line.contents.text.contains("some_function_renamed("))
+            .count()
+            < 2
+    }));
+}
+
+#[test]
+fn rename_test_2_esc_closes_box() {
+    let mut full_setup = get_full_setup();
+    go_to_some_function(&mut full_setup);
+    open_rename(&mut full_setup);
+
+    assert!(full_setup.send_key(Keycode::Esc.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().rename().is_none()));
+    assert!(full_setup.get_code_results_view().is_none());
+}
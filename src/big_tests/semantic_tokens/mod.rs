@@ -0,0 +1 @@
+mod semantic_tokens_test_1;
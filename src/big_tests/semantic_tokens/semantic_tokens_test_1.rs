@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::style::Effect;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockFileMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::NavCompSemanticToken;
+
+fn token(line: u32, begin: u32, len: u32, token_type: &str, modifiers: &[&str]) -> NavCompSemanticToken {
+    NavCompSemanticToken {
+        stupid_range: (StupidCursor::new(begin, line), StupidCursor::new(begin + len, line)),
+        token_type: Arc::new(token_type.to_string()),
+        modifiers: modifiers.iter().map(|modifier| Arc::new(modifier.to_string())).collect(),
+    }
+}
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/semantic_tokens_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        .build();
+
+    {
+        let mut files = full_setup.navcomp_pilot().unwrap().files().unwrap();
+        let mockfs = full_setup.fsf();
+
+        files.push(MockFileMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            semantic_tokens: Some(vec![
+                token(1, 12, 7, "variable", &["declaration", "mutable"]),
+                token(2, 4, 7, "variable", &["mutable"]),
+                token(3, 4, 7, "macro", &[]),
+                token(3, 19, 7, "variable", &["mutable"]),
+            ]),
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+#[test]
+fn semantic_tokens_test_1_modifiers_and_types_are_styled() {
+    let mut full_setup = get_full_setup();
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .consistent_items_iter()
+            .filter(|item| item.text_style.effect == Effect::Underline && item.text == "counter")
+            .count()
+            == 3
+    }));
+
+    let macro_color = full_setup.get_theme().name_to_color("function.macro").unwrap();
+    assert!(full_setup
+        .get_first_editor()
+        .unwrap()
+        .consistent_items_iter()
+        .any(|item| item.text == "println" && item.text_style.foreground == macro_color));
+
+    // tokens are not underlining anything else
+    assert!(full_setup
+        .get_first_editor()
+        .unwrap()
+        .consistent_items_iter()
+        .filter(|item| item.text_style.effect == Effect::Underline)
+        .all(|item| item.text == "counter"));
+}
@@ -36,6 +36,7 @@ fn get_full_setup() -> FullSetup {
                     stupid_range: second_occ,
                 },
             ]),
+            ..Default::default()
         });
         symbols.push(MockSymbolMatcher {
             path: spath!(mockfs, "src", "main.rs"),
@@ -53,6 +54,7 @@ fn get_full_setup() -> FullSetup {
                     stupid_range: second_occ,
                 },
             ]),
+            ..Default::default()
         });
     }
 
@@ -0,0 +1 @@
+mod signature_help_test_1;
@@ -0,0 +1,107 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockSymbolMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{NavCompSignature, NavCompSymbol, SymbolType};
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/signature_help_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        // .with_frame_based_wait()
+        .build();
+
+    {
+        let mut symbols = full_setup.navcomp_pilot().unwrap().symbols().unwrap();
+        let mockfs = full_setup.fsf();
+
+        // entire "//" line, that is where we type the call.
+        symbols.push(MockSymbolMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            symbol: NavCompSymbol {
+                symbol_type: SymbolType::Function,
+                stupid_range: (StupidCursor::new(0, 9), StupidCursor::new(100, 9)),
+            },
+            signature_help: Some(NavCompSignature {
+                label: "fn some_function(s: &str)".to_string(),
+                active_parameter: Some((17, 24)),
+            }),
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn go_to_empty_line(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+
+    for _ in 0..9 {
+        assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+    }
+    assert!(full_setup.send_key(Keycode::End.to_key()));
+
+    assert!(full_setup.wait_for(|f| f
+        .get_first_editor()
+        .unwrap()
+        .get_visible_cursor_lines()
+        .find(|line| line.visible_idx == 10)
+        .is_some()));
+}
+
+#[test]
+fn signature_help_test_1_opens_on_trigger() {
+    let mut full_setup = get_full_setup();
+    go_to_empty_line(&mut full_setup);
+
+    assert!(full_setup.type_in("some_function"));
+    assert!(full_setup.wait_for(|f| f
+        .get_first_editor()
+        .unwrap()
+        .get_visible_cursor_lines()
+        .find(|line| line.contents.text.contains("some_function"))
+        .is_some()));
+    assert!(full_setup.get_first_editor().unwrap().signature_help().is_none());
+
+    assert!(full_setup.type_in("("));
+
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .signature_help()
+            .map(|sh| sh.text() == "fn some_function(s: &str)" && sh.highlighted() == "s: &str")
+            .unwrap_or(false)
+    }));
+
+    // typing goes to the editor, not to signature help
+    assert!(full_setup.type_in("\"a\""));
+
+    assert!(full_setup.wait_for(|f| f
+        .get_first_editor()
+        .unwrap()
+        .get_visible_cursor_lines()
+        .find(|line| line.contents.text.contains("some_function(\"a\""))
+        .is_some()));
+    assert!(full_setup.get_first_editor().unwrap().signature_help().is_some());
+
+    assert!(full_setup.send_key(Keycode::Esc.to_key()));
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().signature_help().is_none()));
+}
+
+#[test]
+fn signature_help_test_2_closes_when_leaving_call() {
+    let mut full_setup = get_full_setup();
+    go_to_empty_line(&mut full_setup);
+
+    assert!(full_setup.type_in("some_function("));
+
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().signature_help().is_some()));
+
+    // there is no signature help on the next line
+    assert!(full_setup.send_key(Keycode::ArrowDown.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().signature_help().is_none()));
+}
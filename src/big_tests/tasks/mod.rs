@@ -0,0 +1 @@
+mod tasks_test_1;
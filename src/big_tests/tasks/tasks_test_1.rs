@@ -0,0 +1,74 @@
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::with_wait_for::WithWaitFor;
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/tasks_test_1").build();
+
+    full_setup
+}
+
+fn run_task(full_setup: &mut FullSetup, filter: &str) {
+    assert!(full_setup.wait_for(|f| f.get_file_tree_view().is_some()));
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.global.run_task));
+
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().map(|menu| menu.is_focused()).unwrap_or(false)));
+    assert!(full_setup.type_in(filter));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_context_menu()
+            .and_then(|menu| menu.selected_option())
+            .map(|option| option.starts_with(filter))
+            .unwrap_or(false)
+    }));
+    assert!(full_setup.send_key(Keycode::Enter.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().is_none()));
+    assert!(full_setup.wait_for(|f| f.get_task_view().map(|view| view.is_focused()).unwrap_or(false)));
+}
+
+#[test]
+fn tasks_test_1_list_shows_custom_tasks() {
+    let mut full_setup = get_full_setup();
+
+    assert!(full_setup.wait_for(|f| f.get_file_tree_view().is_some()));
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.global.run_task));
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().is_some()));
+
+    let items = full_setup.get_first_context_menu().unwrap().visible_items();
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    // custom ones first, then ones contributed by handler
+    assert_eq!(
+        labels[1..],
+        [
+            "greet (sh -c echo hello from task; echo and goodbye)",
+            "nap (sleep 30)",
+            "cargo build",
+            "cargo test",
+            "cargo run -p tasks_test_1 --bin tasks_test_1",
+            "cargo test -p tasks_test_1 --bin tasks_test_1",
+        ]
+    );
+}
+
+#[test]
+fn tasks_test_2_run_and_see_output() {
+    let mut full_setup = get_full_setup();
+    run_task(&mut full_setup, "greet");
+
+    assert!(full_setup.wait_for(|f| f.get_task_view().unwrap().header() == "greet: finished"));
+
+    let lines = full_setup.get_task_view().unwrap().output_lines();
+    assert_eq!(lines, vec!["hello from task", "and goodbye"]);
+}
+
+#[test]
+fn tasks_test_3_esc_cancels() {
+    let mut full_setup = get_full_setup();
+    run_task(&mut full_setup, "nap");
+
+    assert!(full_setup.wait_for(|f| f.get_task_view().unwrap().is_running()));
+    assert!(full_setup.send_key(Keycode::Esc.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_task_view().unwrap().header() == "nap: cancelled"));
+    assert!(full_setup.get_task_view().unwrap().is_focused());
+}
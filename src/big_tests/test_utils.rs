@@ -3,6 +3,9 @@ use crate::mocks::full_setup::FullSetup;
 use crate::mocks::with_wait_for::WithWaitFor;
 use log::error;
 
+// Cargo.toml and workspace with a single rust scope handled by mock navcomp, see FullSetupBuilder::with_shared_env
+pub const MOCK_NAVCOMP_SHARED_ENV: &str = "./test_envs/shared_mock_navcomp_env";
+
 pub fn open_context_and_select(full_setup: &mut FullSetup, name_infix: &str) {
     if full_setup.get_first_context_menu().is_none() {
         assert!(full_setup.send_input(full_setup.config().keyboard_config.global.everything_bar.to_input_event()));
@@ -0,0 +1 @@
+mod workspace_symbols_test_1;
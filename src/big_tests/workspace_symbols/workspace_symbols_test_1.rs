@@ -0,0 +1,127 @@
+use crate::big_tests::test_utils::MOCK_NAVCOMP_SHARED_ENV;
+use crate::io::keys::Keycode;
+use crate::mocks::full_setup::FullSetup;
+use crate::mocks::mock_navcomp_provider::MockFileMatcher;
+use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::spath;
+use crate::w7e::navcomp_provider::{NavCompDocumentSymbol, SymbolType};
+
+fn symbol(name: &str, symbol_type: SymbolType, name_start: StupidCursor, children: Vec<NavCompDocumentSymbol>) -> NavCompDocumentSymbol {
+    let name_end = StupidCursor::new(name_start.char_idx_0b + name.len() as u32, name_start.line_0b);
+
+    NavCompDocumentSymbol {
+        name: name.to_string(),
+        symbol_type,
+        stupid_range: (name_start, name_end),
+        name_stupid_range: (name_start, name_end),
+        children,
+    }
+}
+
+fn get_full_setup() -> FullSetup {
+    let full_setup: FullSetup = FullSetup::new("./test_envs/workspace_symbols_test_1")
+        .with_shared_env(MOCK_NAVCOMP_SHARED_ENV)
+        .with_files(["src/main.rs"])
+        // .with_frame_based_wait()
+        .build();
+
+    {
+        let mut files = full_setup.navcomp_pilot().unwrap().files().unwrap();
+        let mockfs = full_setup.fsf();
+
+        files.push(MockFileMatcher {
+            path: spath!(mockfs, "src", "main.rs"),
+            document_symbols: Some(vec![symbol("main", SymbolType::Function, StupidCursor::new(3, 4), vec![])]),
+            ..Default::default()
+        });
+
+        // this file is not opened
+        files.push(MockFileMatcher {
+            path: spath!(mockfs, "src", "geometry.rs"),
+            document_symbols: Some(vec![
+                symbol("Point", SymbolType::Struct, StupidCursor::new(11, 0), vec![]),
+                symbol(
+                    "Point",
+                    SymbolType::Object,
+                    StupidCursor::new(5, 5),
+                    vec![
+                        symbol("new", SymbolType::Method, StupidCursor::new(11, 6), vec![]),
+                        symbol("manhattan_length", SymbolType::Method, StupidCursor::new(11, 10), vec![]),
+                    ],
+                ),
+            ]),
+            ..Default::default()
+        });
+    }
+
+    full_setup
+}
+
+fn open_workspace_symbols(full_setup: &mut FullSetup) {
+    assert!(full_setup.wait_for(|f| f.is_editor_opened()));
+    assert!(full_setup.send_key(full_setup.config().keyboard_config.global.workspace_symbol));
+
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().map(|menu| menu.is_focused()).unwrap_or(false)));
+}
+
+fn visible_labels(full_setup: &FullSetup) -> Vec<String> {
+    full_setup
+        .get_first_context_menu()
+        .map(|menu| menu.visible_items().into_iter().map(|item| item.label).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn workspace_symbols_test_1_search_and_jump() {
+    let mut full_setup = get_full_setup();
+    open_workspace_symbols(&mut full_setup);
+
+    assert!(full_setup.type_in("manh"));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_context_menu()
+            .map(|menu| menu.selected_option() == Some("manhattan_length: method in Point (src/geometry.rs)".to_string()))
+            .unwrap_or(false)
+    }));
+    assert!(full_setup.send_key(Keycode::Enter.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().is_none()));
+    assert!(full_setup.wait_for(|f| {
+        f.get_first_editor()
+            .unwrap()
+            .get_visible_cursor_lines()
+            .find(|line| line.contents.text.contains("pub fn manhattan_length(&self)"))
+            .is_some()
+    }));
+}
+
+#[test]
+fn workspace_symbols_test_2_results_follow_query() {
+    let mut full_setup = get_full_setup();
+    open_workspace_symbols(&mut full_setup);
+
+    // nothing is asked before anything is typed
+    assert_eq!(visible_labels(&full_setup).len(), 1);
+
+    assert!(full_setup.type_in("Point"));
+    assert!(full_setup.wait_for(|f| {
+        let labels = visible_labels(f);
+        labels.len() == 3
+            && labels.contains(&"Point: struct (src/geometry.rs)".to_string())
+            && labels.contains(&"Point: object (src/geometry.rs)".to_string())
+    }));
+
+    assert!(full_setup.type_in("x"));
+    assert!(full_setup.wait_for(|f| visible_labels(f).len() == 1));
+}
+
+#[test]
+fn workspace_symbols_test_3_esc_closes() {
+    let mut full_setup = get_full_setup();
+    open_workspace_symbols(&mut full_setup);
+
+    assert!(full_setup.send_key(Keycode::Esc.to_key()));
+
+    assert!(full_setup.wait_for(|f| f.get_first_context_menu().is_none()));
+    assert!(full_setup.wait_for(|f| f.get_first_editor().unwrap().is_editor_focused()));
+}
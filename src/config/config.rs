@@ -73,11 +73,35 @@ pub struct Global {
     pub everything_bar: Key,
     pub find_in_files: Key,
     pub fuzzy_file: Key,
+    #[serde(default = "Global::default_workspace_symbol")]
+    pub workspace_symbol: Key,
     pub new_buffer: Key,
     pub close_buffer: Key,
     pub make_screenshot: Key,
     pub next_display: Key,
     pub prev_display: Key,
+
+    // opens list of tasks to run
+    #[serde(default = "Global::default_run_task")]
+    pub run_task: Key,
+
+    // applies changes shown in a preview, like the one of rename
+    #[serde(default = "Global::default_apply")]
+    pub apply: Key,
+}
+
+impl Global {
+    fn default_workspace_symbol() -> Key {
+        Keycode::Char('t').to_key().with_ctrl()
+    }
+
+    fn default_run_task() -> Key {
+        Keycode::F(5).to_key()
+    }
+
+    fn default_apply() -> Key {
+        Keycode::Enter.to_key().with_alt()
+    }
 }
 
 impl Default for Global {
@@ -85,6 +109,7 @@ impl Default for Global {
         Global {
             quit: Keycode::Char('q').to_key().with_ctrl(),
             fuzzy_file: Keycode::Char('j').to_key().with_ctrl(),
+            workspace_symbol: Self::default_workspace_symbol(),
             new_buffer: Keycode::Char('n').to_key().with_ctrl(),
             close_buffer: Keycode::Char('p').to_key().with_ctrl(),
             browse_buffers: Keycode::Char('b').to_key().with_ctrl(),
@@ -96,6 +121,8 @@ impl Default for Global {
             make_screenshot: Keycode::Char('u').to_key().with_ctrl(),
             next_display: Keycode::Char('.').to_key().with_alt(),
             prev_display: Keycode::Char(',').to_key().with_alt(),
+            run_task: Self::default_run_task(),
+            apply: Self::default_apply(),
         }
     }
 }
@@ -110,8 +137,50 @@ pub struct Editor {
     pub replace: Key,
     pub close_find_replace: Key,
     pub request_completions: Key,
+    #[serde(default = "Editor::default_show_hover")]
+    pub show_hover: Key,
+    #[serde(default = "Editor::default_rename")]
+    pub rename: Key,
 
     pub reformat: Key,
+    #[serde(default = "Editor::default_outline")]
+    pub outline: Key,
+
+    // jumping between occurrences of symbol under cursor
+    #[serde(default = "Editor::default_next_usage")]
+    pub next_usage: Key,
+    #[serde(default = "Editor::default_prev_usage")]
+    pub prev_usage: Key,
+
+    // browser of undo tree, including abandoned branches
+    #[serde(default = "Editor::default_history")]
+    pub history: Key,
+}
+
+impl Editor {
+    fn default_show_hover() -> Key {
+        Keycode::Char('k').to_key().with_ctrl()
+    }
+
+    fn default_rename() -> Key {
+        Keycode::F(2).to_key()
+    }
+
+    fn default_outline() -> Key {
+        Keycode::Char('o').to_key().with_ctrl()
+    }
+
+    fn default_next_usage() -> Key {
+        Keycode::F(3).to_key()
+    }
+
+    fn default_prev_usage() -> Key {
+        Keycode::F(3).to_key().with_shift()
+    }
+
+    fn default_history() -> Key {
+        Keycode::Char('z').to_key().with_alt()
+    }
 }
 
 impl Default for Editor {
@@ -124,9 +193,15 @@ impl Default for Editor {
             replace: Keycode::Char('r').to_key().with_ctrl(),
             close_find_replace: Keycode::Esc.to_key(),
             request_completions: Keycode::Space.to_key().with_ctrl(),
+            show_hover: Self::default_show_hover(),
+            rename: Self::default_rename(),
             // I know it's stupid, but at this point I am out of keys on under my left hand
             //  normal people will use context options anyway
             reformat: Keycode::Char('l').to_key().with_ctrl(),
+            outline: Self::default_outline(),
+            next_usage: Self::default_next_usage(),
+            prev_usage: Self::default_prev_usage(),
+            history: Self::default_history(),
         }
     }
 }
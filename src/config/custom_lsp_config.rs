@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::lsp_client::lsp_command::LspCommand;
+use crate::tsw::lang_id::LangId;
+
+/*
+Language server not known to gladius, declared either in config.ron (global.custom_lsps) or inline
+in a project scope of .gladius_workspace.ron. Project scope refers to it by name, via handler_id.
+
+Extensions are routed to lang_id, on top of (and before) the built-in mapping, so for instance
+"tsx" can be served by typescript-language-server.
+ */
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CustomLspConfig {
+    pub name: String,
+    pub lang_id: LangId,
+    pub command: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initialization_options: Option<serde_json::Value>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+impl CustomLspConfig {
+    pub fn lsp_command(&self) -> LspCommand {
+        LspCommand {
+            path: self.command.clone(),
+            args: self.args.clone(),
+            env: self.env.clone(),
+            initialization_options: self.initialization_options.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_custom_lsp_config_de() {
+        let s = r#"(
+            name: "typescript",
+            lang_id: TYPESCRIPT,
+            command: "typescript-language-server",
+            args: ["--stdio"],
+            extensions: ["ts", "tsx"],
+        )"#;
+
+        let item = ron::from_str::<CustomLspConfig>(s).unwrap();
+        assert_eq!(item.name, "typescript");
+        assert_eq!(item.lang_id, LangId::TYPESCRIPT);
+        assert_eq!(item.env.len(), 0);
+        assert_eq!(item.initialization_options, None);
+
+        let command = item.lsp_command();
+        assert_eq!(command.path, PathBuf::from("typescript-language-server"));
+        assert_eq!(command.args, vec!["--stdio".to_string()]);
+    }
+
+    #[test]
+    fn test_custom_lsp_config_ser_de() {
+        let item = CustomLspConfig {
+            name: "haskell".to_string(),
+            lang_id: LangId::HASKELL,
+            command: PathBuf::from("haskell-language-server-wrapper"),
+            args: vec!["--lsp".to_string()],
+            env: maplit::btreemap! { "HLS_LOG".to_string() => "debug".to_string() },
+            initialization_options: Some(json!({ "haskell": { "formattingProvider": "ormolu" } })),
+            extensions: vec!["hs".to_string(), "lhs".to_string()],
+        };
+
+        let s = ron::ser::to_string_pretty(&item, ron::ser::PrettyConfig::new()).unwrap();
+        let read = ron::from_str::<CustomLspConfig>(&s).unwrap();
+        assert_eq!(read, item);
+    }
+}
@@ -4,6 +4,9 @@ use log::{debug, error, warn};
 use serde::{Deserialize, Serialize};
 use which;
 
+use crate::config::custom_lsp_config::CustomLspConfig;
+use crate::text::undo_tree::HistoryLimits;
+use crate::tsw::lang_id::LangId;
 use crate::unpack_or_e;
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -14,12 +17,38 @@ pub struct GlobalEditorOptions {
     pub python_lsp_path: Option<PathBuf>,
 
     pub inline_warnings_and_errors: bool,
+    // type and parameter name hints provided by language server
+    #[serde(default = "GlobalEditorOptions::default_inlay_hints")]
+    pub inlay_hints: bool,
 
     pub auto_indent_extensions: Vec<String>,
 
     // whether to convert tabs to spaces or not
     pub tabs_to_spaces: Option<u8>,
     pub guess_indent: bool,
+
+    // files in these languages are formatted by language server before saving. Opt-in.
+    #[serde(default)]
+    pub format_on_save: Vec<LangId>,
+
+    // language servers gladius has no built-in handler for. Project scopes refer to them by name.
+    #[serde(default)]
+    pub custom_lsps: Vec<CustomLspConfig>,
+
+    // keep undo history of saved files between sessions (under config dir)
+    #[serde(default = "GlobalEditorOptions::default_persistent_undo")]
+    pub persistent_undo: bool,
+
+    // periodically store unsaved buffers (under config dir), to offer them back after a crash
+    #[serde(default = "GlobalEditorOptions::default_crash_recovery")]
+    pub crash_recovery: bool,
+
+    // caps on undo history of a single buffer, oldest states are forgotten first
+    #[serde(default = "GlobalEditorOptions::default_history_max_states")]
+    pub history_max_states: usize,
+    // estimated, in bytes
+    #[serde(default = "GlobalEditorOptions::default_history_max_bytes")]
+    pub history_max_bytes: usize,
 }
 
 impl Default for GlobalEditorOptions {
@@ -30,6 +59,7 @@ impl Default for GlobalEditorOptions {
             golang_lsp_path: None,
             python_lsp_path: None,
             inline_warnings_and_errors: true,
+            inlay_hints: Self::default_inlay_hints(),
             auto_indent_extensions: vec![
                 "sh", "c", "h", "cpp", "cxx", "hpp", "hxx", "go", "hs", "js", "py", "rs", "toml", "ts", "yaml",
             ]
@@ -38,11 +68,45 @@ impl Default for GlobalEditorOptions {
             .collect(),
             tabs_to_spaces: Some(4),
             guess_indent: true,
+            format_on_save: vec![],
+            custom_lsps: vec![],
+            persistent_undo: true,
+            crash_recovery: true,
+            history_max_states: Self::default_history_max_states(),
+            history_max_bytes: Self::default_history_max_bytes(),
         }
     }
 }
 
 impl GlobalEditorOptions {
+    fn default_inlay_hints() -> bool {
+        true
+    }
+
+    fn default_persistent_undo() -> bool {
+        true
+    }
+
+    fn default_crash_recovery() -> bool {
+        true
+    }
+
+    fn default_history_max_states() -> usize {
+        10_000
+    }
+
+    fn default_history_max_bytes() -> usize {
+        64 * 1024 * 1024
+    }
+
+    pub fn history_limits(&self) -> HistoryLimits {
+        HistoryLimits::new(self.history_max_states, self.history_max_bytes)
+    }
+
+    pub fn get_custom_lsp(&self, name: &str) -> Option<&CustomLspConfig> {
+        self.custom_lsps.iter().find(|custom_lsp| custom_lsp.name == name)
+    }
+
     pub fn get_rust_lsp_path(&self) -> Option<PathBuf> {
         self.rust_lsp_path.clone().or_else(|| {
             debug!("discovering location of rust-analyzer");
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod custom_lsp_config;
 pub mod global_editor_options;
 pub mod load_error;
 pub mod save_error;
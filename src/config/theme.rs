@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -8,6 +9,8 @@ use crate::gladius::load_config::get_config_dir;
 use crate::io::style::{Effect, TextStyle};
 use crate::primitives::color::Color;
 use crate::primitives::tmtheme::TmTheme;
+use crate::tsw::tree_sitter_wrapper::HighlightItem;
+use crate::w7e::navcomp_provider::DocumentHighlightKind;
 use lazy_static::lazy_static;
 use log::{error, warn};
 use serde::{Deserialize, Serialize};
@@ -36,6 +39,29 @@ impl Theme {
         None
     }
 
+    pub fn highlight_color(&self, item: &HighlightItem) -> Option<Color> {
+        match self.ui.semantic_tokens.types.get(item.identifier.as_str()) {
+            Some(identifier) => self.name_to_color(identifier),
+            None => self.name_to_color(&item.identifier),
+        }
+    }
+
+    pub fn highlight_effect(&self, item: &HighlightItem) -> Option<Effect> {
+        self.ui
+            .semantic_tokens
+            .modifier_effects
+            .iter()
+            .find(|(modifier, _)| item.modifiers.iter().any(|m| m.as_str() == modifier))
+            .map(|(_, effect)| *effect)
+    }
+
+    pub fn occurrence_background(&self, kind: DocumentHighlightKind) -> Color {
+        match kind {
+            DocumentHighlightKind::Write => self.ui.occurrences.write_background,
+            DocumentHighlightKind::Read | DocumentHighlightKind::Text => self.ui.occurrences.read_background,
+        }
+    }
+
     pub fn default_text(&self, focused: bool) -> TextStyle {
         if focused {
             self.ui.focused
@@ -103,6 +129,63 @@ pub struct UiTheme {
     pub label_warning: TextStyle,
     pub label_error: TextStyle,
     pub label_type_annotation: TextStyle,
+
+    #[serde(default)]
+    pub semantic_tokens: SemanticTokensTheme,
+    // background of occurrences of symbol under cursor
+    #[serde(default)]
+    pub occurrences: OccurrencesTheme,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct OccurrencesTheme {
+    // also used for occurrences of unknown kind
+    pub read_background: Color,
+    pub write_background: Color,
+}
+
+impl Default for OccurrencesTheme {
+    fn default() -> Self {
+        OccurrencesTheme {
+            read_background: *OCCURRENCE_READ_BACKGROUND,
+            write_background: *OCCURRENCE_WRITE_BACKGROUND,
+        }
+    }
+}
+
+/*
+Semantic tokens come named the way language server names them. Types are translated to identifiers
+TmTheme understands (unlisted ones are passed as they are), and modifiers can add an effect, since
+"mutable variable" is still a variable and should keep its color.
+ */
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SemanticTokensTheme {
+    pub types: HashMap<String, String>,
+    // first matching modifier wins
+    pub modifier_effects: Vec<(String, Effect)>,
+}
+
+impl Default for SemanticTokensTheme {
+    fn default() -> Self {
+        SemanticTokensTheme {
+            types: [
+                ("macro", "function.macro"),
+                ("struct", "type"),
+                ("enum", "type"),
+                ("class", "type"),
+                ("interface", "type"),
+                ("typeParameter", "type"),
+                ("builtinType", "type.builtin"),
+                ("enumMember", "constant"),
+                ("parameter", "variable.parameter"),
+                ("method", "function.method"),
+            ]
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect(),
+            modifier_effects: vec![("unsafe".to_string(), Effect::Bold), ("mutable".to_string(), Effect::Underline)],
+        }
+    }
 }
 
 lazy_static! {
@@ -128,6 +211,8 @@ lazy_static! {
     static ref KETCHUP_COLOR: Color = ron::from_str("\"#B10B0B\"").unwrap();
     static ref BLACK_COLOR: Color = ron::from_str("\"#000000\"").unwrap();
     static ref GREY_COLOR: Color = ron::from_str("\"#999999\"").unwrap();
+    static ref OCCURRENCE_READ_BACKGROUND: Color = ron::from_str("\"#3C3836\"").unwrap();
+    static ref OCCURRENCE_WRITE_BACKGROUND: Color = ron::from_str("\"#4A3428\"").unwrap();
 }
 
 impl Default for UiTheme {
@@ -180,6 +265,8 @@ impl Default for UiTheme {
                 background: *GREY_COLOR,
                 effect: Default::default(),
             },
+            semantic_tokens: SemanticTokensTheme::default(),
+            occurrences: OccurrencesTheme::default(),
         }
     }
 }
@@ -25,6 +25,7 @@ use std::cmp::Ordering;
 use std::ops::Range;
 
 use log::{error, warn};
+use serde::{Deserialize, Serialize};
 
 use crate::cursor::cursor_set::CursorSet;
 use crate::primitives::has_invariant::HasInvariant;
@@ -42,7 +43,7 @@ pub enum CursorStatus {
     UnderCursor,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 /*
    Describes a selection of text.
@@ -109,7 +110,7 @@ pub fn default_word_determinant(buffer: &dyn TextBuffer, first_idx: usize, curre
     //         );
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Cursor {
     // selection. Invariant: anchor is either at begin or end of selection, never inside.
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::slice::{Iter, IterMut};
 
 use log::{error, warn};
+use serde::{Deserialize, Serialize};
 
 use crate::cursor::cursor::{
     default_word_determinant, BackwardWordDeterminant, Cursor, CursorStatus, ForwardWordDeterminant, NEWLINE_WIDTH, ZERO_CURSOR,
@@ -23,7 +24,7 @@ use crate::text::text_buffer::TextBuffer;
 // - (maybe) add "supercursor", which is always the first or the last, depending on which direction they were moved.
 //      it would help with anchoring.
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CursorSet {
     set: Vec<Cursor>,
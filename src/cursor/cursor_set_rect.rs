@@ -1,12 +1,13 @@
+use std::cmp::{max, min};
+
 use log::error;
 
 use crate::cursor::cursor::Cursor;
 use crate::cursor::cursor_set::CursorSet;
-use crate::primitives::rect::Rect;
-use crate::primitives::xy::XY;
+use crate::primitives::buffer_xy::BufferXY;
 use crate::text::text_buffer::TextBuffer;
 
-pub fn cursor_to_xy(c: &Cursor, buffer: &dyn TextBuffer) -> XY {
+pub fn cursor_to_xy(c: &Cursor, buffer: &dyn TextBuffer) -> BufferXY {
     let y = buffer.char_to_line(c.a).unwrap_or_else(|| {
         error!("failed translating cursor to XY (1), most likely wrong buffer provided. c: {:?}", c);
         0
@@ -20,16 +21,11 @@ pub fn cursor_to_xy(c: &Cursor, buffer: &dyn TextBuffer) -> XY {
         0
     });
 
-    if x > u16::MAX as usize || y > u16::MAX as usize {
-        error!("failed translating cursor to XY (3), x/y too big. c: {:?} x: {} y: {}", c, x, y);
-        XY::ZERO
-    } else {
-        XY::new(x as u16, y as u16)
-    }
+    BufferXY::new(x, y)
 }
 
 // returns begin of selection, end of selection and "which XY is the anchor": false => first one, true => second one
-pub fn cursor_to_xy_xy(c: &Cursor, buffer: &dyn TextBuffer) -> (XY, Option<XY>, bool) {
+pub fn cursor_to_xy_xy(c: &Cursor, buffer: &dyn TextBuffer) -> (BufferXY, Option<BufferXY>, bool) {
     if let Some(sel) = c.s {
         let begin = cursor_to_xy(&Cursor::new(sel.b), buffer);
         let end = cursor_to_xy(&Cursor::new(sel.e), buffer);
@@ -46,19 +42,22 @@ pub fn cursor_to_xy_xy(c: &Cursor, buffer: &dyn TextBuffer) -> (XY, Option<XY>,
     }
 }
 
-pub fn cursor_set_to_rect(cs: &CursorSet, buffer: &dyn TextBuffer) -> Rect {
+// returns upper-left and lower-right corners of a rect containing all cursors. Both corners are INCLUSIVE.
+pub fn cursor_set_to_rect(cs: &CursorSet, buffer: &dyn TextBuffer) -> (BufferXY, BufferXY) {
     if cs.set().is_empty() {
         error!("asked for cursor_rect on an empty cursor set, returning 0,0");
-        return Rect::ZERO;
+        return (BufferXY::ZERO, BufferXY::ZERO);
     }
 
     let first_cursor_as_xy = cursor_to_xy(&cs.set()[0], buffer);
-    let mut result = Rect::new(first_cursor_as_xy, XY::ZERO);
+    let mut upper_left = first_cursor_as_xy;
+    let mut lower_right = first_cursor_as_xy;
 
     for i in 1..cs.set().len() {
         let cursor_as_xy = cursor_to_xy(&cs.set()[i], buffer);
-        result.expand_to_contain(cursor_as_xy);
+        upper_left = BufferXY::new(min(upper_left.x, cursor_as_xy.x), min(upper_left.y, cursor_as_xy.y));
+        lower_right = BufferXY::new(max(lower_right.x, cursor_as_xy.x), max(lower_right.y, cursor_as_xy.y));
     }
 
-    result
+    (upper_left, lower_right)
 }
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock, Weak};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use log::{debug, error, warn};
+use ropey::Rope;
+
+use crate::experiments::buffer_register::{BufferRegister, BufferRegisterRef};
+use crate::text::recovery;
+use crate::text::recovery::{RecoveryEntry, RecoverySession};
+use crate::text::text_buffer::TextBuffer;
+use crate::widgets::main_view::main_view::BufferId;
+
+/*
+Periodically stores buffers with unsaved changes, so they can be recovered after a crash (see
+text/recovery.rs). A buffer is written again only if its contents changed since the last time, and its
+entry is removed once it's saved or closed.
+
+Like NavCompSupervisor, it holds only a weak reference to the register, so if the editor goes down,
+autosaver follows and leaves its files behind - that's the point. On a regular exit AutosaverHandle::stop
+should be called instead, which also cleans up. Session lock is held as long as autosaver lives, so
+other instances in the same workspace don't take its files for crash leftovers.
+ */
+pub struct Autosaver {
+    buffer_register: Weak<RwLock<BufferRegister>>,
+    session: RecoverySession,
+
+    // what's currently stored for each buffer
+    stored: HashMap<BufferId, Rope>,
+}
+
+pub struct AutosaverHandle {
+    stop_sender: Sender<()>,
+    join_handle: JoinHandle<()>,
+}
+
+impl AutosaverHandle {
+    // Stops autosaver and removes everything it stored, nothing is left to recover after regular exit.
+    pub fn stop(self) {
+        if self.stop_sender.send(()).is_err() {
+            warn!("autosaver is already gone");
+        }
+
+        if self.join_handle.join().is_err() {
+            error!("autosaver panicked");
+        }
+    }
+}
+
+impl Autosaver {
+    pub const INTERVAL: Duration = Duration::from_secs(5);
+
+    pub fn new(buffer_register: &BufferRegisterRef, session: RecoverySession) -> Self {
+        Autosaver {
+            buffer_register: Arc::downgrade(buffer_register),
+            session,
+            stored: HashMap::new(),
+        }
+    }
+
+    pub fn start(self) -> AutosaverHandle {
+        let (stop_sender, stop_receiver) = crossbeam_channel::bounded::<()>(1);
+        let join_handle = thread::spawn(move || self.run(stop_receiver));
+
+        AutosaverHandle { stop_sender, join_handle }
+    }
+
+    fn run(mut self, stop_receiver: Receiver<()>) {
+        loop {
+            match stop_receiver.recv_timeout(Self::INTERVAL) {
+                Err(RecvTimeoutError::Timeout) => {}
+                Ok(_) => {
+                    self.session.remove();
+                    debug!("autosaver terminates");
+                    return;
+                }
+                // handle got dropped without stop, so it's not a regular exit
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let buffer_register = match self.buffer_register.upgrade() {
+                Some(register) => register,
+                None => break,
+            };
+
+            self.autosave(&buffer_register);
+        }
+
+        debug!("autosaver terminates");
+    }
+
+    /*
+    A single round: stores buffers that changed since last round, removes entries of the ones that got
+    saved or closed. If register or a buffer is locked, it's skipped till next round.
+     */
+    pub fn autosave(&mut self, buffer_register: &RwLock<BufferRegister>) {
+        let mut dirty: Vec<(BufferId, Option<PathBuf>, Rope, Option<RecoveryEntry>)> = Vec::new();
+
+        // locks are held only as long as it takes to clone ropes, writing happens after
+        {
+            let register = match buffer_register.try_read() {
+                Ok(register) => register,
+                Err(_) => {
+                    debug!("buffer register is busy, autosave postponed");
+                    return;
+                }
+            };
+
+            for (document_identifier, bsr) in register.iter() {
+                let buffer = match bsr.lock() {
+                    Some(buffer) => buffer,
+                    None => continue,
+                };
+
+                if buffer.is_saved() {
+                    continue;
+                }
+
+                let buffer_id = document_identifier.buffer_id;
+                let path = buffer.get_path().map(|spath| spath.relative_path());
+                let rope = buffer.text().rope().clone();
+
+                let entry = if self.stored.get(&buffer_id) == Some(&rope) {
+                    None
+                } else {
+                    Some(RecoveryEntry {
+                        buffer_id: buffer_id.to_string(),
+                        path: path.clone(),
+                        text: rope.to_string(),
+                        cursor_set: buffer.text().first_cursor_set().cloned(),
+                    })
+                };
+
+                dirty.push((buffer_id, path, rope, entry));
+            }
+        }
+
+        let no_longer_dirty: Vec<BufferId> = self
+            .stored
+            .keys()
+            .filter(|buffer_id| !dirty.iter().any(|item| item.0 == **buffer_id))
+            .copied()
+            .collect();
+
+        for buffer_id in no_longer_dirty {
+            match recovery::remove(self.session.dir(), &buffer_id.to_string()) {
+                Ok(_) => {
+                    self.stored.remove(&buffer_id);
+                }
+                Err(e) => {
+                    error!("failed to remove recovery entry of {}, because {}", buffer_id, e);
+                }
+            }
+        }
+
+        for (buffer_id, path, rope, entry_op) in dirty {
+            if let Some(entry) = entry_op {
+                match recovery::store(self.session.dir(), &entry) {
+                    Ok(_) => {
+                        self.stored.insert(buffer_id, rope);
+                    }
+                    Err(e) => {
+                        error!("failed to store recovery entry of {} ({:?}), because {}", buffer_id, path, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::experiments::autosaver::Autosaver;
+    use crate::experiments::buffer_register::BufferRegisterRef;
+    use crate::mocks::mock_providers_builder::MockProvidersBuilder;
+    use crate::text::recovery::{load_all, RecoverySession};
+
+    fn temp_recovery_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gladius_autosaver_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn new_autosaver(buffer_register: &BufferRegisterRef, recovery_dir: &Path) -> (Autosaver, PathBuf) {
+        let session = RecoverySession::new(recovery_dir).unwrap();
+        let dir = session.dir().to_path_buf();
+        (Autosaver::new(buffer_register, session), dir)
+    }
+
+    #[test]
+    fn autosaver_stores_unsaved_buffers_test() {
+        let recovery_dir = temp_recovery_dir("unsaved");
+        let providers = MockProvidersBuilder::default().build().providers;
+        let buffer_register = providers.buffer_register().clone();
+
+        let bsr = buffer_register.write().unwrap().open_new_file(&providers);
+        let buffer_id = bsr.document_identifier().buffer_id.to_string();
+
+        let (mut autosaver, dir) = new_autosaver(&buffer_register, &recovery_dir);
+
+        // empty new buffer counts as saved
+        autosaver.autosave(&buffer_register);
+        assert!(load_all(&dir).unwrap().is_empty());
+
+        bsr.lock_rw().unwrap().set_text("scratch");
+        autosaver.autosave(&buffer_register);
+
+        let entries = load_all(&dir).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].buffer_id, buffer_id);
+        assert_eq!(entries[0].path, None);
+        assert_eq!(entries[0].text, "scratch");
+
+        bsr.lock_rw().unwrap().set_text("");
+        autosaver.autosave(&buffer_register);
+        assert!(load_all(&dir).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&recovery_dir);
+    }
+
+    #[test]
+    fn autosaver_cleans_up_on_stop_test() {
+        let recovery_dir = temp_recovery_dir("stop");
+        let providers = MockProvidersBuilder::default().build().providers;
+        let buffer_register = providers.buffer_register().clone();
+
+        let bsr = buffer_register.write().unwrap().open_new_file(&providers);
+        bsr.lock_rw().unwrap().set_text("scratch");
+
+        let (mut autosaver, dir) = new_autosaver(&buffer_register, &recovery_dir);
+        autosaver.autosave(&buffer_register);
+        assert_eq!(load_all(&dir).unwrap().len(), 1);
+
+        autosaver.start().stop();
+        assert!(!dir.exists());
+
+        let _ = std::fs::remove_dir_all(&recovery_dir);
+    }
+}
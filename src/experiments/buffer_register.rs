@@ -5,6 +5,7 @@ use std::time::{Duration, Instant};
 
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use log::{debug, error, warn};
+use ropey::Rope;
 
 use crate::fs::path::SPath;
 use crate::fs::read_error::ReadError;
@@ -12,7 +13,11 @@ use crate::gladius::providers::Providers;
 use crate::primitives::has_invariant::HasInvariant;
 use crate::text::buffer_state::BufferState;
 use crate::text::text_buffer::TextBuffer;
+use crate::text::undo_cache;
+use crate::unpack_or_e;
 use crate::w7e::buffer_state_shared_ref::BufferSharedRef;
+use crate::w7e::navcomp_provider::StupidSubstituteMessage;
+use crate::widget::widget::get_new_widget_id;
 use crate::widgets::editor_widget::label::label::Label;
 use crate::widgets::editor_widget::label::labels_provider::LabelsProvider;
 use crate::widgets::main_view::main_view::BufferId;
@@ -71,7 +76,8 @@ impl BufferRegister {
             doc_id.clone(),
             Some(self.debug_channel.0.clone()),
             providers.config().global.tabs_to_spaces,
-        );
+        )
+        .with_history_limits(providers.config().global.history_limits());
 
         let bsr = BufferSharedRef::new_from_buffer(buffer_state);
 
@@ -132,9 +138,22 @@ impl BufferRegister {
                 Some(self.debug_channel.0.clone()),
                 providers.config().global.tabs_to_spaces,
             )
+            .with_history_limits(providers.config().global.history_limits())
             .with_text(buffer_str)
             .with_maked_as_saved();
 
+            if let Some(cache_dir) = undo_cache::cache_dir(providers.config()) {
+                match undo_cache::load(&cache_dir, &path.absolute_path(), &buffer_bytes) {
+                    Ok(Some(snapshot)) => {
+                        buffer_state = buffer_state.with_undo_snapshot(snapshot);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("failed to load undo history of {}, because {}", &path, e);
+                    }
+                }
+            }
+
             if providers.config().global.guess_indent {
                 buffer_state.guess_formatting_whitespace();
             }
@@ -152,6 +171,119 @@ impl BufferRegister {
         }
     }
 
+    /*
+    Applies edits of multiple files (like a result of rename). Buffers that are open are edited in
+    place, so the change can be undone. Files that are not open are edited on disk, without opening
+    them. Either way, navcomp learns about it.
+
+    Edits in buffers go through a temporary cursor set, removed right after.
+    Returns true iff all files were updated.
+     */
+    pub fn apply_workspace_edit(&mut self, providers: &Providers, file_edits: &[(SPath, Vec<StupidSubstituteMessage>)]) -> bool {
+        let mut success = true;
+
+        for (path, edits) in file_edits.iter() {
+            // edits are expressed in coordinates of unchanged file, so I apply them back to front.
+            let mut edits = edits.clone();
+            edits.sort_by_key(|edit| std::cmp::Reverse(edit.stupid_range.0));
+
+            match self.get_buffer_ref_from_path(path) {
+                Some(bsr) => {
+                    let mut buffer = match bsr.lock_rw() {
+                        None => {
+                            error!("failed locking buffer {}", path);
+                            success = false;
+                            continue;
+                        }
+                        Some(lock) => lock,
+                    };
+
+                    let widget_id = get_new_widget_id();
+                    if !buffer.add_temporary_cursor_set(widget_id) {
+                        error!("failed adding cursor set to buffer {}", path);
+                        success = false;
+                        continue;
+                    }
+
+                    if !buffer.apply_stupid_substitute_messages(widget_id, &edits, 1) {
+                        error!("failed applying edits to buffer {}", path);
+                        success = false;
+                    }
+
+                    buffer.remove_cursor_sets(widget_id);
+
+                    let edit_event_op = buffer.take_edit_event();
+                    if let (Some(navcomp_group), Some(edit_event)) = (providers.navcomp_group().try_read().ok(), edit_event_op) {
+                        if let Some(navcomp) = navcomp_group.get_navcomp_for(path) {
+                            navcomp.submit_edit_event(path, edit_event);
+                        }
+                    }
+                }
+                None => {
+                    if !Self::apply_edits_on_disk(providers, path, &edits) {
+                        success = false;
+                        continue;
+                    }
+
+                    if let Ok(navcomp_group) = providers.navcomp_group().try_read() {
+                        if let Some(navcomp) = navcomp_group.get_navcomp_for(path) {
+                            navcomp.file_changed_on_disk(path);
+                        }
+                    }
+                }
+            }
+        }
+
+        success
+    }
+
+    // Expects edits sorted back to front.
+    fn apply_edits_on_disk(providers: &Providers, path: &SPath, edits: &[StupidSubstituteMessage]) -> bool {
+        let mut rope = match providers.fsf().blocking_read_entire_file(path).map(String::from_utf8) {
+            Ok(Ok(s)) => Rope::from_str(&s),
+            Ok(Err(e)) => {
+                error!("failed loading file {}, because utf8 error {}", path, e);
+                return false;
+            }
+            Err(e) => {
+                error!("failed to read {}, because {}", path, e);
+                return false;
+            }
+        };
+
+        for edit in edits.iter() {
+            let begin = unpack_or_e!(
+                edit.stupid_range.0.to_real_cursor(&rope),
+                false,
+                "failed conversion to real cursor in {}",
+                path
+            );
+            let end = unpack_or_e!(
+                edit.stupid_range.1.to_real_cursor(&rope),
+                false,
+                "failed conversion to real cursor in {}",
+                path
+            );
+
+            if begin.a < end.a && !TextBuffer::remove(&mut rope, begin.a, end.a) {
+                error!("failed removing [{}..{}) from {}", begin.a, end.a, path);
+                return false;
+            }
+            if !edit.substitute.is_empty() && !TextBuffer::insert_block(&mut rope, begin.a, &edit.substitute) {
+                error!("failed inserting at {} in {}", begin.a, path);
+                return false;
+            }
+        }
+
+        match providers.fsf().overwrite_with_str(path, &rope.to_string(), true) {
+            Ok(_) => true,
+            Err(e) => {
+                error!("failed writing {}, because {:?}", path, e);
+                false
+            }
+        }
+    }
+
     pub fn close_buffer(&mut self, document_identifier: &DocumentIdentifier) -> bool {
         /*
         This method is a little paranoid over-protected, because leaking references to buffers would
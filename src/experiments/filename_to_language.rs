@@ -49,12 +49,18 @@ lazy_static! {
     };
 }
 
-pub fn filename_to_language(path: &SPath) -> Option<LangId> {
+pub fn filename_extension(path: &SPath) -> Option<&str> {
     path.last_file_name()
         .map(|f| f.extension())
         .flatten()
         .map(|ext| ext.to_str())
         .flatten()
-        .map(|ext| EXT_TO_LANGUAGE.get(ext).map(|p| *p))
-        .flatten()
+}
+
+/*
+Built-in extensions only. Extensions claimed by custom language servers are workspace specific,
+see NavCompGroup::lang_for.
+ */
+pub fn filename_to_language(path: &SPath) -> Option<LangId> {
+    filename_extension(path).and_then(|ext| EXT_TO_LANGUAGE.get(ext).copied())
 }
@@ -1,4 +1,5 @@
 pub mod array_streaming_iterator;
+pub mod autosaver;
 pub mod buffer_register;
 pub mod clipboard;
 pub mod filename_to_language;
@@ -232,6 +232,31 @@ impl MockFS {
         Ok(len_bytes)
     }
 
+    /*
+    Copies files of a real directory that are not in mock fs yet, paths are relative to both roots.
+     */
+    pub fn add_missing_from_real<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        fn visit(mock_fs: &mut MockFS, real_root: &Path, relative: &Path) -> io::Result<()> {
+            for entry in fs::read_dir(real_root.join(relative))? {
+                let entry = entry?;
+                let relative_path = relative.join(entry.file_name());
+
+                if entry.file_type()?.is_dir() {
+                    visit(mock_fs, real_root, &relative_path)?;
+                } else if !mock_fs.exists(&relative_path) {
+                    let bytes = fs::read(entry.path())?;
+                    mock_fs
+                        .add_file(&relative_path, bytes)
+                        .map_err(|_| io::Error::other("failed creating file in mockfs"))?;
+                }
+            }
+
+            Ok(())
+        }
+
+        visit(self, path.as_ref(), Path::new(""))
+    }
+
     pub fn generate_from_real<P: AsRef<Path>>(path: P) -> io::Result<Self> {
         let path: &Path = path.as_ref();
         let path = if path.is_absolute() {
@@ -9,9 +9,11 @@ use crate::config::config::ConfigRef;
 use crate::config::theme::Theme;
 use crate::experiments::buffer_register::{BufferRegister, BufferRegisterRef};
 use crate::experiments::clipboard::ClipboardRef;
+use crate::experiments::filename_to_language::filename_to_language;
 use crate::fs::fsf_ref::FsfRef;
 use crate::fs::path::SPath;
 use crate::gladius::navcomp_loader::NavCompLoader;
+use crate::tsw::lang_id::LangId;
 use crate::tsw::tree_sitter_wrapper::TreeSitterWrapper;
 use crate::w7e::navcomp_group::NavCompGroup;
 use crate::widgets::editor_widget::label::label::Label;
@@ -103,6 +105,14 @@ impl Providers {
         &self.navcomp_group
     }
 
+    // see NavCompGroup::lang_for, falls back to built-in extensions if group is busy
+    pub fn lang_for(&self, path: &SPath) -> Option<LangId> {
+        match self.navcomp_group.try_read() {
+            Ok(lock) => lock.lang_for(path),
+            Err(_) => filename_to_language(path),
+        }
+    }
+
     // this method should not be here, but I have no time to bring Arc<RwLock<NavCompGroup> and Arc<Box<dyn LabelsProvider>> under a common interface, so I cheat by hiding them behind a facade.
     // TODO I give up, there should be no allocation here but I just have no time to fight the borrowchecker.
     // path is optional, there might be labels in unsaved file
@@ -43,6 +43,8 @@ impl NavCompLoader for RealNavCompLoader {
                 buffer_register_ref,
                 &handler_id,
                 project_scope.path.clone(),
+                project_scope.handler_settings.clone(),
+                project_scope.custom_lsp.clone(),
                 navcomp_tick_sender.clone(),
             )?),
         }
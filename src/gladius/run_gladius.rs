@@ -5,6 +5,7 @@ use crossbeam_channel::select;
 use crossterm::terminal::{BeginSynchronizedUpdate, EndSynchronizedUpdate};
 use log::{debug, error, warn};
 
+use crate::experiments::autosaver::Autosaver;
 use crate::experiments::screen_shot::screenshot;
 use crate::experiments::screenspace::Screenspace;
 use crate::gladius::msg::GladiusMsg;
@@ -15,9 +16,13 @@ use crate::io::keys::Keycode;
 use crate::io::output::FinalOutput;
 use crate::lsp_client::diagnostic::DiagnosticSeverity;
 use crate::primitives::helpers::get_next_filename;
+use crate::text::recovery;
+use crate::text::recovery::RecoverySession;
+use crate::text::undo_cache;
 use crate::w7e::handler_load_error::HandlerLoadError;
 use crate::w7e::inspector::{inspect_workspace, InspectError};
 use crate::w7e::navcomp_group::NavCompTick;
+use crate::w7e::navcomp_supervisor::NavCompSupervisor;
 use crate::w7e::workspace::WORKSPACE_FILE_NAME;
 use crate::w7e::workspace::{LoadError, ScopeLoadErrors, Workspace};
 use crate::widget::widget::Widget;
@@ -117,7 +122,17 @@ pub fn run_gladius<I: Input, O: FinalOutput>(providers: Providers, input: I, mut
 
     // error!("c");
 
+    let _supervisor_handle = NavCompSupervisor::start(providers.navcomp_group(), providers.buffer_register());
+
+    // before any file is opened, so no cache that is about to be used is gone in the middle of loading
+    if let Some(cache_dir) = undo_cache::cache_dir(providers.config()) {
+        if let Err(e) = undo_cache::prune(&cache_dir, undo_cache::MAX_CACHE_AGE, undo_cache::MAX_CACHE_BYTES) {
+            error!("failed to prune undo cache {:?}, because {}", cache_dir, e);
+        }
+    }
+
     let mut main_view = MainView::new(providers.clone());
+    main_view.set_tasks(workspace.tasks());
     for f in files.iter() {
         if !providers
             .fsf()
@@ -129,11 +144,26 @@ pub fn run_gladius<I: Input, O: FinalOutput>(providers: Providers, input: I, mut
         }
     }
 
+    // sessions of other running instances are locked, so only crash leftovers are offered
+    let recovery_dir_op = recovery::recovery_dir(providers.config(), providers.fsf().root_path_buf());
+    let autosaver_handle_op = recovery_dir_op.and_then(|dir| {
+        main_view.offer_recovered_buffers(recovery::orphaned_sessions(&dir));
+
+        match RecoverySession::new(&dir) {
+            Ok(session) => Some(Autosaver::new(providers.buffer_register(), session).start()),
+            Err(e) => {
+                error!("failed to start recovery session in {:?}, autosave is off, because {}", dir, e);
+                None
+            }
+        }
+    });
+
     // error!("d");
 
     let mut recorded_input: Vec<InputEvent> = Vec::new();
 
     let nav_comp_tick_receiver = providers.navcomp_group().try_read().map(|lock| lock.recvr().clone()).unwrap(); // TODO unwrap
+    let task_tick_receiver = main_view.task_tick_receiver().clone();
 
     // Genesis
     'main: loop {
@@ -222,11 +252,23 @@ pub fn run_gladius<I: Input, O: FinalOutput>(providers: Providers, input: I, mut
                     }
                 }
             }
+
+            // nothing to do here, but redraw with new task output
+            recv(task_tick_receiver) -> _ => {
+                if providers.is_recording() {
+                    recorded_input.push(InputEvent::Tick);
+                }
+            }
         }
     }
 
     // error!("k");
 
+    // clean exit, so there's nothing to recover
+    if let Some(autosaver_handle) = autosaver_handle_op {
+        autosaver_handle.stop();
+    }
+
     if providers.is_recording() {
         let bytes = match ron::to_string(&recorded_input) {
             Ok(b) => b,
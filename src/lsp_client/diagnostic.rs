@@ -19,14 +19,17 @@ pub struct Diagnostic {
     pub message: String,
     pub severity: DiagnosticSeverity,
     pub line_no_1b: usize,
+    // kept as received, because code actions want diagnostics sent back to server.
+    pub lsp_diagnostic: lsp_types::Diagnostic,
 }
 
 impl From<lsp_types::Diagnostic> for Diagnostic {
     fn from(diag: lsp_types::Diagnostic) -> Self {
         Diagnostic {
-            message: diag.message,
+            message: diag.message.clone(),
             severity: diag.severity.map(DiagnosticSeverity::from).unwrap_or(DiagnosticSeverity::Info),
             line_no_1b: (diag.range.start.line + 1) as usize,
+            lsp_diagnostic: diag,
         }
     }
 }
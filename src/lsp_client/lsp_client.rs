@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Formatter};
 use std::io::BufRead;
 use std::io::BufReader;
 use std::path::PathBuf;
 use std::process::{ChildStderr, ChildStdout, Stdio};
-use std::sync::{Arc, LockResult, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread::JoinHandle;
 use std::time::Duration;
 use std::{process, thread};
@@ -17,10 +17,12 @@ use url::Url;
 use crate::lsp_client::debug_helpers::lsp_debug_save;
 use crate::lsp_client::diagnostic;
 use crate::lsp_client::diagnostic::{Diagnostic, DiagnosticSeverity};
+use crate::lsp_client::lsp_command::LspCommand;
 use crate::lsp_client::lsp_io_error::LspIOError;
 use crate::lsp_client::lsp_notification::LspServerNotification;
 use crate::lsp_client::lsp_read::read_lsp;
 use crate::lsp_client::lsp_read_error::LspReadError;
+use crate::lsp_client::lsp_request_dispatcher::{ApplyEditRequests, LspApplyEditRequest, LspRequestDispatcher, SharedStdin};
 use crate::lsp_client::lsp_write::{internal_send_notification, internal_send_notification_no_params, internal_send_request};
 use crate::lsp_client::lsp_write_error::LspWriteError;
 use crate::lsp_client::promise::LSPPromise;
@@ -54,21 +56,44 @@ pub struct LSPFileDescriptor {
 
 pub type FilesStore = Arc<RwLock<HashMap<Url, LSPFileDescriptor>>>;
 
+/*
+Work done progress reported by server ($/progress), like indexing. Entry is created when progress
+begins and removed when it ends.
+ */
+#[derive(Debug, Clone)]
+pub struct LspProgress {
+    pub title: String,
+    pub message: Option<String>,
+    pub percentage: Option<u32>,
+}
+
+// token -> progress, ordered so it's displayed in a stable way
+pub type ProgressStore = Arc<RwLock<BTreeMap<String, LspProgress>>>;
+
 /*
 Represents a single LSP server connection
  */
 pub struct LspWrapper {
-    server_path: PathBuf,
+    command: LspCommand,
     workspace_root_path: PathBuf,
     language: LangId,
     child: process::Child,
+    stdin: SharedStdin,
+    // served as answers to workspace/configuration, kept so restarted server gets them too.
+    settings: Option<serde_json::Value>,
 
     //TODO the common state should probably be merged to avoid concurrency issues. It's not like I
     // will be sending multiple edit events concurrently.
     ids: Arc<RwLock<IdToCallInfo>>,
     files: FilesStore,
+    // workspace/applyEdit requests of server, waiting to be applied.
+    apply_edit_requests: ApplyEditRequests,
 
     curr_id: u64,
+
+    // negotiated in initialize(), until then (and if server does not say anything) we do full sync.
+    text_document_sync_kind: lsp_types::TextDocumentSyncKind,
+
     reader_handle: JoinHandle<Result<(), LspReadError>>,
     logger_handle: JoinHandle<Result<(), ()>>,
 
@@ -84,21 +109,31 @@ impl LspWrapper {
      */
     // TODO make result
     pub fn new(
-        lsp_path: PathBuf,
+        command: LspCommand,
         workspace_root: PathBuf,
         language: LangId,
+        settings: Option<serde_json::Value>,
+        progress: ProgressStore,
         tick_sender: NavCompTickSender,
         error_sink: Sender<LspReadError>,
     ) -> Option<LspWrapper> {
         debug!("starting LspWrapper for directory {:?}", &workspace_root);
-        let mut child = process::Command::new(lsp_path.as_os_str())
-            // .args(&["--cli"])
+        let mut child = command
+            .to_process_command()
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .ok()?;
 
+        let stdin: SharedStdin = match child.stdin.take() {
+            None => {
+                error!("failed acquiring stdin");
+                return None;
+            }
+            Some(i) => Arc::new(Mutex::new(i)),
+        };
+
         let stdout = match child.stdout.take() {
             None => {
                 error!("failed acquiring stdout");
@@ -117,13 +152,19 @@ impl LspWrapper {
 
         let ids = Arc::new(RwLock::new(IdToCallInfo::default()));
         let files = Arc::new(RwLock::new(HashMap::default()));
+        let apply_edit_requests = ApplyEditRequests::default();
+        let dispatcher = LspRequestDispatcher::new(stdin.clone(), settings.clone(), apply_edit_requests.clone());
 
         let reader_identifier: String = format!(
             "{}-{}",
-            lsp_path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_else(|| {
-                error!("failed to unwrap filename");
-                "noname".to_string()
-            }),
+            command
+                .path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_else(|| {
+                    error!("failed to unwrap filename");
+                    "noname".to_string()
+                }),
             process::id()
         );
 
@@ -131,19 +172,24 @@ impl LspWrapper {
 
         let ids_clone = ids.clone();
         let files_clone = files.clone();
-        let reader_handle: JoinHandle<Result<(), LspReadError>> =
-            thread::spawn(move || Self::lsp_reader_thread_main(reader_identifier, ids_clone, files_clone, stdout, tick_sender));
+        let reader_handle: JoinHandle<Result<(), LspReadError>> = thread::spawn(move || {
+            Self::lsp_reader_thread_main(reader_identifier, ids_clone, files_clone, dispatcher, progress, stdout, tick_sender)
+        });
 
         let logger_handle: JoinHandle<Result<(), ()>> = thread::spawn(|| Self::logger_thread(reader_identifier2, stderr));
 
         Some(LspWrapper {
-            server_path: lsp_path,
+            command,
             workspace_root_path: workspace_root,
             language,
             child,
+            stdin,
+            settings,
             ids,
             files,
+            apply_edit_requests,
             curr_id: 1,
+            text_document_sync_kind: lsp_types::TextDocumentSyncKind::FULL,
             reader_handle,
             logger_handle,
             error_sink,
@@ -191,30 +237,19 @@ impl LspWrapper {
             warn!("id reuse, not handled properly");
         }
 
-        if let Some(stdin) = self.child.stdin.as_mut() {
-            internal_send_request::<R, _>(stdin, new_id.clone(), params)?;
-            Ok(LSPPromise::<R>::new(receiver, self.error_sink.clone()))
-        } else {
-            Err(LspWriteError::BrokenPipe)
-        }
+        let mut stdin = self.stdin.lock()?;
+        internal_send_request::<R, _>(&mut *stdin, new_id.clone(), params)?;
+        Ok(LSPPromise::<R>::new(receiver, self.error_sink.clone()))
     }
 
     fn send_notification_no_params<N: lsp_types::notification::Notification>(&mut self) -> Result<(), LspWriteError> {
-        if let Some(stdin) = self.child.stdin.as_mut() {
-            internal_send_notification_no_params::<N, _>(stdin)?;
-            Ok(())
-        } else {
-            Err(LspWriteError::BrokenPipe.into())
-        }
+        let mut stdin = self.stdin.lock()?;
+        internal_send_notification_no_params::<N, _>(&mut *stdin)
     }
 
     fn send_notification<N: lsp_types::notification::Notification>(&mut self, params: N::Params) -> Result<(), LspWriteError> {
-        if let Some(stdin) = self.child.stdin.as_mut() {
-            internal_send_notification::<N, _>(stdin, params)?;
-            Ok(())
-        } else {
-            Err(LspWriteError::BrokenPipe.into())
-        }
+        let mut stdin = self.stdin.lock()?;
+        internal_send_notification::<N, _>(&mut *stdin, params)
     }
 
     #[allow(deprecated)]
@@ -242,17 +277,31 @@ impl LspWrapper {
             // process_id: None,
             root_path: None,
             root_uri,
-            initialization_options: None,
+            initialization_options: self.command.initialization_options.clone(),
             capabilities: lsp_types::ClientCapabilities {
                 workspace: Some(lsp_types::WorkspaceClientCapabilities {
-                    apply_edit: None,
-                    workspace_edit: None,
+                    apply_edit: Some(true),
+                    workspace_edit: Some(lsp_types::WorkspaceEditClientCapabilities {
+                        document_changes: Some(true),
+                        resource_operations: None,
+                        failure_handling: None,
+                        normalizes_line_endings: None,
+                        change_annotation_support: None,
+                    }),
                     did_change_configuration: None,
                     did_change_watched_files: None,
-                    symbol: None,
-                    execute_command: None,
+                    symbol: Some(lsp_types::WorkspaceSymbolClientCapabilities {
+                        dynamic_registration: None,
+                        symbol_kind: None,
+                        tag_support: None,
+                        // I need ranges to place the cursor, so no lazy resolution.
+                        resolve_support: None,
+                    }),
+                    execute_command: Some(lsp_types::DynamicRegistrationClientCapabilities {
+                        dynamic_registration: None,
+                    }),
                     workspace_folders: None,
-                    configuration: None,
+                    configuration: Some(true),
                     semantic_tokens: None,
                     code_lens: None,
                     file_operations: None,
@@ -264,37 +313,149 @@ impl LspWrapper {
                 text_document: Some(lsp_types::TextDocumentClientCapabilities {
                     synchronization: None,
                     completion: None,
-                    hover: None,
-                    signature_help: None,
+                    hover: Some(lsp_types::HoverClientCapabilities {
+                        dynamic_registration: None,
+                        content_format: Some(vec![lsp_types::MarkupKind::PlainText, lsp_types::MarkupKind::Markdown]),
+                    }),
+                    signature_help: Some(lsp_types::SignatureHelpClientCapabilities {
+                        dynamic_registration: None,
+                        signature_information: Some(lsp_types::SignatureInformationSettings {
+                            documentation_format: Some(vec![lsp_types::MarkupKind::PlainText]),
+                            parameter_information: Some(lsp_types::ParameterInformationSettings {
+                                label_offset_support: Some(true),
+                            }),
+                            active_parameter_support: Some(true),
+                        }),
+                        context_support: None,
+                    }),
                     references: None,
-                    document_highlight: None,
-                    document_symbol: None,
-                    formatting: None,
-                    range_formatting: None,
+                    document_highlight: Some(lsp_types::DocumentHighlightClientCapabilities {
+                        dynamic_registration: None,
+                    }),
+                    document_symbol: Some(lsp_types::DocumentSymbolClientCapabilities {
+                        dynamic_registration: None,
+                        symbol_kind: None,
+                        // otherwise outline would be flat.
+                        hierarchical_document_symbol_support: Some(true),
+                        tag_support: None,
+                    }),
+                    formatting: Some(lsp_types::DocumentFormattingClientCapabilities {
+                        dynamic_registration: None,
+                    }),
+                    range_formatting: Some(lsp_types::DocumentRangeFormattingClientCapabilities {
+                        dynamic_registration: None,
+                    }),
                     on_type_formatting: None,
                     declaration: None,
                     definition: None,
                     type_definition: None,
                     implementation: None,
-                    code_action: None,
+                    code_action: Some(lsp_types::CodeActionClientCapabilities {
+                        dynamic_registration: None,
+                        // without this, servers are allowed to answer with Commands only.
+                        code_action_literal_support: Some(lsp_types::CodeActionLiteralSupport {
+                            code_action_kind: lsp_types::CodeActionKindLiteralSupport {
+                                value_set: vec![
+                                    lsp_types::CodeActionKind::EMPTY.as_str().to_string(),
+                                    lsp_types::CodeActionKind::QUICKFIX.as_str().to_string(),
+                                    lsp_types::CodeActionKind::REFACTOR.as_str().to_string(),
+                                    lsp_types::CodeActionKind::REFACTOR_EXTRACT.as_str().to_string(),
+                                    lsp_types::CodeActionKind::REFACTOR_INLINE.as_str().to_string(),
+                                    lsp_types::CodeActionKind::REFACTOR_REWRITE.as_str().to_string(),
+                                    lsp_types::CodeActionKind::SOURCE.as_str().to_string(),
+                                    lsp_types::CodeActionKind::SOURCE_ORGANIZE_IMPORTS.as_str().to_string(),
+                                ],
+                            },
+                        }),
+                        is_preferred_support: None,
+                        disabled_support: None,
+                        data_support: None,
+                        // I do not implement codeAction/resolve, so servers have to send edits upfront.
+                        resolve_support: None,
+                        honors_change_annotations: None,
+                    }),
                     code_lens: None,
                     document_link: None,
                     color_provider: None,
-                    rename: None,
+                    rename: Some(lsp_types::RenameClientCapabilities {
+                        dynamic_registration: None,
+                        prepare_support: None,
+                        prepare_support_default_behavior: None,
+                        honors_change_annotations: None,
+                    }),
                     publish_diagnostics: None,
                     folding_range: None,
                     selection_range: None,
                     linked_editing_range: None,
-                    call_hierarchy: None,
-                    semantic_tokens: None,
+                    call_hierarchy: Some(lsp_types::CallHierarchyClientCapabilities {
+                        dynamic_registration: None,
+                    }),
+                    semantic_tokens: Some(lsp_types::SemanticTokensClientCapabilities {
+                        dynamic_registration: None,
+                        requests: lsp_types::SemanticTokensClientCapabilitiesRequests {
+                            // visible range would be enough, but I cache whole file and ask for deltas.
+                            range: None,
+                            full: Some(lsp_types::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        },
+                        token_types: vec![
+                            lsp_types::SemanticTokenType::NAMESPACE,
+                            lsp_types::SemanticTokenType::TYPE,
+                            lsp_types::SemanticTokenType::CLASS,
+                            lsp_types::SemanticTokenType::ENUM,
+                            lsp_types::SemanticTokenType::INTERFACE,
+                            lsp_types::SemanticTokenType::STRUCT,
+                            lsp_types::SemanticTokenType::TYPE_PARAMETER,
+                            lsp_types::SemanticTokenType::PARAMETER,
+                            lsp_types::SemanticTokenType::VARIABLE,
+                            lsp_types::SemanticTokenType::PROPERTY,
+                            lsp_types::SemanticTokenType::ENUM_MEMBER,
+                            lsp_types::SemanticTokenType::FUNCTION,
+                            lsp_types::SemanticTokenType::METHOD,
+                            lsp_types::SemanticTokenType::MACRO,
+                            lsp_types::SemanticTokenType::KEYWORD,
+                            lsp_types::SemanticTokenType::COMMENT,
+                            lsp_types::SemanticTokenType::STRING,
+                            lsp_types::SemanticTokenType::NUMBER,
+                            lsp_types::SemanticTokenType::OPERATOR,
+                        ],
+                        token_modifiers: vec![
+                            lsp_types::SemanticTokenModifier::DECLARATION,
+                            lsp_types::SemanticTokenModifier::DEFINITION,
+                            lsp_types::SemanticTokenModifier::READONLY,
+                            lsp_types::SemanticTokenModifier::STATIC,
+                            lsp_types::SemanticTokenModifier::DEPRECATED,
+                            lsp_types::SemanticTokenModifier::ASYNC,
+                            lsp_types::SemanticTokenModifier::DEFAULT_LIBRARY,
+                            // not in the spec, but that's how rust-analyzer tells these apart.
+                            lsp_types::SemanticTokenModifier::new("mutable"),
+                            lsp_types::SemanticTokenModifier::new("unsafe"),
+                        ],
+                        formats: vec![lsp_types::TokenFormat::RELATIVE],
+                        overlapping_token_support: None,
+                        multiline_token_support: None,
+                        server_cancel_support: None,
+                        // tree-sitter colors whatever tokens don't cover.
+                        augments_syntax_tokens: Some(true),
+                    }),
                     moniker: None,
 
-                    type_hierarchy: None,
+                    type_hierarchy: Some(lsp_types::TypeHierarchyClientCapabilities {
+                        dynamic_registration: None,
+                    }),
                     inline_value: None,
-                    inlay_hint: None,
+                    inlay_hint: Some(lsp_types::InlayHintClientCapabilities {
+                        dynamic_registration: None,
+                        // no inlayHint/resolve, hints are drawn as plain text anyway.
+                        resolve_support: None,
+                    }),
                     diagnostic: None,
                 }),
-                window: None,
+                window: Some(lsp_types::WindowClientCapabilities {
+                    // so indexing can be shown in status bar
+                    work_done_progress: Some(true),
+                    show_message: None,
+                    show_document: None,
+                }),
                 general: None,
                 experimental: None,
             },
@@ -309,7 +470,18 @@ impl LspWrapper {
         //before returning I will send syn-ack as protocol demands.
         if result.wait(Some(Self::DEFAULT_TIMEOUT)) == PromiseState::Ready {
             self.send_notification_no_params::<lsp_types::notification::Initialized>()?;
-            Ok(result.take().unwrap())
+            let init_result = result.take().unwrap();
+
+            self.text_document_sync_kind = match init_result.capabilities.text_document_sync.as_ref() {
+                Some(lsp_types::TextDocumentSyncCapability::Kind(kind)) => *kind,
+                Some(lsp_types::TextDocumentSyncCapability::Options(options)) => {
+                    options.change.unwrap_or(lsp_types::TextDocumentSyncKind::FULL)
+                }
+                None => lsp_types::TextDocumentSyncKind::FULL,
+            };
+            debug!("negotiated text document sync kind {:?}", self.text_document_sync_kind);
+
+            Ok(init_result)
         } else {
             // OK maybe I should put errors in promises?
             Err(LspIOError::Write(LspWriteError::IoError("failed init".to_string())))
@@ -352,6 +524,28 @@ impl LspWrapper {
         })
     }
 
+    pub fn text_document_range_formatting(
+        &mut self,
+        url: Url,
+        stupid_range: (StupidCursor, StupidCursor),
+    ) -> Result<LSPPromise<lsp_types::request::RangeFormatting>, LspWriteError> {
+        self.send_message::<lsp_types::request::RangeFormatting>(lsp_types::DocumentRangeFormattingParams {
+            text_document: lsp_types::TextDocumentIdentifier { uri: url },
+            range: lsp_types::Range {
+                start: lsp_types::Position {
+                    line: stupid_range.0.line_0b,
+                    character: stupid_range.0.char_idx_0b,
+                },
+                end: lsp_types::Position {
+                    line: stupid_range.1.line_0b,
+                    character: stupid_range.1.char_idx_0b,
+                },
+            },
+            options: self.get_formatting_options(),
+            work_done_progress_params: Default::default(),
+        })
+    }
+
     pub fn text_document_references(
         &mut self,
         url: Url,
@@ -377,22 +571,184 @@ impl LspWrapper {
         })
     }
 
+    pub fn text_document_type_definition(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::GotoTypeDefinition>, LspWriteError> {
+        self.send_message::<lsp_types::request::GotoTypeDefinition>(lsp_types::request::GotoTypeDefinitionParams {
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn text_document_implementation(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::GotoImplementation>, LspWriteError> {
+        self.send_message::<lsp_types::request::GotoImplementation>(lsp_types::request::GotoImplementationParams {
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn text_document_declaration(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::GotoDeclaration>, LspWriteError> {
+        self.send_message::<lsp_types::request::GotoDeclaration>(lsp_types::request::GotoDeclarationParams {
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn text_document_hover(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::HoverRequest>, LspWriteError> {
+        self.send_message::<lsp_types::request::HoverRequest>(lsp_types::HoverParams {
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    pub fn text_document_signature_help(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::SignatureHelpRequest>, LspWriteError> {
+        self.send_message::<lsp_types::request::SignatureHelpRequest>(lsp_types::SignatureHelpParams {
+            context: None,
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    pub fn text_document_rename(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+        new_name: String,
+    ) -> Result<LSPPromise<lsp_types::request::Rename>, LspWriteError> {
+        self.send_message::<lsp_types::request::Rename>(lsp_types::RenameParams {
+            text_document_position: Self::get_position_params(url, cursor),
+            new_name,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    /*
+    Diagnostics overlapping the range are attached, that's how server knows which quick fixes to offer.
+     */
+    pub fn text_document_code_action(
+        &mut self,
+        url: Url,
+        stupid_range: (StupidCursor, StupidCursor),
+    ) -> Result<LSPPromise<lsp_types::request::CodeActionRequest>, LspWriteError> {
+        let range = lsp_types::Range {
+            start: lsp_types::Position {
+                line: stupid_range.0.line_0b,
+                character: stupid_range.0.char_idx_0b,
+            },
+            end: lsp_types::Position {
+                line: stupid_range.1.line_0b,
+                character: stupid_range.1.char_idx_0b,
+            },
+        };
+
+        let diagnostics: Vec<lsp_types::Diagnostic> = {
+            let lock = self.files.read()?;
+            lock.get(&url)
+                .map(|fd| {
+                    fd.diagnostics
+                        .iter()
+                        .filter(|d| d.lsp_diagnostic.range.start <= range.end && range.start <= d.lsp_diagnostic.range.end)
+                        .map(|d| d.lsp_diagnostic.clone())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        self.send_message::<lsp_types::request::CodeActionRequest>(lsp_types::CodeActionParams {
+            text_document: lsp_types::TextDocumentIdentifier { uri: url },
+            range,
+            context: lsp_types::CodeActionContext {
+                diagnostics,
+                only: None,
+                trigger_kind: Some(lsp_types::CodeActionTriggerKind::INVOKED),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn workspace_execute_command(
+        &mut self,
+        command: String,
+        arguments: Vec<serde_json::Value>,
+    ) -> Result<LSPPromise<lsp_types::request::ExecuteCommand>, LspWriteError> {
+        self.send_message::<lsp_types::request::ExecuteCommand>(lsp_types::ExecuteCommandParams {
+            command,
+            arguments,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    pub fn supports_incremental_sync(&self) -> bool {
+        self.text_document_sync_kind == lsp_types::TextDocumentSyncKind::INCREMENTAL
+    }
+
+    /*
+    Bumps and returns version of the document, None if document was not opened.
+     */
+    fn next_document_version(&mut self, url: &Url) -> Result<Option<i32>, LspWriteError> {
+        let mut lock = self.files.write()?;
+        if let Some(fd) = lock.get_mut(url) {
+            fd.version += 1;
+            Ok(Some(fd.version))
+        } else {
+            error!("failed to find document version for {:?} - was document opened?", url);
+            // TODO add error for "no document version found"
+            Ok(None)
+        }
+    }
+
+    pub fn text_document_inlay_hint(
+        &mut self,
+        url: Url,
+        stupid_range: (StupidCursor, StupidCursor),
+    ) -> Result<LSPPromise<lsp_types::request::InlayHintRequest>, LspWriteError> {
+        self.send_message::<lsp_types::request::InlayHintRequest>(lsp_types::InlayHintParams {
+            work_done_progress_params: Default::default(),
+            text_document: lsp_types::TextDocumentIdentifier { uri: url },
+            range: lsp_types::Range {
+                start: lsp_types::Position {
+                    line: stupid_range.0.line_0b,
+                    character: stupid_range.0.char_idx_0b,
+                },
+                end: lsp_types::Position {
+                    line: stupid_range.1.line_0b,
+                    character: stupid_range.1.char_idx_0b,
+                },
+            },
+        })
+    }
+
     /*
     This is a non-incremental variant of text_document_did_change
      */
     pub fn text_document_did_change(&mut self, url: Url, full_text: String) -> Result<(), LspWriteError> {
-        let version = {
-            let mut lock = self.files.write()?;
-            if let Some(fd) = lock.get_mut(&url) {
-                // debug!("updating document {} from {} to {}", &url, old_id, old_id+1);
-                fd.version += 1;
-                fd.version
-            } else {
-                error!("failed to find document version for {:?} - was document opened?", &url);
-                // TODO add error for "no document version found"
-                return Ok(());
-            }
-        };
+        let version = unpack_or!(
+            self.next_document_version(&url)?,
+            Ok(()),
+            "not sending didChange of unopened document"
+        );
 
         self.send_notification::<lsp_types::notification::DidChangeTextDocument>(lsp_types::DidChangeTextDocumentParams {
             text_document: lsp_types::VersionedTextDocumentIdentifier { uri: url, version },
@@ -404,6 +760,41 @@ impl LspWrapper {
         })
     }
 
+    /*
+    Changes are applied by server in order, so each range has to be expressed in coordinates of
+    document with previous changes applied. Use only if supports_incremental_sync() is true.
+     */
+    pub fn text_document_did_change_incremental(
+        &mut self,
+        url: Url,
+        content_changes: Vec<lsp_types::TextDocumentContentChangeEvent>,
+    ) -> Result<(), LspWriteError> {
+        debug_assert!(self.supports_incremental_sync());
+        let version = unpack_or!(
+            self.next_document_version(&url)?,
+            Ok(()),
+            "not sending didChange of unopened document"
+        );
+
+        self.send_notification::<lsp_types::notification::DidChangeTextDocument>(lsp_types::DidChangeTextDocumentParams {
+            text_document: lsp_types::VersionedTextDocumentIdentifier { uri: url, version },
+            content_changes,
+        })
+    }
+
+    // For files that changed without being opened, like the ones edited on disk by a workspace edit.
+    pub fn workspace_did_change_watched_files(&mut self, urls: Vec<Url>) -> Result<(), LspWriteError> {
+        self.send_notification::<lsp_types::notification::DidChangeWatchedFiles>(lsp_types::DidChangeWatchedFilesParams {
+            changes: urls
+                .into_iter()
+                .map(|uri| lsp_types::FileEvent {
+                    uri,
+                    typ: lsp_types::FileChangeType::CHANGED,
+                })
+                .collect(),
+        })
+    }
+
     pub fn text_document_did_close(&mut self, url: Url) -> Result<(), LspWriteError> {
         self.send_notification::<lsp_types::notification::DidCloseTextDocument>(lsp_types::DidCloseTextDocumentParams {
             text_document: lsp_types::TextDocumentIdentifier { uri: url },
@@ -449,15 +840,167 @@ impl LspWrapper {
         })
     }
 
+    pub fn text_document_document_highlight(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::DocumentHighlightRequest>, LspWriteError> {
+        self.send_message::<lsp_types::request::DocumentHighlightRequest>(lsp_types::DocumentHighlightParams {
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn text_document_semantic_tokens_full(
+        &mut self,
+        url: Url,
+    ) -> Result<LSPPromise<lsp_types::request::SemanticTokensFullRequest>, LspWriteError> {
+        self.send_message::<lsp_types::request::SemanticTokensFullRequest>(lsp_types::SemanticTokensParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: lsp_types::TextDocumentIdentifier { uri: url },
+        })
+    }
+
+    pub fn text_document_semantic_tokens_full_delta(
+        &mut self,
+        url: Url,
+        previous_result_id: String,
+    ) -> Result<LSPPromise<lsp_types::request::SemanticTokensFullDeltaRequest>, LspWriteError> {
+        self.send_message::<lsp_types::request::SemanticTokensFullDeltaRequest>(lsp_types::SemanticTokensDeltaParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: lsp_types::TextDocumentIdentifier { uri: url },
+            previous_result_id,
+        })
+    }
+
+    pub fn workspace_symbol(&mut self, query: String) -> Result<LSPPromise<lsp_types::request::WorkspaceSymbolRequest>, LspWriteError> {
+        self.send_message::<lsp_types::request::WorkspaceSymbolRequest>(lsp_types::WorkspaceSymbolParams {
+            partial_result_params: Default::default(),
+            work_done_progress_params: Default::default(),
+            query,
+        })
+    }
+
+    pub fn call_hierarchy_prepare(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::CallHierarchyPrepare>, LspWriteError> {
+        self.send_message::<lsp_types::request::CallHierarchyPrepare>(lsp_types::CallHierarchyPrepareParams {
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    pub fn call_hierarchy_incoming_calls(
+        &mut self,
+        item: lsp_types::CallHierarchyItem,
+    ) -> Result<LSPPromise<lsp_types::request::CallHierarchyIncomingCalls>, LspWriteError> {
+        self.send_message::<lsp_types::request::CallHierarchyIncomingCalls>(lsp_types::CallHierarchyIncomingCallsParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn call_hierarchy_outgoing_calls(
+        &mut self,
+        item: lsp_types::CallHierarchyItem,
+    ) -> Result<LSPPromise<lsp_types::request::CallHierarchyOutgoingCalls>, LspWriteError> {
+        self.send_message::<lsp_types::request::CallHierarchyOutgoingCalls>(lsp_types::CallHierarchyOutgoingCallsParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn type_hierarchy_prepare(
+        &mut self,
+        url: Url,
+        cursor: StupidCursor,
+    ) -> Result<LSPPromise<lsp_types::request::TypeHierarchyPrepare>, LspWriteError> {
+        self.send_message::<lsp_types::request::TypeHierarchyPrepare>(lsp_types::TypeHierarchyPrepareParams {
+            text_document_position_params: Self::get_position_params(url, cursor),
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    pub fn type_hierarchy_supertypes(
+        &mut self,
+        item: lsp_types::TypeHierarchyItem,
+    ) -> Result<LSPPromise<lsp_types::request::TypeHierarchySupertypes>, LspWriteError> {
+        self.send_message::<lsp_types::request::TypeHierarchySupertypes>(lsp_types::TypeHierarchySupertypesParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    pub fn type_hierarchy_subtypes(
+        &mut self,
+        item: lsp_types::TypeHierarchyItem,
+    ) -> Result<LSPPromise<lsp_types::request::TypeHierarchySubtypes>, LspWriteError> {
+        self.send_message::<lsp_types::request::TypeHierarchySubtypes>(lsp_types::TypeHierarchySubtypesParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
     pub fn wait(&self) -> &JoinHandle<Result<(), LspReadError>> {
         &self.reader_handle
     }
 
+    pub fn command(&self) -> &LspCommand {
+        &self.command
+    }
+
+    pub fn workspace_root_path(&self) -> &PathBuf {
+        &self.workspace_root_path
+    }
+
+    pub fn language(&self) -> LangId {
+        self.language
+    }
+
+    pub fn settings(&self) -> Option<&serde_json::Value> {
+        self.settings.as_ref()
+    }
+
+    /*
+    Each of these has to be answered with their responder, server waits for it.
+     */
+    pub fn take_apply_edit_requests(&self) -> Vec<LspApplyEditRequest> {
+        match self.apply_edit_requests.write() {
+            Ok(mut lock) => std::mem::take(&mut *lock),
+            Err(e) => {
+                error!("failed acquiring apply edit requests lock: {:?}", e);
+                vec![]
+            }
+        }
+    }
+
+    /*
+    Kills the server process. Reader thread will terminate on its own once stdout is closed.
+     */
+    pub fn kill(&mut self) {
+        if let Err(e) = self.child.kill() {
+            warn!("failed to kill lsp server {:?}: {:?}", self.command.path, e);
+        }
+        // reaping the zombie
+        let _ = self.child.wait();
+    }
+
     pub fn lsp_reader_thread_main(
         // used for debugging
         identifier: String,
         id_to_name: Arc<RwLock<IdToCallInfo>>,
         files: FilesStore,
+        dispatcher: LspRequestDispatcher,
+        progress: ProgressStore,
         mut stdout: BufReader<ChildStdout>,
         tick_sender: Sender<NavCompTick>,
     ) -> Result<(), LspReadError> {
@@ -468,7 +1011,7 @@ impl LspWrapper {
 
         loop {
             num += 1;
-            match read_lsp(&identifier, &mut num, &mut stdout, &id_to_name, &files) {
+            match read_lsp(&identifier, &mut num, &mut stdout, &id_to_name, &files, &dispatcher, &progress) {
                 Ok(_) => {
                     // TODO Pass LangId and whatever usize is?
                     match tick_sender.try_send(NavCompTick::LspTick(LangId::RUST, 0)) {
@@ -562,6 +1105,6 @@ impl LspWrapper {
 
 impl Debug for LspWrapper {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "LspWrapper({:?})", &self.server_path)
+        write!(f, "LspWrapper({:?})", &self.command.path)
     }
 }
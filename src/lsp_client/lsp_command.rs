@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process;
+
+/*
+How to start a language server. Built-in handlers know just the binary, custom ones (declared in
+config or workspace file) can add arguments, environment and initialization options.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct LspCommand {
+    pub path: PathBuf,
+    pub args: Vec<String>,
+    // added to (not replacing) environment inherited from editor
+    pub env: BTreeMap<String, String>,
+    // sent once, in "initialize" request. Not to be confused with settings, that server asks for.
+    pub initialization_options: Option<serde_json::Value>,
+}
+
+impl LspCommand {
+    pub fn new(path: PathBuf) -> Self {
+        LspCommand {
+            path,
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            initialization_options: None,
+        }
+    }
+
+    pub fn to_process_command(&self) -> process::Command {
+        let mut command = process::Command::new(self.path.as_os_str());
+        command.args(&self.args).envs(&self.env);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn to_process_command_test() {
+        let mut command = LspCommand::new(PathBuf::from("jdtls"));
+        command.args = vec!["-data".to_string(), "/tmp/jdtls".to_string()];
+        command.env.insert("JAVA_HOME".to_string(), "/usr/lib/jvm/default".to_string());
+
+        let process_command = command.to_process_command();
+        assert_eq!(process_command.get_program(), OsStr::new("jdtls"));
+        assert_eq!(process_command.get_args().collect::<Vec<_>>(), vec!["-data", "/tmp/jdtls"]);
+        assert_eq!(
+            process_command.get_envs().collect::<Vec<_>>(),
+            vec![(OsStr::new("JAVA_HOME"), Some(OsStr::new("/usr/lib/jvm/default")))]
+        );
+    }
+}
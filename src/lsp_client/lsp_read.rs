@@ -5,14 +5,16 @@ use std::sync::{Arc, RwLock};
 use crossbeam_channel::{SendError, Sender};
 use jsonrpc_core::{Call, Id, Output};
 use log::{debug, error, warn};
+use lsp_types::{NumberOrString, ProgressParamsValue, WorkDoneProgress};
 use serde_json::Value;
 
 use crate::lsp_client::debug_helpers::{format_or_noop, lsp_debug_save};
 use crate::lsp_client::diagnostic;
 use crate::lsp_client::diagnostic::Diagnostic;
-use crate::lsp_client::lsp_client::{FilesStore, IdToCallInfo, LSPFileDescriptor};
+use crate::lsp_client::lsp_client::{FilesStore, IdToCallInfo, LSPFileDescriptor, LspProgress, ProgressStore};
 use crate::lsp_client::lsp_notification::{parse_notification, LspServerNotification};
 use crate::lsp_client::lsp_read_error::LspReadError;
+use crate::lsp_client::lsp_request_dispatcher::LspRequestDispatcher;
 use crate::unpack_unit_e;
 
 // TODO one can reduce allocation here
@@ -31,6 +33,8 @@ pub fn read_lsp<R: Read>(
     input: &mut R,
     id_to_method: &Arc<RwLock<IdToCallInfo>>,
     files: &FilesStore,
+    dispatcher: &LspRequestDispatcher,
+    progress: &ProgressStore,
 ) -> Result<(), LspReadError> {
     let mut headers: Vec<u8> = Vec::new();
 
@@ -77,15 +81,16 @@ pub fn read_lsp<R: Read>(
     if let Ok(call) = jsonrpc_core::serde_from_str::<jsonrpc_core::Call>(s) {
         match call {
             Call::MethodCall(call) => {
+                // responses don't have method, so this is server asking me for something.
                 debug!("deserialized call->method_call");
-                let value: Value = call.params.into();
-                internal_send_to_promise(id_to_method, call.id.clone(), value, Some(&call.method))
+                dispatcher.dispatch(call);
+                Ok(())
             }
             Call::Notification(notification) => {
                 debug!("deserialized call->notification {:?}", &notification.method);
                 match parse_notification(notification) {
                     Ok(no) => {
-                        internal_process_incoming_notification(no, files);
+                        internal_process_incoming_notification(no, files, progress);
                         Ok(())
                     }
                     Err(e) => Err(LspReadError::DeError(e.to_string())),
@@ -114,7 +119,7 @@ pub fn read_lsp<R: Read>(
                     debug!("deserialized notification");
                     match parse_notification(notification) {
                         Ok(no) => {
-                            internal_process_incoming_notification(no, files);
+                            internal_process_incoming_notification(no, files, progress);
                             Ok(())
                         }
                         Err(e) => Err(LspReadError::DeError(e.to_string())),
@@ -159,7 +164,7 @@ fn internal_send_to_promise(
     }
 }
 
-fn internal_process_incoming_notification(no: LspServerNotification, files: &FilesStore) {
+fn internal_process_incoming_notification(no: LspServerNotification, files: &FilesStore, progress: &ProgressStore) {
     match no {
         LspServerNotification::TextDocumentPublishDiagnostics(diagnostics) => {
             let mut file_store = unpack_unit_e!(files.write().ok(), "failed to lock LSP FileStore");
@@ -186,6 +191,43 @@ fn internal_process_incoming_notification(no: LspServerNotification, files: &Fil
             let diagnostics: Vec<Diagnostic> = diagnostics.diagnostics.into_iter().map(diagnostic::Diagnostic::from).collect();
             file_desc.diagnostics = diagnostics;
         }
+        LspServerNotification::Progress(params) => {
+            let mut progress_store = unpack_unit_e!(progress.write().ok(), "failed to lock LSP ProgressStore");
+            let token = match params.token {
+                NumberOrString::Number(n) => n.to_string(),
+                NumberOrString::String(s) => s,
+            };
+
+            match params.value {
+                ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(begin)) => {
+                    progress_store.insert(
+                        token,
+                        LspProgress {
+                            title: begin.title,
+                            message: begin.message,
+                            percentage: begin.percentage,
+                        },
+                    );
+                }
+                ProgressParamsValue::WorkDone(WorkDoneProgress::Report(report)) => match progress_store.get_mut(&token) {
+                    Some(item) => {
+                        // both are optional in report, missing means "same as before"
+                        if report.message.is_some() {
+                            item.message = report.message;
+                        }
+                        if report.percentage.is_some() {
+                            item.percentage = report.percentage;
+                        }
+                    }
+                    None => {
+                        debug!("progress report for unknown token {}", token);
+                    }
+                },
+                ProgressParamsValue::WorkDone(WorkDoneProgress::End(_)) => {
+                    progress_store.remove(&token);
+                }
+            }
+        }
         other => {
             debug!("ignoring LSP diagnostics {:?}, because no handling of it is implemented", other);
         }
@@ -208,6 +250,48 @@ pub fn get_len_from_headers(headers: &str) -> Option<usize> {
 
 #[cfg(test)]
 mod tests {
+    use crate::lsp_client::lsp_client::{FilesStore, ProgressStore};
+    use crate::lsp_client::lsp_notification::parse_notification;
+    use crate::lsp_client::lsp_read::internal_process_incoming_notification;
+
+    fn process(s: &str, files: &FilesStore, progress: &ProgressStore) {
+        let notification = jsonrpc_core::serde_from_str::<jsonrpc_core::Notification>(s).unwrap();
+        internal_process_incoming_notification(parse_notification(notification).unwrap(), files, progress);
+    }
+
+    #[test]
+    fn progress_begin_report_end() {
+        let files = FilesStore::default();
+        let progress = ProgressStore::default();
+
+        process(
+            r#"{"jsonrpc":"2.0","method":"$/progress","params":{"token":"rustAnalyzer/Indexing","value":{"kind":"begin","title":"Indexing","percentage":0}}}"#,
+            &files,
+            &progress,
+        );
+        assert_eq!(progress.read().unwrap().get("rustAnalyzer/Indexing").unwrap().percentage, Some(0));
+
+        process(
+            r#"{"jsonrpc":"2.0","method":"$/progress","params":{"token":"rustAnalyzer/Indexing","value":{"kind":"report","message":"47/100","percentage":47}}}"#,
+            &files,
+            &progress,
+        );
+        {
+            let lock = progress.read().unwrap();
+            let item = lock.get("rustAnalyzer/Indexing").unwrap();
+            assert_eq!(item.title, "Indexing");
+            assert_eq!(item.message.as_deref(), Some("47/100"));
+            assert_eq!(item.percentage, Some(47));
+        }
+
+        process(
+            r#"{"jsonrpc":"2.0","method":"$/progress","params":{"token":"rustAnalyzer/Indexing","value":{"kind":"end"}}}"#,
+            &files,
+            &progress,
+        );
+        assert!(progress.read().unwrap().is_empty());
+    }
+
     #[test]
     fn json_deserialize() {
         let s = r#"{"jsonrpc":"2.0","id":0,"method":"client/registerCapability","params":{"registrations":[{"id":"textDocument/didSave","method":"textDocument/didSave","registerOptions":{"includeText":false,"documentSelector":[{"pattern":"**/*.rs"},{"pattern":"**/Cargo.toml"},{"pattern":"**/Cargo.lock"}]}}]}}"#;
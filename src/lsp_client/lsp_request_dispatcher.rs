@@ -0,0 +1,200 @@
+use std::process::ChildStdin;
+use std::sync::{Arc, Mutex, RwLock};
+
+use jsonrpc_core::Id;
+use log::{debug, error, warn};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::lsp_client::lsp_notification::LspNotificationParsingError;
+use crate::lsp_client::lsp_server_request::{parse_server_request, LspServerRequest};
+use crate::lsp_client::lsp_write::{internal_send_error_response, internal_send_response};
+use crate::lsp_client::lsp_write_error::LspWriteError;
+
+// Shared between LspWrapper (requests, notifications) and reader thread (responses).
+pub type SharedStdin = Arc<Mutex<ChildStdin>>;
+
+pub type ApplyEditRequests = Arc<RwLock<Vec<LspApplyEditRequest>>>;
+
+/*
+Whoever holds it is expected to answer the request it was made for, exactly once.
+ */
+pub struct LspResponder {
+    stdin: SharedStdin,
+    id: Id,
+}
+
+impl LspResponder {
+    pub fn respond<T: Serialize>(self, result: T) -> Result<(), LspWriteError> {
+        let value = serde_json::to_value(result)?;
+        let mut stdin = self.stdin.lock()?;
+        internal_send_response(&mut *stdin, self.id, value)
+    }
+}
+
+/*
+workspace/applyEdit has to touch buffers, which live on the UI thread, so it's queued and answered
+once someone gets to applying it.
+ */
+pub struct LspApplyEditRequest {
+    pub params: lsp_types::ApplyWorkspaceEditParams,
+    pub responder: LspResponder,
+}
+
+/*
+Answers requests server sends to me. Everything except applyEdit is answered right away on reader
+thread. Servers tend to wait for these answers before doing anything useful (rust-analyzer won't
+index without configuration), so unknown requests are refused rather than ignored.
+ */
+pub struct LspRequestDispatcher {
+    stdin: SharedStdin,
+    // per-handler settings from workspace file, served as answers to workspace/configuration
+    settings: Option<Value>,
+    apply_edit_requests: ApplyEditRequests,
+}
+
+impl LspRequestDispatcher {
+    pub fn new(stdin: SharedStdin, settings: Option<Value>, apply_edit_requests: ApplyEditRequests) -> Self {
+        LspRequestDispatcher {
+            stdin,
+            settings,
+            apply_edit_requests,
+        }
+    }
+
+    pub fn dispatch(&self, call: jsonrpc_core::MethodCall) {
+        debug!("dispatching server request {} (id {:?})", &call.method, &call.id);
+
+        let result = match parse_server_request(&call) {
+            Ok(request) => self.answer(call.id, request),
+            Err(LspNotificationParsingError::UnknownMethod) => {
+                warn!("refusing unsupported server request {}", &call.method);
+                self.send_error(call.id, jsonrpc_core::Error::method_not_found())
+            }
+            Err(LspNotificationParsingError::ParamsParseFailed(e)) => {
+                error!("failed parsing params of server request {}: {:?}", &call.method, e);
+                self.send_error(call.id, jsonrpc_core::Error::invalid_params(e.message))
+            }
+        };
+
+        if let Err(e) = result {
+            // if server is gone, reader thread will find out on its own
+            error!("failed answering server request {}: {:?}", &call.method, e);
+        }
+    }
+
+    fn answer(&self, id: Id, request: LspServerRequest) -> Result<(), LspWriteError> {
+        match request {
+            LspServerRequest::WorkspaceConfiguration(params) => {
+                let sections: Vec<Value> = params
+                    .items
+                    .iter()
+                    .map(|item| configuration_section(self.settings.as_ref(), item.section.as_deref()))
+                    .collect();
+                self.send_response(id, sections)
+            }
+            LspServerRequest::WorkspaceApplyEdit(params) => {
+                let request = LspApplyEditRequest {
+                    params,
+                    responder: LspResponder {
+                        stdin: self.stdin.clone(),
+                        id,
+                    },
+                };
+                self.apply_edit_requests.write()?.push(request);
+                Ok(())
+            }
+            LspServerRequest::WindowWorkDoneProgressCreate(params) => {
+                debug!("server creates progress {:?}", params.token);
+                self.send_response(id, ())
+            }
+            LspServerRequest::ClientRegisterCapability(params) => {
+                // I don't register anything dynamically, and static capabilities are all I use.
+                debug!("ignoring registration of {} capabilities", params.registrations.len());
+                self.send_response(id, ())
+            }
+            LspServerRequest::ClientUnregisterCapability(params) => {
+                debug!("ignoring unregistration of {} capabilities", params.unregisterations.len());
+                self.send_response(id, ())
+            }
+            LspServerRequest::WindowShowMessageRequest(params) => {
+                // null means "user chose no action". There's no UI for it yet.
+                warn!("server asks {:?}: {}, answering with no action", params.typ, params.message);
+                self.send_response(id, Value::Null)
+            }
+        }
+    }
+
+    fn send_response<T: Serialize>(&self, id: Id, result: T) -> Result<(), LspWriteError> {
+        let value = serde_json::to_value(result)?;
+        let mut stdin = self.stdin.lock()?;
+        internal_send_response(&mut *stdin, id, value)
+    }
+
+    fn send_error(&self, id: Id, error: jsonrpc_core::Error) -> Result<(), LspWriteError> {
+        let mut stdin = self.stdin.lock()?;
+        internal_send_error_response(&mut *stdin, id, error)
+    }
+}
+
+/*
+Section is either a key in settings ("rust-analyzer"), or a dotted path into them
+("rust-analyzer.cargo"). No section means entire settings. Missing ones are null, which servers
+read as "use defaults".
+ */
+pub fn configuration_section(settings: Option<&Value>, section: Option<&str>) -> Value {
+    let settings = match settings {
+        Some(settings) => settings,
+        None => return Value::Null,
+    };
+
+    let section = match section {
+        Some(section) => section,
+        None => return settings.clone(),
+    };
+
+    if let Some(value) = settings.get(section) {
+        return value.clone();
+    }
+
+    let mut value = settings;
+    for key in section.split('.') {
+        value = match value.get(key) {
+            Some(value) => value,
+            None => return Value::Null,
+        };
+    }
+
+    value.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::configuration_section;
+
+    #[test]
+    fn configuration_section_test() {
+        let settings = json!({
+            "rust-analyzer": {
+                "cargo": { "features": "all" },
+                "checkOnSave": true,
+            },
+            "gopls.staticcheck": true,
+        });
+
+        assert_eq!(configuration_section(Some(&settings), None), settings);
+        assert_eq!(
+            configuration_section(Some(&settings), Some("rust-analyzer.cargo")),
+            json!({ "features": "all" })
+        );
+        assert_eq!(
+            configuration_section(Some(&settings), Some("rust-analyzer.checkOnSave")),
+            json!(true)
+        );
+        assert_eq!(configuration_section(Some(&settings), Some("gopls.staticcheck")), json!(true));
+        assert_eq!(configuration_section(Some(&settings), Some("rust-analyzer.procMacro")), json!(null));
+        assert_eq!(configuration_section(None, Some("rust-analyzer")), json!(null));
+    }
+}
@@ -0,0 +1,81 @@
+use lsp_types::request as r;
+use lsp_types::request::Request;
+use lsp_types::{
+    ApplyWorkspaceEditParams, ConfigurationParams, RegistrationParams, ShowMessageRequestParams, UnregistrationParams,
+    WorkDoneProgressCreateParams,
+};
+
+use crate::lsp_client::lsp_notification::LspNotificationParsingError;
+
+/*
+Requests sent by server to me (as opposed to the other way around). Server waits for the answer,
+so each of them has to be responded to, even if just with an error.
+ */
+pub fn parse_server_request(call: &jsonrpc_core::MethodCall) -> Result<LspServerRequest, LspNotificationParsingError> {
+    let params = call.params.clone();
+
+    match call.method.as_str() {
+        r::WorkspaceConfiguration::METHOD => {
+            let params = params.parse::<ConfigurationParams>()?;
+            Ok(LspServerRequest::WorkspaceConfiguration(params))
+        }
+        r::ApplyWorkspaceEdit::METHOD => {
+            let params = params.parse::<ApplyWorkspaceEditParams>()?;
+            Ok(LspServerRequest::WorkspaceApplyEdit(params))
+        }
+        r::WorkDoneProgressCreate::METHOD => {
+            let params = params.parse::<WorkDoneProgressCreateParams>()?;
+            Ok(LspServerRequest::WindowWorkDoneProgressCreate(params))
+        }
+        r::RegisterCapability::METHOD => {
+            let params = params.parse::<RegistrationParams>()?;
+            Ok(LspServerRequest::ClientRegisterCapability(params))
+        }
+        r::UnregisterCapability::METHOD => {
+            let params = params.parse::<UnregistrationParams>()?;
+            Ok(LspServerRequest::ClientUnregisterCapability(params))
+        }
+        r::ShowMessageRequest::METHOD => {
+            let params = params.parse::<ShowMessageRequestParams>()?;
+            Ok(LspServerRequest::WindowShowMessageRequest(params))
+        }
+        _ => Err(LspNotificationParsingError::UnknownMethod),
+    }
+}
+
+#[derive(Debug)]
+pub enum LspServerRequest {
+    WorkspaceConfiguration(ConfigurationParams),
+    WorkspaceApplyEdit(ApplyWorkspaceEditParams),
+    WindowWorkDoneProgressCreate(WorkDoneProgressCreateParams),
+    ClientRegisterCapability(RegistrationParams),
+    ClientUnregisterCapability(UnregistrationParams),
+    WindowShowMessageRequest(ShowMessageRequestParams),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_server_request, LspServerRequest};
+
+    #[test]
+    fn parse_configuration_request() {
+        let s = r#"{"jsonrpc":"2.0","id":3,"method":"workspace/configuration","params":{"items":[{"section":"rust-analyzer"}]}}"#;
+
+        let call = jsonrpc_core::serde_from_str::<jsonrpc_core::MethodCall>(s).unwrap();
+        match parse_server_request(&call).unwrap() {
+            LspServerRequest::WorkspaceConfiguration(params) => {
+                assert_eq!(params.items.len(), 1);
+                assert_eq!(params.items[0].section.as_deref(), Some("rust-analyzer"));
+            }
+            other => panic!("unexpected request {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_unknown_request() {
+        let s = r#"{"jsonrpc":"2.0","id":4,"method":"workspace/somethingNew","params":{}}"#;
+
+        let call = jsonrpc_core::serde_from_str::<jsonrpc_core::MethodCall>(s).unwrap();
+        assert!(parse_server_request(&call).is_err());
+    }
+}
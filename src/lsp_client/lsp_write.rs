@@ -112,3 +112,48 @@ where
         Err(LspWriteError::InterruptedWrite)
     }
 }
+
+/*
+Answers a request server sent to me. Result is already serialized, since every request has a different type of it.
+ */
+pub fn internal_send_response<W: Write>(stdin: &mut W, id: jsonrpc_core::Id, result: serde_json::Value) -> Result<(), LspWriteError> {
+    internal_send_output(
+        stdin,
+        jsonrpc_core::Output::Success(jsonrpc_core::Success {
+            jsonrpc: Some(Version::V2),
+            result,
+            id,
+        }),
+    )
+}
+
+pub fn internal_send_error_response<W: Write>(
+    stdin: &mut W,
+    id: jsonrpc_core::Id,
+    error: jsonrpc_core::Error,
+) -> Result<(), LspWriteError> {
+    internal_send_output(
+        stdin,
+        jsonrpc_core::Output::Failure(jsonrpc_core::Failure {
+            jsonrpc: Some(Version::V2),
+            error,
+            id,
+        }),
+    )
+}
+
+fn internal_send_output<W: Write>(stdin: &mut W, output: jsonrpc_core::Output) -> Result<(), LspWriteError> {
+    let response = serde_json::to_string(&output)?;
+    let mut buffer: Vec<u8> = Vec::new();
+    write!(&mut buffer, "Content-Length: {}\r\n\r\n{}", response.len(), response)?;
+
+    debug!("Sending response:\n---\n{}\n---\n", std::str::from_utf8(&buffer).unwrap());
+
+    let len = stdin.write(&buffer)?;
+    if buffer.len() == len {
+        stdin.flush()?;
+        Ok(())
+    } else {
+        Err(LspWriteError::InterruptedWrite)
+    }
+}
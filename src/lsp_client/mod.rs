@@ -1,11 +1,14 @@
 mod debug_helpers;
 pub mod diagnostic;
 pub mod lsp_client;
+pub mod lsp_command;
 pub mod lsp_io_error;
 mod lsp_notification;
 mod lsp_read;
 pub mod lsp_read_error;
+pub mod lsp_request_dispatcher;
 pub mod lsp_response;
+mod lsp_server_request;
 mod lsp_write;
 pub mod lsp_write_error;
 pub mod promise;
@@ -39,6 +39,16 @@ impl<'a> CodeResultsViewInterpreter<'a> {
         self.editors.iter().collect()
     }
 
+    pub fn description(&self) -> String {
+        self.output
+            .buffer
+            .lines_iter()
+            .with_rect(self.meta.rect)
+            .next()
+            .map(|line| line.text.trim().to_string())
+            .unwrap_or_default()
+    }
+
     // pub fn contents(&self) -> String {
     //     self.output.buffer.lines_iter().with_rect(self.meta.rect).next().unwrap().text.trim().to_string()
     // }
@@ -8,9 +8,12 @@ use crate::io::style::TextStyle;
 use crate::mocks::completion_interpreter::CompletionInterpreter;
 use crate::mocks::context_menu_interpreter::ContextMenuInterpreter;
 use crate::mocks::editbox_interpreter::EditWidgetInterpreter;
+use crate::mocks::hover_interpreter::HoverInterpreter;
 use crate::mocks::meta_frame::MetaOutputFrame;
+use crate::mocks::rename_interpreter::RenameInterpreter;
 use crate::mocks::savefile_interpreter::SaveFileInterpreter;
 use crate::mocks::scroll_interpreter::ScrollInterpreter;
+use crate::mocks::signature_help_interpreter::SignatureHelpInterpreter;
 use crate::primitives::printable::Printable;
 use crate::primitives::rect::Rect;
 use crate::primitives::sized_xy::SizedXY;
@@ -20,8 +23,10 @@ use crate::widgets::edit_box::EditBoxWidget;
 use crate::widgets::editor_view::editor_view::EditorView;
 use crate::widgets::editor_widget::completion::completion_widget::CompletionWidget;
 use crate::widgets::editor_widget::editor_widget::{count_tabs_starting_at, EditorWidget, BEYOND, NEWLINE, TAB, TAB_LEN};
+use crate::widgets::editor_widget::hover::hover_widget::HoverWidget;
+use crate::widgets::editor_widget::rename::rename_widget::RenameWidget;
+use crate::widgets::editor_widget::signature_help::signature_help_widget::SignatureHelpWidget;
 use crate::widgets::save_file_dialog::save_file_dialog::SaveFileDialogWidget;
-use crate::widgets::with_scroll::with_scroll::WithScroll;
 use log::{error, warn};
 use lsp_types::TagSupport;
 use std::collections::HashSet;
@@ -36,6 +41,9 @@ pub struct EditorInterpreter<'a> {
     rect_without_scroll: Rect,
     scroll: ScrollInterpreter<'a>,
     compeltion_op: Option<CompletionInterpreter<'a>>,
+    hover_op: Option<HoverInterpreter<'a>>,
+    signature_help_op: Option<SignatureHelpInterpreter<'a>>,
+    rename_op: Option<RenameInterpreter<'a>>,
 
     saveas_op: Option<SaveFileInterpreter<'a>>,
 
@@ -73,7 +81,7 @@ impl<'a> EditorInterpreter<'a> {
         );
 
         let scrolls: Vec<&Metadata> = mock_output
-            .get_meta_by_type(WithScroll::<EditorWidget>::TYPENAME_FOR_MARGIN)
+            .get_meta_by_type(EditorWidget::TYPENAME_FOR_MARGIN)
             .filter(|c| meta.rect.contains_rect(c.rect))
             .collect();
 
@@ -96,6 +104,28 @@ impl<'a> EditorInterpreter<'a> {
             Some(CompletionInterpreter::new(comps[0], mock_output))
         };
 
+        let hovers: Vec<&Metadata> = mock_output
+            .get_meta_by_type(HoverWidget::TYPENAME)
+            .filter(|c| meta.rect.contains_rect(c.rect))
+            .collect();
+        debug_assert!(hovers.len() < 2);
+        let hover_op: Option<HoverInterpreter> = hovers.first().map(|h| HoverInterpreter::new(h, mock_output));
+
+        let signature_helps: Vec<&Metadata> = mock_output
+            .get_meta_by_type(SignatureHelpWidget::TYPENAME)
+            .filter(|c| meta.rect.contains_rect(c.rect))
+            .collect();
+        debug_assert!(signature_helps.len() < 2);
+        let signature_help_op: Option<SignatureHelpInterpreter> =
+            signature_helps.first().map(|s| SignatureHelpInterpreter::new(s, mock_output));
+
+        let renames: Vec<&Metadata> = mock_output
+            .get_meta_by_type(RenameWidget::TYPENAME)
+            .filter(|c| meta.rect.contains_rect(c.rect))
+            .collect();
+        debug_assert!(renames.len() < 2);
+        let rename_op: Option<RenameInterpreter> = renames.first().and_then(|r| RenameInterpreter::new(r, mock_output));
+
         let saveases: Vec<&Metadata> = mock_output
             .get_meta_by_type(SaveFileDialogWidget::TYPENAME)
             .filter(|c| meta.rect.contains_rect(c.rect))
@@ -120,6 +150,8 @@ impl<'a> EditorInterpreter<'a> {
                 meta.rect.contains_rect(eb.rect)
                     // and is NOT contained by eventual save as
                     && saveas_op.as_ref().map(|s| !s.meta().rect.contains_rect(eb.rect)).unwrap_or(true)
+                    // nor by rename box
+                    && rename_op.as_ref().map(|r| !r.meta().rect.contains_rect(eb.rect)).unwrap_or(true)
             })
             .collect();
 
@@ -156,6 +188,9 @@ impl<'a> EditorInterpreter<'a> {
             rect_without_scroll,
             scroll,
             compeltion_op,
+            hover_op,
+            signature_help_op,
+            rename_op,
             saveas_op,
             find_op,
             replace_op,
@@ -333,6 +368,18 @@ impl<'a> EditorInterpreter<'a> {
         self.compeltion_op.as_ref()
     }
 
+    pub fn hover(&self) -> Option<&HoverInterpreter<'a>> {
+        self.hover_op.as_ref()
+    }
+
+    pub fn signature_help(&self) -> Option<&SignatureHelpInterpreter<'a>> {
+        self.signature_help_op.as_ref()
+    }
+
+    pub fn rename(&self) -> Option<&RenameInterpreter<'a>> {
+        self.rename_op.as_ref()
+    }
+
     pub fn save_file_dialog(&self) -> Option<&SaveFileInterpreter<'a>> {
         self.saveas_op.as_ref()
     }
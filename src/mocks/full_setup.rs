@@ -28,23 +28,31 @@ use crate::mocks::code_results_interpreter::CodeResultsViewInterpreter;
 use crate::mocks::context_menu_interpreter::ContextMenuInterpreter;
 use crate::mocks::editor_interpreter::EditorInterpreter;
 use crate::mocks::generic_dialog_interpreter::GenericDialogWidgetInterpreter;
+use crate::mocks::history_browser_interpreter::HistoryBrowserInterpreter;
 use crate::mocks::log_capture::CapturingLogger;
 use crate::mocks::meta_frame::MetaOutputFrame;
 use crate::mocks::mock_clipboard::MockClipboard;
 use crate::mocks::mock_input::MockInput;
 use crate::mocks::mock_navcomp_loader::MockNavcompLoader;
-use crate::mocks::mock_navcomp_provider::{MockCompletionMatcher, MockNavCompEvent, MockNavCompProviderPilot, MockSymbolMatcher};
+use crate::mocks::mock_navcomp_provider::{
+    MockCompletionMatcher, MockFileMatcher, MockNavCompEvent, MockNavCompHealth, MockNavCompProviderPilot, MockSymbolMatcher,
+};
 use crate::mocks::mock_output::MockOutput;
+use crate::mocks::recovery_view_interpreter::RecoveryViewInterpreter;
+use crate::mocks::task_view_interpreter::TaskViewInterpreter;
 use crate::mocks::treeview_interpreter::TreeViewInterpreter;
 use crate::mocks::with_wait_for::WithWaitFor;
 use crate::primitives::xy::XY;
 use crate::tsw::language_set::LanguageSet;
 use crate::tsw::tree_sitter_wrapper::TreeSitterWrapper;
+use crate::w7e::navcomp_provider::NavCompApplyEditRequest;
 use crate::widgets::find_in_files_widget::tests::find_in_files_widget_interpreter::FindInFilesWidgetInterpreter;
 use crate::widgets::tree_view;
 
 pub struct FullSetupBuilder {
     path: PathBuf,
+    // files missing in path are taken from here
+    shared_env: Option<PathBuf>,
     config: Option<Config>,
     files: Vec<PathBuf>,
     size: XY,
@@ -68,6 +76,17 @@ impl FullSetupBuilder {
         }
     }
 
+    /*
+    Files common to many test envs (like Cargo.toml and workspace file of a project) are kept once,
+    in shared env. Test env's own files take precedence.
+     */
+    pub fn with_shared_env<P: AsRef<OsStr>>(self, path: P) -> Self {
+        FullSetupBuilder {
+            shared_env: Some(PathBuf::from(path.as_ref())),
+            ..self
+        }
+    }
+
     pub fn with_mock_navcomp(self, mock_navcomp: bool) -> Self {
         FullSetupBuilder { mock_navcomp, ..self }
     }
@@ -148,7 +167,10 @@ impl FullSetupBuilder {
 
         let theme = Theme::default();
 
-        let mock_fs = MockFS::generate_from_real(self.path).unwrap();
+        let mut mock_fs = MockFS::generate_from_real(self.path).unwrap();
+        if let Some(shared_env) = self.shared_env {
+            mock_fs.add_missing_from_real(shared_env).unwrap();
+        }
         let fsf = mock_fs.to_fsf();
         let (input, input_sender) = MockInput::new();
         let (output, output_receiver) = MockOutput::new(self.size, self.step_frame, theme.clone());
@@ -170,16 +192,27 @@ impl FullSetupBuilder {
             let (mock_navcomp_event_sender, mock_navcomp_event_recvr) = crossbeam_channel::unbounded::<MockNavCompEvent>();
             let comp_matcher: Arc<RwLock<Vec<MockCompletionMatcher>>> = Arc::new(RwLock::new(Vec::new()));
             let symbol_matcher: Arc<RwLock<Vec<MockSymbolMatcher>>> = Arc::new(RwLock::new(Vec::new()));
+            let file_matcher: Arc<RwLock<Vec<MockFileMatcher>>> = Arc::new(RwLock::new(Vec::new()));
+            let health: Arc<MockNavCompHealth> = Arc::new(MockNavCompHealth::default());
+            let apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>> = Arc::new(RwLock::new(Vec::new()));
 
             mock_navcomp_pilot = Some(MockNavCompProviderPilot::new(
                 mock_navcomp_event_recvr,
                 comp_matcher.clone(),
                 symbol_matcher.clone(),
+                file_matcher.clone(),
+                health.clone(),
+                apply_edit_requests.clone(),
             ));
 
-            navcomp_loader = Arc::new(
-                Box::new(MockNavcompLoader::new(mock_navcomp_event_sender, comp_matcher, symbol_matcher)) as Box<dyn NavCompLoader>,
-            );
+            navcomp_loader = Arc::new(Box::new(MockNavcompLoader::new(
+                mock_navcomp_event_sender,
+                comp_matcher,
+                symbol_matcher,
+                file_matcher,
+                health,
+                apply_edit_requests,
+            )) as Box<dyn NavCompLoader>);
         }
 
         let providers = Providers::new(
@@ -224,6 +257,7 @@ impl FullSetup {
     pub fn new<P: AsRef<OsStr>>(path: P) -> FullSetupBuilder {
         FullSetupBuilder {
             path: PathBuf::from(path.as_ref()),
+            shared_env: None,
             config: None,
             files: vec![],
             size: FullSetupBuilder::DEFAULT_MOCK_OUTPUT_SIZE,
@@ -338,6 +372,18 @@ impl FullSetup {
         self.last_frame.as_ref().map(|frame| frame.get_code_results_view()).flatten()
     }
 
+    pub fn get_task_view(&self) -> Option<TaskViewInterpreter<'_>> {
+        self.last_frame.as_ref().and_then(|frame| frame.get_task_view())
+    }
+
+    pub fn get_history_browser(&self) -> Option<HistoryBrowserInterpreter<'_>> {
+        self.last_frame.as_ref().and_then(|frame| frame.get_history_browser())
+    }
+
+    pub fn get_recovery_view(&self) -> Option<RecoveryViewInterpreter<'_>> {
+        self.last_frame.as_ref().and_then(|frame| frame.get_recovery_view())
+    }
+
     pub fn get_fuzzy_search(&self) -> Option<ContextMenuInterpreter<'_>> {
         self.last_frame.as_ref().map(|frame| frame.get_fuzzy_search()).flatten()
     }
@@ -0,0 +1,78 @@
+use crate::io::output::Metadata;
+use crate::mocks::meta_frame::MetaOutputFrame;
+use crate::mocks::treeview_interpreter::{TreeViewInterpreter, TreeViewInterpreterItem};
+use crate::widgets::history_browser::history_browser_widget::HISTORY_BROWSER_WIDGET_NAME;
+use crate::widgets::text_widget::TextWidget;
+use crate::widgets::tree_view::tree_view;
+
+pub struct HistoryBrowserInterpreter<'a> {
+    meta: &'a Metadata,
+    output: &'a MetaOutputFrame,
+
+    tree_view: TreeViewInterpreter<'a>,
+    // TextWidget emits metadata of the area it used, so there is none for empty preview
+    preview_meta_op: Option<&'a Metadata>,
+}
+
+impl<'a> HistoryBrowserInterpreter<'a> {
+    pub fn new(output: &'a MetaOutputFrame, meta: &'a Metadata) -> Self {
+        debug_assert!(meta.typename == HISTORY_BROWSER_WIDGET_NAME);
+
+        let tree_view_meta: Vec<&Metadata> = output
+            .get_meta_by_type(tree_view::TYPENAME)
+            .filter(|c| meta.rect.contains_rect(c.rect))
+            .collect();
+
+        debug_assert!(tree_view_meta.len() == 1);
+        let tree_view = TreeViewInterpreter::new(tree_view_meta[0], output);
+
+        let preview_meta_op = output
+            .get_meta_by_type(TextWidget::TYPENAME)
+            .find(|c| meta.rect.contains_rect(c.rect));
+
+        HistoryBrowserInterpreter {
+            meta,
+            output,
+            tree_view,
+            preview_meta_op,
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.meta.focused
+    }
+
+    pub fn tree_view(&self) -> &TreeViewInterpreter<'a> {
+        &self.tree_view
+    }
+
+    pub fn visible_items(&self) -> Vec<TreeViewInterpreterItem> {
+        self.tree_view.items()
+    }
+
+    pub fn selected_option(&self) -> Option<String> {
+        self.tree_view.selected().map(|item| item.label)
+    }
+
+    // visible lines of preview, right-trimmed, empty ones at the bottom skipped
+    pub fn preview_lines(&self) -> Vec<String> {
+        let preview_meta = match self.preview_meta_op {
+            Some(meta) => meta,
+            None => return Vec::new(),
+        };
+
+        let mut lines: Vec<String> = self
+            .output
+            .buffer
+            .lines_iter()
+            .with_rect(preview_meta.rect)
+            .map(|line| line.text.trim_end().to_string())
+            .collect();
+
+        while lines.last().map(|line| line.is_empty()).unwrap_or(false) {
+            lines.pop();
+        }
+
+        lines
+    }
+}
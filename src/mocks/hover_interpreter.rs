@@ -0,0 +1,28 @@
+use crate::io::output::Metadata;
+use crate::mocks::meta_frame::MetaOutputFrame;
+use crate::widgets::editor_widget::hover::hover_widget::HoverWidget;
+
+pub struct HoverInterpreter<'a> {
+    meta: &'a Metadata,
+    output: &'a MetaOutputFrame,
+}
+
+impl<'a> HoverInterpreter<'a> {
+    pub fn new(meta: &'a Metadata, output: &'a MetaOutputFrame) -> Self {
+        debug_assert!(meta.typename == HoverWidget::TYPENAME);
+
+        Self { meta, output }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.output
+            .buffer
+            .lines_iter()
+            .with_rect(self.meta.rect)
+            .map(|line| line.text.trim().to_string())
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.lines().next() == Some(HoverWidget::LOADING.to_string())
+    }
+}
@@ -6,8 +6,11 @@ use crate::mocks::code_results_interpreter::CodeResultsViewInterpreter;
 use crate::mocks::context_menu_interpreter::ContextMenuInterpreter;
 use crate::mocks::editor_interpreter::EditorInterpreter;
 use crate::mocks::generic_dialog_interpreter::GenericDialogWidgetInterpreter;
+use crate::mocks::history_browser_interpreter::HistoryBrowserInterpreter;
 use crate::mocks::nested_menu_interpreter::NestedMenuInterpreter;
 use crate::mocks::no_editor_interpreter::NoEditorInterpreter;
+use crate::mocks::recovery_view_interpreter::RecoveryViewInterpreter;
+use crate::mocks::task_view_interpreter::TaskViewInterpreter;
 use crate::mocks::with_scroll_interpreter::WithScrollWidgetInterpreter;
 use crate::widget::widget::Widget;
 use crate::widgets::code_results_view::code_results_widget::CodeResultsView;
@@ -16,9 +19,13 @@ use crate::widgets::editor_view::editor_view::EditorView;
 use crate::widgets::find_in_files_widget::find_in_files_widget::FindInFilesWidget;
 use crate::widgets::find_in_files_widget::tests::find_in_files_widget_interpreter::FindInFilesWidgetInterpreter;
 use crate::widgets::generic_dialog::generic_dialog::GenericDialog;
+use crate::widgets::history_browser::history_browser_widget::HISTORY_BROWSER_WIDGET_NAME;
+use crate::widgets::main_view::focus_path_widget::FocusPathWidget;
 use crate::widgets::nested_menu::widget::NESTED_MENU_TYPENAME;
 use crate::widgets::no_editor::NoEditorWidget;
+use crate::widgets::recovery_view::recovery_view_widget::RECOVERY_VIEW_WIDGET_NAME;
 use crate::widgets::spath_tree_view_node::FileTreeNode;
+use crate::widgets::task_view::task_view_widget::TaskView;
 use crate::widgets::with_scroll::with_scroll::WithScroll;
 
 /*
@@ -83,9 +90,33 @@ impl MetaOutputFrame {
             .next()
     }
 
+    pub fn get_task_view(&self) -> Option<TaskViewInterpreter<'_>> {
+        self.get_meta_by_type(TaskView::TYPENAME)
+            .map(|meta| TaskViewInterpreter::new(self, meta))
+            .next()
+    }
+
+    pub fn get_history_browser(&self) -> Option<HistoryBrowserInterpreter<'_>> {
+        self.get_meta_by_type(HISTORY_BROWSER_WIDGET_NAME)
+            .map(|meta| HistoryBrowserInterpreter::new(self, meta))
+            .next()
+    }
+
+    pub fn get_recovery_view(&self) -> Option<RecoveryViewInterpreter<'_>> {
+        self.get_meta_by_type(RECOVERY_VIEW_WIDGET_NAME)
+            .map(|meta| RecoveryViewInterpreter::new(self, meta))
+            .next()
+    }
+
     pub fn get_find_in_files(&self) -> Option<FindInFilesWidgetInterpreter> {
         self.get_meta_by_type(FindInFilesWidget::static_typename())
             .map(|meta| FindInFilesWidgetInterpreter::new(meta, self))
             .next()
     }
+
+    pub fn get_status_bar_text(&self) -> Option<String> {
+        self.get_meta_by_type(FocusPathWidget::TYPENAME)
+            .next()
+            .and_then(|meta| self.buffer.get_line(meta.rect.pos.y))
+    }
 }
@@ -5,11 +5,11 @@ use crossbeam_channel::Sender;
 use crate::config::config::ConfigRef;
 use crate::experiments::buffer_register::BufferRegisterRef;
 use crate::gladius::navcomp_loader::NavCompLoader;
-use crate::mocks::mock_navcomp_provider::{MockCompletionMatcher, MockNavCompEvent, MockSymbolMatcher};
+use crate::mocks::mock_navcomp_provider::{MockCompletionMatcher, MockFileMatcher, MockNavCompEvent, MockNavCompHealth, MockSymbolMatcher};
 use crate::w7e::handler::{Handler, NavCompRef};
 use crate::w7e::handler_load_error::HandlerLoadError;
 use crate::w7e::navcomp_group::NavCompTickSender;
-use crate::w7e::navcomp_provider::NavCompProvider;
+use crate::w7e::navcomp_provider::{NavCompApplyEditRequest, NavCompProvider};
 use crate::w7e::project_scope::ProjectScope;
 use crate::w7e::rust::handler_rust::RustHandler;
 
@@ -17,6 +17,9 @@ pub struct MockNavcompLoader {
     event_sender: Sender<MockNavCompEvent>,
     completions: Arc<RwLock<Vec<MockCompletionMatcher>>>,
     symbols: Arc<RwLock<Vec<MockSymbolMatcher>>>,
+    files: Arc<RwLock<Vec<MockFileMatcher>>>,
+    health: Arc<MockNavCompHealth>,
+    apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>>,
 }
 
 impl MockNavcompLoader {
@@ -24,11 +27,17 @@ impl MockNavcompLoader {
         event_sender: Sender<MockNavCompEvent>,
         completions: Arc<RwLock<Vec<MockCompletionMatcher>>>,
         symbols: Arc<RwLock<Vec<MockSymbolMatcher>>>,
+        files: Arc<RwLock<Vec<MockFileMatcher>>>,
+        health: Arc<MockNavCompHealth>,
+        apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>>,
     ) -> Self {
         MockNavcompLoader {
             event_sender,
             completions,
             symbols,
+            files,
+            health,
+            apply_edit_requests,
         }
     }
 }
@@ -49,6 +58,9 @@ impl NavCompLoader for MockNavcompLoader {
                 self.event_sender.clone(),
                 self.completions.clone(),
                 self.symbols.clone(),
+                self.files.clone(),
+                self.health.clone(),
+                self.apply_edit_requests.clone(),
             )) as Box<dyn NavCompProvider>,
         ));
 
@@ -1,7 +1,7 @@
 use std::thread;
 use std::time::Duration;
 
-use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TryRecvError};
 use log::{debug, error};
 
 use crate::promise::promise::{Promise, PromiseState, UpdateResult};
@@ -16,13 +16,18 @@ pub struct MockNavCompPromise<T: Send + 'static> {
 }
 
 impl<T: Send + 'static> MockNavCompPromise<T> {
-    const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+    pub const DEFAULT_DELAY: Duration = Duration::from_millis(100);
 
     pub fn new_succ(tick_sender: Sender<NavCompTick>, value: T) -> Self {
+        Self::new_succ_after(tick_sender, value, Self::DEFAULT_DELAY)
+    }
+
+    // Use delay longer than caller's timeout to pretend navcomp got stuck.
+    pub fn new_succ_after(tick_sender: Sender<NavCompTick>, value: T, delay: Duration) -> Self {
         let (sender, receiver) = crossbeam_channel::bounded::<T>(1);
 
         let join_handle = thread::spawn(move || {
-            thread::sleep(Self::DEFAULT_DELAY);
+            thread::sleep(delay);
             if sender.send(value).is_ok() {
                 tick_sender.send(NavCompTick::LspTick(LangId::RUST, 0)).unwrap();
                 debug!("sent succ");
@@ -115,14 +120,24 @@ impl<T: Send + 'static> Promise<T> for MockNavCompPromise<T> {
         res
     }
 
-    fn wait(&mut self, _how_long: Option<Duration>) -> PromiseState {
-        let res = match self.receiver.recv() {
+    fn wait(&mut self, how_long: Option<Duration>) -> PromiseState {
+        if self.done {
+            return self.internal_update().state;
+        }
+
+        let recv_result = match how_long {
+            None => self.receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(duration) => self.receiver.recv_timeout(duration),
+        };
+
+        let res = match recv_result {
             Ok(value) => {
                 self.item = Some(value);
                 self.done = true;
                 PromiseState::Ready
             }
-            Err(_) => {
+            Err(RecvTimeoutError::Timeout) => PromiseState::Unresolved,
+            Err(RecvTimeoutError::Disconnected) => {
                 self.done = true;
                 PromiseState::Broken
             }
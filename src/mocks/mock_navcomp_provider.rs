@@ -1,4 +1,5 @@
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock, RwLockWriteGuard};
 use std::time::Duration;
 
@@ -11,14 +12,19 @@ use crate::fs::path::SPath;
 use crate::lsp_client::diagnostic::Diagnostic;
 use crate::mocks::mock_navcomp_promise::MockNavCompPromise;
 use crate::mocks::mock_navcomp_provider::MockNavCompEvent::FileOpened;
+use crate::primitives::common_query::CommonQuery;
 use crate::primitives::stupid_cursor::StupidCursor;
 use crate::promise::promise::Promise;
-use crate::unpack_or_e;
 use crate::w7e::navcomp_group::{NavCompTick, NavCompTickSender};
 use crate::w7e::navcomp_provider::{
-    Completion, CompletionsPromise, FormattingPromise, NavCompProvider, NavCompSymbol, StupidSymbolUsage, SymbolType, SymbolUsagesPromise,
+    CodeActionsPromise, Completion, CompletionsPromise, DocumentHighlightsPromise, DocumentSymbolsPromise, EditEvent, FormattingPromise,
+    HierarchyKind, HierarchyPromise, HoverPromise, InlayHintsPromise, NavCompApplyEditRequest, NavCompCodeAction, NavCompCommand,
+    NavCompDocumentHighlight, NavCompDocumentSymbol, NavCompHierarchyItem, NavCompProgress, NavCompProvider, NavCompSemanticToken,
+    NavCompSignature, NavCompSymbol, NavCompWorkspaceEdit, NavCompWorkspaceSymbol, RenamePromise, SemanticTokensPromise,
+    SignatureHelpPromise, StupidSubstituteMessage, StupidSymbolUsage, SymbolType, SymbolUsagesPromise, WorkspaceSymbolsPromise,
 };
-use crate::widgets::editor_widget::label::label::Label;
+use crate::widgets::editor_widget::label::label::{Label, LabelPos};
+use crate::{unpack_or, unpack_or_e};
 
 pub struct MockCompletionMatcher {
     // None matches all
@@ -27,11 +33,48 @@ pub struct MockCompletionMatcher {
     pub answer: Option<Vec<Completion>>,
 }
 
+#[derive(Default)]
 pub struct MockSymbolMatcher {
     pub path: Option<SPath>,
     pub symbol: NavCompSymbol,
-    // None means "return broken promise"
+    // None means "return broken promise". Rename substitutes new name at all usages. Go-to type
+    // definition, implementations and declaration jump to these too.
     pub usages: Option<Vec<StupidSymbolUsage>>,
+    // None means "nothing to show"
+    pub hover: Option<String>,
+    // None means "not within a call"
+    pub signature_help: Option<NavCompSignature>,
+    // offered for any range beginning within the symbol
+    pub code_actions: Vec<NavCompCodeAction>,
+    // None means "no hierarchy here"
+    pub hierarchy: Option<MockHierarchy>,
+    // occurrences within the same file
+    pub document_highlights: Vec<NavCompDocumentHighlight>,
+}
+
+/*
+Item is what hierarchy starts with when asked at symbol. Children are found by name of the item
+they are asked for, so to expand a child further, give it a matcher of its own.
+ */
+pub struct MockHierarchy {
+    pub item: NavCompHierarchyItem,
+    pub children: Vec<(HierarchyKind, Vec<NavCompHierarchyItem>)>,
+}
+
+// answers to questions about entire file, as opposed to symbol under cursor
+#[derive(Default)]
+pub struct MockFileMatcher {
+    pub path: Option<SPath>,
+    // returned if requested range covers their position
+    pub inlay_hints: Vec<Label>,
+    // None means "return no promise". Workspace symbol search goes through these too.
+    pub document_symbols: Option<Vec<NavCompDocumentSymbol>>,
+    // None means "return no promise". Range formatting returns edits beginning within the range.
+    pub formatting: Option<Vec<StupidSubstituteMessage>>,
+    // None means usual mock delay
+    pub formatting_delay: Option<Duration>,
+    // None means "return no promise"
+    pub semantic_tokens: Option<Vec<NavCompSemanticToken>>,
 }
 
 impl MockSymbolMatcher {
@@ -51,18 +94,34 @@ impl MockSymbolMatcher {
     }
 }
 
+// Shared between provider and pilot, so tests can pretend the underlying service died or is busy.
+#[derive(Debug, Default)]
+pub struct MockNavCompHealth {
+    crashed: AtomicBool,
+    // while set, restart attempts fail
+    refuse_restart: AtomicBool,
+    progress: RwLock<Vec<NavCompProgress>>,
+}
+
 #[derive(Clone, Debug)]
 pub enum MockNavCompEvent {
     FileOpened(SPath, String),
     FileUpdated(SPath, String),
+    FileChangedOnDisk(SPath),
+    CommandExecuted(String),
 }
 
 pub struct MockNavCompProvider {
     triggers: Vec<String>,
+    signature_help_triggers: Vec<String>,
     event_sender: Sender<MockNavCompEvent>,
     navcomp_tick_server: Sender<NavCompTick>,
     completions: Arc<RwLock<Vec<MockCompletionMatcher>>>,
     symbols: Arc<RwLock<Vec<MockSymbolMatcher>>>,
+    files: Arc<RwLock<Vec<MockFileMatcher>>>,
+    health: Arc<MockNavCompHealth>,
+    // pushed by pilot, pretending service asked for them
+    apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>>,
 }
 
 impl MockNavCompProvider {
@@ -71,13 +130,20 @@ impl MockNavCompProvider {
         event_sender: Sender<MockNavCompEvent>,
         completions: Arc<RwLock<Vec<MockCompletionMatcher>>>,
         symbols: Arc<RwLock<Vec<MockSymbolMatcher>>>,
+        files: Arc<RwLock<Vec<MockFileMatcher>>>,
+        health: Arc<MockNavCompHealth>,
+        apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>>,
     ) -> Self {
         MockNavCompProvider {
             event_sender,
             triggers: vec![".".to_string(), "::".to_string()],
+            signature_help_triggers: vec!["(".to_string(), ",".to_string()],
             navcomp_tick_server,
             completions,
             symbols,
+            files,
+            health,
+            apply_edit_requests,
         }
     }
 }
@@ -86,6 +152,10 @@ pub struct MockNavCompProviderPilot {
     recvr: Receiver<MockNavCompEvent>,
     completions: Arc<RwLock<Vec<MockCompletionMatcher>>>,
     symbols: Arc<RwLock<Vec<MockSymbolMatcher>>>,
+    files: Arc<RwLock<Vec<MockFileMatcher>>>,
+    health: Arc<MockNavCompHealth>,
+    // pushed by pilot, pretending service asked for them
+    apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>>,
 }
 
 impl MockNavCompProviderPilot {
@@ -95,11 +165,17 @@ impl MockNavCompProviderPilot {
         recvr: Receiver<MockNavCompEvent>,
         completions: Arc<RwLock<Vec<MockCompletionMatcher>>>,
         symbols: Arc<RwLock<Vec<MockSymbolMatcher>>>,
+        files: Arc<RwLock<Vec<MockFileMatcher>>>,
+        health: Arc<MockNavCompHealth>,
+        apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>>,
     ) -> Self {
         MockNavCompProviderPilot {
             recvr,
             completions,
             symbols,
+            files,
+            health,
+            apply_edit_requests,
         }
     }
 
@@ -132,6 +208,56 @@ impl MockNavCompProviderPilot {
         }
     }
 
+    pub fn wait_for_command(&self, requested_command: &str) -> bool {
+        loop {
+            select! {
+                recv(self.recvr) -> msg_res => {
+                    match msg_res {
+                        Ok(MockNavCompEvent::CommandExecuted(command)) if command == requested_command => {
+                            return true;
+                        }
+                        Ok(other) => {
+                            debug!("received {:?}", other);
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("failed retrieving msg: {:?}", e);
+                            return false;
+                        }
+                    }
+                },
+                default(Self::DEFAULT_TIMEOUT) => {
+                    return false;
+                }
+            }
+        }
+    }
+
+    pub fn wait_for_file_changed_on_disk(&self, requested_path: &SPath) -> bool {
+        loop {
+            select! {
+                recv(self.recvr) -> msg_res => {
+                    match msg_res {
+                        Ok(MockNavCompEvent::FileChangedOnDisk(path)) if &path == requested_path => {
+                            return true;
+                        }
+                        Ok(other) => {
+                            debug!("received {:?}", other);
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("failed retrieving msg: {:?}", e);
+                            return false;
+                        }
+                    }
+                },
+                default(Self::DEFAULT_TIMEOUT) => {
+                    return false;
+                }
+            }
+        }
+    }
+
     pub fn completions(&self) -> Option<RwLockWriteGuard<Vec<MockCompletionMatcher>>> {
         match self.completions.write() {
             Ok(lock) => Some(lock),
@@ -151,6 +277,61 @@ impl MockNavCompProviderPilot {
             }
         }
     }
+
+    pub fn files(&self) -> Option<RwLockWriteGuard<'_, Vec<MockFileMatcher>>> {
+        match self.files.write() {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                error!("failed acquiring files lock: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /*
+    Pretends the service asked for an edit. Returned receiver tells whether it was applied. Requests
+    are picked up on the next frame, so send a Tick if nothing else is happening.
+     */
+    pub fn request_apply_edit(&self, edit: NavCompWorkspaceEdit) -> Option<Receiver<bool>> {
+        let (sender, receiver) = crossbeam_channel::bounded::<bool>(1);
+        let request = NavCompApplyEditRequest::new(
+            None,
+            edit,
+            Box::new(move |applied| {
+                if sender.send(applied).is_err() {
+                    error!("nobody waits for apply edit result");
+                }
+            }),
+        );
+
+        match self.apply_edit_requests.write() {
+            Ok(mut lock) => {
+                lock.push(request);
+                Some(receiver)
+            }
+            Err(e) => {
+                error!("failed acquiring apply edit requests lock: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Navcomp reports being unhealthy until it's restarted.
+    pub fn crash(&self) {
+        self.health.crashed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn set_refuse_restart(&self, refuse: bool) {
+        self.health.refuse_restart.store(refuse, Ordering::SeqCst);
+    }
+
+    // Like with edit requests, it's noticed on the next frame.
+    pub fn set_progress(&self, progress: Vec<NavCompProgress>) {
+        match self.health.progress.write() {
+            Ok(mut lock) => *lock = progress,
+            Err(e) => error!("failed acquiring progress lock: {:?}", e),
+        }
+    }
 }
 
 impl NavCompProvider for MockNavCompProvider {
@@ -160,12 +341,16 @@ impl NavCompProvider for MockNavCompProvider {
             .unwrap()
     }
 
-    fn submit_edit_event(&self, path: &SPath, file_contents: ropey::Rope) {
+    fn submit_edit_event(&self, path: &SPath, edit_event: EditEvent) {
         self.event_sender
-            .send(MockNavCompEvent::FileUpdated(path.clone(), file_contents.to_string()))
+            .send(MockNavCompEvent::FileUpdated(path.clone(), edit_event.file_contents().to_string()))
             .unwrap()
     }
 
+    fn file_changed_on_disk(&self, path: &SPath) {
+        self.event_sender.send(MockNavCompEvent::FileChangedOnDisk(path.clone())).unwrap()
+    }
+
     fn completions(&self, path: SPath, _cursor: StupidCursor, _trigger: Option<String>) -> Option<CompletionsPromise> {
         let completions = unpack_or_e!(self.completions.read().ok(), None, "failed acquiring lock on completions");
 
@@ -257,8 +442,279 @@ impl NavCompProvider for MockNavCompProvider {
         todo!()
     }
 
-    fn todo_reformat(&self, _path: &SPath) -> Option<FormattingPromise> {
-        todo!()
+    fn go_to_type_definition(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise> {
+        self.get_symbol_usages(path, cursor)
+    }
+
+    fn go_to_implementations(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise> {
+        self.get_symbol_usages(path, cursor)
+    }
+
+    fn go_to_declaration(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise> {
+        self.get_symbol_usages(path, cursor)
+    }
+
+    fn hover(&self, path: &SPath, cursor: StupidCursor) -> Option<HoverPromise> {
+        let symbols = unpack_or_e!(self.symbols.read().ok(), None, "failed acquiring lock on symbols");
+
+        let res = symbols
+            .iter()
+            .find(|candidate| candidate.matches(Some(path), cursor))
+            .map(|c| Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), c.hover.clone())) as HoverPromise);
+
+        if res.is_none() {
+            debug!("no results for hover");
+        }
+
+        res
+    }
+
+    fn document_highlights(&self, path: &SPath, cursor: StupidCursor) -> Option<DocumentHighlightsPromise> {
+        let symbols = unpack_or_e!(self.symbols.read().ok(), None, "failed acquiring lock on symbols");
+
+        let res = symbols.iter().find(|candidate| candidate.matches(Some(path), cursor)).map(|c| {
+            Box::new(MockNavCompPromise::new_succ(
+                self.navcomp_tick_server.clone(),
+                c.document_highlights.clone(),
+            )) as DocumentHighlightsPromise
+        });
+
+        if res.is_none() {
+            debug!("no results for document highlights");
+        }
+
+        res
+    }
+
+    fn signature_help(&self, path: &SPath, cursor: StupidCursor) -> Option<SignatureHelpPromise> {
+        let symbols = unpack_or_e!(self.symbols.read().ok(), None, "failed acquiring lock on symbols");
+
+        let res = symbols.iter().find(|candidate| candidate.matches(Some(path), cursor)).map(|c| {
+            Box::new(MockNavCompPromise::new_succ(
+                self.navcomp_tick_server.clone(),
+                c.signature_help.clone(),
+            )) as SignatureHelpPromise
+        });
+
+        if res.is_none() {
+            debug!("no results for signature help");
+        }
+
+        res
+    }
+
+    fn signature_help_triggers(&self, _path: &SPath) -> &Vec<String> {
+        &self.signature_help_triggers
+    }
+
+    fn rename(&self, path: &SPath, cursor: StupidCursor, new_name: String) -> Option<RenamePromise> {
+        let symbols = unpack_or_e!(self.symbols.read().ok(), None, "failed acquiring lock on symbols");
+
+        let res = symbols
+            .iter()
+            .find(|candidate| candidate.matches(Some(path), cursor))
+            .map(|c| match c.usages.as_ref() {
+                None => {
+                    debug!("returning broken rename promise");
+                    Box::new(MockNavCompPromise::<Option<NavCompWorkspaceEdit>>::new_broken(
+                        self.navcomp_tick_server.clone(),
+                    )) as RenamePromise
+                }
+                Some(usages) => {
+                    let mut workspace_edit = NavCompWorkspaceEdit::default();
+                    for usage in usages {
+                        let edit = StupidSubstituteMessage {
+                            substitute: new_name.clone(),
+                            stupid_range: usage.stupid_range,
+                        };
+
+                        match workspace_edit.file_edits.iter_mut().find(|(p, _)| *p == usage.path) {
+                            Some((_, edits)) => edits.push(edit),
+                            None => workspace_edit.file_edits.push((usage.path.clone(), vec![edit])),
+                        }
+                    }
+
+                    debug!("returning successful rename promise");
+                    Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), Some(workspace_edit))) as RenamePromise
+                }
+            });
+
+        if res.is_none() {
+            debug!("no results for rename");
+        }
+
+        res
+    }
+
+    fn code_actions(&self, path: &SPath, stupid_range: (StupidCursor, StupidCursor)) -> Option<CodeActionsPromise> {
+        let symbols = unpack_or_e!(self.symbols.read().ok(), None, "failed acquiring lock on symbols");
+
+        let res = symbols
+            .iter()
+            .find(|candidate| candidate.matches(Some(path), stupid_range.0))
+            .map(|c| {
+                Box::new(MockNavCompPromise::new_succ(
+                    self.navcomp_tick_server.clone(),
+                    c.code_actions.clone(),
+                )) as CodeActionsPromise
+            });
+
+        if res.is_none() {
+            debug!("no results for code actions");
+        }
+
+        res
+    }
+
+    fn execute_command(&self, command: &NavCompCommand) -> bool {
+        self.event_sender
+            .send(MockNavCompEvent::CommandExecuted(command.command.clone()))
+            .unwrap();
+        true
+    }
+
+    fn inlay_hints(&self, path: &SPath, stupid_range: (StupidCursor, StupidCursor)) -> Option<InlayHintsPromise> {
+        let files = unpack_or_e!(self.files.read().ok(), None, "failed acquiring lock on files");
+
+        let res = files.iter().find(|c| c.path.as_ref() == Some(path)).map(|c| {
+            let hints: Vec<Label> = c
+                .inlay_hints
+                .iter()
+                .filter(|hint| match &hint.pos {
+                    LabelPos::InlineStupid { stupid_cursor } => stupid_cursor.is_between(stupid_range.0, stupid_range.1),
+                    _ => true,
+                })
+                .cloned()
+                .collect();
+
+            Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), hints)) as InlayHintsPromise
+        });
+
+        if res.is_none() {
+            debug!("no results for inlay hints");
+        }
+
+        res
+    }
+
+    fn document_symbols(&self, path: &SPath) -> Option<DocumentSymbolsPromise> {
+        let files = unpack_or_e!(self.files.read().ok(), None, "failed acquiring lock on files");
+
+        let res = files
+            .iter()
+            .find(|c| c.path.as_ref() == Some(path))
+            .and_then(|c| c.document_symbols.clone())
+            .map(|symbols| Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), symbols)) as DocumentSymbolsPromise);
+
+        if res.is_none() {
+            debug!("no results for document symbols");
+        }
+
+        res
+    }
+
+    fn semantic_tokens(&self, path: &SPath) -> Option<SemanticTokensPromise> {
+        let files = unpack_or_e!(self.files.read().ok(), None, "failed acquiring lock on files");
+
+        files
+            .iter()
+            .find(|c| c.path.as_ref() == Some(path))
+            .and_then(|c| c.semantic_tokens.clone())
+            .map(|tokens| Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), Some(tokens))) as SemanticTokensPromise)
+    }
+
+    fn workspace_symbols(&self, query: &str) -> Option<WorkspaceSymbolsPromise> {
+        fn collect(
+            query: &CommonQuery,
+            url: &Url,
+            container_name_op: Option<&str>,
+            symbols: &[NavCompDocumentSymbol],
+            result: &mut Vec<NavCompWorkspaceSymbol>,
+        ) {
+            for symbol in symbols {
+                if query.matches(&symbol.name) {
+                    result.push(NavCompWorkspaceSymbol {
+                        name: symbol.name.clone(),
+                        symbol_type: symbol.symbol_type.clone(),
+                        container_name_op: container_name_op.map(|c| c.to_string()),
+                        location: StupidSymbolUsage {
+                            path: url.to_string(),
+                            stupid_range: symbol.name_stupid_range,
+                        },
+                    });
+                }
+
+                collect(query, url, Some(&symbol.name), &symbol.children, result);
+            }
+        }
+
+        let files = unpack_or_e!(self.files.read().ok(), None, "failed acquiring lock on files");
+        let query = CommonQuery::Fuzzy(query.to_string());
+
+        let mut result: Vec<NavCompWorkspaceSymbol> = Vec::new();
+        for file in files.iter() {
+            if let (Some(path), Some(symbols)) = (file.path.as_ref(), file.document_symbols.as_ref()) {
+                let url = unpack_or_e!(path.to_url().ok(), None, "failed converting {} to url", path);
+                collect(&query, &url, None, symbols, &mut result);
+            }
+        }
+
+        Some(Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), result)))
+    }
+
+    fn prepare_hierarchy(&self, path: &SPath, cursor: StupidCursor, _kind: HierarchyKind) -> Option<HierarchyPromise> {
+        let symbols = unpack_or_e!(self.symbols.read().ok(), None, "failed acquiring lock on symbols");
+
+        let res = symbols.iter().find(|candidate| candidate.matches(Some(path), cursor)).map(|c| {
+            let items: Vec<NavCompHierarchyItem> = c.hierarchy.iter().map(|hierarchy| hierarchy.item.clone()).collect();
+            Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), items)) as HierarchyPromise
+        });
+
+        if res.is_none() {
+            debug!("no results for hierarchy");
+        }
+
+        res
+    }
+
+    fn hierarchy_children(&self, item: &NavCompHierarchyItem, kind: HierarchyKind) -> Option<HierarchyPromise> {
+        let symbols = unpack_or_e!(self.symbols.read().ok(), None, "failed acquiring lock on symbols");
+
+        let children: Vec<NavCompHierarchyItem> = symbols
+            .iter()
+            .filter_map(|c| c.hierarchy.as_ref())
+            .find(|hierarchy| hierarchy.item.name == item.name)
+            .and_then(|hierarchy| hierarchy.children.iter().find(|(k, _)| *k == kind))
+            .map(|(_, children)| children.clone())
+            .unwrap_or_default();
+
+        Some(Box::new(MockNavCompPromise::new_succ(self.navcomp_tick_server.clone(), children)))
+    }
+
+    fn format(&self, path: &SPath, stupid_range_op: Option<(StupidCursor, StupidCursor)>) -> Option<FormattingPromise> {
+        let files = unpack_or_e!(self.files.read().ok(), None, "failed acquiring lock on files");
+        let file = unpack_or!(
+            files.iter().find(|c| c.path.as_ref() == Some(path)),
+            None,
+            "no results for formatting"
+        );
+        let edits = unpack_or!(file.formatting.as_ref(), None, "no results for formatting");
+
+        let edits: Vec<StupidSubstituteMessage> = match stupid_range_op {
+            None => edits.clone(),
+            Some((begin, end)) => edits
+                .iter()
+                .filter(|edit| edit.stupid_range.0.is_between(begin, end))
+                .cloned()
+                .collect(),
+        };
+
+        let delay = file.formatting_delay.unwrap_or(MockNavCompPromise::<()>::DEFAULT_DELAY);
+        Some(Box::new(MockNavCompPromise::new_succ_after(
+            self.navcomp_tick_server.clone(),
+            Some(edits),
+            delay,
+        )))
     }
 
     fn file_closed(&self, _path: &SPath) {}
@@ -271,8 +727,38 @@ impl NavCompProvider for MockNavCompProvider {
         &self.navcomp_tick_server
     }
 
-    fn todo_is_healthy(&self) -> bool {
-        todo!()
+    fn take_apply_edit_requests(&self) -> Vec<NavCompApplyEditRequest> {
+        match self.apply_edit_requests.write() {
+            Ok(mut lock) => std::mem::take(&mut *lock),
+            Err(e) => {
+                error!("failed acquiring apply edit requests lock: {:?}", e);
+                vec![]
+            }
+        }
+    }
+
+    fn progress(&self) -> Vec<NavCompProgress> {
+        match self.health.progress.read() {
+            Ok(lock) => lock.clone(),
+            Err(e) => {
+                error!("failed acquiring progress lock: {:?}", e);
+                vec![]
+            }
+        }
+    }
+
+    fn is_healthy(&self) -> bool {
+        !self.health.crashed.load(Ordering::SeqCst)
+    }
+
+    fn restart(&self) -> bool {
+        if self.health.refuse_restart.load(Ordering::SeqCst) {
+            debug!("refusing to restart");
+            return false;
+        }
+
+        self.health.crashed.store(false, Ordering::SeqCst);
+        true
     }
 }
 
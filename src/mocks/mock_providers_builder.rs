@@ -8,9 +8,12 @@ use crate::fs::mock_fs::MockFS;
 use crate::gladius::providers::Providers;
 use crate::mocks::mock_clipboard::MockClipboard;
 use crate::mocks::mock_navcomp_loader::MockNavcompLoader;
-use crate::mocks::mock_navcomp_provider::{MockCompletionMatcher, MockNavCompEvent, MockNavCompProviderPilot, MockSymbolMatcher};
+use crate::mocks::mock_navcomp_provider::{
+    MockCompletionMatcher, MockFileMatcher, MockNavCompEvent, MockNavCompHealth, MockNavCompProviderPilot, MockSymbolMatcher,
+};
 use crate::tsw::language_set::LanguageSet;
 use crate::tsw::tree_sitter_wrapper::TreeSitterWrapper;
+use crate::w7e::navcomp_provider::NavCompApplyEditRequest;
 use crate::widgets::editor_widget::label::labels_provider::LabelsProviderRef;
 
 pub struct SideChannels {
@@ -72,8 +75,18 @@ impl MockProvidersBuilder {
         let (mock_navcomp_event_sender, mock_navcomp_event_recvr) = crossbeam_channel::unbounded::<MockNavCompEvent>();
         let comp_matcher: Arc<RwLock<Vec<MockCompletionMatcher>>> = Arc::new(RwLock::new(Vec::new()));
         let symbol_matcher: Arc<RwLock<Vec<MockSymbolMatcher>>> = Arc::new(RwLock::new(Vec::new()));
+        let file_matcher: Arc<RwLock<Vec<MockFileMatcher>>> = Arc::new(RwLock::new(Vec::new()));
+        let health: Arc<MockNavCompHealth> = Arc::new(MockNavCompHealth::default());
+        let apply_edit_requests: Arc<RwLock<Vec<NavCompApplyEditRequest>>> = Arc::new(RwLock::new(Vec::new()));
 
-        let navcomp_loader = MockNavcompLoader::new(mock_navcomp_event_sender, comp_matcher.clone(), symbol_matcher.clone());
+        let navcomp_loader = MockNavcompLoader::new(
+            mock_navcomp_event_sender,
+            comp_matcher.clone(),
+            symbol_matcher.clone(),
+            file_matcher.clone(),
+            health.clone(),
+            apply_edit_requests.clone(),
+        );
         let label_providers = self.label_providers;
 
         let providers = Providers::new(
@@ -89,7 +102,14 @@ impl MockProvidersBuilder {
         BuildResult {
             providers,
             side_channels: SideChannels {
-                navcomp_pilot: MockNavCompProviderPilot::new(mock_navcomp_event_recvr, comp_matcher, symbol_matcher),
+                navcomp_pilot: MockNavCompProviderPilot::new(
+                    mock_navcomp_event_recvr,
+                    comp_matcher,
+                    symbol_matcher,
+                    file_matcher,
+                    health,
+                    apply_edit_requests,
+                ),
             },
         }
     }
@@ -15,6 +15,8 @@ pub mod editor_interpreter;
 pub mod full_setup;
 pub mod fuzz_call;
 pub mod generic_dialog_interpreter;
+pub mod history_browser_interpreter;
+pub mod hover_interpreter;
 pub mod listview_interpreter;
 pub mod log_capture;
 pub mod meta_frame;
@@ -29,8 +31,12 @@ pub mod mock_providers_builder;
 pub mod mock_tree_item;
 pub mod nested_menu_interpreter;
 pub mod no_editor_interpreter;
+pub mod recovery_view_interpreter;
+pub mod rename_interpreter;
 pub mod savefile_interpreter;
 pub mod scroll_interpreter;
+pub mod signature_help_interpreter;
+pub mod task_view_interpreter;
 pub mod text_widget_interpreter;
 pub mod treeview_interpreter;
 pub mod with_scroll_interpreter;
@@ -0,0 +1,37 @@
+use crate::io::output::Metadata;
+use crate::mocks::editbox_interpreter::EditWidgetInterpreter;
+use crate::mocks::meta_frame::MetaOutputFrame;
+use crate::widgets::edit_box::EditBoxWidget;
+use crate::widgets::editor_widget::rename::rename_widget::RenameWidget;
+
+pub struct RenameInterpreter<'a> {
+    meta: &'a Metadata,
+    edit_box: EditWidgetInterpreter<'a>,
+}
+
+impl<'a> RenameInterpreter<'a> {
+    pub fn new(meta: &'a Metadata, output: &'a MetaOutputFrame) -> Option<Self> {
+        debug_assert!(meta.typename == RenameWidget::TYPENAME);
+
+        let edit_box_meta = output
+            .get_meta_by_type(EditBoxWidget::TYPENAME)
+            .find(|eb| meta.rect.contains_rect(eb.rect))?;
+
+        Some(Self {
+            meta,
+            edit_box: EditWidgetInterpreter::new(edit_box_meta, output),
+        })
+    }
+
+    pub fn meta(&self) -> &Metadata {
+        self.meta
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.edit_box.is_focused()
+    }
+
+    pub fn new_name(&self) -> String {
+        self.edit_box.contents()
+    }
+}
@@ -0,0 +1,46 @@
+use crate::io::cell::Cell;
+use crate::io::output::Metadata;
+use crate::mocks::meta_frame::MetaOutputFrame;
+use crate::widgets::editor_widget::signature_help::signature_help_widget::SignatureHelpWidget;
+
+pub struct SignatureHelpInterpreter<'a> {
+    meta: &'a Metadata,
+    output: &'a MetaOutputFrame,
+}
+
+impl<'a> SignatureHelpInterpreter<'a> {
+    pub fn new(meta: &'a Metadata, output: &'a MetaOutputFrame) -> Self {
+        debug_assert!(meta.typename == SignatureHelpWidget::TYPENAME);
+
+        Self { meta, output }
+    }
+
+    pub fn text(&self) -> String {
+        self.output
+            .buffer
+            .lines_iter()
+            .with_rect(self.meta.rect)
+            .next()
+            .map(|line| line.text.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    // Returns text of active parameter
+    pub fn highlighted(&self) -> String {
+        let highlighted_background = self.output.theme.highlighted(self.meta.focused).background;
+
+        self.output
+            .buffer
+            .cells_iter()
+            .with_rect(self.meta.rect)
+            .filter_map(|(_pos, cell)| match cell {
+                Cell::Begin { style, grapheme } if style.background == highlighted_background => Some(grapheme.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.text() == SignatureHelpWidget::LOADING
+    }
+}
@@ -0,0 +1,53 @@
+use crate::io::output::Metadata;
+use crate::mocks::meta_frame::MetaOutputFrame;
+use crate::widgets::task_view::task_view_widget::TaskView;
+
+pub struct TaskViewInterpreter<'a> {
+    meta: &'a Metadata,
+    output: &'a MetaOutputFrame,
+}
+
+impl<'a> TaskViewInterpreter<'a> {
+    pub fn new(output: &'a MetaOutputFrame, meta: &'a Metadata) -> Self {
+        debug_assert!(meta.typename == TaskView::TYPENAME);
+
+        Self { meta, output }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.meta.focused
+    }
+
+    // like "greet: finished"
+    pub fn header(&self) -> String {
+        self.output
+            .buffer
+            .lines_iter()
+            .with_rect(self.meta.rect)
+            .next()
+            .map(|line| line.text.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.header().ends_with(TaskView::CANCEL_HINT.trim())
+    }
+
+    // visible output lines, trimmed, empty ones at the bottom skipped
+    pub fn output_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .output
+            .buffer
+            .lines_iter()
+            .with_rect(self.meta.rect)
+            .skip(1)
+            .map(|line| line.text.trim().to_string())
+            .collect();
+
+        while lines.last().map(|line| line.is_empty()).unwrap_or(false) {
+            lines.pop();
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,101 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Formatter;
+
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::xy::XY;
+
+/*
+Position in document ("buffer space"): x is column, y is line, both 0-based.
+
+XY is u16 and is reserved for screen space. Documents are routinely longer (and sometimes wider) than
+65k, so anything that points into text uses this type, and gets cast to XY only after subtracting the
+viewport offset.
+
+Unlike XY, BufferXY is totally ordered in "reading order" (line first, then column), so it can be used
+as a key in ordered maps.
+ */
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BufferXY {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl BufferXY {
+    pub const ZERO: BufferXY = BufferXY::new(0, 0);
+
+    pub const fn new(x: usize, y: usize) -> Self {
+        BufferXY { x, y }
+    }
+
+    /*
+    Returns position relative to origin, as long as it's not above or left of it and it fits in screen
+    space.
+     */
+    pub fn relative_to(&self, origin: BufferXY) -> Option<XY> {
+        let x = self.x.checked_sub(origin.x)?;
+        let y = self.y.checked_sub(origin.y)?;
+
+        if x > u16::MAX as usize || y > u16::MAX as usize {
+            return None;
+        }
+
+        Some(XY::new(x as u16, y as u16))
+    }
+
+    // Cuts values that do not fit in screen space to u16::MAX.
+    pub fn to_xy_saturating(&self) -> XY {
+        XY::new(
+            std::cmp::min(self.x, u16::MAX as usize) as u16,
+            std::cmp::min(self.y, u16::MAX as usize) as u16,
+        )
+    }
+}
+
+impl From<XY> for BufferXY {
+    fn from(xy: XY) -> Self {
+        BufferXY::new(xy.x as usize, xy.y as usize)
+    }
+}
+
+impl Ord for BufferXY {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.y.cmp(&other.y).then(self.x.cmp(&other.x))
+    }
+}
+
+impl PartialOrd for BufferXY {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BufferXY {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::buffer_xy::BufferXY;
+    use crate::primitives::xy::XY;
+
+    #[test]
+    fn buffer_xy_reading_order_test() {
+        assert!(BufferXY::new(100, 1) < BufferXY::new(0, 2));
+        assert!(BufferXY::new(1, 70_000) < BufferXY::new(2, 70_000));
+        assert_eq!(BufferXY::new(3, 3), BufferXY::new(3, 3));
+    }
+
+    #[test]
+    fn buffer_xy_relative_to_test() {
+        let origin = BufferXY::new(10, 100_000);
+
+        assert_eq!(BufferXY::new(12, 100_005).relative_to(origin), Some(XY::new(2, 5)));
+        assert_eq!(BufferXY::new(9, 100_005).relative_to(origin), None);
+        assert_eq!(BufferXY::new(12, 99_999).relative_to(origin), None);
+        assert_eq!(BufferXY::new(12, 200_000).relative_to(origin), None);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod arrow;
 pub mod border;
+pub mod buffer_xy;
 pub mod color;
 pub mod helpers;
 pub mod rect;
@@ -8,7 +8,7 @@ use unicode_width::UnicodeWidthStr;
 use crate::cursor::cursor::Cursor;
 use crate::cursor::cursor::Selection;
 
-use crate::primitives::xy::XY;
+use crate::primitives::buffer_xy::BufferXY;
 use crate::text::text_buffer::TextBuffer;
 use crate::unpack_or;
 
@@ -16,8 +16,11 @@ use crate::unpack_or;
 This is a completely useless variant of cursor, that is used only because LSP uses it.
 It does not easily convert neither to byte offset NOR char idx NOR screen pos, because it does not
 mind multi-column chars.
+
+Whatever comes from LSP counts columns in UTF-16 code units, so to_real_cursor does the same. It's
+only different from char idx in lines with chars outside of BMP (emojis).
  */
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct StupidCursor {
     // Zero based
     pub char_idx_0b: u32,
@@ -67,13 +70,30 @@ impl StupidCursor {
         })
     }
 
-    pub fn to_xy(&self, rope: &dyn TextBuffer) -> Option<XY> {
-        if rope.len_lines() <= self.line_0b as usize {
-            debug!("StupidCursor.line {} > {} rope.lines().count", self.line_0b, rope.len_lines());
+    /*
+    Same as from_real_cursor, but column is counted in UTF-16 code units, which is what LSP means by
+    "character" unless told otherwise. It only differs when line has chars outside of BMP (emojis).
+    Used for incremental didChange, where an error would make server's copy of document drift away.
+     */
+    pub fn from_char_idx_utf16(rope: &ropey::Rope, char_idx: usize) -> Option<StupidCursor> {
+        if char_idx > rope.len_chars() {
+            error!("failed casting char idx {} to LSP cursor - beyond end of rope", char_idx);
             return None;
         }
-        if self.line_0b >= u16::MAX as u32 {
-            debug!("StupidCursor.line {} > u16::MAX", self.line_0b);
+
+        let line = rope.char_to_line(char_idx);
+        let line_begin = rope.line_to_char(line);
+        let col = rope.char_to_utf16_cu(char_idx) - rope.char_to_utf16_cu(line_begin);
+
+        Some(StupidCursor {
+            line_0b: line as u32,
+            char_idx_0b: col as u32,
+        })
+    }
+
+    pub fn to_xy(&self, rope: &dyn TextBuffer) -> Option<BufferXY> {
+        if rope.len_lines() <= self.line_0b as usize {
+            debug!("StupidCursor.line {} > {} rope.lines().count", self.line_0b, rope.len_lines());
             return None;
         }
 
@@ -88,7 +108,7 @@ impl StupidCursor {
                 None
             } else {
                 //I could support "wide characters" here, but I kinda expect the "stupid cursor" to be stupid.
-                Some(XY::new(self.char_idx_0b as u16, self.line_0b as u16))
+                Some(BufferXY::new(self.char_idx_0b as usize, self.line_0b as usize))
             }
         } else {
             debug!("no line no {} (0b)", self.line_0b);
@@ -102,24 +122,28 @@ impl StupidCursor {
             None,
             "can't cast stupid cursor to real cursor: not enough lines"
         );
-        let candidate = line_begin_char + self.char_idx_0b as usize;
-        if let Some(next_line_begin_char) = buffer.line_to_char((self.line_0b + 1) as usize) {
-            // I don't know why it works, but it works. So maybe test it, but sharp inequality was failing.
-            if candidate <= next_line_begin_char {
-                Some(Cursor::new(candidate))
-            } else {
+        // here we ALLOW character pointing to one-after-line (or one-after-buffer in the last line)
+        let line_end_char = buffer.line_to_char((self.line_0b + 1) as usize).unwrap_or(buffer.len_chars());
+
+        let mut char_idx = line_begin_char;
+        let mut utf16_col: usize = 0;
+        while utf16_col < self.char_idx_0b as usize {
+            if char_idx >= line_end_char {
                 debug!("can't cast stupid cursor to real cursor: not enough chars in given line");
-                None
-            }
-        } else {
-            // here we ALLOW character pointing to one-after-buffer
-            if candidate <= buffer.len_chars() {
-                Some(Cursor::new(candidate))
-            } else {
-                debug!("can't cast stupid cursor to real cursor: not enough chars in last line");
-                None
+                return None;
             }
+
+            let c = unpack_or!(buffer.char_at(char_idx), None, "can't cast stupid cursor to real cursor: no char");
+            utf16_col += c.len_utf16();
+            char_idx += 1;
+        }
+
+        if utf16_col != self.char_idx_0b as usize {
+            debug!("can't cast stupid cursor to real cursor: column points to the middle of a char");
+            return None;
         }
+
+        Some(Cursor::new(char_idx))
     }
 
     pub fn to_real_cursor_range(range: (StupidCursor, StupidCursor), buffer: &dyn TextBuffer) -> Option<Selection> {
@@ -165,3 +189,32 @@ impl Ord for StupidCursor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cursor::cursor::Cursor;
+    use crate::primitives::stupid_cursor::StupidCursor;
+
+    #[test]
+    fn stupid_cursor_to_real_cursor_counts_utf16_test() {
+        let rope = ropey::Rope::from_str("a😀b\n😀c");
+
+        // the emoji is 2 UTF-16 code units, but a single char
+        assert_eq!(StupidCursor::new(0, 0).to_real_cursor(&rope), Some(Cursor::new(0)));
+        assert_eq!(StupidCursor::new(3, 0).to_real_cursor(&rope), Some(Cursor::new(2)));
+        assert_eq!(StupidCursor::new(4, 0).to_real_cursor(&rope), Some(Cursor::new(3)));
+        assert_eq!(StupidCursor::new(2, 1).to_real_cursor(&rope), Some(Cursor::new(5)));
+        assert_eq!(StupidCursor::new(3, 1).to_real_cursor(&rope), Some(Cursor::new(6)));
+
+        // in the middle of the emoji
+        assert_eq!(StupidCursor::new(2, 0).to_real_cursor(&rope), None);
+        // beyond the line
+        assert_eq!(StupidCursor::new(4, 1).to_real_cursor(&rope), None);
+
+        // round trip
+        for char_idx in [0, 2, 3, 4, 5, 6] {
+            let stupid_cursor = StupidCursor::from_char_idx_utf16(&rope, char_idx).unwrap();
+            assert_eq!(stupid_cursor.to_real_cursor(&rope), Some(Cursor::new(char_idx)));
+        }
+    }
+}
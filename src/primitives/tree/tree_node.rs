@@ -47,6 +47,11 @@ pub trait TreeNode<Key: Hash + Eq + Debug>: Clone + Debug {
 pub trait TreeItFilter<Node>: Send + Sync + 'static {
     fn call(&self, node: &Node) -> bool;
 
+    // Text the filter was made from, if any. Nodes that stream search results use it as the search query.
+    fn query(&self) -> Option<&str> {
+        None
+    }
+
     fn arc_box(self) -> Arc<Box<dyn TreeItFilter<Node> + Send + Sync + 'static>>
     where
         Self: Sized,
@@ -57,11 +62,22 @@ pub trait TreeItFilter<Node>: Send + Sync + 'static {
 
 pub struct ClosureFilter<Node> {
     function: Box<dyn for<'a> Fn(&'a Node) -> bool + Send + Sync + 'static>,
+    query_op: Option<String>,
 }
 
 impl<Node> ClosureFilter<Node> {
     pub fn new<F: for<'a> Fn(&'a Node) -> bool + Send + Sync + 'static>(f: F) -> Self {
-        ClosureFilter { function: Box::new(f) }
+        ClosureFilter {
+            function: Box::new(f),
+            query_op: None,
+        }
+    }
+
+    pub fn with_query(self, query: String) -> Self {
+        ClosureFilter {
+            query_op: Some(query),
+            ..self
+        }
     }
 }
 
@@ -69,6 +85,10 @@ impl<Node: 'static> TreeItFilter<Node> for ClosureFilter<Node> {
     fn call(&self, node: &Node) -> bool {
         (self.function)(node)
     }
+
+    fn query(&self) -> Option<&str> {
+        self.query_op.as_deref()
+    }
 }
 
 pub type FilterRef<Node> = Arc<Box<dyn TreeItFilter<Node> + Send + Sync + 'static>>;
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use crate::promise::promise::{Promise, PromiseState};
+use crate::promise::streaming_promise::{StreamingPromise, StreamingPromiseState, UpdateResult};
+
+/*
+Streams results of multiple promises in order they are resolved, not in order they were given.
+Broken promises are just skipped, so this one is never "broken" itself. Used to ask several
+navcomps the same question without waiting for the slowest one.
+ */
+pub struct JoinedPromises<A: Clone> {
+    pending: Vec<Box<dyn Promise<Vec<A>>>>,
+    cached: Vec<A>,
+}
+
+impl<A: Clone> JoinedPromises<A> {
+    pub fn new(promises: Vec<Box<dyn Promise<Vec<A>>>>) -> Self {
+        let mut res = JoinedPromises {
+            pending: promises,
+            cached: vec![],
+        };
+
+        // some promises are resolved upfront
+        res.collect_resolved();
+        res
+    }
+
+    // returns whether anything was resolved
+    fn collect_resolved(&mut self) -> bool {
+        let old_len = self.pending.len();
+        let cached = &mut self.cached;
+
+        self.pending.retain(|promise| match promise.state() {
+            PromiseState::Unresolved => true,
+            PromiseState::Ready => {
+                if let Some(items) = promise.read() {
+                    cached.extend(items.iter().cloned());
+                }
+                false
+            }
+            PromiseState::Broken => {
+                debug!("skipping broken promise");
+                false
+            }
+        });
+
+        old_len != self.pending.len()
+    }
+}
+
+impl<A: Clone> StreamingPromise<A> for JoinedPromises<A> {
+    fn state(&self) -> StreamingPromiseState {
+        if self.pending.is_empty() {
+            StreamingPromiseState::Finished
+        } else {
+            StreamingPromiseState::Streaming
+        }
+    }
+
+    fn drain(&mut self, how_long: Option<Duration>) -> StreamingPromiseState {
+        let deadline_op = how_long.map(|duration| Instant::now() + duration);
+
+        for promise in self.pending.iter_mut() {
+            let time_left = deadline_op.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            promise.wait(time_left);
+        }
+
+        self.collect_resolved();
+        self.state()
+    }
+
+    fn update(&mut self) -> UpdateResult {
+        for promise in self.pending.iter_mut() {
+            promise.update();
+        }
+
+        let has_changed = self.collect_resolved();
+
+        UpdateResult {
+            state: self.state(),
+            has_changed,
+        }
+    }
+
+    fn read(&self) -> &Vec<A> {
+        &self.cached
+    }
+}
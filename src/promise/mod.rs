@@ -1,3 +1,4 @@
+pub mod joined_promises;
 pub mod promise;
 pub mod promise_map;
 pub mod streaming_promise;
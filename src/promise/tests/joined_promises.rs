@@ -0,0 +1,56 @@
+use crate::promise::joined_promises::JoinedPromises;
+use crate::promise::promise::{Promise, ResolvedPromise};
+use crate::promise::promise_map::MappedPromise;
+use crate::promise::streaming_promise::{StreamingPromise, StreamingPromiseState, UpdateResult};
+
+// mapped promise resolves on first update, so it's "slower" than ResolvedPromise
+fn slow(value: Vec<i32>) -> Box<dyn Promise<Vec<i32>>> {
+    Box::new(MappedPromise::new(ResolvedPromise::new(Some(value)), |a| a))
+}
+
+#[test]
+fn joined_promises_stream_in_order_of_resolution() {
+    let mut promise = JoinedPromises::new(vec![
+        slow(vec![1, 2]),
+        Box::new(ResolvedPromise::new(None)),
+        Box::new(ResolvedPromise::new(Some(vec![3]))),
+    ]);
+
+    assert_eq!(promise.state(), StreamingPromiseState::Streaming);
+    assert_eq!(promise.read(), &vec![3]);
+
+    assert_eq!(
+        promise.update(),
+        UpdateResult {
+            state: StreamingPromiseState::Finished,
+            has_changed: true,
+        }
+    );
+    assert_eq!(promise.read(), &vec![3, 1, 2]);
+
+    assert_eq!(
+        promise.update(),
+        UpdateResult {
+            state: StreamingPromiseState::Finished,
+            has_changed: false,
+        }
+    );
+}
+
+#[test]
+fn joined_promises_drain() {
+    let mut promise = JoinedPromises::new(vec![slow(vec![1]), slow(vec![2, 3])]);
+
+    assert_eq!(promise.state(), StreamingPromiseState::Streaming);
+    assert!(promise.read().is_empty());
+
+    assert_eq!(promise.drain(None), StreamingPromiseState::Finished);
+    assert_eq!(promise.read(), &vec![1, 2, 3]);
+}
+
+#[test]
+fn joined_promises_empty_is_finished() {
+    let promise: JoinedPromises<i32> = JoinedPromises::new(vec![]);
+
+    assert_eq!(promise.state(), StreamingPromiseState::Finished);
+}
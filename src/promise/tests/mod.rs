@@ -1,3 +1,4 @@
+mod joined_promises;
 mod promise_map;
 mod streaming_promise;
 mod streaming_promise_map;
@@ -1,5 +1,5 @@
 use std::any;
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::fmt::Debug;
 use std::ops::Range;
 use std::sync::Arc;
@@ -17,18 +17,21 @@ use crate::experiments::clipboard::ClipboardRef;
 use crate::experiments::filename_to_language::filename_to_language;
 use crate::experiments::regex_search::FindError;
 use crate::fs::path::SPath;
-use crate::io::output::Output;
+use crate::primitives::buffer_xy::BufferXY;
 use crate::primitives::common_edit_msgs::{apply_common_edit_message, cme_to_direction, ApplyCemResult, CommonEditMsg};
 use crate::primitives::has_invariant::HasInvariant;
 use crate::primitives::printable::Printable;
-use crate::primitives::xy::XY;
+use crate::primitives::stupid_cursor::StupidCursor;
 use crate::text::contents_and_cursors::ContentsAndCursors;
 use crate::text::ident_type::IndentType;
 use crate::text::text_buffer::{LinesIter, TextBuffer};
+use crate::text::undo_cache;
+use crate::text::undo_cache::{UndoSnapshot, UndoSnapshotEntry};
+use crate::text::undo_tree::{HistoryLimits, TextEdit, UndoTree};
 use crate::tsw::lang_id::LangId;
 use crate::tsw::tree_sitter_wrapper::{HighlightItem, TreeSitterWrapper};
 use crate::w7e::buffer_state_shared_ref::BufferSharedRef;
-use crate::w7e::navcomp_provider::StupidSubstituteMessage;
+use crate::w7e::navcomp_provider::{EditEvent, NavCompDocumentSymbol, NavCompSemanticToken, StupidSubstituteMessage};
 use crate::widget::widget::WID;
 use crate::widgets::main_view::main_view::DocumentIdentifier;
 use crate::{unpack_or, unpack_or_e};
@@ -63,8 +66,8 @@ pub struct BufferState {
     subtype: BufferType,
 
     tree_sitter_op: Option<Arc<TreeSitterWrapper>>,
-    history: Vec<ContentsAndCursors>,
-    history_pos: usize,
+    history: UndoTree,
+    // id of history node that was last saved
     last_save_pos: Option<usize>,
 
     lang_id: Option<LangId>,
@@ -74,9 +77,25 @@ pub struct BufferState {
 
     indent_type: IndentType,
     tabs_to_spaces: Option<u8>,
+
+    // Edits applied since last take_edit_event, used to feed navcomp with incremental updates.
+    // If they can't describe the change (undo, redo, too many of them), full_sync_required is set.
+    pending_edits: Vec<StupidSubstituteMessage>,
+    full_sync_required: bool,
+
+    // Provided by navcomp, layered over tree-sitter highlighting. They are not updated on edit, so
+    // until navcomp answers again they may be a little off.
+    semantic_tokens: Vec<NavCompSemanticToken>,
+
+    // Cursor sets of history restored from undo cache, one per history entry. They don't belong to any
+    // widget yet, so the first one to call initialize_for_widget claims them.
+    restored_cursor_sets: Vec<Option<CursorSet>>,
 }
 
 impl BufferState {
+    // Beyond this number of pending edits it's cheaper to just send the whole file.
+    const MAX_PENDING_EDITS: usize = 1000;
+
     pub fn into_bsr(self) -> BufferSharedRef {
         BufferSharedRef::new_from_buffer(self)
     }
@@ -332,13 +351,12 @@ impl BufferState {
         true
     }
 
-    pub fn get_visible_chars_range(&self, output: &mut dyn Output) -> Option<Range<usize>> {
+    // Returns range of chars covering given lines, plus one line after them.
+    pub fn get_visible_chars_range(&self, lines: Range<usize>) -> Option<Range<usize>> {
         let rope = self.text().rope();
 
-        let visible_rect = output.visible_rect();
-
-        let first_line = visible_rect.upper_left().y as usize;
-        let beyond_last_lane = visible_rect.lower_right().y as usize + 1;
+        let first_line = lines.start;
+        let beyond_last_lane = lines.end + 1;
 
         let first_char_idx = rope.try_line_to_char(first_line).ok()?;
         let beyond_last_char_idx = rope.try_line_to_char(beyond_last_lane).unwrap_or(rope.len_chars()); // if you do add +1 here, treesitter fails.
@@ -399,14 +417,17 @@ impl BufferState {
         let res = BufferState {
             subtype: BufferType::Full,
             tree_sitter_op,
-            history: vec![ContentsAndCursors::empty()],
-            history_pos: 0,
+            history: UndoTree::new(ContentsAndCursors::empty()),
             last_save_pos: None,
             lang_id: None,
             document_identifier,
             drop_notice_sink: debug_sink,
             indent_type: IndentType::Spaces,
             tabs_to_spaces,
+            pending_edits: Vec::new(),
+            full_sync_required: false,
+            semantic_tokens: Vec::new(),
+            restored_cursor_sets: Vec::new(),
         };
 
         debug_assert!(res.check_invariant());
@@ -427,12 +448,76 @@ impl BufferState {
     }
 
     // TODO move to text?
+    // code coloring, tree-sitter items with semantic tokens layered over them.
     pub fn highlight(&self, char_range_op: Option<Range<usize>>) -> Vec<HighlightItem> {
+        let text = self.text();
+        let tree_sitter_items = text
+            .parsing()
+            .and_then(|parsing| parsing.highlight_iter(text.rope(), char_range_op.clone()))
+            .unwrap_or(vec![]);
+
+        let semantic_items = self.semantic_highlight(char_range_op);
+        layer_highlights(tree_sitter_items, semantic_items)
+    }
+
+    /*
+    Semantic tokens converted to char ranges, sorted. Tokens reaching past their line are trimmed.
+    Columns are in UTF-16 code units (see StupidCursor), so they are converted within their line.
+     */
+    fn semantic_highlight(&self, char_range_op: Option<Range<usize>>) -> Vec<HighlightItem> {
+        let rope = self.text().rope();
+        let mut result: Vec<HighlightItem> = Vec::new();
+
+        for token in self.semantic_tokens.iter() {
+            let line_idx = token.stupid_range.0.line_0b as usize;
+            if line_idx >= rope.len_lines() {
+                continue;
+            }
+
+            let line_begin_utf16 = rope.char_to_utf16_cu(rope.line_to_char(line_idx));
+            let line_len_utf16 = rope.line(line_idx).len_utf16_cu();
+            let to_char_idx = |col: u32| rope.utf16_cu_to_char(line_begin_utf16 + min(col as usize, line_len_utf16));
+
+            let char_begin = to_char_idx(token.stupid_range.0.char_idx_0b);
+            let char_end = to_char_idx(token.stupid_range.1.char_idx_0b);
+            if char_begin >= char_end {
+                continue;
+            }
+
+            if let Some(char_range) = char_range_op.as_ref() {
+                if char_end <= char_range.start || char_range.end <= char_begin {
+                    continue;
+                }
+            }
+
+            result.push(HighlightItem {
+                char_begin,
+                char_end,
+                identifier: token.token_type.clone(),
+                modifiers: token.modifiers.clone(),
+            });
+        }
+
+        result.sort_by_key(|item| item.char_begin);
+        result
+    }
+
+    pub fn set_semantic_tokens(&mut self, semantic_tokens: Vec<NavCompSemanticToken>) {
+        self.semantic_tokens = semantic_tokens;
+    }
+
+    // identifiers spelled as the one at char_idx, sorted. Empty if file is not parsed.
+    pub fn identifier_occurrences(&self, char_idx: usize) -> Vec<Range<usize>> {
         let text = self.text();
         text.parsing()
-            .map(|parsing| parsing.highlight_iter(text.rope(), char_range_op))
-            .flatten()
-            .unwrap_or(vec![])
+            .and_then(|parsing| parsing.identifier_occurrences(text.rope(), char_idx))
+            .unwrap_or_default()
+    }
+
+    // outline derived from syntax tree, None if file is not parsed
+    pub fn tree_sitter_document_symbols(&self) -> Option<Vec<NavCompDocumentSymbol>> {
+        let text = self.text();
+        text.parsing()?.document_symbols(text.rope())
     }
 
     // TODO merge with above?
@@ -467,14 +552,11 @@ impl BufferState {
     pub fn remove_history(&mut self) {
         let is_saved = self.is_saved();
 
-        if self.history_pos != 0 {
-            self.history.swap(0, self.history_pos)
-        }
-        self.history.truncate(1);
-        self.history_pos = 0;
+        self.history.truncate_to_current();
+        self.restored_cursor_sets.clear();
 
         if is_saved {
-            self.last_save_pos = Some(0);
+            self.last_save_pos = Some(self.history.current_id());
         } else {
             self.last_save_pos = None;
         }
@@ -486,16 +568,17 @@ impl BufferState {
       used to chain multiple operations into a single milestone
     */
     fn reduce_merge_milestone(&mut self) {
-        debug_assert!(self.history_pos + 1 == self.history.len());
-        debug_assert!(self.history_pos >= 1);
-
-        self.history.remove(self.history_pos - 1);
-        self.history_pos -= 1;
+        let merged = self.history.current_id();
+        if !self.history.merge_current_into_parent() {
+            error!("failed to merge milestone {} into its parent", merged);
+            return;
+        }
 
         if let Some(last_save_pos) = self.last_save_pos {
-            if last_save_pos == self.history_pos + 1 {
-                self.last_save_pos = Some(last_save_pos - 1);
-            } else if last_save_pos >= self.history.len() {
+            if last_save_pos == merged {
+                self.last_save_pos = Some(self.history.current_id());
+            } else if last_save_pos == self.history.current_id() {
+                // contents of saved node just changed
                 self.last_save_pos = None;
             }
         }
@@ -504,19 +587,57 @@ impl BufferState {
     }
 
     // TODO overload and optimise?
-    pub fn size(&self) -> XY {
-        let mut size = XY::ZERO;
+    pub fn size(&self) -> BufferXY {
+        let mut size = BufferXY::ZERO;
 
-        size.y = self.len_lines() as u16; // TODO overflow
+        size.y = self.len_lines();
 
         let mut lines_iter = self.lines();
         while let Some(line) = lines_iter.next() {
-            size.x = max(size.x, line.width() as u16 + 1) // TODO overflow
+            size.x = max(size.x, line.width() + 1)
         }
 
         size
     }
 
+    /*
+    Returns all changes since last call, or None if contents did not change.
+     */
+    pub fn take_edit_event(&mut self) -> Option<EditEvent> {
+        let file_contents = self.text().rope().clone(); // shallow copy
+        let edits = std::mem::take(&mut self.pending_edits);
+
+        if self.full_sync_required {
+            self.full_sync_required = false;
+            Some(EditEvent::Full { file_contents })
+        } else if edits.is_empty() {
+            None
+        } else {
+            Some(EditEvent::Incremental { edits, file_contents })
+        }
+    }
+
+    fn record_edit(&mut self, begin: Option<StupidCursor>, end: Option<StupidCursor>, substitute: String) {
+        if self.subtype != BufferType::Full || self.full_sync_required {
+            return;
+        }
+
+        match (begin, end) {
+            (Some(begin), Some(end)) if self.pending_edits.len() < Self::MAX_PENDING_EDITS => {
+                self.pending_edits.push(StupidSubstituteMessage {
+                    substitute,
+                    stupid_range: (begin, end),
+                });
+            }
+            _ => self.require_full_sync(),
+        }
+    }
+
+    fn require_full_sync(&mut self) {
+        self.pending_edits.clear();
+        self.full_sync_required = true;
+    }
+
     /*
     Returns updated DocumentIdentifier
      */
@@ -554,26 +675,30 @@ impl BufferState {
     This creates new milestone to undo/redo. The reason for it is that potentially multiple edits inform a single milestone.
     Returns false only if buffer have not changed since last milestone.
 
-    set_milestone drops "forward history".
+    set_milestone does not drop "forward history", it becomes an abandoned branch of the undo tree.
      */
     fn set_milestone(&mut self) -> bool {
-        if self.history_pos > 0 {
-            let last = &self.history[self.history_pos - 1];
-            let current = &self.history[self.history_pos];
-
-            if last.rope() == current.rope() {
-                return false;
-            }
+        if self.history.current_equals_parent() {
+            return false;
         }
 
-        self.history.truncate(self.history_pos + 1);
-        self.history.push(self.history[self.history_pos].clone());
-        self.history_pos += 1;
+        self.history.push_child();
+        // history might have outgrown its limits and forgotten the saved state
+        self.forget_save_pos_if_pruned();
 
         debug_assert!(self.check_invariant());
         true
     }
 
+    fn forget_save_pos_if_pruned(&mut self) {
+        if let Some(last_save_pos) = self.last_save_pos {
+            if self.history.node(last_save_pos).is_none() {
+                debug!("saved state {} was dropped from history", last_save_pos);
+                self.last_save_pos = None;
+            }
+        }
+    }
+
     fn set_parsing_tuple(&mut self) -> bool {
         let lang_id = match self.lang_id {
             Some(li) => li,
@@ -618,14 +743,17 @@ impl BufferState {
         let res = BufferState {
             subtype: BufferType::SingleLine,
             tree_sitter_op: None,
-            history: vec![ContentsAndCursors::empty()],
-            history_pos: 0,
+            history: UndoTree::new(ContentsAndCursors::empty()),
             last_save_pos: None,
             lang_id: None,
             document_identifier: doc_id,
             drop_notice_sink: None,
             indent_type: IndentType::Spaces,
             tabs_to_spaces: None,
+            pending_edits: Vec::new(),
+            full_sync_required: false,
+            semantic_tokens: Vec::new(),
+            restored_cursor_sets: Vec::new(),
         };
 
         debug_assert!(res.check_invariant());
@@ -645,8 +773,54 @@ impl BufferState {
     }
 
     pub fn initialize_for_widget(&mut self, widget_id: WID, cursors_op: Option<CursorSet>) -> bool {
+        if !self.restored_cursor_sets.is_empty() {
+            return self.initialize_restored_history_for_widget(widget_id, cursors_op);
+        }
+
         let cursor_set = cursors_op.unwrap_or(CursorSet::single());
-        let result = self.history[self.history_pos].add_cursor_set(widget_id, cursor_set);
+        let result = self.history.current_mut().add_cursor_set(widget_id, cursor_set);
+
+        debug_assert!(self.check_invariant());
+
+        result
+    }
+
+    /*
+    For edits made without a widget displaying the buffer (see BufferRegister::apply_workspace_edit).
+    Unlike initialize_for_widget, it does not take cursors restored with history, and it has to be
+    removed with remove_cursor_sets when done.
+     */
+    pub fn add_temporary_cursor_set(&mut self, widget_id: WID) -> bool {
+        self.history.current_mut().add_cursor_set(widget_id, CursorSet::single())
+    }
+
+    // Removes cursor sets of the widget from all states in history.
+    pub fn remove_cursor_sets(&mut self, widget_id: WID) {
+        self.history.remove_cursor_sets(widget_id);
+
+        debug_assert!(self.check_invariant());
+    }
+
+    fn initialize_restored_history_for_widget(&mut self, widget_id: WID, cursors_op: Option<CursorSet>) -> bool {
+        let restored = std::mem::take(&mut self.restored_cursor_sets);
+        debug_assert!(restored.len() == self.history.nodes_count());
+
+        let mut result = true;
+        let mut cursors_op = cursors_op;
+        let current_id = self.history.current_id();
+        // restored history has no branches, so node ids are positions in snapshot
+        for (idx, restored_op) in restored.into_iter().enumerate() {
+            let len_chars = unpack_or_e!(self.history.rope_at(idx), false, "restored history node {} missing", idx).len_chars();
+            let fits = |cs: &CursorSet| cs.iter().all(|c| c.a <= len_chars && c.s.map(|s| s.e <= len_chars).unwrap_or(true));
+
+            let cursor_set = if idx == current_id && cursors_op.is_some() {
+                cursors_op.take().unwrap()
+            } else {
+                restored_op.filter(fits).unwrap_or(CursorSet::single())
+            };
+
+            result &= self.history.add_cursor_set_at(idx, widget_id, cursor_set);
+        }
 
         debug_assert!(self.check_invariant());
 
@@ -656,26 +830,30 @@ impl BufferState {
     pub fn text(&self) -> &ContentsAndCursors {
         // debug_assert!(self.check_invariant());
 
-        &self.history[self.history_pos]
+        self.history.current()
     }
 
     pub fn text_mut(&mut self) -> &mut ContentsAndCursors {
         // debug_assert!(self.check_invariant());
 
-        &mut self.history[self.history_pos]
+        self.history.current_mut()
     }
 
     // to be used only in apply_cem
     fn undo_milestone(&mut self) {
-        debug_assert!(self.history_pos + 1 == self.history.len());
-        debug_assert!(self.history_pos > 0);
-        self.history_pos -= 1;
-        self.history.truncate(self.history_pos + 1);
+        let removed = self.history.current_id();
+        let text_changed = self.history.node(removed).map(|node| !node.edits().is_empty()).unwrap_or(false);
+        if !self.history.pop_current() {
+            error!("failed to remove milestone {}", removed);
+            return;
+        }
 
-        if let Some(last_save_pos) = self.last_save_pos {
-            if last_save_pos >= self.history.len() {
-                self.last_save_pos = None;
-            }
+        if text_changed {
+            self.reparse_current();
+        }
+
+        if self.last_save_pos == Some(removed) {
+            self.last_save_pos = None;
         }
 
         debug_assert!(self.check_invariant());
@@ -739,8 +917,9 @@ impl BufferState {
     pub fn with_text<T: AsRef<str>>(mut self, text: T) -> Self {
         let rope = ropey::Rope::from_str(text.as_ref());
 
-        self.history = vec![ContentsAndCursors::empty().with_rope(rope)];
-        self.history_pos = 0;
+        self.history = UndoTree::new(ContentsAndCursors::empty().with_rope(rope)).with_limits(self.history.limits());
+        self.pending_edits.clear();
+        self.restored_cursor_sets.clear();
 
         self.set_parsing_tuple();
 
@@ -749,8 +928,18 @@ impl BufferState {
         self
     }
 
+    // Caps on how much history is kept, oldest states are forgotten first.
+    pub fn with_history_limits(mut self, limits: HistoryLimits) -> Self {
+        self.history.set_limits(limits);
+        self.forget_save_pos_if_pruned();
+
+        debug_assert!(self.check_invariant());
+
+        self
+    }
+
     pub fn with_maked_as_saved(mut self) -> Self {
-        let pos = self.history_pos;
+        let pos = self.history.current_id();
 
         self.last_save_pos = Some(pos);
 
@@ -759,12 +948,121 @@ impl BufferState {
         self
     }
 
+    /*
+    Returns history around the last saved version, to be persisted in undo cache. None if buffer was
+    never saved or has no file path.
+
+    Only the line that undo/redo walks through the saved version is persisted, abandoned branches are
+    not.
+     */
+    pub fn undo_snapshot(&self, max_entries: usize, max_bytes: usize) -> Option<UndoSnapshot> {
+        let path = self.get_path()?.absolute_path();
+        let saved_id = self.last_save_pos?;
+
+        let mut chain = self.history.path_from_root(saved_id);
+        let saved_pos = chain.len() - 1;
+        chain.extend(self.history.redo_chain(saved_id));
+
+        let ropes: Vec<Rope> = chain.iter().map(|id| self.history.rope_at(*id)).collect::<Option<Vec<_>>>()?;
+        let sizes: Vec<usize> = ropes.iter().map(|rope| rope.len_bytes()).collect();
+        let range = undo_cache::window(&sizes, saved_pos, max_entries, max_bytes);
+
+        let entries: Vec<UndoSnapshotEntry> = chain[range.clone()]
+            .iter()
+            .zip(ropes[range.clone()].iter())
+            .map(|(id, rope)| UndoSnapshotEntry {
+                text: rope.to_string(),
+                cursor_set: self
+                    .history
+                    .cursors_and_labels_at(*id)
+                    .and_then(|cursors_and_labels| cursors_and_labels.first_cursor_set().cloned()),
+            })
+            .collect();
+
+        let saved_pos = saved_pos - range.start;
+        let content_hash = undo_cache::stable_hash(entries[saved_pos].text.as_bytes());
+
+        Some(UndoSnapshot {
+            path,
+            content_hash,
+            entries,
+            saved_pos,
+        })
+    }
+
+    /*
+    Replaces the (single entry) history of freshly loaded buffer with one restored from undo cache.
+    Current position is the saved entry, so it's expected to match the text already loaded, otherwise the
+    snapshot is ignored.
+
+    Only current entry is parsed, others are parsed when undo/redo gets to them.
+     */
+    pub fn with_undo_snapshot(mut self, snapshot: UndoSnapshot) -> Self {
+        if self.history.nodes_count() != 1 {
+            warn!("not restoring undo history into buffer that already has some");
+            return self;
+        }
+
+        let saved_pos = snapshot.saved_pos;
+        match snapshot.entries.get(saved_pos) {
+            Some(entry) if *self.text().rope() == entry.text.as_str() => {}
+            _ => {
+                warn!("undo history does not match contents of {:?}, ignoring it", self.get_path());
+                return self;
+            }
+        }
+
+        let current = self.history.current().clone();
+        let mut history: Vec<ContentsAndCursors> = Vec::with_capacity(snapshot.entries.len());
+        let mut restored_cursor_sets: Vec<Option<CursorSet>> = Vec::with_capacity(snapshot.entries.len());
+
+        for entry in snapshot.entries.into_iter() {
+            history.push(ContentsAndCursors::empty().with_rope(Rope::from_str(&entry.text)));
+            restored_cursor_sets.push(entry.cursor_set);
+        }
+
+        debug!("restored {} undo entries for {:?}", history.len(), self.get_path());
+
+        // restored history has no branches, so node ids are positions in snapshot
+        let limits = self.history.limits();
+        self.history = UndoTree::from_chain(history);
+        self.history.jump_to(saved_pos);
+        // same text, but already parsed
+        *self.history.current_mut() = current;
+        self.history.set_limits(limits);
+        self.last_save_pos = Some(saved_pos);
+        self.forget_save_pos_if_pruned();
+        self.restored_cursor_sets = restored_cursor_sets;
+
+        debug_assert!(self.check_invariant());
+
+        self
+    }
+
+    /*
+    History does not keep syntax trees, so whenever it replaces current text, it's parsed again. Trees of
+    restored history (see with_undo_snapshot) are not even created until needed.
+     */
+    fn reparse_current(&mut self) {
+        if self.tree_sitter_op.is_none() {
+            return;
+        }
+
+        if self.text().parsing().is_none() {
+            self.set_parsing_tuple();
+        } else if !self.text_mut().reparse_from_scratch() {
+            error!("failed to reparse {:?}", self.get_path());
+        }
+    }
+
     /*
     Destroys history
      */
     pub fn set_text<T: AsRef<str>>(&mut self, text: T) {
-        self.history = vec![ContentsAndCursors::empty().with_rope(Rope::from_str(text.as_ref()))];
-        self.history_pos = 0;
+        let limits = self.history.limits();
+        self.history = UndoTree::new(ContentsAndCursors::empty().with_rope(Rope::from_str(text.as_ref()))).with_limits(limits);
+        self.restored_cursor_sets.clear();
+        self.require_full_sync();
 
         self.set_parsing_tuple();
         self.check_invariant();
@@ -776,9 +1074,10 @@ impl BufferState {
     pub fn with_text_from_rope(mut self, rope: Rope, lang_id: Option<LangId>) -> Self {
         let text = ContentsAndCursors::empty().with_rope(rope);
 
-        self.history = vec![text];
-        self.history_pos = 0;
+        self.history = UndoTree::new(text).with_limits(self.history.limits());
+        self.restored_cursor_sets.clear();
         self.lang_id = lang_id;
+        self.pending_edits.clear();
 
         self.set_parsing_tuple();
 
@@ -788,11 +1087,41 @@ impl BufferState {
     }
 
     pub fn can_redo(&self) -> bool {
-        self.history_pos + 1 < self.history.len()
+        self.history.can_redo()
     }
 
     pub fn can_undo(&self) -> bool {
-        self.history_pos > 0
+        self.history.can_undo()
+    }
+
+    pub fn history(&self) -> &UndoTree {
+        &self.history
+    }
+
+    pub fn last_saved_history_node(&self) -> Option<usize> {
+        self.last_save_pos
+    }
+
+    /*
+    Makes any state from history current, including ones on abandoned branches. If the widget was not
+    around when that state was created, it gets a fresh cursor set.
+     */
+    pub fn jump_to_history(&mut self, node_id: usize, widget_id: WID) -> bool {
+        if !self.history.jump_to(node_id) {
+            error!("no history node {}", node_id);
+            return false;
+        }
+
+        self.reparse_current();
+        self.require_full_sync();
+
+        if !self.text().has_cursor_set_for(widget_id) {
+            self.text_mut().add_cursor_set(widget_id, CursorSet::single());
+        }
+
+        debug_assert!(self.check_invariant());
+
+        true
     }
 }
 
@@ -840,6 +1169,7 @@ impl TextBuffer for BufferState {
     fn insert_block(&mut self, char_idx: usize, block: &str) -> bool {
         // TODO maybe blocks will be more performant?
         let grapheme_len = block.graphemes(true).count();
+        let stupid_pos = StupidCursor::from_char_idx_utf16(self.text().rope(), char_idx);
         let text = self.text_mut();
 
         let result = match text.rope_mut().try_insert(char_idx, block) {
@@ -863,12 +1193,21 @@ impl TextBuffer for BufferState {
             }
         };
 
+        if result {
+            self.history.record(TextEdit::Insert {
+                char_idx,
+                text: block.to_string(),
+            });
+            self.record_edit(stupid_pos, stupid_pos, block.to_string());
+        }
+
         // debug_assert!(self.check_invariant());
 
         result
     }
 
     fn insert_char(&mut self, char_idx: usize, ch: char) -> bool {
+        let stupid_pos = StupidCursor::from_char_idx_utf16(self.text().rope(), char_idx);
         let text = self.text_mut();
         let result = match text.rope_mut().try_insert_char(char_idx, ch) {
             Ok(_) => {
@@ -892,6 +1231,14 @@ impl TextBuffer for BufferState {
             }
         };
 
+        if result {
+            self.history.record(TextEdit::Insert {
+                char_idx,
+                text: ch.to_string(),
+            });
+            self.record_edit(stupid_pos, stupid_pos, ch.to_string());
+        }
+
         debug_assert!(self.check_invariant());
 
         result
@@ -925,9 +1272,10 @@ impl TextBuffer for BufferState {
     }
 
     fn redo(&mut self) -> bool {
-        debug!("REDO pos {} len {}", self.history_pos, self.history.len());
-        let result = if self.history_pos + 1 < self.history.len() {
-            self.history_pos += 1;
+        debug!("REDO pos {} len {}", self.history.current_id(), self.history.nodes_count());
+        let result = if self.history.redo() {
+            self.reparse_current();
+            self.require_full_sync();
             true
         } else {
             false
@@ -944,6 +1292,16 @@ impl TextBuffer for BufferState {
             return false;
         }
 
+        // positions have to be computed before removal, that's what LSP expects.
+        let stupid_begin = StupidCursor::from_char_idx_utf16(self.text().rope(), char_idx_begin);
+        let stupid_end = StupidCursor::from_char_idx_utf16(self.text().rope(), char_idx_end);
+        // history needs to know what's removed, to be able to put it back
+        let removed = self
+            .text()
+            .rope()
+            .get_slice(char_idx_begin..char_idx_end)
+            .map(|slice| slice.to_string());
+
         let text = self.text_mut();
         let result = match text.rope_mut().try_remove(char_idx_begin..char_idx_end) {
             Ok(_) => {
@@ -966,13 +1324,22 @@ impl TextBuffer for BufferState {
             }
         };
 
+        if result {
+            self.history.record(TextEdit::Remove {
+                char_idx: char_idx_begin,
+                text: removed.unwrap_or_default(),
+            });
+            self.record_edit(stupid_begin, stupid_end, String::new());
+        }
+
         result
     }
 
     fn undo(&mut self) -> bool {
-        debug!("UNDO pos {} len {}", self.history_pos, self.history.len());
-        let result = if self.history_pos > 0 {
-            self.history_pos -= 1;
+        debug!("UNDO pos {} len {}", self.history.current_id(), self.history.nodes_count());
+        let result = if self.history.undo() {
+            self.reparse_current();
+            self.require_full_sync();
             true
         } else {
             false
@@ -984,16 +1351,20 @@ impl TextBuffer for BufferState {
     }
 
     fn mark_as_saved(&mut self) {
-        self.last_save_pos = Some(self.history_pos);
+        self.last_save_pos = Some(self.history.current_id());
         debug_assert!(self.check_invariant());
     }
 
     fn is_saved(&self) -> bool {
         if let Some(last_save_pos) = self.last_save_pos {
-            let saved_version = self.history[last_save_pos].rope();
-            let current_version = self.history[self.history_pos].rope();
+            if last_save_pos == self.history.current_id() {
+                return true;
+            }
+
+            let saved_version = unpack_or_e!(self.history.rope_at(last_save_pos), false, "saved node {} missing", last_save_pos);
+            let current_version = self.history.current().rope();
 
-            saved_version == current_version
+            saved_version.len_chars() == current_version.len_chars() && saved_version == *current_version
         } else {
             // if it's not saved, but is empty, we say it's saved
             self.len_chars() == 0
@@ -1005,6 +1376,48 @@ impl TextBuffer for BufferState {
     }
 }
 
+/*
+Semantic items win over tree-sitter ones, which are cut around them, so the result stays sorted
+and semantic items don't get overshadowed by longer items starting before them. Both inputs are
+expected sorted by char_begin, semantic items also non-overlapping.
+ */
+pub fn layer_highlights(tree_sitter_items: Vec<HighlightItem>, semantic_items: Vec<HighlightItem>) -> Vec<HighlightItem> {
+    if semantic_items.is_empty() {
+        return tree_sitter_items;
+    }
+
+    let mut result: Vec<HighlightItem> = Vec::with_capacity(tree_sitter_items.len() + semantic_items.len());
+
+    for item in tree_sitter_items {
+        let mut begin = item.char_begin;
+        let first_idx = semantic_items.partition_point(|semantic| semantic.char_end <= item.char_begin);
+
+        for semantic in semantic_items[first_idx..].iter() {
+            if semantic.char_begin >= item.char_end {
+                break;
+            }
+
+            if begin < semantic.char_begin {
+                result.push(HighlightItem {
+                    char_begin: begin,
+                    char_end: semantic.char_begin,
+                    ..item.clone()
+                });
+            }
+            begin = max(begin, semantic.char_end);
+        }
+
+        if begin < item.char_end {
+            result.push(HighlightItem { char_begin: begin, ..item });
+        }
+    }
+
+    result.extend(semantic_items);
+    // stable, so order of tree-sitter items starting at the same place is kept
+    result.sort_by_key(|item| item.char_begin);
+    result
+}
+
 pub struct BufferStateStreamingIterator<'a> {
     chunks: Chunks<'a>,
     curr_chunk: Option<&'a str>,
@@ -1024,22 +1437,20 @@ impl<'a> StreamingIterator for BufferStateStreamingIterator<'a> {
 
 impl HasInvariant for BufferState {
     fn check_invariant(&self) -> bool {
-        if self.history_pos >= self.history.len() {
-            error!("history_pos >= self.history.len() {} {}", self.history_pos, self.history.len());
+        if !self.history.check_invariant() {
+            error!("history invariant broken");
             return false;
         }
 
         if let Some(last_save_pos) = self.last_save_pos {
-            if last_save_pos >= self.history.len() {
-                error!("last_save_pos >= self.history.len() {} {}", last_save_pos, self.history.len());
+            if self.history.node(last_save_pos).is_none() {
+                error!("last_save_pos {} not in history", last_save_pos);
                 return false;
             }
         }
 
-        for i in self.history.iter() {
-            if i.check_invariant() == false {
-                return false;
-            }
+        if !self.history.current().check_invariant() {
+            return false;
         }
 
         true
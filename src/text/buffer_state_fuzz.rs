@@ -1,7 +1,13 @@
+use std::collections::BTreeMap;
+
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+use ropey::Rope;
+
 use crate::primitives::common_edit_msgs::CommonEditMsg;
 use crate::text::buffer_state::BufferState;
+use crate::text::contents_and_cursors::ContentsAndCursors;
+use crate::text::undo_tree::{HistoryLimits, TextEdit, UndoTree};
 use crate::widgets::main_view::main_view::DocumentIdentifier;
-use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
 
 impl<'a> Arbitrary<'a> for BufferState {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -20,4 +26,331 @@ pub fn fuzz_call(text: String, msgs: Vec<CommonEditMsg>) {
     for msg in msgs {
         bf.apply_common_edit_message(msg, 1, 3, None, true);
     }
+
+    // whatever happened, undoing everything and redoing it back has to land in the same place
+    let final_text = bf.text().rope().clone();
+    while bf.can_undo() {
+        bf.apply_common_edit_message(CommonEditMsg::Undo, 1, 3, None, true);
+    }
+    while bf.can_redo() {
+        bf.apply_common_edit_message(CommonEditMsg::Redo, 1, 3, None, true);
+    }
+    assert_eq!(bf.text().rope(), &final_text);
+}
+
+/*
+Operations on edit history, as BufferState does them. Positions and lengths are taken modulo
+whatever makes sense at the moment they're applied, so every sequence is valid.
+ */
+#[derive(Clone, Debug)]
+pub enum HistoryFuzzOp {
+    Insert { pos: usize, text: String },
+    Remove { pos: usize, len: usize },
+    Milestone,
+    Undo,
+    Redo,
+    JumpTo(usize),
+    PopCurrent,
+    MergeIntoParent,
+    TruncateToCurrent,
+    SetLimits { max_states: usize, max_bytes: usize },
+}
+
+impl<'a> Arbitrary<'a> for HistoryFuzzOp {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        // milestones and edits are what happens most of the time, so they get more weight
+        Ok(match u.int_in_range(0..=63u8)? {
+            0..=15 => HistoryFuzzOp::Insert {
+                pos: u.arbitrary::<u16>()? as usize,
+                text: String::arbitrary(u)?,
+            },
+            16..=23 => HistoryFuzzOp::Remove {
+                pos: u.arbitrary::<u16>()? as usize,
+                len: u.int_in_range(1..=8usize)?,
+            },
+            24..=43 => HistoryFuzzOp::Milestone,
+            44..=49 => HistoryFuzzOp::Undo,
+            50..=54 => HistoryFuzzOp::Redo,
+            55..=57 => HistoryFuzzOp::JumpTo(u.arbitrary::<u8>()? as usize),
+            58..=59 => HistoryFuzzOp::PopCurrent,
+            60..=61 => HistoryFuzzOp::MergeIntoParent,
+            62 => HistoryFuzzOp::TruncateToCurrent,
+            _ => HistoryFuzzOp::SetLimits {
+                max_states: u.int_in_range(1..=256usize)?,
+                // tight memory cap leaves little more than current state, so it's not always there
+                max_bytes: if u.arbitrary()? {
+                    u.int_in_range(1024..=1024 * 1024usize)?
+                } else {
+                    usize::MAX
+                },
+            },
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SnapshotNode {
+    parent: Option<usize>,
+    children: Vec<usize>,
+    redo_child: Option<usize>,
+    rope: Rope,
+}
+
+/*
+Reference model: every state is a full copy of text, like history used to be before it was turned
+into an operation log. Slow and memory hungry, but obviously right.
+ */
+#[derive(Clone, Debug)]
+struct SnapshotHistory {
+    nodes: BTreeMap<usize, SnapshotNode>,
+    next_id: usize,
+    current: usize,
+}
+
+impl SnapshotHistory {
+    fn new(rope: Rope) -> Self {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            0,
+            SnapshotNode {
+                parent: None,
+                children: Vec::new(),
+                redo_child: None,
+                rope,
+            },
+        );
+
+        SnapshotHistory {
+            nodes,
+            next_id: 1,
+            current: 0,
+        }
+    }
+
+    fn current_rope_mut(&mut self) -> &mut Rope {
+        &mut self.nodes.get_mut(&self.current).unwrap().rope
+    }
+
+    fn push_child(&mut self) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let rope = self.nodes[&self.current].rope.clone();
+        self.nodes.insert(
+            id,
+            SnapshotNode {
+                parent: Some(self.current),
+                children: Vec::new(),
+                redo_child: None,
+                rope,
+            },
+        );
+
+        let parent = self.nodes.get_mut(&self.current).unwrap();
+        parent.children.push(id);
+        parent.redo_child = Some(id);
+        self.current = id;
+    }
+
+    fn undo(&mut self) -> bool {
+        let from = self.current;
+        match self.nodes[&from].parent {
+            Some(parent) => {
+                self.nodes.get_mut(&parent).unwrap().redo_child = Some(from);
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn redo(&mut self) -> bool {
+        match self.nodes[&self.current].redo_child {
+            Some(child) => {
+                self.current = child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn jump_to(&mut self, id: usize) -> bool {
+        if !self.nodes.contains_key(&id) {
+            return false;
+        }
+
+        let mut node = id;
+        while let Some(parent) = self.nodes[&node].parent {
+            self.nodes.get_mut(&parent).unwrap().redo_child = Some(node);
+            node = parent;
+        }
+
+        self.current = id;
+        true
+    }
+
+    fn is_current_removable(&self) -> bool {
+        let node = &self.nodes[&self.current];
+        self.nodes.keys().next_back() == Some(&self.current) && node.children.is_empty() && node.parent.is_some()
+    }
+
+    // returns removed node, parent of which is current now
+    fn remove_current(&mut self) -> SnapshotNode {
+        let removed = self.current;
+        let node = self.nodes.remove(&removed).unwrap();
+        let parent_id = node.parent.unwrap();
+
+        let parent = self.nodes.get_mut(&parent_id).unwrap();
+        parent.children.retain(|child| *child != removed);
+        parent.redo_child = parent.children.last().copied();
+
+        self.current = parent_id;
+        node
+    }
+
+    fn pop_current(&mut self) -> bool {
+        if !self.is_current_removable() {
+            return false;
+        }
+
+        self.remove_current();
+        true
+    }
+
+    fn merge_current_into_parent(&mut self) -> bool {
+        if !self.is_current_removable() {
+            return false;
+        }
+
+        let node = self.remove_current();
+        *self.current_rope_mut() = node.rope;
+        true
+    }
+
+    fn truncate_to_current(&mut self) {
+        let mut node = self.nodes.remove(&self.current).unwrap();
+        node.parent = None;
+        node.children.clear();
+        node.redo_child = None;
+
+        self.nodes.clear();
+        self.nodes.insert(self.current, node);
+    }
+
+    /*
+    Forgetting old states is a matter of policy, not of representation, so model just follows what
+    UndoTree decided, checking only that it was allowed to: abandoned leaves and root are fair game,
+    current state is not.
+     */
+    fn forget_all_but(&mut self, tree: &UndoTree) {
+        let mut forgotten: Vec<usize> = self.nodes.keys().copied().filter(|id| tree.node(*id).is_none()).collect();
+
+        while !forgotten.is_empty() {
+            let idx = forgotten.iter().position(|id| {
+                let node = &self.nodes[id];
+                node.children.is_empty() || (node.parent.is_none() && node.children.len() == 1)
+            });
+            let victim = forgotten.remove(idx.expect("forgotten states are neither leaves nor a root with single child"));
+            assert_ne!(victim, self.current, "current state forgotten");
+
+            let node = self.nodes.remove(&victim).unwrap();
+            match node.parent {
+                Some(parent_id) => {
+                    let parent = self.nodes.get_mut(&parent_id).unwrap();
+                    parent.children.retain(|item| *item != victim);
+                    if parent.redo_child == Some(victim) {
+                        parent.redo_child = parent.children.last().copied();
+                    }
+                }
+                None => {
+                    self.nodes.get_mut(&node.children[0]).unwrap().parent = None;
+                }
+            }
+        }
+    }
+}
+
+fn assert_equivalent(tree: &UndoTree, model: &SnapshotHistory) {
+    assert!(tree.check_invariant());
+    assert_eq!(tree.current_id(), model.current);
+    assert_eq!(tree.current().rope(), &model.nodes[&model.current].rope);
+    assert_eq!(tree.ids().collect::<Vec<_>>(), model.nodes.keys().copied().collect::<Vec<_>>());
+
+    for (id, model_node) in model.nodes.iter() {
+        let node = tree.node(*id).unwrap();
+        assert_eq!(node.parent(), model_node.parent, "parent of {}", id);
+        assert_eq!(node.children(), model_node.children.as_slice(), "children of {}", id);
+        assert_eq!(node.redo_child(), model_node.redo_child, "redo child of {}", id);
+        assert_eq!(tree.rope_at(*id).as_ref(), Some(&model_node.rope), "text of {}", id);
+    }
+}
+
+/*
+Runs the same operations against UndoTree and a history of full snapshots, and checks after every
+step that both agree on shape of the tree and text of every state in it.
+ */
+pub fn history_fuzz_call(text: String, ops: Vec<HistoryFuzzOp>) {
+    let rope = Rope::from_str(&text);
+    let mut tree = UndoTree::new(ContentsAndCursors::empty().with_rope(rope.clone()));
+    let mut model = SnapshotHistory::new(rope);
+
+    for op in ops {
+        match op {
+            HistoryFuzzOp::Insert { pos, text } => {
+                let edit = TextEdit::Insert {
+                    char_idx: pos % (model.current_rope_mut().len_chars() + 1),
+                    text,
+                };
+                assert!(edit.apply(model.current_rope_mut()));
+                assert!(tree.apply_edit(edit));
+            }
+            HistoryFuzzOp::Remove { pos, len } => {
+                let rope = model.current_rope_mut();
+                if rope.len_chars() == 0 {
+                    continue;
+                }
+
+                let begin = pos % rope.len_chars();
+                let end = (begin + len).min(rope.len_chars());
+                let edit = TextEdit::Remove {
+                    char_idx: begin,
+                    text: rope.slice(begin..end).to_string(),
+                };
+                assert!(edit.apply(rope));
+                assert!(tree.apply_edit(edit));
+            }
+            HistoryFuzzOp::Milestone => {
+                model.push_child();
+                tree.push_child();
+            }
+            HistoryFuzzOp::Undo => {
+                assert_eq!(tree.undo(), model.undo());
+            }
+            HistoryFuzzOp::Redo => {
+                assert_eq!(tree.redo(), model.redo());
+            }
+            HistoryFuzzOp::JumpTo(idx) => {
+                let ids: Vec<usize> = model.nodes.keys().copied().collect();
+                let id = ids[idx % ids.len()];
+                assert!(model.jump_to(id));
+                assert!(tree.jump_to(id));
+            }
+            HistoryFuzzOp::PopCurrent => {
+                assert_eq!(tree.pop_current(), model.pop_current());
+            }
+            HistoryFuzzOp::MergeIntoParent => {
+                assert_eq!(tree.merge_current_into_parent(), model.merge_current_into_parent());
+            }
+            HistoryFuzzOp::TruncateToCurrent => {
+                model.truncate_to_current();
+                tree.truncate_to_current();
+            }
+            HistoryFuzzOp::SetLimits { max_states, max_bytes } => {
+                tree.set_limits(HistoryLimits::new(max_states, max_bytes));
+            }
+        }
+
+        model.forget_all_but(&tree);
+        assert_equivalent(&tree, &model);
+    }
 }
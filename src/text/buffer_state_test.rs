@@ -1,9 +1,29 @@
 #[cfg(test)]
 pub mod tests {
+    use crate::cursor::cursor::Cursor;
+    use crate::cursor::cursor_set::CursorSet;
+    use crate::cursor::cursor_set_rect::cursor_to_xy;
+    use crate::fs::filesystem_front::FilesystemFront;
+    use crate::fs::mock_fs::MockFS;
+    use crate::primitives::buffer_xy::BufferXY;
     use crate::primitives::common_edit_msgs::CommonEditMsg;
-    use crate::text::buffer_state::BufferState;
+    use crate::primitives::stupid_cursor::StupidCursor;
+    use crate::spath;
+    use std::sync::Arc;
+
+    use crate::text::buffer_state::{layer_highlights, BufferState};
+    use crate::text::text_buffer::TextBuffer;
+    use crate::text::undo_cache;
+    use crate::tsw::tree_sitter_wrapper::HighlightItem;
+    use crate::w7e::navcomp_provider::{EditEvent, NavCompSemanticToken, StupidSubstituteMessage};
     use crate::widget::widget::get_new_widget_id;
+    use crate::widgets::editor_widget::label::label::LabelPos;
     use crate::widgets::main_view::main_view::DocumentIdentifier;
+    use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::text::buffer_state_fuzz::{history_fuzz_call, HistoryFuzzOp};
 
     #[test]
     fn fuzz_1() {
@@ -11,4 +31,300 @@ pub mod tests {
 
         bf.apply_common_edit_message(CommonEditMsg::Char('䄀'), get_new_widget_id(), 10, None, false);
     }
+
+    // same as fuzz_target_3, but deterministic
+    #[test]
+    fn history_matches_snapshot_semantics() {
+        let mut rng = StdRng::seed_from_u64(2024);
+
+        for _ in 0..50 {
+            let data: Vec<u8> = (0..4096).map(|_| rng.gen()).collect();
+            let mut u = Unstructured::new(&data);
+
+            let text = String::arbitrary(&mut u).unwrap_or_default();
+            let mut ops = Vec::new();
+            while let Ok(op) = HistoryFuzzOp::arbitrary(&mut u) {
+                ops.push(op);
+                if u.is_empty() {
+                    break;
+                }
+            }
+
+            history_fuzz_call(text, ops);
+        }
+    }
+
+    #[test]
+    fn edit_events_are_incremental() {
+        let wid = get_new_widget_id();
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("ab\ncd");
+        bf.initialize_for_widget(wid, None);
+
+        assert!(bf.take_edit_event().is_none());
+
+        bf.apply_common_edit_message(CommonEditMsg::Char('x'), wid, 10, None, false);
+        bf.apply_common_edit_message(CommonEditMsg::DeleteBlock { char_range: 2..4 }, wid, 10, None, false);
+
+        match bf.take_edit_event() {
+            Some(EditEvent::Incremental { edits, file_contents }) => {
+                assert_eq!(file_contents.to_string(), "xacd");
+                assert_eq!(edits.len(), 2);
+
+                assert_eq!(edits[0].substitute, "x");
+                assert_eq!(edits[0].stupid_range, (StupidCursor::new(0, 0), StupidCursor::new(0, 0)));
+
+                // removal range is expressed in coordinates from before the removal
+                assert_eq!(edits[1].substitute, "");
+                assert_eq!(edits[1].stupid_range, (StupidCursor::new(2, 0), StupidCursor::new(0, 1)));
+            }
+            other => panic!("expected incremental edit event, got {:?}", other),
+        }
+
+        assert!(bf.take_edit_event().is_none());
+    }
+
+    // LSP counts columns in UTF-16 code units, and 😀 takes two of them
+    #[test]
+    fn edit_events_after_astral_char_use_utf16_columns() {
+        let wid = get_new_widget_id();
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("😀b\n😀cd");
+        bf.initialize_for_widget(wid, Some(CursorSet::singleton(Cursor::new(2))));
+
+        bf.apply_common_edit_message(CommonEditMsg::Char('x'), wid, 10, None, false);
+        bf.apply_common_edit_message(CommonEditMsg::DeleteBlock { char_range: 5..7 }, wid, 10, None, false);
+
+        match bf.take_edit_event() {
+            Some(EditEvent::Incremental { edits, file_contents }) => {
+                assert_eq!(file_contents.to_string(), "😀bx\n😀");
+                assert_eq!(edits.len(), 2);
+
+                assert_eq!(edits[0].substitute, "x");
+                assert_eq!(edits[0].stupid_range, (StupidCursor::new(3, 0), StupidCursor::new(3, 0)));
+
+                assert_eq!(edits[1].substitute, "");
+                assert_eq!(edits[1].stupid_range, (StupidCursor::new(2, 1), StupidCursor::new(4, 1)));
+            }
+            other => panic!("expected incremental edit event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn edit_events_after_undo_are_full() {
+        let wid = get_new_widget_id();
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("ab");
+        bf.initialize_for_widget(wid, None);
+
+        bf.apply_common_edit_message(CommonEditMsg::Char('x'), wid, 10, None, false);
+        bf.apply_common_edit_message(CommonEditMsg::Undo, wid, 10, None, false);
+
+        match bf.take_edit_event() {
+            Some(EditEvent::Full { file_contents }) => {
+                assert_eq!(file_contents.to_string(), "ab");
+            }
+            other => panic!("expected full edit event, got {:?}", other),
+        }
+
+        bf.apply_common_edit_message(CommonEditMsg::Char('y'), wid, 10, None, false);
+        assert!(matches!(bf.take_edit_event(), Some(EditEvent::Incremental { .. })));
+    }
+
+    fn item(char_begin: usize, char_end: usize, identifier: &str) -> HighlightItem {
+        HighlightItem {
+            char_begin,
+            char_end,
+            identifier: Arc::new(identifier.to_string()),
+            modifiers: vec![],
+        }
+    }
+
+    fn summary(items: &[HighlightItem]) -> Vec<(usize, usize, String)> {
+        items
+            .iter()
+            .map(|item| (item.char_begin, item.char_end, item.identifier.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn semantic_highlights_cut_tree_sitter_ones() {
+        let tree_sitter = vec![item(0, 10, "string"), item(12, 14, "keyword")];
+        let semantic = vec![item(2, 4, "variable"), item(6, 7, "macro")];
+
+        assert_eq!(
+            summary(&layer_highlights(tree_sitter, semantic)),
+            vec![
+                (0, 2, "string".to_string()),
+                (2, 4, "variable".to_string()),
+                (4, 6, "string".to_string()),
+                (6, 7, "macro".to_string()),
+                (7, 10, "string".to_string()),
+                (12, 14, "keyword".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn semantic_tokens_are_converted_to_char_ranges() {
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("ab\nlet mut x = 1;");
+
+        bf.set_semantic_tokens(vec![
+            NavCompSemanticToken {
+                stupid_range: (StupidCursor::new(8, 1), StupidCursor::new(9, 1)),
+                token_type: Arc::new("variable".to_string()),
+                modifiers: vec![Arc::new("mutable".to_string())],
+            },
+            // outdated, line got shorter
+            NavCompSemanticToken {
+                stupid_range: (StupidCursor::new(30, 1), StupidCursor::new(35, 1)),
+                token_type: Arc::new("function".to_string()),
+                modifiers: vec![],
+            },
+        ]);
+
+        let items = bf.highlight(None);
+        assert_eq!(summary(&items), vec![(11, 12, "variable".to_string())]);
+        assert_eq!(items[0].modifiers, vec![Arc::new("mutable".to_string())]);
+
+        assert!(bf.highlight(Some(0..3)).is_empty());
+    }
+
+    #[test]
+    fn semantic_tokens_columns_are_utf16() {
+        // the emoji is a single char, but 2 UTF-16 code units
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("ab\n😀 mut x");
+
+        bf.set_semantic_tokens(vec![NavCompSemanticToken {
+            stupid_range: (StupidCursor::new(7, 1), StupidCursor::new(8, 1)),
+            token_type: Arc::new("variable".to_string()),
+            modifiers: vec![],
+        }]);
+
+        assert_eq!(summary(&bf.highlight(None)), vec![(9, 10, "variable".to_string())]);
+    }
+
+    #[test]
+    fn positions_beyond_u16_are_not_truncated() {
+        let text = format!("{}\n{}", "abc\n".repeat(70_000), "x".repeat(70_000));
+        let bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text(text);
+
+        assert_eq!(bf.size(), BufferXY::new(70_001, 70_002));
+
+        let char_idx = bf.line_to_char(69_999).unwrap() + 2;
+        assert_eq!(cursor_to_xy(&Cursor::new(char_idx), &bf), BufferXY::new(2, 69_999));
+        assert_eq!(bf.char_idx_to_xy(char_idx), Some(BufferXY::new(2, 69_999)));
+        assert_eq!(StupidCursor::new(3, 69_999).to_xy(&bf), Some(BufferXY::new(3, 69_999)));
+
+        assert_eq!(
+            LabelPos::LineAfter { line_no_1b: 70_000 }.into_position(&bf),
+            Some(BufferXY::new(4, 69_999))
+        );
+        assert_eq!(
+            LabelPos::Inline { char_idx: bf.len_chars() }.into_position(&bf),
+            Some(BufferXY::new(70_000, 70_001))
+        );
+    }
+
+    #[test]
+    fn undo_history_survives_snapshot_test() {
+        let fsf = MockFS::new("/tmp").with_file("file.txt", "a").to_fsf();
+        let path = spath!(fsf, "file.txt").unwrap();
+        let doc_id = DocumentIdentifier::new_unique().with_file_path(path);
+
+        let wid = get_new_widget_id();
+        let mut bf = BufferState::full(None, doc_id.clone(), None, None)
+            .with_text("a")
+            .with_maked_as_saved();
+        bf.initialize_for_widget(wid, None);
+        bf.text_mut().set_cursor_set(wid, CursorSet::new(vec![Cursor::new(1)]));
+
+        bf.apply_common_edit_message(CommonEditMsg::Char('b'), wid, 10, None, false);
+        bf.apply_common_edit_message(CommonEditMsg::Char('c'), wid, 10, None, false);
+        bf.mark_as_saved();
+
+        let snapshot = bf.undo_snapshot(undo_cache::MAX_ENTRIES, undo_cache::MAX_BYTES).unwrap();
+        assert_eq!(snapshot.entries.len(), 3);
+        assert_eq!(snapshot.saved_pos, 2);
+        assert_eq!(snapshot.content_hash, undo_cache::stable_hash("abc".as_bytes()));
+
+        // only two newest entries fit
+        let small_snapshot = bf.undo_snapshot(2, undo_cache::MAX_BYTES).unwrap();
+        assert_eq!(small_snapshot.entries.len(), 2);
+        assert_eq!(small_snapshot.saved_pos, 1);
+        assert_eq!(small_snapshot.entries[0].text, "ab");
+
+        // "reopening" the file
+        let other_wid = get_new_widget_id();
+        let mut reopened = BufferState::full(None, doc_id.clone(), None, None)
+            .with_text("abc")
+            .with_maked_as_saved()
+            .with_undo_snapshot(snapshot.clone());
+        reopened.initialize_for_widget(other_wid, None);
+
+        assert!(reopened.is_saved());
+        assert_eq!(reopened.to_string(), "abc");
+        assert!(reopened.undo());
+        assert!(reopened.undo());
+        assert_eq!(reopened.to_string(), "a");
+        assert_eq!(
+            reopened.text().get_cursor_set(other_wid),
+            Some(&CursorSet::new(vec![Cursor::new(1)]))
+        );
+        assert!(!reopened.undo());
+
+        // contents changed outside of editor
+        let mut modified = BufferState::full(None, doc_id, None, None)
+            .with_text("xyz")
+            .with_maked_as_saved()
+            .with_undo_snapshot(snapshot);
+        modified.initialize_for_widget(other_wid, None);
+        assert!(!modified.can_undo());
+    }
+
+    #[test]
+    fn typing_after_undo_keeps_old_branch_test() {
+        let wid = get_new_widget_id();
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("");
+        bf.initialize_for_widget(wid, None);
+
+        bf.apply_common_edit_message(CommonEditMsg::Char('a'), wid, 10, None, false);
+        bf.apply_common_edit_message(CommonEditMsg::Char('b'), wid, 10, None, false);
+        let old_branch = bf.history().current_id();
+
+        assert!(bf.undo());
+        bf.apply_common_edit_message(CommonEditMsg::Char('x'), wid, 10, None, false);
+        assert_eq!(bf.to_string(), "ax");
+        assert!(!bf.can_redo());
+
+        assert!(bf.jump_to_history(old_branch, wid));
+        assert_eq!(bf.to_string(), "ab");
+        assert_eq!(bf.text().get_cursor_set(wid), Some(&CursorSet::new(vec![Cursor::new(2)])));
+
+        assert!(bf.undo());
+        assert!(bf.redo());
+        assert_eq!(bf.to_string(), "ab");
+    }
+
+    #[test]
+    fn temporary_cursor_set_leaves_no_trace_test() {
+        let wid = get_new_widget_id();
+        let temp_wid = get_new_widget_id();
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("ab\ncd");
+        bf.initialize_for_widget(wid, None);
+
+        assert!(bf.add_temporary_cursor_set(temp_wid));
+        let edit = StupidSubstituteMessage {
+            substitute: "xy".to_string(),
+            stupid_range: (StupidCursor::new(0, 1), StupidCursor::new(2, 1)),
+        };
+        assert!(bf.apply_stupid_substitute_messages(temp_wid, &vec![edit], 1));
+        bf.remove_cursor_sets(temp_wid);
+
+        assert_eq!(bf.to_string(), "ab\nxy");
+        assert!(!bf.text().has_cursor_set_for(temp_wid));
+        assert!(bf.text().has_cursor_set_for(wid));
+
+        assert!(bf.undo());
+        assert_eq!(bf.to_string(), "ab\ncd");
+        assert!(!bf.text().has_cursor_set_for(temp_wid));
+        assert!(bf.text().has_cursor_set_for(wid));
+    }
 }
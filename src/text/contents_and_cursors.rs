@@ -22,7 +22,7 @@ use crate::tsw::rope_wrappers::RopeWrapper;
 use crate::tsw::tree_sitter_wrapper::TreeSitterWrapper;
 use crate::widget::widget::WID;
 use crate::widgets::editor_widget::label::label::Label;
-use crate::{unpack, unpack_or, unpack_or_e};
+use crate::{unpack_or, unpack_or_e};
 /*
 I allow empty history, it means "nobody is looking at the buffer now, first who comes needs to set
 it's cursors.
@@ -36,6 +36,39 @@ pub struct ContentsAndCursors {
     labels: Vec<Label>,
 }
 
+/*
+Everything ContentsAndCursors holds apart from text and parse tree. That's what history keeps for
+states other than the current one, text of those is recovered from edits (see UndoTree).
+ */
+#[derive(Clone, Debug, Default)]
+pub struct CursorsAndLabels {
+    cursor_sets: Vec<(WID, CursorSet)>,
+    labels: Vec<Label>,
+}
+
+impl CursorsAndLabels {
+    pub fn add_cursor_set(&mut self, widget_id: WID, cs: CursorSet) -> bool {
+        if self.cursor_sets.iter().any(|(wid, _)| *wid == widget_id) {
+            error!("can't add cursor set for WidgetID {} - it's already present.", widget_id);
+            return false;
+        }
+
+        self.cursor_sets.push((widget_id, cs));
+        true
+    }
+
+    pub fn first_cursor_set(&self) -> Option<&CursorSet> {
+        self.cursor_sets.first().map(|(_, cs)| cs)
+    }
+
+    // Returns whether there was one.
+    pub fn remove_cursor_set(&mut self, widget_id: WID) -> bool {
+        let len_before = self.cursor_sets.len();
+        self.cursor_sets.retain(|(wid, _)| *wid != widget_id);
+        self.cursor_sets.len() != len_before
+    }
+}
+
 impl ContentsAndCursors {
     pub fn new(rope: Rope, parsing: Option<ParsingTuple>) -> Self {
         ContentsAndCursors {
@@ -172,6 +205,11 @@ impl ContentsAndCursors {
         res
     }
 
+    // cursor set of the view that registered first, used when persisting undo history
+    pub fn first_cursor_set(&self) -> Option<&CursorSet> {
+        self.cursor_sets.first().map(|(_, cs)| cs)
+    }
+
     pub fn get_cursor_set_mut(&mut self, widget_id: WID) -> Option<&mut CursorSet> {
         self.cursor_sets.iter_mut().find(|(wid, _)| *wid == widget_id).map(|(_, cs)| cs)
     }
@@ -202,6 +240,13 @@ impl ContentsAndCursors {
         &mut self.rope
     }
 
+    // Returns whether there was one.
+    pub fn remove_cursor_set(&mut self, widget_id: WID) -> bool {
+        let len_before = self.cursor_sets.len();
+        self.cursor_sets.retain(|(wid, _)| *wid != widget_id);
+        self.cursor_sets.len() != len_before
+    }
+
     pub fn has_cursor_set_for(&self, widget_id: WID) -> bool {
         self.cursor_sets.iter().find(|(wid, _)| *wid == widget_id).is_some()
     }
@@ -228,6 +273,29 @@ impl ContentsAndCursors {
         Self { rope, ..self }
     }
 
+    pub fn cursors_and_labels(&self) -> CursorsAndLabels {
+        CursorsAndLabels {
+            cursor_sets: self.cursor_sets.clone(),
+            labels: self.labels.clone(),
+        }
+    }
+
+    pub fn set_cursors_and_labels(&mut self, cursors_and_labels: CursorsAndLabels) {
+        self.cursor_sets = cursors_and_labels.cursor_sets;
+        self.labels = cursors_and_labels.labels;
+    }
+
+    /*
+    Drops the syntax tree and parses the text again, keeping parser and queries. Used when text was
+    replaced by history, so there's no edit to update the old tree with. Returns false if there's
+    no parsing to begin with.
+     */
+    pub fn reparse_from_scratch(&mut self) -> bool {
+        let parsing = unpack_or!(self.parsing.as_mut(), false, "no parsing to redo");
+        parsing.tree = None;
+        parsing.try_reparse(&self.rope)
+    }
+
     pub fn labels(&self) -> &[Label] {
         &self.labels
     }
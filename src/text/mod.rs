@@ -1,5 +1,8 @@
 pub mod buffer_state;
+pub mod recovery;
 pub mod text_buffer;
+pub mod undo_cache;
+pub mod undo_tree;
 
 mod buffer_state_test;
 mod contents_and_cursors;
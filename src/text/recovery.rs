@@ -0,0 +1,299 @@
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+
+use log::{debug, error, warn};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::config::Config;
+use crate::config::load_error::LoadError;
+use crate::config::save_error::SaveError;
+use crate::cursor::cursor_set::CursorSet;
+use crate::text::undo_cache::stable_hash;
+
+/*
+Crash recovery.
+
+While gladius runs, Autosaver periodically dumps every buffer with unsaved changes into
+<config_dir>/recovery/<hash of workspace root>/<session>/, one file per buffer, named after its buffer
+id. Files of buffers that got saved or closed are removed, and so is the entire session folder when
+gladius exits normally.
+
+Each session folder has an OWNER_LOCK_FILE, locked for as long as the instance that writes there lives.
+OS releases the lock when the process dies, however it dies. So on startup, session folders that can
+be locked are leftovers of a crash, and are offered back to the user (see RecoveryView). The ones
+that can't belong to another instance running in the same workspace, and are left alone.
+
+Unlike undo cache, this covers buffers that were never saved too. Paths are relative to workspace
+root, which is what the folder is named after.
+ */
+
+pub const RECOVERY_FOLDER: &str = "recovery";
+pub const OWNER_LOCK_FILE: &str = "owner.lock";
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryEntry {
+    // of DocumentIdentifier the buffer had, used only to name the file
+    pub buffer_id: String,
+    // None for buffers that were never saved
+    pub path: Option<PathBuf>,
+    pub text: String,
+    // cursors of the first view that had any, like in undo cache
+    pub cursor_set: Option<CursorSet>,
+}
+
+// Returns None if crash recovery is disabled, or there is no config dir (tests).
+pub fn recovery_dir(config: &Config, workspace_root: &Path) -> Option<PathBuf> {
+    if !config.global.crash_recovery || config.config_dir.as_os_str().is_empty() {
+        return None;
+    }
+
+    Some(
+        config
+            .config_dir
+            .join(RECOVERY_FOLDER)
+            .join(stable_hash(workspace_root.to_string_lossy().as_bytes())),
+    )
+}
+
+/*
+A session folder, locked by this process. Dropping it releases the lock and leaves the folder, which is
+what happens on crash. Session that ends normally should be removed instead.
+ */
+#[derive(Debug)]
+pub struct RecoverySession {
+    dir: PathBuf,
+    _lock: File,
+}
+
+impl RecoverySession {
+    // Creates a fresh session folder under recovery_dir.
+    pub fn new(recovery_dir: &Path) -> Result<RecoverySession, SaveError> {
+        let dir = recovery_dir.join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir)?;
+
+        match Self::lock(dir)? {
+            Some(session) => Ok(session),
+            None => Err(SaveError::IoError(std::io::ErrorKind::WouldBlock.into())),
+        }
+    }
+
+    // None if someone alive holds the lock.
+    fn lock(dir: PathBuf) -> Result<Option<RecoverySession>, SaveError> {
+        let lock = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(OWNER_LOCK_FILE))?;
+
+        match lock.try_lock() {
+            Ok(_) => Ok(Some(RecoverySession { dir, _lock: lock })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Removes the folder with everything in it.
+    pub fn remove(self) {
+        let RecoverySession { dir, _lock } = self;
+        drop(_lock);
+
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            error!("failed to remove recovery session {:?}, because {}", dir, e);
+        }
+    }
+}
+
+/*
+Locks and returns sessions under recovery_dir whose owners are gone. While they are held, other
+instances will not offer them.
+ */
+pub fn orphaned_sessions(recovery_dir: &Path) -> Vec<RecoverySession> {
+    let read_dir = match std::fs::read_dir(recovery_dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                error!("failed to read recovery folder {:?}, because {}", recovery_dir, e);
+            }
+            return Vec::new();
+        }
+    };
+
+    let mut result: Vec<RecoverySession> = Vec::new();
+
+    for dir_entry in read_dir.flatten() {
+        let dir = dir_entry.path();
+        if !dir.is_dir() {
+            continue;
+        }
+
+        match RecoverySession::lock(dir.clone()) {
+            Ok(Some(session)) => result.push(session),
+            Ok(None) => {
+                debug!("recovery session {:?} belongs to a running instance", dir);
+            }
+            Err(e) => {
+                error!("failed to lock recovery session {:?}, because {}", dir, e);
+            }
+        }
+    }
+
+    result.sort_by(|a, b| a.dir.cmp(&b.dir));
+    result
+}
+
+pub fn entry_file_path(dir: &Path, buffer_id: &str) -> PathBuf {
+    dir.join(format!("{}.ron", buffer_id))
+}
+
+pub fn store(dir: &Path, entry: &RecoveryEntry) -> Result<(), SaveError> {
+    std::fs::create_dir_all(dir)?;
+
+    let file_path = entry_file_path(dir, &entry.buffer_id);
+    let contents = ron::to_string(entry)?;
+
+    // same as in undo cache, a crash while writing must not destroy the previous copy
+    let tmp_path = file_path.with_extension("ron.tmp");
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, &file_path)?;
+
+    debug!("stored recovery entry of {} ({:?})", entry.buffer_id, entry.path);
+    Ok(())
+}
+
+// Removing an entry that's not there is not an error.
+pub fn remove(dir: &Path, buffer_id: &str) -> Result<(), SaveError> {
+    match std::fs::remove_file(entry_file_path(dir, buffer_id)) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/*
+Returns all entries found in dir, ordered by path (unsaved buffers go first). Files that can't be read
+are skipped, they're left where they are, so nothing is lost if it's a bug on my side.
+ */
+pub fn load_all(dir: &Path) -> Result<Vec<RecoveryEntry>, LoadError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut result: Vec<RecoveryEntry> = Vec::new();
+
+    for dir_entry in std::fs::read_dir(dir)? {
+        let file_path = dir_entry?.path();
+        if file_path.extension().map(|ext| ext != "ron").unwrap_or(true) {
+            continue;
+        }
+
+        let entry = std::fs::read_to_string(&file_path)
+            .map_err(LoadError::from)
+            .and_then(|s| ron::from_str::<RecoveryEntry>(&s).map_err(LoadError::from));
+
+        match entry {
+            Ok(entry) => result.push(entry),
+            Err(e) => {
+                warn!("failed to read recovery entry {:?}, because {}", file_path, e);
+            }
+        }
+    }
+
+    result.sort_by(|a, b| (&a.path, &a.buffer_id).cmp(&(&b.path, &b.buffer_id)));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::cursor::cursor::Cursor;
+    use crate::cursor::cursor_set::CursorSet;
+    use crate::text::recovery::{entry_file_path, load_all, orphaned_sessions, remove, store, RecoveryEntry, RecoverySession};
+
+    fn temp_recovery_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gladius_recovery_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn entry(buffer_id: &str, path: Option<&str>, text: &str) -> RecoveryEntry {
+        RecoveryEntry {
+            buffer_id: buffer_id.to_string(),
+            path: path.map(PathBuf::from),
+            text: text.to_string(),
+            cursor_set: Some(CursorSet::new(vec![Cursor::new(1)])),
+        }
+    }
+
+    #[test]
+    fn recovery_round_trip_test() {
+        let dir = temp_recovery_dir("round_trip");
+        assert_eq!(load_all(&dir).unwrap(), vec![]);
+
+        let a = entry("a", Some("src/main.rs"), "fn main() {}");
+        let b = entry("b", None, "scratch");
+        store(&dir, &a).unwrap();
+        store(&dir, &b).unwrap();
+
+        // unnamed buffers go first
+        assert_eq!(load_all(&dir).unwrap(), vec![b.clone(), a.clone()]);
+
+        // storing again overwrites
+        let a2 = entry("a", Some("src/main.rs"), "fn main() { panic!() }");
+        store(&dir, &a2).unwrap();
+        assert_eq!(load_all(&dir).unwrap(), vec![b.clone(), a2]);
+
+        remove(&dir, "a").unwrap();
+        remove(&dir, "not_there").unwrap();
+        assert_eq!(load_all(&dir).unwrap(), vec![b]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recovery_skips_broken_entries_test() {
+        let dir = temp_recovery_dir("broken");
+        store(&dir, &entry("a", None, "x")).unwrap();
+        std::fs::write(entry_file_path(&dir, "b"), "definitely not ron").unwrap();
+        std::fs::write(dir.join("c.txt"), "not an entry at all").unwrap();
+
+        assert_eq!(load_all(&dir).unwrap(), vec![entry("a", None, "x")]);
+        // broken one is not removed
+        assert!(entry_file_path(&dir, "b").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recovery_offers_only_sessions_without_owner_test() {
+        let dir = temp_recovery_dir("owner");
+
+        let crashed = RecoverySession::new(&dir).unwrap();
+        let crashed_dir = crashed.dir().to_path_buf();
+        store(&crashed_dir, &entry("a", None, "x")).unwrap();
+        drop(crashed);
+
+        let running = RecoverySession::new(&dir).unwrap();
+        store(running.dir(), &entry("b", None, "y")).unwrap();
+
+        let orphans = orphaned_sessions(&dir);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].dir(), crashed_dir.as_path());
+
+        // adopted orphan is not offered to anyone else
+        assert!(orphaned_sessions(&dir).is_empty());
+
+        for orphan in orphans {
+            orphan.remove();
+        }
+        running.remove();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
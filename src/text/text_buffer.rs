@@ -1,12 +1,12 @@
 use std::fmt::{Debug, Formatter};
 
-use log::{error, warn};
+use log::warn;
 use ropey::iter::{Chars, Chunks};
 use streaming_iterator::StreamingIterator;
 
 use crate::cursor::cursor::{Cursor, Selection};
+use crate::primitives::buffer_xy::BufferXY;
 use crate::primitives::stupid_cursor::StupidCursor;
-use crate::primitives::xy::XY;
 use crate::tsw::lang_id::LangId;
 use crate::unpack_or_e;
 //TODO create tests for undo/redo/set milestone
@@ -63,25 +63,15 @@ pub trait TextBuffer: ToString {
     }
 
     // TODO test
-    fn char_idx_to_xy(&self, char_idx: usize) -> Option<XY> {
+    fn char_idx_to_xy(&self, char_idx: usize) -> Option<BufferXY> {
         if self.len_chars() < char_idx {
             return None;
         }
 
-        let line_idx_0b = self.char_to_line(char_idx).unwrap();
-        let char_idx_in_line_0b = char_idx - line_idx_0b;
+        let line_idx_0b = self.char_to_line(char_idx)?;
+        let char_idx_in_line_0b = char_idx - self.line_to_char(line_idx_0b)?;
 
-        if line_idx_0b > u16::MAX as usize {
-            error!("line index too high");
-            return None;
-        }
-
-        if char_idx_in_line_0b > u16::MAX as usize {
-            error!("char in line index too high");
-            return None;
-        }
-
-        Some(XY::new(char_idx_in_line_0b as u16, line_idx_0b as u16))
+        Some(BufferXY::new(char_idx_in_line_0b, line_idx_0b))
     }
 
     // TODO test
@@ -103,11 +93,14 @@ pub trait TextBuffer: ToString {
         Some(result)
     }
 
-    fn stupid_cursor_to_cursor(&self, sc1: StupidCursor, sc2: Option<StupidCursor>) -> Option<Cursor> {
-        let pos1 = self.line_to_char(sc1.line_0b as usize)? + sc1.char_idx_0b as usize;
+    fn stupid_cursor_to_cursor(&self, sc1: StupidCursor, sc2: Option<StupidCursor>) -> Option<Cursor>
+    where
+        Self: Sized,
+    {
+        let pos1 = sc1.to_real_cursor(self)?.a;
         let pos2: Option<usize> = match sc2 {
             None => None,
-            Some(sc2) => Some(self.line_to_char(sc2.line_0b as usize)? + sc2.char_idx_0b as usize),
+            Some(sc2) => Some(sc2.to_real_cursor(self)?.a),
         };
 
         let mut cursor = Cursor::new(pos1);
@@ -135,13 +128,13 @@ pub trait TextBuffer: ToString {
         None
     }
 
-    fn get_tabs_expansion_for_line(&self, line_no_0b: usize) -> u16 {
-        let mut added_from_tabs: u16 = 0;
+    fn get_tabs_expansion_for_line(&self, line_no_0b: usize) -> usize {
+        let mut added_from_tabs: usize = 0;
         if let Some(tabs_to_spaces) = self.get_tab_width() {
             if let Some(line) = self.get_line(line_no_0b) {
                 for char in line.chars() {
                     if char == '\t' {
-                        added_from_tabs += tabs_to_spaces as u16;
+                        added_from_tabs += tabs_to_spaces as usize;
                     }
                 }
             }
@@ -0,0 +1,308 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+
+use crate::config::config::Config;
+use crate::config::load_error::LoadError;
+use crate::config::save_error::SaveError;
+use crate::cursor::cursor_set::CursorSet;
+
+/*
+Undo history that outlives the editor process.
+
+On every save, BufferState dumps (a bounded window of) its history into a file under
+<config_dir>/undo/, named after hash of the absolute path. When the same file is opened again and its
+contents still match what was saved, the history is restored, so one can undo into edits from previous
+session. If the file was modified outside gladius in the meantime, the cache is ignored (and overwritten
+on next save).
+
+Entries are stored as full texts, not diffs. That's wasteful, but it's exactly what BufferState keeps
+in memory anyway, and the window is bounded by both number of entries and total bytes.
+
+Files are never removed when their buffers close, so the whole folder is pruned on startup: caches not
+written for MAX_CACHE_AGE go first, then the least recently written ones till it fits MAX_CACHE_BYTES.
+ */
+
+pub const UNDO_CACHE_FOLDER: &str = "undo";
+
+pub const MAX_ENTRIES: usize = 200;
+pub const MAX_BYTES: usize = 4 * 1024 * 1024;
+
+pub const MAX_CACHE_AGE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+pub const MAX_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UndoSnapshotEntry {
+    pub text: String,
+    // cursors of the first view that had any. Views are not persisted, so there is nobody to assign others to.
+    pub cursor_set: Option<CursorSet>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UndoSnapshot {
+    pub path: PathBuf,
+    // hash of entries[saved_pos].text, which is what should be on disk
+    pub content_hash: String,
+    pub entries: Vec<UndoSnapshotEntry>,
+    pub saved_pos: usize,
+}
+
+// Returns None if persistent undo is disabled, or there is no config dir (tests).
+pub fn cache_dir(config: &Config) -> Option<PathBuf> {
+    if !config.global.persistent_undo || config.config_dir.as_os_str().is_empty() {
+        return None;
+    }
+
+    Some(config.config_dir.join(UNDO_CACHE_FOLDER))
+}
+
+/*
+FNV-1a. Used instead of DefaultHasher, because the results have to be stable across builds - they
+end up in filenames and in the cache itself.
+ */
+pub fn stable_hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{:016x}", hash)
+}
+
+pub fn cache_file_path(cache_dir: &Path, path: &Path) -> PathBuf {
+    cache_dir.join(format!("{}.ron", stable_hash(path.to_string_lossy().as_bytes())))
+}
+
+/*
+Given sizes (in bytes) of history entries, returns range of entries to persist. It always contains
+anchor (the saved entry), and drops oldest entries first, then the "forward" ones.
+ */
+pub fn window(sizes: &[usize], anchor: usize, max_entries: usize, max_bytes: usize) -> Range<usize> {
+    debug_assert!(anchor < sizes.len());
+
+    let mut begin = 0;
+    let mut end = sizes.len();
+    let mut total: usize = sizes.iter().sum();
+
+    while end - begin > max_entries.max(1) || total > max_bytes {
+        if begin < anchor {
+            total -= sizes[begin];
+            begin += 1;
+        } else if end - 1 > anchor {
+            end -= 1;
+            total -= sizes[end];
+        } else {
+            break;
+        }
+    }
+
+    begin..end
+}
+
+pub fn store(cache_dir: &Path, snapshot: &UndoSnapshot) -> Result<(), SaveError> {
+    std::fs::create_dir_all(cache_dir)?;
+
+    let file_path = cache_file_path(cache_dir, &snapshot.path);
+    let contents = ron::to_string(snapshot)?;
+
+    // writing to a temporary file first, so a crash in the middle does not leave half a cache behind
+    let tmp_path = file_path.with_extension("ron.tmp");
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, &file_path)?;
+
+    debug!("stored {} undo entries for {:?}", snapshot.entries.len(), snapshot.path);
+    Ok(())
+}
+
+/*
+Returns Ok(None) if there's no cache for the path, or it does not describe current contents of the file.
+ */
+pub fn load(cache_dir: &Path, path: &Path, contents: &[u8]) -> Result<Option<UndoSnapshot>, LoadError> {
+    let file_path = cache_file_path(cache_dir, path);
+    if !file_path.exists() {
+        return Ok(None);
+    }
+
+    let s = std::fs::read_to_string(&file_path)?;
+    let snapshot = ron::from_str::<UndoSnapshot>(&s)?;
+
+    if snapshot.path != path {
+        debug!("undo cache {:?} belongs to {:?}, not {:?}", file_path, snapshot.path, path);
+        return Ok(None);
+    }
+
+    if snapshot.saved_pos >= snapshot.entries.len() {
+        return Err(LoadError::UnmappedError(format!("saved_pos out of bounds in {:?}", file_path)));
+    }
+
+    if snapshot.content_hash != stable_hash(contents) {
+        debug!("{:?} changed since undo history was stored, ignoring it", path);
+        return Ok(None);
+    }
+
+    Ok(Some(snapshot))
+}
+
+/*
+Removes cache files older than max_age, then oldest ones till the rest fits in max_bytes. Leftover
+temporary files count as any other. Returns number of removed files.
+ */
+pub fn prune(cache_dir: &Path, max_age: Duration, max_bytes: u64) -> Result<usize, SaveError> {
+    let read_dir = match std::fs::read_dir(cache_dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    // (modified, size, path)
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = Vec::new();
+    for dir_entry in read_dir {
+        let dir_entry = dir_entry?;
+        let metadata = dir_entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+
+        files.push((metadata.modified()?, metadata.len(), dir_entry.path()));
+    }
+
+    // newest first, so whatever is over the budget is at the end
+    files.sort_by_key(|(modified, _, _)| std::cmp::Reverse(*modified));
+
+    let now = SystemTime::now();
+    let mut total: u64 = 0;
+    let mut removed: usize = 0;
+
+    for (modified, size, path) in files {
+        let too_old = now.duration_since(modified).map(|age| age > max_age).unwrap_or(false);
+        if !too_old && total + size <= max_bytes {
+            total += size;
+            continue;
+        }
+
+        match std::fs::remove_file(&path) {
+            Ok(_) => removed += 1,
+            Err(e) => {
+                error!("failed to remove undo cache {:?}, because {}", path, e);
+            }
+        }
+    }
+
+    debug!("pruned {} undo caches, {} bytes left", removed, total);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    use crate::cursor::cursor::Cursor;
+    use crate::cursor::cursor_set::CursorSet;
+    use crate::text::undo_cache::{cache_file_path, load, prune, stable_hash, store, window, UndoSnapshot, UndoSnapshotEntry};
+
+    fn temp_cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gladius_undo_cache_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn snapshot(path: &str) -> UndoSnapshot {
+        UndoSnapshot {
+            path: PathBuf::from(path),
+            content_hash: stable_hash("ab".as_bytes()),
+            entries: vec![
+                UndoSnapshotEntry {
+                    text: "a".to_string(),
+                    cursor_set: Some(CursorSet::new(vec![Cursor::new(1)])),
+                },
+                UndoSnapshotEntry {
+                    text: "ab".to_string(),
+                    cursor_set: None,
+                },
+            ],
+            saved_pos: 1,
+        }
+    }
+
+    #[test]
+    fn undo_cache_round_trip_test() {
+        let dir = temp_cache_dir("round_trip");
+        let s = snapshot("/some/file.txt");
+
+        store(&dir, &s).unwrap();
+
+        let read = load(&dir, &PathBuf::from("/some/file.txt"), "ab".as_bytes()).unwrap();
+        assert_eq!(read, Some(s));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn undo_cache_ignores_modified_file_test() {
+        let dir = temp_cache_dir("modified");
+        store(&dir, &snapshot("/some/file.txt")).unwrap();
+
+        assert_eq!(load(&dir, &PathBuf::from("/some/file.txt"), "abc".as_bytes()).unwrap(), None);
+        assert_eq!(load(&dir, &PathBuf::from("/some/other.txt"), "ab".as_bytes()).unwrap(), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn undo_cache_window_test() {
+        // everything fits
+        assert_eq!(window(&[1, 1, 1], 1, 10, 10), 0..3);
+        // oldest go first
+        assert_eq!(window(&[1, 1, 1, 1], 3, 2, 10), 2..4);
+        // then the ones after anchor
+        assert_eq!(window(&[1, 1, 1, 1], 0, 2, 10), 0..2);
+        assert_eq!(window(&[5, 5, 5, 5], 2, 10, 11), 2..4);
+        // anchor is kept even if it's over budget by itself
+        assert_eq!(window(&[5, 100, 5], 1, 10, 10), 1..2);
+    }
+
+    #[test]
+    fn undo_cache_prune_test() {
+        let dir = temp_cache_dir("prune");
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        // written 1, 2, 3 and 100 days ago
+        let files: Vec<PathBuf> = [1, 2, 3, 100]
+            .iter()
+            .map(|days_ago| {
+                let s = snapshot(&format!("/some/file_{}.txt", days_ago));
+                store(&dir, &s).unwrap();
+
+                let file_path = cache_file_path(&dir, &s.path);
+                let file = std::fs::File::options().write(true).open(&file_path).unwrap();
+                file.set_modified(SystemTime::now() - day * *days_ago).unwrap();
+                file_path
+            })
+            .collect();
+
+        let sizes: Vec<u64> = files.iter().map(|f| std::fs::metadata(f).unwrap().len()).collect();
+        let total: u64 = sizes.iter().sum();
+
+        // nothing to do
+        assert_eq!(prune(&dir, day * 1000, total).unwrap(), 0);
+
+        // too old
+        assert_eq!(prune(&dir, day * 30, total).unwrap(), 1);
+        assert!(!files[3].exists());
+
+        // over budget, least recently written go first
+        assert_eq!(prune(&dir, day * 30, sizes[0] + sizes[1]).unwrap(), 1);
+        assert!(files[0].exists());
+        assert!(files[1].exists());
+        assert!(!files[2].exists());
+
+        // missing folder is fine
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(prune(&dir, day, 0).unwrap(), 0);
+    }
+}
@@ -0,0 +1,983 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::SystemTime;
+
+use log::{debug, error};
+use ropey::Rope;
+
+use crate::cursor::cursor_set::CursorSet;
+use crate::text::contents_and_cursors::{ContentsAndCursors, CursorsAndLabels};
+use crate::unpack_or_e;
+use crate::widget::widget::WID;
+
+/*
+History of edits of a single buffer. It's a tree, not a list: when you undo a couple of steps and
+type something, the states you undid are not dropped, they stay as an abandoned branch you can go
+back to (see HistoryBrowserWidget).
+
+Only the current state is kept in full (that's the "live" ContentsAndCursors edits are applied to).
+Every other state remembers edits that turn its parent into it, so undo applies them inverted and
+redo applies them as they are. To keep reconstruction of arbitrary states (previews, persisting) cheap,
+every CHECKPOINT_INTERVAL-th state along a branch keeps its full text too. Ropes share unchanged
+chunks, so a checkpoint costs little more than the edits would.
+
+Cursors and labels of each state are stored as they are, they're small. Parse tree is not, it's
+rebuilt by BufferState whenever current state changes.
+
+Nodes are referred to by ids, which are never reused. Ids grow with time, so parent has smaller
+id than any of its children. When history gets over its limits, oldest states are forgotten (see
+enforce_limits).
+ */
+
+// no state is further than that many steps from one that keeps full text
+pub const CHECKPOINT_INTERVAL: usize = 32;
+
+// rough cost of a state apart from its edits, used to estimate memory footprint
+const NODE_OVERHEAD_BYTES: usize = 256;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HistoryLimits {
+    // number of states, including current one
+    pub max_states: usize,
+    // estimate of memory taken by edits plus some constant per state, checkpoints are not counted
+    pub max_bytes: usize,
+}
+
+impl HistoryLimits {
+    pub const UNLIMITED: HistoryLimits = HistoryLimits {
+        max_states: usize::MAX,
+        max_bytes: usize::MAX,
+    };
+
+    pub fn new(max_states: usize, max_bytes: usize) -> Self {
+        HistoryLimits { max_states, max_bytes }
+    }
+}
+
+impl Default for HistoryLimits {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
+// A single change of text, in chars.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TextEdit {
+    Insert { char_idx: usize, text: String },
+    Remove { char_idx: usize, text: String },
+}
+
+impl TextEdit {
+    pub fn inverse(&self) -> TextEdit {
+        match self {
+            TextEdit::Insert { char_idx, text } => TextEdit::Remove {
+                char_idx: *char_idx,
+                text: text.clone(),
+            },
+            TextEdit::Remove { char_idx, text } => TextEdit::Insert {
+                char_idx: *char_idx,
+                text: text.clone(),
+            },
+        }
+    }
+
+    pub fn apply(&self, rope: &mut Rope) -> bool {
+        let result = match self {
+            TextEdit::Insert { char_idx, text } => rope.try_insert(*char_idx, text),
+            TextEdit::Remove { char_idx, text } => rope.try_remove(*char_idx..*char_idx + text.chars().count()),
+        };
+
+        if let Err(e) = result {
+            error!("failed to apply {:?}, because {}", self, e);
+            return false;
+        }
+
+        true
+    }
+
+    fn size_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + match self {
+                TextEdit::Insert { text, .. } | TextEdit::Remove { text, .. } => text.len(),
+            }
+    }
+
+    /*
+    Edits turning one text into another: whatever is between their common prefix and common suffix
+    gets replaced. Empty if texts are equal.
+     */
+    pub fn diff(from: &Rope, to: &Rope) -> Vec<TextEdit> {
+        let prefix = from.chars().zip(to.chars()).take_while(|(a, b)| a == b).count();
+
+        let max_suffix = from.len_chars().min(to.len_chars()) - prefix;
+        let suffix = from
+            .chars_at(from.len_chars())
+            .reversed()
+            .zip(to.chars_at(to.len_chars()).reversed())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let mut result = Vec::new();
+
+        let removed: String = from.slice(prefix..from.len_chars() - suffix).to_string();
+        if !removed.is_empty() {
+            result.push(TextEdit::Remove {
+                char_idx: prefix,
+                text: removed,
+            });
+        }
+
+        let inserted: String = to.slice(prefix..to.len_chars() - suffix).to_string();
+        if !inserted.is_empty() {
+            result.push(TextEdit::Insert {
+                char_idx: prefix,
+                text: inserted,
+            });
+        }
+
+        result
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UndoNode {
+    parent: Option<usize>,
+    // in order of creation
+    children: Vec<usize>,
+    // where redo goes: the child we last came from (or created)
+    redo_child: Option<usize>,
+    created: SystemTime,
+
+    // turn parent's text into this one's, empty in root
+    edits: Vec<TextEdit>,
+    // full text, always present in root. Outdated in current node, live contents are the truth there.
+    checkpoint: Option<Rope>,
+    // same as above, outdated in current node
+    cursors_and_labels: CursorsAndLabels,
+}
+
+impl UndoNode {
+    fn new(parent: Option<usize>, edits: Vec<TextEdit>) -> Self {
+        UndoNode {
+            parent,
+            children: Vec::new(),
+            redo_child: None,
+            created: SystemTime::now(),
+            edits,
+            checkpoint: None,
+            cursors_and_labels: CursorsAndLabels::default(),
+        }
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    pub fn redo_child(&self) -> Option<usize> {
+        self.redo_child
+    }
+
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    pub fn edits(&self) -> &[TextEdit] {
+        &self.edits
+    }
+
+    pub fn is_checkpoint(&self) -> bool {
+        self.checkpoint.is_some()
+    }
+
+    fn size_bytes(&self) -> usize {
+        NODE_OVERHEAD_BYTES + self.edits.iter().map(|edit| edit.size_bytes()).sum::<usize>()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UndoTree {
+    nodes: BTreeMap<usize, UndoNode>,
+    next_id: usize,
+    current: usize,
+
+    live: ContentsAndCursors,
+    // text of current node when it became current. If it's edited in place, its children are rebased on it.
+    arrival_rope: Rope,
+    current_modified: bool,
+
+    limits: HistoryLimits,
+}
+
+impl UndoTree {
+    pub fn new(root: ContentsAndCursors) -> Self {
+        let mut node = UndoNode::new(None, Vec::new());
+        node.checkpoint = Some(root.rope().clone());
+
+        let mut nodes = BTreeMap::new();
+        nodes.insert(0, node);
+
+        UndoTree {
+            nodes,
+            next_id: 1,
+            current: 0,
+            arrival_rope: root.rope().clone(),
+            live: root,
+            current_modified: false,
+            limits: HistoryLimits::UNLIMITED,
+        }
+    }
+
+    /*
+    Builds a tree without branches, every state being a child of the previous one. Current is the last
+    one, ids are positions in chain. Panics on empty input.
+     */
+    pub fn from_chain(chain: Vec<ContentsAndCursors>) -> Self {
+        let mut iter = chain.into_iter();
+        let mut tree = UndoTree::new(iter.next().unwrap());
+
+        for contents in iter {
+            tree.push_child();
+            for edit in TextEdit::diff(tree.live.rope(), contents.rope()) {
+                tree.record(edit);
+            }
+            tree.live = contents;
+        }
+
+        tree
+    }
+
+    pub fn with_limits(self, limits: HistoryLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    pub fn limits(&self) -> HistoryLimits {
+        self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: HistoryLimits) {
+        self.limits = limits;
+        self.enforce_limits();
+    }
+
+    pub fn nodes_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // no node has id equal or greater than this
+    pub fn next_id(&self) -> usize {
+        self.next_id
+    }
+
+    pub fn current_id(&self) -> usize {
+        self.current
+    }
+
+    pub fn current(&self) -> &ContentsAndCursors {
+        &self.live
+    }
+
+    /*
+    Changes of text done through this have to be reported with record, otherwise history gets out
+    of sync.
+     */
+    pub fn current_mut(&mut self) -> &mut ContentsAndCursors {
+        &mut self.live
+    }
+
+    pub fn node(&self, id: usize) -> Option<&UndoNode> {
+        self.nodes.get(&id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes.keys().copied()
+    }
+
+    // Text of given state. Costs at most CHECKPOINT_INTERVAL nodes worth of edits.
+    pub fn rope_at(&self, id: usize) -> Option<Rope> {
+        let mut path: Vec<usize> = Vec::new();
+        let mut node_id = id;
+
+        let mut rope = loop {
+            if node_id == self.current {
+                // children of current node are not rebased until it's left, see rebase_children
+                break if path.is_empty() {
+                    self.live.rope().clone()
+                } else {
+                    self.arrival_rope.clone()
+                };
+            }
+
+            let node = self.nodes.get(&node_id)?;
+            if let Some(checkpoint) = node.checkpoint.as_ref() {
+                break checkpoint.clone();
+            }
+
+            path.push(node_id);
+            node_id = node.parent?;
+        };
+
+        for node_id in path.iter().rev() {
+            for edit in self.nodes[node_id].edits.iter() {
+                if !edit.apply(&mut rope) {
+                    return None;
+                }
+            }
+        }
+
+        Some(rope)
+    }
+
+    pub fn cursors_and_labels_at(&self, id: usize) -> Option<CursorsAndLabels> {
+        if id == self.current {
+            Some(self.live.cursors_and_labels())
+        } else {
+            self.nodes.get(&id).map(|node| node.cursors_and_labels.clone())
+        }
+    }
+
+    pub fn add_cursor_set_at(&mut self, id: usize, widget_id: WID, cs: CursorSet) -> bool {
+        if id == self.current {
+            self.live.add_cursor_set(widget_id, cs)
+        } else {
+            match self.nodes.get_mut(&id) {
+                Some(node) => node.cursors_and_labels.add_cursor_set(widget_id, cs),
+                None => false,
+            }
+        }
+    }
+
+    pub fn remove_cursor_sets(&mut self, widget_id: WID) {
+        self.live.remove_cursor_set(widget_id);
+        for node in self.nodes.values_mut() {
+            node.cursors_and_labels.remove_cursor_set(widget_id);
+        }
+    }
+
+    // Whether current state has the same text as its parent. False in root.
+    pub fn current_equals_parent(&self) -> bool {
+        let node = &self.nodes[&self.current];
+        if node.parent.is_none() {
+            return false;
+        }
+
+        if node.edits.is_empty() {
+            return true;
+        }
+
+        match self.parent_rope_of_current() {
+            Some(parent_rope) => parent_rope.len_chars() == self.live.rope().len_chars() && parent_rope == *self.live.rope(),
+            None => false,
+        }
+    }
+
+    fn parent_rope_of_current(&self) -> Option<Rope> {
+        let mut rope = self.live.rope().clone();
+        for edit in self.nodes[&self.current].edits.iter().rev() {
+            if !edit.inverse().apply(&mut rope) {
+                return None;
+            }
+        }
+
+        Some(rope)
+    }
+
+    /*
+    Reports a change already applied to current text. Current state's parent stays the same, so it's
+    the edits from parent that grow.
+     */
+    pub fn record(&mut self, edit: TextEdit) {
+        let node = self.nodes.get_mut(&self.current).unwrap();
+        if node.parent.is_some() {
+            node.edits.push(edit);
+        }
+
+        self.current_modified = true;
+    }
+
+    // Applies edit to current text and records it.
+    pub fn apply_edit(&mut self, edit: TextEdit) -> bool {
+        if !edit.apply(self.live.rope_mut()) {
+            return false;
+        }
+
+        self.record(edit);
+        true
+    }
+
+    /*
+    Adds a new child of current node, with the same text, and makes it current. Other children of
+    current node (if any) become abandoned branches. Oldest states may be forgotten, if history grows
+    over its limits.
+     */
+    pub fn push_child(&mut self) -> usize {
+        self.leave_current();
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.nodes.insert(id, UndoNode::new(Some(self.current), Vec::new()));
+
+        let parent = self.nodes.get_mut(&self.current).unwrap();
+        parent.children.push(id);
+        parent.redo_child = Some(id);
+
+        self.current = id;
+        self.arrival_rope = self.live.rope().clone();
+        self.current_modified = false;
+
+        self.enforce_limits();
+        id
+    }
+
+    /*
+    Removes current node, provided it's the newest one and has no children. Parent becomes current,
+    along with its text. Returns false if that's not the case.
+     */
+    pub fn pop_current(&mut self) -> bool {
+        if !self.is_current_removable() {
+            return false;
+        }
+
+        let removed = self.current;
+        let parent_rope = unpack_or_e!(self.parent_rope_of_current(), false, "failed to recover parent of {}", removed);
+        let node = self.nodes.remove(&removed).unwrap();
+        let parent_id = node.parent.unwrap();
+
+        let parent = self.nodes.get_mut(&parent_id).unwrap();
+        parent.children.retain(|child| *child != removed);
+        parent.redo_child = parent.children.last().copied();
+
+        *self.live.rope_mut() = parent_rope;
+        self.arrive(parent_id);
+        true
+    }
+
+    /*
+    Replaces contents of current node's parent with contents of current node, and removes the latter.
+    This "merges" the last step into the previous one. Same conditions as in pop_current apply.
+     */
+    pub fn merge_current_into_parent(&mut self) -> bool {
+        if !self.is_current_removable() {
+            return false;
+        }
+
+        let merged = self.current;
+        let parent_rope = unpack_or_e!(self.parent_rope_of_current(), false, "failed to recover parent of {}", merged);
+        let node = self.nodes.remove(&merged).unwrap();
+        let parent_id = node.parent.unwrap();
+
+        let parent = self.nodes.get_mut(&parent_id).unwrap();
+        parent.children.retain(|child| *child != merged);
+        parent.redo_child = parent.children.last().copied();
+        if parent.parent.is_some() {
+            parent.edits.extend(node.edits);
+        }
+
+        // live contents stay, it's the parent that changed
+        self.current = parent_id;
+        self.arrival_rope = parent_rope;
+        self.current_modified = true;
+        true
+    }
+
+    fn is_current_removable(&self) -> bool {
+        let node = &self.nodes[&self.current];
+        self.nodes.keys().next_back() == Some(&self.current) && node.children.is_empty() && node.parent.is_some()
+    }
+
+    /*
+    Drops all nodes but current, which becomes the root.
+     */
+    pub fn truncate_to_current(&mut self) {
+        let mut node = self.nodes.remove(&self.current).unwrap();
+        node.parent = None;
+        node.children.clear();
+        node.redo_child = None;
+        node.edits.clear();
+        node.checkpoint = Some(self.live.rope().clone());
+
+        self.nodes.clear();
+        self.nodes.insert(self.current, node);
+        self.arrival_rope = self.live.rope().clone();
+        self.current_modified = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.nodes[&self.current].parent.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.nodes[&self.current].redo_child.is_some()
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let from = self.current;
+        let parent = match self.nodes[&from].parent {
+            Some(parent) => parent,
+            None => return false,
+        };
+
+        self.leave_current();
+        self.walk_up(from);
+        self.nodes.get_mut(&parent).unwrap().redo_child = Some(from);
+        self.arrive(parent);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let child = match self.nodes[&self.current].redo_child {
+            Some(child) => child,
+            None => return false,
+        };
+
+        self.leave_current();
+        self.walk_down(child);
+        self.arrive(child);
+        true
+    }
+
+    /*
+    Makes any node current. Redo pointers along the way from root are updated, so undo followed by redo
+    brings you back here.
+     */
+    pub fn jump_to(&mut self, id: usize) -> bool {
+        if !self.nodes.contains_key(&id) {
+            return false;
+        }
+
+        self.leave_current();
+
+        // up to the closest common ancestor, then down to target
+        let target_path = self.path_from_root(id);
+        let on_target_path: HashSet<usize> = target_path.iter().copied().collect();
+
+        let mut node_id = self.current;
+        while !on_target_path.contains(&node_id) {
+            self.walk_up(node_id);
+            node_id = self.nodes[&node_id].parent.unwrap();
+        }
+
+        let common_idx = target_path.iter().position(|item| *item == node_id).unwrap();
+        for child in target_path[common_idx + 1..].iter() {
+            self.walk_down(*child);
+        }
+
+        for pair in target_path.windows(2) {
+            self.nodes.get_mut(&pair[0]).unwrap().redo_child = Some(pair[1]);
+        }
+
+        self.arrive(id);
+        true
+    }
+
+    // Ids of nodes from root to given one (inclusive).
+    pub fn path_from_root(&self, id: usize) -> Vec<usize> {
+        let mut result = vec![id];
+        let mut node = id;
+        while let Some(parent) = self.nodes[&node].parent {
+            result.push(parent);
+            node = parent;
+        }
+
+        result.reverse();
+        result
+    }
+
+    // Ids of nodes reachable with consecutive redos from given one (exclusive).
+    pub fn redo_chain(&self, id: usize) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut node = id;
+        while let Some(child) = self.nodes[&node].redo_child {
+            result.push(child);
+            node = child;
+        }
+
+        result
+    }
+
+    // Estimate of memory taken by history, live contents aside.
+    pub fn size_bytes(&self) -> usize {
+        self.nodes.values().map(|node| node.size_bytes()).sum()
+    }
+
+    // turns live text of node "from" into text of its parent
+    fn walk_up(&mut self, from: usize) {
+        for edit in self.nodes[&from].edits.iter().rev() {
+            edit.inverse().apply(self.live.rope_mut());
+        }
+    }
+
+    // turns live text of parent of node "to" into text of "to"
+    fn walk_down(&mut self, to: usize) {
+        for edit in self.nodes[&to].edits.iter() {
+            edit.apply(self.live.rope_mut());
+        }
+    }
+
+    /*
+    Stores what's needed to get back to current state later. Has to be called before current changes
+    (live text may be already changed, as long as it's not node-related).
+     */
+    fn leave_current(&mut self) {
+        let current = self.current;
+
+        if self.current_modified {
+            self.rebase_children(current);
+        }
+
+        let needs_checkpoint = {
+            let node = &self.nodes[&current];
+            node.parent.is_none() || node.checkpoint.is_some() || self.distance_to_checkpoint(current) >= CHECKPOINT_INTERVAL
+        };
+
+        let cursors_and_labels = self.live.cursors_and_labels();
+        let node = self.nodes.get_mut(&current).unwrap();
+        node.cursors_and_labels = cursors_and_labels;
+        if needs_checkpoint {
+            node.checkpoint = Some(self.live.rope().clone());
+        }
+
+        self.current_modified = false;
+    }
+
+    // Edits of children of a node are relative to its text, so if it changed in place they have to be redone.
+    fn rebase_children(&mut self, id: usize) {
+        let children = self.nodes[&id].children.clone();
+
+        for child in children {
+            let mut child_rope = self.arrival_rope.clone();
+            for edit in self.nodes[&child].edits.iter() {
+                edit.apply(&mut child_rope);
+            }
+
+            debug!("rebasing history node {} on edited parent {}", child, id);
+            let edits = TextEdit::diff(self.live.rope(), &child_rope);
+            self.nodes.get_mut(&child).unwrap().edits = edits;
+        }
+    }
+
+    fn arrive(&mut self, id: usize) {
+        self.current = id;
+
+        let cursors_and_labels = self.nodes[&id].cursors_and_labels.clone();
+        self.live.set_cursors_and_labels(cursors_and_labels);
+
+        self.arrival_rope = self.live.rope().clone();
+        self.current_modified = false;
+    }
+
+    // number of steps up to the closest ancestor with full text
+    fn distance_to_checkpoint(&self, id: usize) -> usize {
+        let mut distance = 0;
+        let mut node = &self.nodes[&id];
+        while let Some(parent) = node.parent {
+            distance += 1;
+            node = &self.nodes[&parent];
+            if node.checkpoint.is_some() {
+                break;
+            }
+        }
+
+        distance
+    }
+
+    /*
+    Forgets oldest states until history fits its limits. Current state and the ones leading to it
+    from root are kept as long as possible: abandoned branches go leaf by leaf, root goes when it has a
+    single child (which becomes the new root).
+     */
+    fn enforce_limits(&mut self) {
+        let limits = self.limits;
+        let mut size = if limits.max_bytes == usize::MAX { 0 } else { self.size_bytes() };
+
+        while self.nodes.len() > limits.max_states.max(1) || size > limits.max_bytes {
+            let main_line: HashSet<usize> = self.path_from_root(self.current).into_iter().collect();
+            let root = *self.nodes.keys().next().unwrap();
+
+            let abandoned_leaf = self
+                .nodes
+                .iter()
+                .find(|(id, node)| node.children.is_empty() && !main_line.contains(*id))
+                .map(|(id, _)| *id);
+
+            let victim = abandoned_leaf.or_else(|| {
+                let root_node = &self.nodes[&root];
+                (root != self.current && root_node.children.len() == 1).then_some(root)
+            });
+
+            let victim = match victim {
+                Some(victim) => victim,
+                None => break,
+            };
+
+            size = size.saturating_sub(self.nodes[&victim].size_bytes());
+            self.forget(victim, root);
+        }
+    }
+
+    fn forget(&mut self, victim: usize, root: usize) {
+        if victim == root {
+            let child = self.nodes[&victim].children[0];
+            let child_rope = self.rope_at(child);
+
+            self.nodes.remove(&victim);
+
+            let child_node = self.nodes.get_mut(&child).unwrap();
+            child_node.parent = None;
+            child_node.edits.clear();
+            child_node.checkpoint = child_rope;
+        } else {
+            let node = self.nodes.remove(&victim).unwrap();
+            let parent = self.nodes.get_mut(&node.parent.unwrap()).unwrap();
+            parent.children.retain(|item| *item != victim);
+            if parent.redo_child == Some(victim) {
+                parent.redo_child = parent.children.last().copied();
+            }
+        }
+    }
+
+    pub fn check_invariant(&self) -> bool {
+        if !self.nodes.contains_key(&self.current) {
+            return false;
+        }
+
+        let root = match self.nodes.keys().next() {
+            Some(root) => *root,
+            None => return false,
+        };
+
+        if self.next_id <= *self.nodes.keys().next_back().unwrap() {
+            return false;
+        }
+
+        for (id, node) in self.nodes.iter() {
+            match node.parent {
+                Some(parent) => {
+                    if parent >= *id || !self.nodes.get(&parent).map(|p| p.children.contains(id)).unwrap_or(false) {
+                        return false;
+                    }
+                }
+                None => {
+                    if *id != root || node.checkpoint.is_none() || !node.edits.is_empty() {
+                        return false;
+                    }
+                }
+            }
+
+            if let Some(redo_child) = node.redo_child {
+                if !node.children.contains(&redo_child) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use crate::text::contents_and_cursors::ContentsAndCursors;
+    use crate::text::undo_tree::{HistoryLimits, TextEdit, UndoTree, CHECKPOINT_INTERVAL};
+
+    fn contents(s: &str) -> ContentsAndCursors {
+        ContentsAndCursors::empty().with_rope(Rope::from_str(s))
+    }
+
+    fn current_text(tree: &UndoTree) -> String {
+        tree.current().rope().to_string()
+    }
+
+    fn set_text(tree: &mut UndoTree, s: &str) {
+        for edit in TextEdit::diff(tree.current().rope(), &Rope::from_str(s)) {
+            assert!(tree.apply_edit(edit));
+        }
+    }
+
+    fn push(tree: &mut UndoTree, s: &str) -> usize {
+        let id = tree.push_child();
+        set_text(tree, s);
+        id
+    }
+
+    #[test]
+    fn undo_tree_keeps_abandoned_branch_test() {
+        let mut tree = UndoTree::new(contents("a"));
+        push(&mut tree, "ab");
+        push(&mut tree, "abc");
+
+        assert!(tree.undo());
+        assert!(tree.undo());
+        assert_eq!(current_text(&tree), "a");
+
+        // typing after undo starts a new branch
+        let branch = push(&mut tree, "ax");
+        assert_eq!(tree.node(0).unwrap().children(), &[1, branch]);
+        assert!(!tree.can_redo());
+
+        assert!(tree.undo());
+        // redo follows the branch we came back from
+        assert!(tree.redo());
+        assert_eq!(current_text(&tree), "ax");
+
+        // but the old one is still there
+        assert!(tree.jump_to(2));
+        assert_eq!(current_text(&tree), "abc");
+        assert!(tree.undo());
+        assert!(tree.undo());
+        assert!(tree.redo());
+        assert!(tree.redo());
+        assert_eq!(current_text(&tree), "abc");
+
+        assert!(tree.check_invariant());
+    }
+
+    #[test]
+    fn undo_tree_pop_and_merge_test() {
+        let mut tree = UndoTree::new(contents("a"));
+        push(&mut tree, "ab");
+        push(&mut tree, "abc");
+
+        assert!(tree.merge_current_into_parent());
+        assert_eq!(tree.nodes_count(), 2);
+        assert_eq!(current_text(&tree), "abc");
+        assert!(tree.undo());
+        assert_eq!(current_text(&tree), "a");
+
+        // not the newest node
+        assert!(!tree.pop_current());
+
+        assert!(tree.redo());
+        assert!(tree.pop_current());
+        assert_eq!(current_text(&tree), "a");
+        assert!(!tree.can_redo());
+
+        // popping brings back text of parent
+        push(&mut tree, "xyz");
+        assert!(tree.pop_current());
+        assert_eq!(current_text(&tree), "a");
+
+        assert!(tree.check_invariant());
+    }
+
+    #[test]
+    fn undo_tree_paths_test() {
+        let mut tree = UndoTree::from_chain(vec![contents("a"), contents("ab"), contents("abc")]);
+        assert_eq!(tree.current_id(), 2);
+
+        assert!(tree.jump_to(0));
+        push(&mut tree, "x");
+        assert!(tree.jump_to(1));
+
+        assert_eq!(tree.path_from_root(2), vec![0, 1, 2]);
+        assert_eq!(tree.redo_chain(0), vec![1, 2]);
+
+        tree.truncate_to_current();
+        assert_eq!(tree.nodes_count(), 1);
+        assert_eq!(current_text(&tree), "ab");
+        assert!(!tree.can_undo());
+        assert!(tree.check_invariant());
+    }
+
+    #[test]
+    fn undo_tree_reconstructs_from_checkpoints_test() {
+        let mut tree = UndoTree::new(contents(""));
+        let mut expected = vec![String::new()];
+
+        for idx in 0..(CHECKPOINT_INTERVAL * 3) {
+            let text = format!("{}{}\n", expected.last().unwrap(), idx);
+            push(&mut tree, &text);
+            expected.push(text);
+        }
+
+        let checkpoints = tree.ids().filter(|id| tree.node(*id).unwrap().is_checkpoint()).count();
+        assert_eq!(checkpoints, 3);
+
+        for (id, text) in expected.iter().enumerate() {
+            assert_eq!(tree.rope_at(id).unwrap().to_string(), *text);
+        }
+
+        assert!(tree.jump_to(CHECKPOINT_INTERVAL + 1));
+        assert_eq!(current_text(&tree), expected[CHECKPOINT_INTERVAL + 1]);
+        assert!(tree.check_invariant());
+    }
+
+    #[test]
+    fn undo_tree_edit_in_place_rebases_children_test() {
+        let mut tree = UndoTree::new(contents("a"));
+        push(&mut tree, "ab");
+        assert!(tree.undo());
+
+        // edited without a milestone, so child has to be recomputed against new text
+        set_text(&mut tree, "_a");
+        assert_eq!(tree.rope_at(1).unwrap().to_string(), "ab");
+        assert!(tree.redo());
+        assert_eq!(current_text(&tree), "ab");
+        assert!(tree.undo());
+        assert_eq!(current_text(&tree), "_a");
+
+        assert!(tree.check_invariant());
+    }
+
+    #[test]
+    fn undo_tree_limits_test() {
+        let mut tree = UndoTree::new(contents("")).with_limits(HistoryLimits::new(4, usize::MAX));
+        push(&mut tree, "a");
+        push(&mut tree, "ab");
+        assert!(tree.undo());
+        push(&mut tree, "ax");
+
+        // abandoned branch goes first
+        push(&mut tree, "axy");
+        assert_eq!(tree.nodes_count(), 4);
+        assert!(tree.node(2).is_none());
+        assert!(tree.node(0).is_some());
+
+        // then the oldest states
+        push(&mut tree, "axyz");
+        assert_eq!(tree.nodes_count(), 4);
+        assert!(tree.node(0).is_none());
+        assert_eq!(tree.rope_at(1).unwrap().to_string(), "a");
+
+        let mut undos = 0;
+        while tree.undo() {
+            undos += 1;
+        }
+        assert_eq!(undos, 3);
+        assert_eq!(current_text(&tree), "a");
+        assert!(tree.check_invariant());
+
+        // memory limit works the same way, keeping at least current state
+        tree.set_limits(HistoryLimits::new(usize::MAX, 1));
+        assert_eq!(tree.nodes_count(), 1);
+        assert_eq!(current_text(&tree), "a");
+        assert!(tree.check_invariant());
+    }
+
+    #[test]
+    fn text_edit_diff_test() {
+        let from = Rope::from_str("abcdef");
+        let to = Rope::from_str("abXYef");
+
+        let edits = TextEdit::diff(&from, &to);
+        let mut rope = from.clone();
+        for edit in edits.iter() {
+            assert!(edit.apply(&mut rope));
+        }
+        assert_eq!(rope, to);
+
+        for edit in edits.iter().rev() {
+            assert!(edit.inverse().apply(&mut rope));
+        }
+        assert_eq!(rope, from);
+
+        assert!(TextEdit::diff(&from, &from).is_empty());
+        // overlapping prefix and suffix
+        assert_eq!(TextEdit::diff(&Rope::from_str("aa"), &Rope::from_str("aaa")).len(), 1);
+    }
+}
@@ -15,11 +15,13 @@ use tree_sitter_loader::{CompileConfig, Config};
 // #[allow(unused_imports)]
 // use tree_sitter_cpp::*;
 
+use crate::primitives::stupid_cursor::StupidCursor;
 use crate::tsw::lang_id::LangId;
 use crate::tsw::language_set::LanguageSet;
 use crate::tsw::parsing_tuple::ParsingTuple;
 use crate::tsw::rope_wrappers::RopeWrapper;
 use crate::unpack_or_e;
+use crate::w7e::navcomp_provider::{NavCompDocumentSymbol, SymbolType};
 
 static EMPTY_SLICE: [u8; 0] = [0; 0];
 
@@ -284,6 +286,8 @@ pub struct HighlightItem {
     pub char_begin: usize,
     pub char_end: usize,
     pub identifier: Arc<String>,
+    // semantic token modifiers like "mutable", always empty for tree-sitter items.
+    pub modifiers: Vec<Arc<String>>,
 }
 
 impl ParsingTuple {
@@ -325,6 +329,7 @@ impl ParsingTuple {
                     char_begin: begin_char,
                     char_end: end_char,
                     identifier: name.clone(),
+                    modifiers: vec![],
                 })
             }
         }
@@ -334,6 +339,41 @@ impl ParsingTuple {
         Some(results)
     }
 
+    /*
+    Poor man's outline, used when there is no LSP around. I don't have "tags" queries for all the
+    languages, so I walk the tree instead, treating any "*_item", "*_definition" or "*_declaration"
+    node with a name as a symbol. Works well enough for Rust, Python, Go, Java and JS/TS.
+     */
+    pub fn document_symbols(&self, rope: &ropey::Rope) -> Option<Vec<NavCompDocumentSymbol>> {
+        let tree = self.tree.as_ref()?;
+        let mut result: Vec<NavCompDocumentSymbol> = Vec::new();
+        collect_document_symbols(tree.root_node(), rope, &mut result);
+        Some(result)
+    }
+
+    /*
+    Poor man's document highlight, used when there is no LSP around: all identifiers spelled the
+    same as the one at char_idx. Scopes are not understood, so shadowed variables light up too.
+    Returns char ranges, empty if there's no identifier at char_idx.
+     */
+    pub fn identifier_occurrences(&self, rope: &ropey::Rope, char_idx: usize) -> Option<Vec<Range<usize>>> {
+        let tree = self.tree.as_ref()?;
+        let byte_idx = rope.try_char_to_byte(char_idx).ok()?;
+
+        // cursor right after the identifier counts as well
+        let node = [Some(byte_idx), byte_idx.checked_sub(1)]
+            .into_iter()
+            .flatten()
+            .filter_map(|idx| tree.root_node().descendant_for_byte_range(idx, idx))
+            .find(|node| is_identifier_kind(node.kind()))?;
+
+        let name = rope.get_byte_slice(node.start_byte()..node.end_byte())?.to_string();
+
+        let mut result: Vec<Range<usize>> = Vec::new();
+        collect_identifier_occurrences(tree.root_node(), rope, &name, &mut result);
+        Some(result)
+    }
+
     pub fn try_reparse(&mut self, rope: &ropey::Rope) -> bool {
         let mut callback = pack_rope_with_callback(rope);
         let mut parser = unpack_or_e!(self.parser.try_write().ok(), false, "failed to lock parser");
@@ -453,3 +493,108 @@ impl ParsingTuple {
         self.try_reparse(rope)
     }
 }
+
+fn node_kind_to_symbol_type(kind: &str) -> Option<SymbolType> {
+    if !(kind.ends_with("_item") || kind.ends_with("_definition") || kind.ends_with("_declaration")) {
+        return None;
+    }
+
+    let res = if kind.starts_with("function") {
+        SymbolType::Function
+    } else if kind.starts_with("method") {
+        SymbolType::Method
+    } else if kind.starts_with("struct") {
+        SymbolType::Struct
+    } else if kind.starts_with("class") {
+        SymbolType::Class
+    } else if kind.starts_with("enum") {
+        SymbolType::Enum
+    } else if kind.starts_with("trait") || kind.starts_with("interface") {
+        SymbolType::Interface
+    } else if kind.starts_with("mod") || kind.starts_with("namespace") {
+        SymbolType::Module
+    } else if kind.starts_with("const") || kind.starts_with("static") {
+        SymbolType::Constant
+    } else if kind.starts_with("impl") {
+        SymbolType::Object
+    } else {
+        SymbolType::Unmapped(kind.to_string())
+    };
+
+    Some(res)
+}
+
+fn byte_to_stupid_cursor(rope: &ropey::Rope, byte_idx: usize) -> Option<StupidCursor> {
+    let char_idx = rope.try_byte_to_char(byte_idx).ok()?;
+    StupidCursor::from_char_idx_utf16(rope, char_idx)
+}
+
+// identifier, field_identifier, type_identifier, property_identifier and so on.
+fn is_identifier_kind(kind: &str) -> bool {
+    kind.ends_with("identifier")
+}
+
+fn collect_identifier_occurrences(node: tree_sitter::Node, rope: &ropey::Rope, name: &str, result: &mut Vec<Range<usize>>) {
+    if is_identifier_kind(node.kind()) && node.end_byte() - node.start_byte() == name.len() {
+        let same_name = rope
+            .get_byte_slice(node.start_byte()..node.end_byte())
+            .map(|slice| slice == name)
+            .unwrap_or(false);
+
+        if same_name {
+            if let (Ok(begin), Ok(end)) = (rope.try_byte_to_char(node.start_byte()), rope.try_byte_to_char(node.end_byte())) {
+                result.push(begin..end);
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_identifier_occurrences(child, rope, name, result);
+    }
+}
+
+fn collect_document_symbols(node: tree_sitter::Node, rope: &ropey::Rope, result: &mut Vec<NavCompDocumentSymbol>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        // impl blocks have no name, but they do have a type
+        let name_node_op = child.child_by_field_name("name").or_else(|| child.child_by_field_name("type"));
+
+        let symbol_op = match (node_kind_to_symbol_type(child.kind()), name_node_op) {
+            (Some(symbol_type), Some(name_node)) => {
+                let name = rope
+                    .get_byte_slice(name_node.start_byte()..name_node.end_byte())
+                    .map(|slice| slice.to_string());
+
+                match (
+                    name,
+                    byte_to_stupid_cursor(rope, child.start_byte()),
+                    byte_to_stupid_cursor(rope, child.end_byte()),
+                    byte_to_stupid_cursor(rope, name_node.start_byte()),
+                    byte_to_stupid_cursor(rope, name_node.end_byte()),
+                ) {
+                    (Some(name), Some(begin), Some(end), Some(name_begin), Some(name_end)) => Some(NavCompDocumentSymbol {
+                        name,
+                        symbol_type,
+                        stupid_range: (begin, end),
+                        name_stupid_range: (name_begin, name_end),
+                        children: vec![],
+                    }),
+                    _ => {
+                        warn!("failed to convert positions of node {}", child.kind());
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        match symbol_op {
+            Some(mut symbol) => {
+                collect_document_symbols(child, rope, &mut symbol.children);
+                result.push(symbol);
+            }
+            None => collect_document_symbols(child, rope, result),
+        }
+    }
+}
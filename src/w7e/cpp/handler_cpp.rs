@@ -5,6 +5,7 @@ use crate::fs::path::SPath;
 use crate::tsw::lang_id::LangId;
 use crate::w7e::handler::{Handler, NavCompRef};
 use crate::w7e::handler_load_error::HandlerLoadError;
+use crate::w7e::task::Task;
 
 pub const INIT_TIMEOUT: Duration = Duration::from_millis(2000);
 
@@ -19,7 +20,7 @@ impl Handler for CppHandler {
         LangId::CPP
     }
 
-    fn handler_id(&self) -> &'static str {
+    fn handler_id(&self) -> &str {
         "cpp"
     }
 
@@ -30,6 +31,16 @@ impl Handler for CppHandler {
     fn navcomp(&self) -> Option<NavCompRef> {
         self.navcomp.clone()
     }
+
+    // compile_commands.json says nothing about how to build, so I only guess make.
+    fn tasks(&self) -> Vec<Task> {
+        let has_makefile = self.root.descendant_checked("Makefile").map(|f| f.is_file()).unwrap_or(false);
+        if has_makefile {
+            vec![Task::new("make", &[], self.root.clone())]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 /*
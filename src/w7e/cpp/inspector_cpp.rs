@@ -9,6 +9,10 @@ impl LangInspector for CppLangInspector {
         LangId::CPP
     }
 
+    fn handler_id(&self) -> &'static str {
+        "cpp"
+    }
+
     fn is_project_dir(&self, ff: &SPath) -> bool {
         ff.is_dir()
             && ff
@@ -0,0 +1,58 @@
+use crate::config::config::ConfigRef;
+use crate::config::custom_lsp_config::CustomLspConfig;
+use crate::fs::path::SPath;
+use crate::tsw::lang_id::LangId;
+use crate::w7e::handler::{Handler, NavCompRef};
+use crate::w7e::handler_load_error::HandlerLoadError;
+
+/*
+Handler of language servers declared by user (see CustomLspConfig). It knows nothing about project
+structure, it just serves whatever directory it was pointed at.
+ */
+pub struct GenericHandler {
+    root: SPath,
+    custom_lsp: CustomLspConfig,
+
+    navcomp: Option<NavCompRef>,
+}
+
+impl Handler for GenericHandler {
+    fn lang_id(&self) -> LangId {
+        self.custom_lsp.lang_id
+    }
+
+    fn handler_id(&self) -> &str {
+        &self.custom_lsp.name
+    }
+
+    fn project_name(&self) -> &str {
+        self.root.last_file_name().and_then(|f| f.to_str()).unwrap_or("todo")
+    }
+
+    fn navcomp(&self) -> Option<NavCompRef> {
+        self.navcomp.clone()
+    }
+
+    fn custom_extensions(&self) -> Vec<String> {
+        self.custom_lsp.extensions.clone()
+    }
+}
+
+impl GenericHandler {
+    pub fn load(
+        _config: &ConfigRef,
+        ff: SPath,
+        custom_lsp: CustomLspConfig,
+        navcomp_op: Option<NavCompRef>,
+    ) -> Result<GenericHandler, HandlerLoadError> {
+        if !ff.is_dir() {
+            return Err(HandlerLoadError::NotAProject);
+        }
+
+        Ok(GenericHandler {
+            root: ff,
+            custom_lsp,
+            navcomp: navcomp_op,
+        })
+    }
+}
@@ -0,0 +1,2 @@
+pub mod handler_generic;
+mod workspace_test;
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::{Arc, RwLock};
+
+    use crate::config::config::Config;
+    use crate::config::custom_lsp_config::CustomLspConfig;
+    use crate::experiments::buffer_register::BufferRegister;
+    use crate::experiments::filename_to_language::filename_to_language;
+    use crate::experiments::pretty_ron::ToPrettyRonString;
+    use crate::fs::filesystem_front::FilesystemFront;
+    use crate::fs::mock_fs::MockFS;
+    use crate::spath;
+    use crate::tsw::lang_id::LangId;
+    use crate::w7e::handler_factory::handler_factory;
+    use crate::w7e::handler_load_error::HandlerLoadError;
+    use crate::w7e::navcomp_group::NavCompGroup;
+    use crate::w7e::workspace::SerializableWorkspace;
+
+    fn typescript_lsp() -> CustomLspConfig {
+        CustomLspConfig {
+            name: "typescript".to_string(),
+            lang_id: LangId::TYPESCRIPT,
+            // does not exist, so handler loads without navcomp
+            command: PathBuf::from("/nonexistent/typescript-language-server"),
+            args: vec!["--stdio".to_string()],
+            env: Default::default(),
+            initialization_options: None,
+            extensions: vec!["ts".to_string(), "tsx".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_read_workspace_with_custom_lsp() {
+        let workspace = r#"(
+    scopes: [
+        (
+            lang_id: TYPESCRIPT,
+            path: "ts_project",
+            handler_id_op: Some("typescript"),
+            custom_lsp: Some((
+                name: "typescript",
+                lang_id: TYPESCRIPT,
+                command: "typescript-language-server",
+                args: ["--stdio"],
+                initialization_options: Some({
+                    "preferences": {
+                        "importModuleSpecifierPreference": "relative",
+                    },
+                }),
+                extensions: ["ts", "tsx"],
+            )),
+        ),
+    ],
+)
+        "#;
+
+        let workspace_pill = ron::from_str::<SerializableWorkspace>(workspace).unwrap();
+
+        let custom_lsp = workspace_pill.scopes[0].custom_lsp.as_ref().unwrap();
+        assert_eq!(custom_lsp.name, "typescript");
+        assert_eq!(custom_lsp.command, PathBuf::from("typescript-language-server"));
+        assert_eq!(custom_lsp.extensions, vec!["ts".to_string(), "tsx".to_string()]);
+        assert_eq!(
+            custom_lsp.initialization_options,
+            Some(serde_json::json!({
+                "preferences": { "importModuleSpecifierPreference": "relative" }
+            }))
+        );
+
+        let written = workspace_pill.to_pretty_ron_string().unwrap();
+        let reread = ron::from_str::<SerializableWorkspace>(&written).unwrap();
+        assert_eq!(reread.scopes[0].custom_lsp, workspace_pill.scopes[0].custom_lsp);
+    }
+
+    #[test]
+    fn test_handler_factory_custom_lsp_from_config() {
+        let mock_fs = MockFS::new("/tmp").with_file("ts_project/index.tsx", "").to_fsf();
+        let ff = spath!(mock_fs, "ts_project").unwrap();
+
+        let mut config = Config::default();
+        config.global.custom_lsps.push(typescript_lsp());
+        let config = Arc::new(config);
+
+        let buffer_register = Arc::new(RwLock::new(BufferRegister::new()));
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+
+        let handler = handler_factory(&config, &buffer_register, "typescript", ff.clone(), None, None, sender.clone()).unwrap();
+
+        assert_eq!(handler.handler_id(), "typescript");
+        assert_eq!(handler.lang_id(), LangId::TYPESCRIPT);
+        assert!(handler.navcomp().is_none());
+
+        // extensions declared by custom lsp are routed to its language, but only in navcomp group that knows about them
+        let tsx_file = spath!(mock_fs, "ts_project", "index.tsx").unwrap();
+        assert_eq!(filename_to_language(&tsx_file), None);
+
+        let mut navcomp_group = NavCompGroup::new();
+        navcomp_group.add_custom_extensions(handler.lang_id(), &handler.custom_extensions());
+        assert_eq!(navcomp_group.lang_for(&tsx_file), Some(LangId::TYPESCRIPT));
+
+        assert_eq!(
+            handler_factory(&config, &buffer_register, "jdtls", ff, None, None, sender).err(),
+            Some(HandlerLoadError::HandlerNotFound)
+        );
+    }
+
+    #[test]
+    fn test_handler_factory_custom_lsp_from_scope() {
+        let mock_fs = MockFS::new("/tmp").with_dir("ts_project").to_fsf();
+        let ff = spath!(mock_fs, "ts_project").unwrap();
+
+        let config = Arc::new(Config::default());
+        let buffer_register = Arc::new(RwLock::new(BufferRegister::new()));
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+
+        let handler = handler_factory(
+            &config,
+            &buffer_register,
+            "typescript",
+            ff.clone(),
+            None,
+            Some(typescript_lsp()),
+            sender.clone(),
+        )
+        .unwrap();
+        assert_eq!(handler.handler_id(), "typescript");
+
+        // name has to match handler_id
+        assert_eq!(
+            handler_factory(&config, &buffer_register, "deno", ff, None, Some(typescript_lsp()), sender).err(),
+            Some(HandlerLoadError::HandlerNotFound)
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::fs::path::SPath;
 use crate::tsw::lang_id::LangId;
 use crate::w7e::handler::{Handler, NavCompRef};
 use crate::w7e::handler_load_error::HandlerLoadError;
+use crate::w7e::task::Task;
 
 pub const INIT_TIMEOUT: Duration = Duration::from_millis(2000);
 
@@ -19,7 +20,7 @@ impl Handler for GolangHandler {
         LangId::GO
     }
 
-    fn handler_id(&self) -> &'static str {
+    fn handler_id(&self) -> &str {
         "go"
     }
 
@@ -30,6 +31,13 @@ impl Handler for GolangHandler {
     fn navcomp(&self) -> Option<NavCompRef> {
         self.navcomp.clone()
     }
+
+    fn tasks(&self) -> Vec<Task> {
+        vec![
+            Task::new("go", &["build", "./..."], self.root.clone()),
+            Task::new("go", &["test", "./..."], self.root.clone()),
+        ]
+    }
 }
 
 /*
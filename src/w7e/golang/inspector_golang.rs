@@ -9,6 +9,10 @@ impl LangInspector for GoLangInspector {
         LangId::GO
     }
 
+    fn handler_id(&self) -> &'static str {
+        "go"
+    }
+
     fn is_project_dir(&self, ff: &SPath) -> bool {
         ff.is_dir() && ff.descendant_checked("go.mod").map(|desc| desc.is_file()).unwrap_or(false)
     }
@@ -1 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
 
+    use crate::config::config::Config;
+    use crate::fs::filesystem_front::FilesystemFront;
+    use crate::fs::mock_fs::MockFS;
+    use crate::spath;
+    use crate::tsw::lang_id::LangId;
+    use crate::w7e::golang::handler_golang::GolangHandler;
+    use crate::w7e::handler::Handler;
+    use crate::w7e::inspector::inspect_workspace;
+
+    #[test]
+    fn test_inspect_monorepo() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file("repo/Cargo.toml", "[package]\nname = \"repo\"\n")
+            .with_file("repo/backend/go.mod", "module example.com/backend\n")
+            .with_file("repo/backend/main.go", "package main\n")
+            .with_file("repo/tools/requirements.txt", "requests\n")
+            .with_file("repo/native/compile_commands.json", "[]")
+            .with_file("repo/docs/index.md", "nothing to see here")
+            .to_fsf();
+
+        let repo = spath!(mock_fs, "repo").unwrap();
+        let scopes = inspect_workspace(&repo).unwrap();
+
+        let found: Vec<(LangId, String, Option<String>)> = scopes
+            .iter()
+            .map(|scope| (scope.lang_id, scope.path.label().to_string(), scope.handler_id.clone()))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (LangId::RUST, "repo".to_string(), Some("rust".to_string())),
+                (LangId::GO, "backend".to_string(), Some("go".to_string())),
+                (LangId::CPP, "native".to_string(), Some("cpp".to_string())),
+                (LangId::PYTHON3, "tools".to_string(), Some("python3".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inspect_nothing_found() {
+        let mock_fs = MockFS::new("/tmp").with_file("repo/backend/main.go", "package main\n").to_fsf();
+
+        let repo = spath!(mock_fs, "repo").unwrap();
+        assert!(inspect_workspace(&repo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_golang_handler_tasks() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file("backend/go.mod", "module example.com/backend\n")
+            .with_file("backend/main.go", "package main\n")
+            .to_fsf();
+
+        let ff = spath!(mock_fs, "backend").unwrap();
+        let handler = GolangHandler::load(&Arc::new(Config::default()), ff, None).unwrap();
+
+        assert_eq!(
+            handler.tasks().iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+            vec!["go build ./...", "go test ./..."]
+        );
+    }
+}
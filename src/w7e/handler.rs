@@ -8,6 +8,8 @@ use std::sync::Arc;
 
 use crate::tsw::lang_id::LangId;
 use crate::w7e::navcomp_provider::NavCompProvider;
+use crate::w7e::target::Target;
+use crate::w7e::task::Task;
 
 // TODO this might become a more complex type, so all methods on it can be sync, but they are
 // executed asynchronously by affiliated task. Though it does sound like just another layer of
@@ -19,16 +21,40 @@ impl NavCompRef {
     pub fn new(inner: Box<dyn NavCompProvider>) -> Self {
         NavCompRef(Arc::new(inner))
     }
+
+    pub fn ptr_eq(&self, other: &NavCompRef) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 pub trait Handler {
     fn lang_id(&self) -> LangId;
-    fn handler_id(&self) -> &'static str;
+    fn handler_id(&self) -> &str;
     fn project_name(&self) -> &str;
 
     fn navcomp(&self) -> Option<NavCompRef> {
         None
     }
+
+    // things that can be built and run, like binaries and examples
+    fn run_targets(&self) -> Vec<Target> {
+        Vec::new()
+    }
+
+    // things that can be tested
+    fn test_targets(&self) -> Vec<Target> {
+        Vec::new()
+    }
+
+    // commands worth running in this project, like build or test
+    fn tasks(&self) -> Vec<Task> {
+        Vec::new()
+    }
+
+    // file extensions of lang_id() on top of built-in ones
+    fn custom_extensions(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 impl Deref for NavCompRef {
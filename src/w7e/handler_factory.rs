@@ -1,12 +1,15 @@
 use std::sync::Arc;
 
-use log::{debug, error};
+use log::{debug, error, warn};
 
 use crate::config::config::ConfigRef;
+use crate::config::custom_lsp_config::CustomLspConfig;
 use crate::experiments::buffer_register::BufferRegisterRef;
 use crate::fs::path::SPath;
+use crate::lsp_client::lsp_command::LspCommand;
 use crate::tsw::lang_id::LangId;
 use crate::w7e::cpp::handler_cpp::CppHandler;
+use crate::w7e::generic::handler_generic::GenericHandler;
 use crate::w7e::golang::handler_golang::GolangHandler;
 use crate::w7e::handler::{Handler, NavCompRef};
 use crate::w7e::handler_load_error::HandlerLoadError;
@@ -26,6 +29,8 @@ pub fn handler_factory(
     buffer_register_ref: &BufferRegisterRef,
     handler_id: &str,
     ff: SPath,
+    handler_settings: Option<serde_json::Value>,
+    custom_lsp: Option<CustomLspConfig>,
     navcomp_tick_sender: NavCompTickSender,
 ) -> Result<Box<dyn Handler>, HandlerLoadError> {
     debug!("attempting to load handler {} for {:?}", handler_id, ff.absolute_path());
@@ -35,7 +40,13 @@ pub fn handler_factory(
             let lsp_path = config.global.get_rust_lsp_path().ok_or(HandlerLoadError::LspNotFound)?;
             let workspace_root = ff.absolute_path();
             let mut navcomp_op: Option<NavCompRef> = None;
-            if let Some(navcomp_lsp) = NavCompProviderLsp::new(lsp_path, workspace_root, LangId::RUST, navcomp_tick_sender) {
+            if let Some(navcomp_lsp) = NavCompProviderLsp::new(
+                LspCommand::new(lsp_path),
+                workspace_root,
+                LangId::RUST,
+                handler_settings,
+                navcomp_tick_sender,
+            ) {
                 navcomp_op = Some(NavCompRef::new(Box::new(navcomp_lsp)));
             } else {
                 error!("LspWrapper construction failed.")
@@ -52,7 +63,13 @@ pub fn handler_factory(
             let lsp_path = config.global.get_clangd_lsp_path().ok_or(HandlerLoadError::LspNotFound)?;
             let workspace_root = ff.absolute_path();
             let mut navcomp_op: Option<NavCompRef> = None;
-            if let Some(navcomp_lsp) = NavCompProviderLsp::new(lsp_path, workspace_root, LangId::CPP, navcomp_tick_sender) {
+            if let Some(navcomp_lsp) = NavCompProviderLsp::new(
+                LspCommand::new(lsp_path),
+                workspace_root,
+                LangId::CPP,
+                handler_settings,
+                navcomp_tick_sender,
+            ) {
                 navcomp_op = Some(NavCompRef::new(Box::new(navcomp_lsp)));
             } else {
                 error!("LspWrapper construction failed.")
@@ -67,7 +84,13 @@ pub fn handler_factory(
             let lsp_path = config.global.get_golang_lsp_path().ok_or(HandlerLoadError::LspNotFound)?;
             let workspace_root = ff.absolute_path();
             let mut navcomp_op: Option<NavCompRef> = None;
-            if let Some(navcomp_lsp) = NavCompProviderLsp::new(lsp_path, workspace_root, LangId::GO, navcomp_tick_sender) {
+            if let Some(navcomp_lsp) = NavCompProviderLsp::new(
+                LspCommand::new(lsp_path),
+                workspace_root,
+                LangId::GO,
+                handler_settings,
+                navcomp_tick_sender,
+            ) {
                 navcomp_op = Some(NavCompRef::new(Box::new(navcomp_lsp)));
             } else {
                 error!("LspWrapper construction failed.")
@@ -82,7 +105,13 @@ pub fn handler_factory(
             let lsp_path = config.global.get_python_lsp_path().ok_or(HandlerLoadError::LspNotFound)?;
             let workspace_root = ff.absolute_path();
             let mut navcomp_op: Option<NavCompRef> = None;
-            if let Some(navcomp_lsp) = NavCompProviderLsp::new(lsp_path, workspace_root, LangId::PYTHON3, navcomp_tick_sender) {
+            if let Some(navcomp_lsp) = NavCompProviderLsp::new(
+                LspCommand::new(lsp_path),
+                workspace_root,
+                LangId::PYTHON3,
+                handler_settings,
+                navcomp_tick_sender,
+            ) {
                 navcomp_op = Some(NavCompRef::new(Box::new(navcomp_lsp)));
             } else {
                 error!("LspWrapper construction failed.")
@@ -93,7 +122,43 @@ pub fn handler_factory(
                 Err(e) => Err(e),
             }
         }
+        _ => {
+            /*
+            Not a built-in one, so it has to be a custom language server. One declared in scope
+            itself goes first, then the ones from config.
+             */
+            let custom_lsp = match custom_lsp {
+                Some(custom_lsp) if custom_lsp.name == handler_id => Some(custom_lsp),
+                Some(custom_lsp) => {
+                    warn!(
+                        "custom lsp [{}] declared in scope, but handler_id is [{}], ignoring it",
+                        custom_lsp.name, handler_id
+                    );
+                    None
+                }
+                None => None,
+            }
+            .or_else(|| config.global.get_custom_lsp(handler_id).cloned())
+            .ok_or(HandlerLoadError::HandlerNotFound)?;
+
+            let workspace_root = ff.absolute_path();
+            let mut navcomp_op: Option<NavCompRef> = None;
+            if let Some(navcomp_lsp) = NavCompProviderLsp::new(
+                custom_lsp.lsp_command(),
+                workspace_root,
+                custom_lsp.lang_id,
+                handler_settings,
+                navcomp_tick_sender,
+            ) {
+                navcomp_op = Some(NavCompRef::new(Box::new(navcomp_lsp)));
+            } else {
+                error!("LspWrapper construction failed.")
+            }
 
-        _ => Err(HandlerLoadError::HandlerNotFound),
+            match GenericHandler::load(config, ff, custom_lsp, navcomp_op) {
+                Ok(o) => Ok(Box::new(o)),
+                Err(e) => Err(e),
+            }
+        }
     }
 }
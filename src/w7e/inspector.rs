@@ -1,13 +1,14 @@
-use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 use lazy_static::lazy_static;
-use log::debug;
-use maplit::hashmap;
+use log::{debug, error};
 
 use crate::fs::path::SPath;
 use crate::tsw::lang_id::LangId;
+use crate::w7e::cpp::inspector_cpp::CppLangInspector;
+use crate::w7e::golang::inspector_golang::GoLangInspector;
 use crate::w7e::project_scope::ProjectScope;
+use crate::w7e::python::inspector_python::PythonLangInspector;
 use crate::w7e::rust::inspector_rust::RustLangInspector;
 
 #[derive(Debug)]
@@ -25,6 +26,11 @@ impl Display for InspectError {
 pub trait LangInspector: Sync {
     fn lang_id(&self) -> LangId;
 
+    /*
+    Id that handler_factory knows this kind of projects by.
+     */
+    fn handler_id(&self) -> &'static str;
+
     /*
     This is supposed to be quick.
      */
@@ -32,37 +38,71 @@ pub trait LangInspector: Sync {
 }
 
 lazy_static! {
-    static ref KNOWN_INSPECTORS: HashMap<LangId, Box<dyn LangInspector>> = hashmap! {
-        LangId::RUST => Box::new(RustLangInspector::new()) as Box<dyn LangInspector>,
-    };
+    // A Vec, not a map, so scopes come out in the same order every time.
+    static ref KNOWN_INSPECTORS: Vec<Box<dyn LangInspector>> = vec![
+        Box::new(RustLangInspector::new()) as Box<dyn LangInspector>,
+        Box::new(CppLangInspector::new()),
+        Box::new(GoLangInspector::new()),
+        Box::new(PythonLangInspector::new()),
+    ];
 }
 
 /*
-This is a stub method that is supposed to figure out if there are projects to be found in this
-directory.
+Figures out what projects are there to be found in this directory, and its immediate
+subdirectories (monorepos with like "backend/" in Go and "tools/" in Python). One scope per
+project found.
+
+Subdirectories are not checked for language already matched in folder itself, since project at
+the top is assumed to cover them (think Rust workspace with member crates). Hidden ones are skipped.
  */
 pub fn inspect_workspace(folder: &SPath) -> Result<Vec<ProjectScope>, InspectError> {
     if !folder.is_dir() {
         return Err(InspectError::NotAFolder);
     }
 
+    let mut scopes: Vec<ProjectScope> = inspect_dir(folder, &[]);
+    let top_langs: Vec<LangId> = scopes.iter().map(|scope| scope.lang_id).collect();
+
+    match folder.blocking_list() {
+        Ok(items) => {
+            let mut subdirs: Vec<SPath> = items.filter(|item| item.is_dir() && !item.is_hidden()).collect();
+            subdirs.sort();
+
+            for subdir in subdirs.iter() {
+                scopes.extend(inspect_dir(subdir, &top_langs));
+            }
+        }
+        Err(e) => {
+            error!("failed listing {:?}, not descending: {:?}", folder.absolute_path(), e);
+        }
+    }
+
+    Ok(scopes)
+}
+
+fn inspect_dir(folder: &SPath, skip_langs: &[LangId]) -> Vec<ProjectScope> {
     let mut scopes: Vec<ProjectScope> = Vec::new();
 
-    // TODO add one level more of descending (multiple projects per dir)
+    for inspector in KNOWN_INSPECTORS.iter() {
+        let lang_id = inspector.lang_id();
+        if skip_langs.contains(&lang_id) {
+            continue;
+        }
 
-    for (lang_id, inspector) in KNOWN_INSPECTORS.iter() {
         debug!("checking for lang {} in {}", lang_id, &folder);
-        if inspector.is_project_dir(&folder) {
+        if inspector.is_project_dir(folder) {
             debug!("matched {}", lang_id);
             scopes.push(ProjectScope {
                 path: folder.clone(),
-                lang_id: inspector.lang_id(),
+                lang_id,
                 // this is a place where we set default handler_ids
-                handler_id: Some(inspector.lang_id().to_lsp_lang_id_string().to_string()),
+                handler_id: Some(inspector.handler_id().to_string()),
                 handler: None,
+                handler_settings: None,
+                custom_lsp: None,
             });
         }
     }
 
-    Ok(scopes)
+    scopes
 }
@@ -2,6 +2,7 @@
 
 pub mod buffer_state_shared_ref;
 mod cpp;
+pub mod generic;
 pub mod golang;
 pub mod handler;
 pub mod handler_factory;
@@ -10,8 +11,12 @@ pub mod inspector;
 pub mod navcomp_group;
 pub mod navcomp_provider;
 pub mod navcomp_provider_lsp;
+pub mod navcomp_supervisor;
 pub mod project_scope;
 pub mod rust;
+pub mod target;
+pub mod task;
+pub mod task_run;
 
 pub mod python;
 pub mod workspace;
@@ -1,10 +1,10 @@
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use log::debug;
 use url::Url;
 
-use crate::experiments::filename_to_language::filename_to_language;
+use crate::experiments::filename_to_language::{filename_extension, filename_to_language};
 use crate::fs::path::SPath;
 use crate::lsp_client::diagnostic::Diagnostic;
 use crate::tsw::lang_id::LangId;
@@ -13,8 +13,20 @@ use crate::w7e::handler::NavCompRef;
 #[derive(Debug, Copy, Clone)]
 pub enum NavCompTick {
     LspTick(LangId, usize),
+    // sent by NavCompSupervisor, so status bar gets redrawn
+    HealthChanged(LangId),
 }
 
+// Healthy navcomps are not listed at all
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NavCompHealth {
+    Restarting,
+    // gave up restarting
+    Down,
+}
+
+pub type NavCompHealthRef = Arc<RwLock<HashMap<LangId, NavCompHealth>>>;
+
 pub type NavCompTickSender = crossbeam_channel::Sender<NavCompTick>;
 pub type NavCompTickRecv = crossbeam_channel::Receiver<NavCompTick>;
 
@@ -34,6 +46,12 @@ pub struct NavCompGroup {
 
     tick_sender: NavCompTickSender,
     tick_receiver: NavCompTickRecv,
+
+    // kept separately so it can be updated without locking the entire group
+    health: NavCompHealthRef,
+
+    // declared by custom language servers (see CustomLspConfig) of this workspace
+    custom_extensions: HashMap<String, LangId>,
 }
 
 impl NavCompGroup {
@@ -44,6 +62,8 @@ impl NavCompGroup {
             navcomps: Default::default(),
             tick_sender,
             tick_receiver,
+            health: Default::default(),
+            custom_extensions: Default::default(),
         }
     }
 
@@ -51,9 +71,24 @@ impl NavCompGroup {
         // TODO(never) theoretically for the same language we can have multiple navcomps. I do not
         // intend to handle this case any time soon, but I prefer to use spath whenever possible.
 
-        filename_to_language(spath)
-            .map(|lang_id| self.get_navcomp_for_lang(lang_id))
-            .flatten()
+        self.lang_for(spath).and_then(|lang_id| self.get_navcomp_for_lang(lang_id))
+    }
+
+    /*
+    Custom extensions take precedence over built-in ones, so a language server can claim a file that
+    would be otherwise recognized as something else (like "h" for C++).
+     */
+    pub fn lang_for(&self, spath: &SPath) -> Option<LangId> {
+        filename_extension(spath)
+            .and_then(|ext| self.custom_extensions.get(ext).copied())
+            .or_else(|| filename_to_language(spath))
+    }
+
+    pub fn add_custom_extensions(&mut self, lang_id: LangId, extensions: &[String]) {
+        for ext in extensions.iter() {
+            debug!("routing extension {} to {}", ext, lang_id);
+            self.custom_extensions.insert(ext.clone(), lang_id);
+        }
     }
 
     pub fn get_navcomp_for_lang(&self, lang: LangId) -> Option<&NavCompRef> {
@@ -69,6 +104,31 @@ impl NavCompGroup {
         });
     }
 
+    // Each navcomp is listed once, even if it serves multiple languages.
+    pub fn navcomps(&self) -> Vec<NavCompRef> {
+        let mut result: Vec<NavCompRef> = Vec::new();
+
+        for navcomp in self.navcomps.values() {
+            if !result.iter().any(|other| other.ptr_eq(navcomp)) {
+                result.push(navcomp.clone());
+            }
+        }
+
+        result
+    }
+
+    pub fn langs_of(&self, navcomp: &NavCompRef) -> Vec<LangId> {
+        self.navcomps
+            .iter()
+            .filter(|(_, other)| other.ptr_eq(navcomp))
+            .map(|(lang_id, _)| *lang_id)
+            .collect()
+    }
+
+    pub fn health(&self) -> &NavCompHealthRef {
+        &self.health
+    }
+
     pub fn len(&self) -> usize {
         self.navcomps.len()
     }
@@ -1,6 +1,7 @@
 use log::warn;
 use parking_lot::MappedRwLockReadGuard;
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use crate::fs::path::SPath;
 use crate::primitives::stupid_cursor::StupidCursor;
@@ -72,7 +73,49 @@ pub enum SymbolType {
     Unmapped(String),
 }
 
-#[derive(Debug, Clone)]
+// nothing known about the symbol
+impl Default for SymbolType {
+    fn default() -> Self {
+        SymbolType::Unmapped(String::new())
+    }
+}
+
+impl SymbolType {
+    // lowercase, human readable, used in outline
+    pub fn description(&self) -> &str {
+        match self {
+            SymbolType::File => "file",
+            SymbolType::Module => "module",
+            SymbolType::Namespace => "namespace",
+            SymbolType::Package => "package",
+            SymbolType::Class => "class",
+            SymbolType::Method => "method",
+            SymbolType::Property => "property",
+            SymbolType::Field => "field",
+            SymbolType::Constructor => "constructor",
+            SymbolType::Enum => "enum",
+            SymbolType::Interface => "interface",
+            SymbolType::Function => "function",
+            SymbolType::Variable => "variable",
+            SymbolType::Constant => "constant",
+            SymbolType::String => "string",
+            SymbolType::Number => "number",
+            SymbolType::Boolean => "boolean",
+            SymbolType::Array => "array",
+            SymbolType::Object => "object",
+            SymbolType::Key => "key",
+            SymbolType::Null => "null",
+            SymbolType::EnumMember => "enum member",
+            SymbolType::Event => "event",
+            SymbolType::Struct => "struct",
+            SymbolType::Operator => "operator",
+            SymbolType::TypeParameter => "type parameter",
+            SymbolType::Unmapped(desc) => desc.as_str(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct NavCompSymbol {
     pub symbol_type: SymbolType,
     pub stupid_range: (StupidCursor, StupidCursor),
@@ -84,6 +127,206 @@ pub struct StupidSubstituteMessage {
     pub stupid_range: (StupidCursor, StupidCursor),
 }
 
+/*
+Describes how contents of a buffer changed since previous EditEvent was submitted.
+
+Incremental edits are ordered, and each of them is expressed in coordinates of the document with all
+previous edits already applied, which is exactly what LSP's incremental sync expects. Columns of these
+are in UTF-16 code units (see StupidCursor::from_char_idx_utf16), as LSP's default. Contents are
+attached to both variants, so implementations that can't do incremental updates can always fall
+back to "full sync".
+ */
+#[derive(Debug, Clone)]
+pub enum EditEvent {
+    // Used when change can't be expressed as a list of edits, like undo, redo or text replacement.
+    Full {
+        file_contents: ropey::Rope,
+    },
+    Incremental {
+        edits: Vec<StupidSubstituteMessage>,
+        file_contents: ropey::Rope,
+    },
+}
+
+impl EditEvent {
+    pub fn file_contents(&self) -> &ropey::Rope {
+        match self {
+            EditEvent::Full { file_contents } => file_contents,
+            EditEvent::Incremental { file_contents, .. } => file_contents,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NavCompSignature {
+    pub label: String,
+    // [begin, end) char indices of active parameter within label, if known.
+    pub active_parameter: Option<(usize, usize)>,
+}
+
+/*
+Changes to be applied to multiple files, like result of rename. Paths are urls (same as in
+StupidSymbolUsage), edits of a single file don't overlap and are expressed in coordinates of the
+file before any of them is applied.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct NavCompWorkspaceEdit {
+    pub file_edits: Vec<(String, Vec<StupidSubstituteMessage>)>,
+}
+
+/*
+Long running work of the underlying service, like indexing. Until it's done, answers can be
+incomplete or empty, so user should know about it.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NavCompProgress {
+    pub title: String,
+    pub message: Option<String>,
+    // 0-100, if known
+    pub percentage: Option<u32>,
+}
+
+/*
+Edit the underlying service wants applied on its own initiative (LSP's workspace/applyEdit, usually
+sent while executing a command). It waits for the answer, so respond() has to be called, whether
+the edit was applied or not.
+ */
+pub struct NavCompApplyEditRequest {
+    pub label: Option<String>,
+    pub edit: NavCompWorkspaceEdit,
+    responder: Box<dyn FnOnce(bool) + Send + Sync>,
+}
+
+impl NavCompApplyEditRequest {
+    pub fn new(label: Option<String>, edit: NavCompWorkspaceEdit, responder: Box<dyn FnOnce(bool) + Send + Sync>) -> Self {
+        NavCompApplyEditRequest { label, edit, responder }
+    }
+
+    pub fn respond(self, applied: bool) {
+        (self.responder)(applied)
+    }
+}
+
+impl Debug for NavCompApplyEditRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NavCompApplyEditRequest({:?}, {:?})", self.label, self.edit)
+    }
+}
+
+/*
+Command to be executed by whoever offered it (in case of LSP that's workspace/executeCommand).
+Arguments are opaque to me, I just pass them back.
+ */
+#[derive(Debug, Clone)]
+pub struct NavCompCommand {
+    pub title: String,
+    pub command: String,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+/*
+A quick fix or refactor available at given range. If both edit and command are set, edit goes first.
+ */
+#[derive(Debug, Clone)]
+pub struct NavCompCodeAction {
+    pub title: String,
+    // like "quickfix" or "refactor.rewrite", if provided.
+    pub kind: Option<String>,
+    pub edit: Option<NavCompWorkspaceEdit>,
+    pub command: Option<NavCompCommand>,
+}
+
+/*
+Symbol defined in a file, as displayed in outline. Children are symbols defined within this one,
+like methods of a class. Range covers the entire definition, name_range just the identifier.
+ */
+#[derive(Debug, Clone)]
+pub struct NavCompDocumentSymbol {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub stupid_range: (StupidCursor, StupidCursor),
+    pub name_stupid_range: (StupidCursor, StupidCursor),
+    pub children: Vec<NavCompDocumentSymbol>,
+}
+
+// Read and Write are for variables, Text is for everything else (or servers that don't tell).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentHighlightKind {
+    Text,
+    Read,
+    Write,
+}
+
+// Occurrence of the symbol under cursor in the same file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavCompDocumentHighlight {
+    pub stupid_range: (StupidCursor, StupidCursor),
+    pub kind: DocumentHighlightKind,
+}
+
+/*
+Semantic token, already decoded from relative positions LSP sends. Type and modifiers are named
+as the server named them in its legend ("variable", "mutable" etc.), it's up to theme to color them.
+Tokens never span multiple lines.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavCompSemanticToken {
+    pub stupid_range: (StupidCursor, StupidCursor),
+    pub token_type: Arc<String>,
+    pub modifiers: Vec<Arc<String>>,
+}
+
+/*
+Result of a workspace-wide symbol search. Container is whatever the symbol is defined in (module,
+class etc.), it's there just to tell apart symbols with the same name.
+ */
+#[derive(Debug, Clone)]
+pub struct NavCompWorkspaceSymbol {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub container_name_op: Option<String>,
+    pub location: StupidSymbolUsage,
+}
+
+/*
+Which way to walk the hierarchy. Calls go through functions, types through types, asking for one
+kind with an item of the other is up to underlying service to refuse.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HierarchyKind {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyKind {
+    pub fn description(&self) -> &str {
+        match self {
+            HierarchyKind::IncomingCalls => "incoming calls",
+            HierarchyKind::OutgoingCalls => "outgoing calls",
+            HierarchyKind::Supertypes => "supertypes",
+            HierarchyKind::Subtypes => "subtypes",
+        }
+    }
+}
+
+/*
+A node of call or type hierarchy. Location is where to jump on hit: for incoming calls that's the
+call site within the caller, for everything else the name of the item itself.
+
+raw is whatever underlying service needs to be given back to expand this item further. I don't
+look into it.
+ */
+#[derive(Debug, Clone)]
+pub struct NavCompHierarchyItem {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub detail_op: Option<String>,
+    pub location: StupidSymbolUsage,
+    pub raw: serde_json::Value,
+}
+
 #[derive(Debug, Clone)]
 pub struct StupidSymbolUsage {
     pub path: String,
@@ -95,9 +338,13 @@ This is super work in progress, I added some top of the head options to "smoke o
 #[derive(Debug, Clone)]
 pub enum NavCompSymbolContextActions {
     GoToDefinition,
+    GoToTypeDefinition,
+    GoToImplementations,
+    GoToDeclaration,
     FindUsages,
     NextUsage,
     PrevUsage,
+    Rename,
 }
 
 pub type CompletionsPromise = Box<dyn Promise<Vec<Completion>> + 'static>;
@@ -105,6 +352,20 @@ pub type FormattingPromise = Box<dyn Promise<Option<Vec<StupidSubstituteMessage>
 pub type SymbolContextActionsPromise = Box<dyn Promise<Vec<Completion>> + 'static>;
 pub type SymbolPromise = Box<dyn Promise<Option<NavCompSymbol>> + 'static>;
 pub type SymbolUsagesPromise = Box<dyn Promise<Vec<StupidSymbolUsage>> + 'static>;
+// None means "nothing to show here", which is not an error.
+pub type HoverPromise = Box<dyn Promise<Option<String>> + 'static>;
+// None means "cursor is not within a call", which is not an error either.
+pub type SignatureHelpPromise = Box<dyn Promise<Option<NavCompSignature>> + 'static>;
+// None means "symbol can't be renamed".
+pub type RenamePromise = Box<dyn Promise<Option<NavCompWorkspaceEdit>> + 'static>;
+pub type CodeActionsPromise = Box<dyn Promise<Vec<NavCompCodeAction>> + 'static>;
+pub type InlayHintsPromise = Box<dyn Promise<Vec<Label>> + 'static>;
+pub type DocumentSymbolsPromise = Box<dyn Promise<Vec<NavCompDocumentSymbol>> + 'static>;
+pub type WorkspaceSymbolsPromise = Box<dyn Promise<Vec<NavCompWorkspaceSymbol>> + 'static>;
+pub type DocumentHighlightsPromise = Box<dyn Promise<Vec<NavCompDocumentHighlight>> + 'static>;
+// None means "no usable answer this time": previous tokens should be kept, and asked for again.
+pub type SemanticTokensPromise = Box<dyn Promise<Option<Vec<NavCompSemanticToken>>> + 'static>;
+pub type HierarchyPromise = Box<dyn Promise<Vec<NavCompHierarchyItem>> + 'static>;
 
 // this is a wrapper around LSP and "similar services".
 pub trait NavCompProvider: Debug + Send + Sync {
@@ -114,9 +375,13 @@ pub trait NavCompProvider: Debug + Send + Sync {
     fn file_open_for_edition(&self, path: &SPath, file_contents: ropey::Rope);
 
     /*
-    I will add "incremental updates" at later stage.
+    Implementation decides whether to use incremental edits or full contents, depending on what
+    underlying service supports.
      */
-    fn submit_edit_event(&self, path: &SPath, file_contents: ropey::Rope);
+    fn submit_edit_event(&self, path: &SPath, edit_event: EditEvent);
+
+    // File that is not open for edition was changed on disk by gladius (see BufferRegister::apply_workspace_edit).
+    fn file_changed_on_disk(&self, path: &SPath);
 
     fn completions(&self, path: SPath, cursor: StupidCursor, trigger: Option<String>) -> Option<CompletionsPromise>;
 
@@ -131,11 +396,76 @@ pub trait NavCompProvider: Debug + Send + Sync {
 
     fn go_to_definition(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise>;
 
+    fn go_to_type_definition(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise>;
+
+    // For traits and interfaces, all places implementing them. Usually more than one.
+    fn go_to_implementations(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise>;
+
+    // Most servers don't distinguish declaration from definition, but clangd does.
+    fn go_to_declaration(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise>;
+
     /*
-    missing items:
-    - formatting non-saved files
+    Returns text to be displayed to user, usually documentation and signature of symbol under cursor.
+    Markdown is passed as-is, I don't render it (yet).
      */
-    fn todo_reformat(&self, path: &SPath) -> Option<FormattingPromise>;
+    fn hover(&self, path: &SPath, cursor: StupidCursor) -> Option<HoverPromise>;
+
+    fn signature_help(&self, path: &SPath, cursor: StupidCursor) -> Option<SignatureHelpPromise>;
+
+    // Typing one of these opens signature help.
+    fn signature_help_triggers(&self, path: &SPath) -> &Vec<String>;
+
+    /*
+    Does NOT apply anything, just computes what would change. Applying is up to the caller, so user
+    can review the changes first.
+     */
+    fn rename(&self, path: &SPath, cursor: StupidCursor, new_name: String) -> Option<RenamePromise>;
+
+    fn code_actions(&self, path: &SPath, stupid_range: (StupidCursor, StupidCursor)) -> Option<CodeActionsPromise>;
+
+    /*
+    Fire and forget, whatever command does is up to the other side. Returns false if the command
+    was not sent, for instance because it's meant to be handled by the client, which I don't do.
+     */
+    fn execute_command(&self, command: &NavCompCommand) -> bool;
+
+    // Edits requested by the underlying service since the last call. MainView applies them.
+    fn take_apply_edit_requests(&self) -> Vec<NavCompApplyEditRequest>;
+
+    /*
+    Returns hints for given range of the file, as labels ready to be drawn inline.
+     */
+    fn inlay_hints(&self, path: &SPath, stupid_range: (StupidCursor, StupidCursor)) -> Option<InlayHintsPromise>;
+
+    // Hierarchical if underlying service supports it, flat otherwise.
+    fn document_symbols(&self, path: &SPath) -> Option<DocumentSymbolsPromise>;
+
+    // All occurrences of the symbol under cursor within the file, including the one under cursor.
+    fn document_highlights(&self, path: &SPath, cursor: StupidCursor) -> Option<DocumentHighlightsPromise>;
+
+    /*
+    Semantic tokens of entire file. Implementation is free to ask only for changes since the last
+    call for given file (that's what "delta" in LSP is for), but the result is always complete.
+     */
+    fn semantic_tokens(&self, path: &SPath) -> Option<SemanticTokensPromise>;
+
+    // Matching is done by the underlying service, query is expected to be non-empty.
+    fn workspace_symbols(&self, query: &str) -> Option<WorkspaceSymbolsPromise>;
+
+    /*
+    Items the hierarchy starts with, usually just the one under cursor. Kind is needed, because
+    LSP has separate "prepare" calls for calls and types.
+     */
+    fn prepare_hierarchy(&self, path: &SPath, cursor: StupidCursor, kind: HierarchyKind) -> Option<HierarchyPromise>;
+
+    // Items one level down from given one, that is callers, callees, supertypes or subtypes.
+    fn hierarchy_children(&self, item: &NavCompHierarchyItem, kind: HierarchyKind) -> Option<HierarchyPromise>;
+
+    /*
+    Formats file as it is in the buffer, saved or not (edits are submitted as they happen, so they are
+    computed against exactly what user sees). With range given, only that part gets formatted.
+     */
+    fn format(&self, path: &SPath, stupid_range_op: Option<(StupidCursor, StupidCursor)>) -> Option<FormattingPromise>;
 
     // TODO this might need wiring
     fn file_closed(&self, path: &SPath);
@@ -144,7 +474,17 @@ pub trait NavCompProvider: Debug + Send + Sync {
 
     fn todo_navcomp_sender(&self) -> &NavCompTickSender;
 
-    fn todo_is_healthy(&self) -> bool;
+    // Work in progress, empty if service is idle.
+    fn progress(&self) -> Vec<NavCompProgress>;
+
+    // False means underlying service died and needs a restart, see NavCompSupervisor.
+    fn is_healthy(&self) -> bool;
+
+    /*
+    Brings the underlying service back up, returns whether it succeeded. Provider does not remember
+    files it was given, so whoever calls this is expected to open them again.
+     */
+    fn restart(&self) -> bool;
 
     fn can_reformat(&self) -> bool {
         warn!("mock implementation of can_reformat() always returns true");
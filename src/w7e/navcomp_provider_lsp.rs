@@ -1,17 +1,23 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::path::PathBuf;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
 use crossbeam_channel::{Receiver, Sender};
 use log::{debug, error, warn};
-use lsp_types::{CompletionResponse, CompletionTextEdit, GotoDefinitionResponse, Location, LocationLink, Position, SymbolKind};
+use lsp_types::{
+    CallHierarchyItem, CodeActionOrCommand, CompletionResponse, CompletionTextEdit, DocumentChangeOperation, DocumentChanges,
+    DocumentSymbolResponse, GotoDefinitionResponse, HoverContents, InlayHint, InlayHintLabel, Location, LocationLink, MarkedString, OneOf,
+    ParameterLabel, Position, SemanticToken, SemanticTokensEdit, SemanticTokensFullDeltaResult, SemanticTokensFullOptions,
+    SemanticTokensResult, SemanticTokensServerCapabilities, SymbolKind, TypeHierarchyItem, WorkspaceSymbolResponse,
+};
 use parking_lot::{MappedRwLockReadGuard, RwLockReadGuard};
 use url::Url;
 
 use crate::fs::path::SPath;
 use crate::lsp_client::diagnostic::Diagnostic;
-use crate::lsp_client::lsp_client::LspWrapper;
+use crate::lsp_client::lsp_client::{LspWrapper, ProgressStore};
+use crate::lsp_client::lsp_command::LspCommand;
 use crate::lsp_client::lsp_io_error::LspIOError;
 use crate::lsp_client::lsp_read_error::LspReadError;
 use crate::lsp_client::lsp_write_error::LspWriteError;
@@ -20,11 +26,15 @@ use crate::promise::promise::Promise;
 use crate::tsw::lang_id::LangId;
 use crate::w7e::navcomp_group::NavCompTickSender;
 use crate::w7e::navcomp_provider::{
-    Completion, CompletionAction, CompletionsPromise, FormattingPromise, NavCompProvider, StupidSubstituteMessage, StupidSymbolUsage,
-    SymbolType, SymbolUsagesPromise,
+    CodeActionsPromise, Completion, CompletionAction, CompletionsPromise, DocumentHighlightKind, DocumentHighlightsPromise,
+    DocumentSymbolsPromise, EditEvent, FormattingPromise, HierarchyKind, HierarchyPromise, HoverPromise, InlayHintsPromise,
+    NavCompApplyEditRequest, NavCompCodeAction, NavCompCommand, NavCompDocumentHighlight, NavCompDocumentSymbol, NavCompHierarchyItem,
+    NavCompProgress, NavCompProvider, NavCompSemanticToken, NavCompSignature, NavCompWorkspaceEdit, NavCompWorkspaceSymbol, RenamePromise,
+    SemanticTokensPromise, SignatureHelpPromise, StupidSubstituteMessage, StupidSymbolUsage, SymbolType, SymbolUsagesPromise,
+    WorkspaceSymbolsPromise,
 };
-use crate::widgets::editor_widget::label::label::Label;
-use crate::{unpack_or_e, unpack_unit_e};
+use crate::widgets::editor_widget::label::label::{Label, LabelPos, LabelStyle};
+use crate::{unpack_or, unpack_or_e, unpack_unit_e};
 
 /*
 TODO I am silently ignoring errors here. I guess that if NavComp fails it should get re-started.
@@ -50,6 +60,9 @@ pub struct NavCompProviderLsp {
     lsp: RwLock<LspWrapper>,
     todo_tick_sender: NavCompTickSender,
     triggers: Vec<String>,
+    signature_help_triggers: Vec<String>,
+    // commands server is willing to execute, announced on initialization.
+    execute_commands: Vec<String>,
     read_error_channel: (Sender<LspReadError>, Receiver<LspReadError>),
 
     // mapping of "LSP file version to labels", so I know when I need to recompute them.
@@ -57,26 +70,116 @@ pub struct NavCompProviderLsp {
     // "stream promise" or "refreshable state" ("observer?") or "receiver".
     file_to_labels: parking_lot::RwLock<HashMap<SPath, (i32, Vec<Label>)>>,
 
+    // None if server does not provide semantic tokens.
+    semantic_tokens_legend: Option<Arc<SemanticTokensLegend>>,
+    semantic_tokens_delta: bool,
+    // last complete answer per file, with its result id, so I can ask just for changes.
+    file_to_semantic_tokens: Arc<parking_lot::RwLock<SemanticTokensCache>>,
+
+    // filled by reader thread of server, as it reports progress
+    progress: ProgressStore,
+
     crashed: RwLock<bool>,
 }
 
+// file -> (result id, tokens)
+type SemanticTokensCache = HashMap<Url, (String, Vec<SemanticToken>)>;
+
+// names of token types and modifiers, as announced by server. Tokens refer to them by index.
+struct SemanticTokensLegend {
+    token_types: Vec<Arc<String>>,
+    token_modifiers: Vec<Arc<String>>,
+}
+
 impl NavCompProviderLsp {
     // TODO add errors
-    pub fn new(lsp_path: PathBuf, workspace_root: PathBuf, language: LangId, tick_sender: NavCompTickSender) -> Option<Self> {
+    /*
+    Settings are per-handler, from workspace file. Server gets them when it asks for configuration.
+     */
+    pub fn new(
+        command: LspCommand,
+        workspace_root: PathBuf,
+        language: LangId,
+        settings: Option<serde_json::Value>,
+        tick_sender: NavCompTickSender,
+    ) -> Option<Self> {
         let error_channel = crossbeam_channel::unbounded::<LspReadError>();
+        let progress = ProgressStore::default();
 
-        if let Some(mut lsp) = LspWrapper::new(lsp_path, workspace_root, language, tick_sender.clone(), error_channel.0.clone()) {
+        if let Some(mut lsp) = LspWrapper::new(
+            command,
+            workspace_root,
+            language,
+            settings,
+            progress.clone(),
+            tick_sender.clone(),
+            error_channel.0.clone(),
+        ) {
             match lsp.initialize() {
                 Ok(lsp_answer) => {
                     debug!("lsp initialization success: {:?}", lsp_answer);
 
+                    let signature_help_triggers = lsp_answer
+                        .capabilities
+                        .signature_help_provider
+                        .as_ref()
+                        .and_then(|options| options.trigger_characters.clone())
+                        .unwrap_or_default();
+
+                    let execute_commands = lsp_answer
+                        .capabilities
+                        .execute_command_provider
+                        .as_ref()
+                        .map(|options| options.commands.clone())
+                        .unwrap_or_default();
+
+                    let semantic_tokens_options =
+                        lsp_answer
+                            .capabilities
+                            .semantic_tokens_provider
+                            .as_ref()
+                            .map(|provider| match provider {
+                                SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
+                                SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+                                    &options.semantic_tokens_options
+                                }
+                            });
+
+                    let semantic_tokens_legend = semantic_tokens_options.map(|options| {
+                        Arc::new(SemanticTokensLegend {
+                            token_types: options
+                                .legend
+                                .token_types
+                                .iter()
+                                .map(|token_type| Arc::new(token_type.as_str().to_string()))
+                                .collect(),
+                            token_modifiers: options
+                                .legend
+                                .token_modifiers
+                                .iter()
+                                .map(|modifier| Arc::new(modifier.as_str().to_string()))
+                                .collect(),
+                        })
+                    });
+
+                    let semantic_tokens_delta = matches!(
+                        semantic_tokens_options.and_then(|options| options.full.as_ref()),
+                        Some(SemanticTokensFullOptions::Delta { delta: Some(true) })
+                    );
+
                     Some(NavCompProviderLsp {
                         lsp: RwLock::new(lsp),
                         todo_tick_sender: tick_sender,
                         // TODO this will get lang specific
                         triggers: vec![".".to_string(), "::".to_string()],
+                        signature_help_triggers,
+                        execute_commands,
                         read_error_channel: error_channel,
                         file_to_labels: Default::default(),
+                        semantic_tokens_legend,
+                        semantic_tokens_delta,
+                        file_to_semantic_tokens: Default::default(),
+                        progress,
                         crashed: RwLock::new(false),
                     })
                 }
@@ -116,6 +219,398 @@ fn location_to_symbol_usage(loc: Location) -> StupidSymbolUsage {
     }
 }
 
+// Definition, type definition, implementation and declaration all share the same response type.
+fn goto_response_to_symbol_usages(response: Option<GotoDefinitionResponse>) -> Vec<StupidSymbolUsage> {
+    match response {
+        None => Vec::new(),
+        Some(GotoDefinitionResponse::Scalar(item)) => vec![location_to_symbol_usage(item)],
+        Some(GotoDefinitionResponse::Array(items)) => items.into_iter().map(location_to_symbol_usage).collect(),
+        Some(GotoDefinitionResponse::Link(links)) => links.into_iter().map(location_link_to_symbol_usage).collect(),
+    }
+}
+
+fn stupid_substitute_to_content_change(msg: StupidSubstituteMessage) -> lsp_types::TextDocumentContentChangeEvent {
+    lsp_types::TextDocumentContentChangeEvent {
+        range: Some(lsp_types::Range {
+            start: Position {
+                line: msg.stupid_range.0.line_0b,
+                character: msg.stupid_range.0.char_idx_0b,
+            },
+            end: Position {
+                line: msg.stupid_range.1.line_0b,
+                character: msg.stupid_range.1.char_idx_0b,
+            },
+        }),
+        range_length: None,
+        text: msg.substitute,
+    }
+}
+
+fn marked_string_to_string(ms: MarkedString) -> String {
+    match ms {
+        MarkedString::String(s) => s,
+        MarkedString::LanguageString(ls) => format!("```{}\n{}\n```", ls.language, ls.value),
+    }
+}
+
+// Markdown and plain text are not distinguished here, both are displayed as they are.
+fn hover_contents_to_string(contents: HoverContents) -> String {
+    match contents {
+        HoverContents::Scalar(ms) => marked_string_to_string(ms),
+        HoverContents::Array(items) => items.into_iter().map(marked_string_to_string).collect::<Vec<_>>().join("\n\n"),
+        HoverContents::Markup(markup) => markup.value,
+    }
+}
+
+fn text_edit_to_stupid_substitute(te: lsp_types::TextEdit) -> StupidSubstituteMessage {
+    StupidSubstituteMessage {
+        substitute: te.new_text,
+        stupid_range: (te.range.start.into(), te.range.end.into()),
+    }
+}
+
+/*
+Servers can answer with either "changes" or "document_changes" (or both, in which case the latter wins,
+as the spec says). File create/rename/delete operations are not supported, I just skip them.
+ */
+fn translate_workspace_edit(edit: lsp_types::WorkspaceEdit) -> NavCompWorkspaceEdit {
+    let mut result = NavCompWorkspaceEdit::default();
+
+    if let Some(document_changes) = edit.document_changes {
+        let text_document_edits: Vec<lsp_types::TextDocumentEdit> = match document_changes {
+            DocumentChanges::Edits(edits) => edits,
+            DocumentChanges::Operations(ops) => ops
+                .into_iter()
+                .filter_map(|op| match op {
+                    DocumentChangeOperation::Edit(edit) => Some(edit),
+                    DocumentChangeOperation::Op(op) => {
+                        warn!("ignoring unsupported resource operation {:?}", op);
+                        None
+                    }
+                })
+                .collect(),
+        };
+
+        for tde in text_document_edits {
+            let edits = tde
+                .edits
+                .into_iter()
+                .map(|edit| match edit {
+                    OneOf::Left(te) => text_edit_to_stupid_substitute(te),
+                    OneOf::Right(ate) => text_edit_to_stupid_substitute(ate.text_edit),
+                })
+                .collect();
+            result.file_edits.push((tde.text_document.uri.to_string(), edits));
+        }
+    } else if let Some(changes) = edit.changes {
+        for (url, edits) in changes {
+            result
+                .file_edits
+                .push((url.to_string(), edits.into_iter().map(text_edit_to_stupid_substitute).collect()));
+        }
+        // HashMap has no order, and I want the preview to be stable.
+        result.file_edits.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    result
+}
+
+fn translate_command(command: lsp_types::Command) -> NavCompCommand {
+    NavCompCommand {
+        title: command.title,
+        command: command.command,
+        arguments: command.arguments.unwrap_or_default(),
+    }
+}
+
+// Disabled actions are dropped, since I have no way to show "why" anyway.
+fn translate_code_action(item: CodeActionOrCommand) -> Option<NavCompCodeAction> {
+    match item {
+        CodeActionOrCommand::Command(command) => Some(NavCompCodeAction {
+            title: command.title.clone(),
+            kind: None,
+            edit: None,
+            command: Some(translate_command(command)),
+        }),
+        CodeActionOrCommand::CodeAction(action) => {
+            if let Some(disabled) = action.disabled {
+                debug!("skipping disabled code action {}: {}", action.title, disabled.reason);
+                return None;
+            }
+
+            Some(NavCompCodeAction {
+                title: action.title,
+                kind: action.kind.map(|kind| kind.as_str().to_string()),
+                edit: action.edit.map(translate_workspace_edit),
+                command: action.command.map(translate_command),
+            })
+        }
+    }
+}
+
+fn translate_document_symbol(symbol: lsp_types::DocumentSymbol) -> NavCompDocumentSymbol {
+    NavCompDocumentSymbol {
+        name: symbol.name,
+        symbol_type: symbol.kind.into(),
+        stupid_range: (symbol.range.start.into(), symbol.range.end.into()),
+        name_stupid_range: (symbol.selection_range.start.into(), symbol.selection_range.end.into()),
+        children: symbol
+            .children
+            .unwrap_or_default()
+            .into_iter()
+            .map(translate_document_symbol)
+            .collect(),
+    }
+}
+
+// Servers without hierarchical support answer with a flat list, I don't rebuild the tree from container names.
+fn translate_document_symbol_response(response: DocumentSymbolResponse) -> Vec<NavCompDocumentSymbol> {
+    match response {
+        DocumentSymbolResponse::Nested(symbols) => symbols.into_iter().map(translate_document_symbol).collect(),
+        DocumentSymbolResponse::Flat(symbols) => symbols
+            .into_iter()
+            .map(|symbol| {
+                let range = (symbol.location.range.start.into(), symbol.location.range.end.into());
+                NavCompDocumentSymbol {
+                    name: symbol.name,
+                    symbol_type: symbol.kind.into(),
+                    stupid_range: range,
+                    name_stupid_range: range,
+                    children: vec![],
+                }
+            })
+            .collect(),
+    }
+}
+
+// kind is optional in LSP, and "text" is the default.
+fn translate_document_highlight(highlight: lsp_types::DocumentHighlight) -> NavCompDocumentHighlight {
+    let kind = match highlight.kind {
+        Some(lsp_types::DocumentHighlightKind::READ) => DocumentHighlightKind::Read,
+        Some(lsp_types::DocumentHighlightKind::WRITE) => DocumentHighlightKind::Write,
+        _ => DocumentHighlightKind::Text,
+    };
+
+    NavCompDocumentHighlight {
+        stupid_range: (highlight.range.start.into(), highlight.range.end.into()),
+        kind,
+    }
+}
+
+/*
+LSP sends tokens as positions relative to previous token, so they have to be decoded in order.
+Tokens of types missing in legend are skipped, unknown modifier bits are ignored.
+ */
+fn decode_semantic_tokens(legend: &SemanticTokensLegend, data: &[SemanticToken]) -> Vec<NavCompSemanticToken> {
+    let mut result: Vec<NavCompSemanticToken> = Vec::with_capacity(data.len());
+    let mut line: u32 = 0;
+    let mut start: u32 = 0;
+
+    for token in data {
+        if token.delta_line > 0 {
+            line += token.delta_line;
+            start = token.delta_start;
+        } else {
+            start += token.delta_start;
+        }
+
+        let token_type = match legend.token_types.get(token.token_type as usize) {
+            Some(token_type) => token_type.clone(),
+            None => {
+                debug!("skipping semantic token of unknown type {}", token.token_type);
+                continue;
+            }
+        };
+
+        let modifiers = legend
+            .token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx < 32 && token.token_modifiers_bitset & (1 << idx) != 0)
+            .map(|(_, modifier)| modifier.clone())
+            .collect();
+
+        result.push(NavCompSemanticToken {
+            stupid_range: (StupidCursor::new(start, line), StupidCursor::new(start + token.length, line)),
+            token_type,
+            modifiers,
+        });
+    }
+
+    result
+}
+
+/*
+Complete tokens (and result id to ask for the next delta) after delta response. None if response is
+not usable, then the full set should be requested.
+ */
+fn semantic_tokens_after_delta(
+    previous_data: &[SemanticToken],
+    response: Option<SemanticTokensFullDeltaResult>,
+) -> Option<(Option<String>, Vec<SemanticToken>)> {
+    match response {
+        Some(SemanticTokensFullDeltaResult::Tokens(tokens)) => Some((tokens.result_id, tokens.data)),
+        Some(SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+            apply_semantic_tokens_edits(previous_data, delta.edits).map(|data| (delta.result_id, data))
+        }
+        Some(SemanticTokensFullDeltaResult::PartialTokensDelta { .. }) | None => None,
+    }
+}
+
+/*
+Delta edits address the flat array of integers (5 per token), not tokens, so that's what I edit.
+Edits refer to positions in the old array, so they are applied back to front. Returns None if
+edits don't fit the old array, the caller should ask for full tokens then.
+ */
+fn apply_semantic_tokens_edits(old: &[SemanticToken], mut edits: Vec<SemanticTokensEdit>) -> Option<Vec<SemanticToken>> {
+    let mut flat: Vec<u32> = old
+        .iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect();
+
+    edits.sort_by_key(|edit| edit.start);
+
+    for edit in edits.into_iter().rev() {
+        let begin = edit.start as usize;
+        let end = begin + edit.delete_count as usize;
+        if end > flat.len() {
+            warn!("semantic tokens edit [{}..{}) out of bounds (len {})", begin, end, flat.len());
+            return None;
+        }
+
+        let new_data = edit.data.unwrap_or_default().into_iter().flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        });
+        flat.splice(begin..end, new_data);
+    }
+
+    if !flat.len().is_multiple_of(5) {
+        warn!("semantic tokens edits left {} integers, which is not a multiple of 5", flat.len());
+        return None;
+    }
+
+    Some(
+        flat.chunks_exact(5)
+            .map(|chunk| SemanticToken {
+                delta_line: chunk[0],
+                delta_start: chunk[1],
+                length: chunk[2],
+                token_type: chunk[3],
+                token_modifiers_bitset: chunk[4],
+            })
+            .collect(),
+    )
+}
+
+fn translate_workspace_symbol_response(response: WorkspaceSymbolResponse) -> Vec<NavCompWorkspaceSymbol> {
+    match response {
+        WorkspaceSymbolResponse::Flat(symbols) => symbols
+            .into_iter()
+            .map(|symbol| NavCompWorkspaceSymbol {
+                name: symbol.name,
+                symbol_type: symbol.kind.into(),
+                container_name_op: symbol.container_name,
+                location: location_to_symbol_usage(symbol.location),
+            })
+            .collect(),
+        WorkspaceSymbolResponse::Nested(symbols) => symbols
+            .into_iter()
+            .map(|symbol| NavCompWorkspaceSymbol {
+                name: symbol.name,
+                symbol_type: symbol.kind.into(),
+                container_name_op: symbol.container_name,
+                location: match symbol.location {
+                    OneOf::Left(location) => location_to_symbol_usage(location),
+                    // we don't declare resolve support, but in case server sends it anyway, beginning of file will do.
+                    OneOf::Right(workspace_location) => StupidSymbolUsage {
+                        path: workspace_location.uri.to_string(),
+                        stupid_range: (StupidCursor::new(0, 0), StupidCursor::new(0, 0)),
+                    },
+                },
+            })
+            .collect(),
+    }
+}
+
+fn hierarchy_item_location(uri: &Url, range: lsp_types::Range) -> StupidSymbolUsage {
+    StupidSymbolUsage {
+        path: uri.to_string(),
+        stupid_range: (range.start.into(), range.end.into()),
+    }
+}
+
+fn to_raw<T: serde::Serialize>(item: &T) -> serde_json::Value {
+    match serde_json::to_value(item) {
+        Ok(value) => value,
+        Err(e) => {
+            error!("failed to serialize hierarchy item: {:?}, it won't expand", e);
+            serde_json::Value::Null
+        }
+    }
+}
+
+// Call site is given for incoming calls only, otherwise location is the name of the item.
+fn translate_call_hierarchy_item(item: CallHierarchyItem, call_site_op: Option<lsp_types::Range>) -> NavCompHierarchyItem {
+    let location = hierarchy_item_location(&item.uri, call_site_op.unwrap_or(item.selection_range));
+
+    NavCompHierarchyItem {
+        raw: to_raw(&item),
+        name: item.name,
+        symbol_type: item.kind.into(),
+        detail_op: item.detail,
+        location,
+    }
+}
+
+fn translate_type_hierarchy_item(item: TypeHierarchyItem) -> NavCompHierarchyItem {
+    let location = hierarchy_item_location(&item.uri, item.selection_range);
+
+    NavCompHierarchyItem {
+        raw: to_raw(&item),
+        name: item.name,
+        symbol_type: item.kind.into(),
+        detail_op: item.detail,
+        location,
+    }
+}
+
+// Padding is rendered as plain spaces, label parts are just glued together.
+fn translate_inlay_hint(hint: InlayHint) -> Label {
+    let mut text = match hint.label {
+        InlayHintLabel::String(text) => text,
+        InlayHintLabel::LabelParts(parts) => parts.into_iter().map(|part| part.value).collect(),
+    };
+
+    if hint.padding_left == Some(true) {
+        text.insert(0, ' ');
+    }
+    if hint.padding_right == Some(true) {
+        text.push(' ');
+    }
+
+    Label::new(
+        LabelPos::InlineStupid {
+            stupid_cursor: hint.position.into(),
+        },
+        LabelStyle::TypeAnnotation,
+        Box::new(text),
+    )
+}
+
 fn location_link_to_symbol_usage(loc: LocationLink) -> StupidSymbolUsage {
     StupidSymbolUsage {
         path: loc.target_uri.to_string(),
@@ -140,11 +635,33 @@ impl NavCompProvider for NavCompProviderLsp {
         lock.text_document_did_open(url, file_contents.to_string());
     }
 
-    fn submit_edit_event(&self, path: &SPath, file_contents: ropey::Rope) {
+    fn submit_edit_event(&self, path: &SPath, edit_event: EditEvent) {
         let url = unpack_unit_e!(path.to_url().ok(), "failed to convert spath [{}] to url", path);
-        let mut lock = unpack_unit_e!(self.lsp.try_write().ok(), "failed acquiring lock",);
+        // Edits are already taken out of the buffer, so unlike requests, this one can't be skipped when
+        // the lock is busy: server's copy of the document would never catch up. Lock is held only for
+        // the time of writing a message, so it's fine to wait.
+        let mut lock = unpack_unit_e!(self.lsp.write().ok(), "lsp lock poisoned, edits are lost",);
+
+        let result = match edit_event {
+            EditEvent::Incremental { edits, .. } if lock.supports_incremental_sync() => {
+                lock.text_document_did_change_incremental(url, edits.into_iter().map(stupid_substitute_to_content_change).collect())
+            }
+            other => lock.text_document_did_change(url, other.file_contents().to_string()),
+        };
 
-        lock.text_document_did_change(url, file_contents.to_string());
+        if let Err(e) = result {
+            self.eat_write_error(e);
+        }
+    }
+
+    fn file_changed_on_disk(&self, path: &SPath) {
+        let url = unpack_unit_e!(path.to_url().ok(), "failed to convert spath [{}] to url", path);
+        // same as with edit events, skipping it would leave server with stale contents
+        let mut lock = unpack_unit_e!(self.lsp.write().ok(), "lsp lock poisoned, change is lost",);
+
+        if let Err(e) = lock.workspace_did_change_watched_files(vec![url]) {
+            self.eat_write_error(e);
+        }
     }
 
     fn completions(&self, path: SPath, cursor: StupidCursor, _trigger: Option<String>) -> Option<CompletionsPromise> {
@@ -263,30 +780,201 @@ impl NavCompProvider for NavCompProviderLsp {
         let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
 
         match lock.text_document_goto_definition(url, cursor) {
+            Ok(resp) => Some(Box::new(resp.map(goto_response_to_symbol_usages))),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn go_to_type_definition(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_type_definition(url, cursor) {
+            Ok(resp) => Some(Box::new(resp.map(goto_response_to_symbol_usages))),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn go_to_implementations(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_implementation(url, cursor) {
+            Ok(resp) => Some(Box::new(resp.map(goto_response_to_symbol_usages))),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn go_to_declaration(&self, path: &SPath, cursor: StupidCursor) -> Option<SymbolUsagesPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_declaration(url, cursor) {
+            Ok(resp) => Some(Box::new(resp.map(goto_response_to_symbol_usages))),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn hover(&self, path: &SPath, cursor: StupidCursor) -> Option<HoverPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_hover(url, cursor) {
             Ok(resp) => {
-                let new_promise = resp.map(|response| match response {
-                    None => Vec::new(),
-                    Some(items) => {
-                        let mut locations: Vec<StupidSymbolUsage> = Vec::default();
+                let new_promise = resp.map(|response| {
+                    response
+                        .map(|hover| hover_contents_to_string(hover.contents))
+                        .filter(|text| !text.trim().is_empty())
+                });
 
-                        match items {
-                            GotoDefinitionResponse::Scalar(item) => {
-                                locations.push(location_to_symbol_usage(item));
-                            }
-                            GotoDefinitionResponse::Array(links) => {
-                                for link in links {
-                                    locations.push(location_to_symbol_usage(link));
-                                }
-                            }
-                            GotoDefinitionResponse::Link(links) => {
-                                locations = links.into_iter().map(location_link_to_symbol_usage).collect();
-                            }
+                Some(Box::new(new_promise))
+            }
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn signature_help(&self, path: &SPath, cursor: StupidCursor) -> Option<SignatureHelpPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_signature_help(url, cursor) {
+            Ok(resp) => {
+                let new_promise = resp.map(|response| response.and_then(translate_signature_help));
+                Some(Box::new(new_promise))
+            }
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn signature_help_triggers(&self, _path: &SPath) -> &Vec<String> {
+        &self.signature_help_triggers
+    }
+
+    fn rename(&self, path: &SPath, cursor: StupidCursor, new_name: String) -> Option<RenamePromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_rename(url, cursor, new_name) {
+            Ok(resp) => {
+                let new_promise = resp.map(|response| response.map(translate_workspace_edit));
+                Some(Box::new(new_promise))
+            }
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn code_actions(&self, path: &SPath, stupid_range: (StupidCursor, StupidCursor)) -> Option<CodeActionsPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_code_action(url, stupid_range) {
+            Ok(resp) => {
+                let new_promise = resp.map(|response| {
+                    response
+                        .map(|items| items.into_iter().filter_map(translate_code_action).collect())
+                        .unwrap_or_default()
+                });
+                Some(Box::new(new_promise))
+            }
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn execute_command(&self, command: &NavCompCommand) -> bool {
+        if !self.execute_commands.contains(&command.command) {
+            // like rust-analyzer's "editor.action.triggerParameterHints", these are for client to handle.
+            debug!("command {} not supported by server, ignoring", command.command);
+            return false;
+        }
+
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), false, "failed acquiring lock");
+
+        // Result is ignored, if server wants to edit something, it will ask for it.
+        match lock.workspace_execute_command(command.command.clone(), command.arguments.clone()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.eat_write_error(e);
+                false
+            }
+        }
+    }
+
+    fn take_apply_edit_requests(&self) -> Vec<NavCompApplyEditRequest> {
+        let lock = unpack_or!(self.lsp.try_read().ok(), vec![], "lsp lock taken, apply edit requests will wait");
+
+        lock.take_apply_edit_requests()
+            .into_iter()
+            .map(|request| {
+                let responder = request.responder;
+                NavCompApplyEditRequest::new(
+                    request.params.label,
+                    translate_workspace_edit(request.params.edit),
+                    Box::new(move |applied| {
+                        let response = lsp_types::ApplyWorkspaceEditResponse {
+                            applied,
+                            failure_reason: None,
+                            failed_change: None,
+                        };
+                        if let Err(e) = responder.respond(response) {
+                            error!("failed answering workspace/applyEdit: {:?}", e);
                         }
+                    }),
+                )
+            })
+            .collect()
+    }
 
-                        locations
-                    }
+    fn inlay_hints(&self, path: &SPath, stupid_range: (StupidCursor, StupidCursor)) -> Option<InlayHintsPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.text_document_inlay_hint(url, stupid_range) {
+            Ok(resp) => {
+                let new_promise = resp.map(|response| {
+                    response
+                        .map(|hints| hints.into_iter().map(translate_inlay_hint).collect())
+                        .unwrap_or_default()
                 });
+                Some(Box::new(new_promise))
+            }
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn document_symbols(&self, path: &SPath) -> Option<DocumentSymbolsPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
 
+        match lock.text_document_document_symbol(url) {
+            Ok(resp) => {
+                let new_promise = resp.map(|response| response.map(translate_document_symbol_response).unwrap_or_default());
                 Some(Box::new(new_promise))
             }
             Err(e) => {
@@ -296,36 +984,98 @@ impl NavCompProvider for NavCompProviderLsp {
         }
     }
 
-    fn todo_reformat(&self, path: &SPath) -> Option<FormattingPromise> {
+    fn document_highlights(&self, path: &SPath, cursor: StupidCursor) -> Option<DocumentHighlightsPromise> {
         let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
         let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
 
-        match lock.text_document_formatting(url) {
+        match lock.text_document_document_highlight(url, cursor) {
             Ok(resp) => {
-                let new_promise = resp.map(|response| match response {
-                    None => None,
-                    Some(vte) => {
-                        let stupid_edits: Vec<StupidSubstituteMessage> = vte
-                            .into_iter()
-                            .map(|te| StupidSubstituteMessage {
-                                substitute: te.new_text,
-                                stupid_range: (
-                                    StupidCursor {
-                                        char_idx_0b: te.range.start.character,
-                                        line_0b: te.range.start.line,
-                                    },
-                                    StupidCursor {
-                                        char_idx_0b: te.range.end.character,
-                                        line_0b: te.range.end.line,
-                                    },
-                                ),
-                            })
-                            .collect();
-
-                        Some(stupid_edits)
-                    }
+                let new_promise = resp.map(|response| {
+                    response
+                        .map(|highlights| highlights.into_iter().map(translate_document_highlight).collect())
+                        .unwrap_or_default()
                 });
+                Some(Box::new(new_promise))
+            }
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn semantic_tokens(&self, path: &SPath) -> Option<SemanticTokensPromise> {
+        let legend = unpack_or!(self.semantic_tokens_legend.clone(), None, "server does not provide semantic tokens");
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        let previous_op = if self.semantic_tokens_delta {
+            self.file_to_semantic_tokens.read().get(&url).cloned()
+        } else {
+            None
+        };
+
+        let cache = self.file_to_semantic_tokens.clone();
 
+        // whatever the answer, the complete tokens are remembered for the next delta.
+        let remember_and_decode = move |url: Url, result_id_op: Option<String>, data: Vec<SemanticToken>| {
+            let tokens = decode_semantic_tokens(&legend, &data);
+            match result_id_op {
+                Some(result_id) => cache.write().insert(url, (result_id, data)),
+                None => cache.write().remove(&url),
+            };
+            Some(tokens)
+        };
+        let forget = {
+            let cache = self.file_to_semantic_tokens.clone();
+            move |url: Url| {
+                cache.write().remove(&url);
+            }
+        };
+
+        let result: Result<SemanticTokensPromise, LspWriteError> = match previous_op {
+            Some((previous_result_id, previous_data)) => lock
+                .text_document_semantic_tokens_full_delta(url.clone(), previous_result_id)
+                .map(|resp| -> SemanticTokensPromise {
+                    Box::new(
+                        resp.map(move |response| match semantic_tokens_after_delta(&previous_data, response) {
+                            Some((result_id_op, data)) => remember_and_decode(url, result_id_op, data),
+                            None => {
+                                // forgetting result id, so the next request is for full tokens
+                                debug!("no usable semantic tokens delta");
+                                forget(url);
+                                None
+                            }
+                        }),
+                    )
+                }),
+            None => lock
+                .text_document_semantic_tokens_full(url.clone())
+                .map(|resp| -> SemanticTokensPromise {
+                    Box::new(resp.map(move |response| match response {
+                        Some(SemanticTokensResult::Tokens(tokens)) => remember_and_decode(url, tokens.result_id, tokens.data),
+                        // I don't ask for partial results, so this is just in case
+                        Some(SemanticTokensResult::Partial(partial)) => remember_and_decode(url, None, partial.data),
+                        None => remember_and_decode(url, None, vec![]),
+                    }))
+                }),
+        };
+
+        match result {
+            Ok(promise) => Some(promise),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn workspace_symbols(&self, query: &str) -> Option<WorkspaceSymbolsPromise> {
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        match lock.workspace_symbol(query.to_string()) {
+            Ok(resp) => {
+                let new_promise = resp.map(|response| response.map(translate_workspace_symbol_response).unwrap_or_default());
                 Some(Box::new(new_promise))
             }
             Err(e) => {
@@ -335,6 +1085,150 @@ impl NavCompProvider for NavCompProviderLsp {
         }
     }
 
+    fn prepare_hierarchy(&self, path: &SPath, cursor: StupidCursor, kind: HierarchyKind) -> Option<HierarchyPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        let result: Result<HierarchyPromise, LspWriteError> = match kind {
+            HierarchyKind::IncomingCalls | HierarchyKind::OutgoingCalls => lock.call_hierarchy_prepare(url, cursor).map(|resp| {
+                let new_promise = resp.map(|response| {
+                    response
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|item| translate_call_hierarchy_item(item, None))
+                        .collect()
+                });
+                Box::new(new_promise) as HierarchyPromise
+            }),
+            HierarchyKind::Supertypes | HierarchyKind::Subtypes => lock.type_hierarchy_prepare(url, cursor).map(|resp| {
+                let new_promise = resp.map(|response| {
+                    response
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(translate_type_hierarchy_item)
+                        .collect()
+                });
+                Box::new(new_promise) as HierarchyPromise
+            }),
+        };
+
+        match result {
+            Ok(promise) => Some(promise),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn hierarchy_children(&self, item: &NavCompHierarchyItem, kind: HierarchyKind) -> Option<HierarchyPromise> {
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        let result: Result<HierarchyPromise, LspWriteError> = match kind {
+            HierarchyKind::IncomingCalls | HierarchyKind::OutgoingCalls => {
+                let lsp_item: CallHierarchyItem = unpack_or_e!(
+                    serde_json::from_value(item.raw.clone()).ok(),
+                    None,
+                    "item {} is not a call hierarchy item",
+                    item.name
+                );
+
+                if kind == HierarchyKind::IncomingCalls {
+                    lock.call_hierarchy_incoming_calls(lsp_item).map(|resp| {
+                        let new_promise = resp.map(|response| {
+                            response
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|call| {
+                                    let call_site_op = call.from_ranges.first().cloned();
+                                    translate_call_hierarchy_item(call.from, call_site_op)
+                                })
+                                .collect()
+                        });
+                        Box::new(new_promise) as HierarchyPromise
+                    })
+                } else {
+                    lock.call_hierarchy_outgoing_calls(lsp_item).map(|resp| {
+                        let new_promise = resp.map(|response| {
+                            response
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|call| translate_call_hierarchy_item(call.to, None))
+                                .collect()
+                        });
+                        Box::new(new_promise) as HierarchyPromise
+                    })
+                }
+            }
+            HierarchyKind::Supertypes | HierarchyKind::Subtypes => {
+                let lsp_item: TypeHierarchyItem = unpack_or_e!(
+                    serde_json::from_value(item.raw.clone()).ok(),
+                    None,
+                    "item {} is not a type hierarchy item",
+                    item.name
+                );
+
+                if kind == HierarchyKind::Supertypes {
+                    lock.type_hierarchy_supertypes(lsp_item).map(|resp| {
+                        let new_promise = resp.map(|response| {
+                            response
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(translate_type_hierarchy_item)
+                                .collect()
+                        });
+                        Box::new(new_promise) as HierarchyPromise
+                    })
+                } else {
+                    lock.type_hierarchy_subtypes(lsp_item).map(|resp| {
+                        let new_promise = resp.map(|response| {
+                            response
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(translate_type_hierarchy_item)
+                                .collect()
+                        });
+                        Box::new(new_promise) as HierarchyPromise
+                    })
+                }
+            }
+        };
+
+        match result {
+            Ok(promise) => Some(promise),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
+    fn format(&self, path: &SPath, stupid_range_op: Option<(StupidCursor, StupidCursor)>) -> Option<FormattingPromise> {
+        let url = unpack_or_e!(path.to_url().ok(), None, "failed to convert spath [{}] to url", path);
+        let mut lock = unpack_or_e!(self.lsp.try_write().ok(), None, "failed acquiring lock");
+
+        let result: Result<FormattingPromise, LspWriteError> = match stupid_range_op {
+            None => lock.text_document_formatting(url).map(|resp| {
+                let new_promise =
+                    resp.map(|response| response.map(|edits| edits.into_iter().map(text_edit_to_stupid_substitute).collect()));
+                Box::new(new_promise) as FormattingPromise
+            }),
+            Some(stupid_range) => lock.text_document_range_formatting(url, stupid_range).map(|resp| {
+                let new_promise =
+                    resp.map(|response| response.map(|edits| edits.into_iter().map(text_edit_to_stupid_substitute).collect()));
+                Box::new(new_promise) as FormattingPromise
+            }),
+        };
+
+        match result {
+            Ok(promise) => Some(promise),
+            Err(e) => {
+                self.eat_write_error(e);
+                None
+            }
+        }
+    }
+
     fn file_closed(&self, path: &SPath) {
         let url = unpack_unit_e!(path.to_url().ok(), "failed to convert spath [{}] to url", path);
         let mut lock = unpack_unit_e!(self.lsp.try_write().ok(), "failed acquiring lock",);
@@ -376,8 +1270,93 @@ impl NavCompProvider for NavCompProviderLsp {
         &self.todo_tick_sender
     }
 
-    fn todo_is_healthy(&self) -> bool {
-        //TODO
+    fn progress(&self) -> Vec<NavCompProgress> {
+        let lock = unpack_or_e!(self.progress.read().ok(), vec![], "failed acquiring progress lock");
+
+        lock.values()
+            .map(|item| NavCompProgress {
+                title: item.title.clone(),
+                message: item.message.clone(),
+                percentage: item.percentage,
+            })
+            .collect()
+    }
+
+    fn is_healthy(&self) -> bool {
+        // if locks are taken, someone is using the server, so I assume it's alive.
+        let crashed = self.crashed.try_read().map(|lock| *lock).unwrap_or(false);
+        let reader_alive = self.lsp.try_read().map(|lock| !lock.wait().is_finished()).unwrap_or(true);
+
+        !crashed && reader_alive
+    }
+
+    /*
+    New server is started and initialized before the old one is swapped out, so calls made in the
+    meantime don't have to wait for it. Capabilities are assumed not to change between runs.
+     */
+    fn restart(&self) -> bool {
+        let (command, workspace_root, language, settings) = match self.lsp.try_read() {
+            Ok(lock) => (
+                lock.command().clone(),
+                lock.workspace_root_path().clone(),
+                lock.language(),
+                lock.settings().cloned(),
+            ),
+            Err(e) => {
+                error!("failed acquiring lock: {:?}", e);
+                return false;
+            }
+        };
+
+        debug!("restarting lsp server {:?}", command.path);
+
+        let mut new_lsp = unpack_or_e!(
+            LspWrapper::new(
+                command,
+                workspace_root,
+                language,
+                settings,
+                self.progress.clone(),
+                self.todo_tick_sender.clone(),
+                self.read_error_channel.0.clone(),
+            ),
+            false,
+            "failed to start lsp server again"
+        );
+
+        if let Err(e) = new_lsp.initialize() {
+            error!("failed to initialize restarted lsp server: {:?}", e);
+            new_lsp.kill();
+            return false;
+        }
+
+        match self.lsp.try_write() {
+            Ok(mut lock) => {
+                let mut old_lsp = std::mem::replace(&mut *lock, new_lsp);
+                old_lsp.kill();
+            }
+            Err(e) => {
+                error!("failed acquiring lock: {:?}", e);
+                new_lsp.kill();
+                return false;
+            }
+        }
+
+        // labels are versioned per server, new one starts counting from scratch.
+        self.file_to_labels.write().clear();
+        // and result ids of semantic tokens mean nothing to the new server.
+        self.file_to_semantic_tokens.write().clear();
+        // progress of the dead one will never end.
+        match self.progress.write() {
+            Ok(mut lock) => lock.clear(),
+            Err(e) => error!("failed acquiring progress lock: {:?}", e),
+        }
+
+        match self.crashed.try_write() {
+            Ok(mut lock) => *lock = false,
+            Err(e) => error!("failed acquiring lock for crashed field: {:?}", e),
+        }
+
         true
     }
 }
@@ -406,6 +1385,43 @@ fn translate_completion_item(i: lsp_types::CompletionItem) -> Completion {
     }
 }
 
+// LSP offsets are in UTF-16 code units by default, I want chars.
+fn utf16_offset_to_char_idx(s: &str, offset: u32) -> usize {
+    let mut utf16_idx: usize = 0;
+    for (char_idx, c) in s.chars().enumerate() {
+        if utf16_idx >= offset as usize {
+            return char_idx;
+        }
+        utf16_idx += c.len_utf16();
+    }
+    s.chars().count()
+}
+
+fn translate_signature_help(help: lsp_types::SignatureHelp) -> Option<NavCompSignature> {
+    let active_signature = help.active_signature.unwrap_or(0) as usize;
+    let signature = help.signatures.into_iter().nth(active_signature)?;
+
+    let active_parameter = signature
+        .active_parameter
+        .or(help.active_parameter)
+        .and_then(|idx| signature.parameters.as_ref().and_then(|params| params.get(idx as usize)))
+        .and_then(|param| match &param.label {
+            ParameterLabel::Simple(substring) => signature.label.find(substring.as_str()).map(|byte_idx| {
+                let begin = signature.label[..byte_idx].chars().count();
+                (begin, begin + substring.chars().count())
+            }),
+            ParameterLabel::LabelOffsets([begin, end]) => Some((
+                utf16_offset_to_char_idx(&signature.label, *begin),
+                utf16_offset_to_char_idx(&signature.label, *end),
+            )),
+        });
+
+    Some(NavCompSignature {
+        label: signature.label,
+        active_parameter,
+    })
+}
+
 impl From<SymbolKind> for SymbolType {
     fn from(sk: SymbolKind) -> Self {
         match sk {
@@ -439,3 +1455,125 @@ impl From<SymbolKind> for SymbolType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lsp_types::{SemanticToken, SemanticTokensDelta, SemanticTokensEdit, SemanticTokensFullDeltaResult};
+
+    use crate::primitives::stupid_cursor::StupidCursor;
+    use crate::w7e::navcomp_provider_lsp::{
+        apply_semantic_tokens_edits, decode_semantic_tokens, semantic_tokens_after_delta, SemanticTokensLegend,
+    };
+
+    fn token(delta_line: u32, delta_start: u32, length: u32, token_type: u32, token_modifiers_bitset: u32) -> SemanticToken {
+        SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset,
+        }
+    }
+
+    fn legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec![Arc::new("variable".to_string()), Arc::new("macro".to_string())],
+            token_modifiers: vec![Arc::new("declaration".to_string()), Arc::new("mutable".to_string())],
+        }
+    }
+
+    #[test]
+    fn semantic_tokens_are_decoded_from_relative_positions() {
+        // let mut counter = 0;
+        // counter += 1; println!("{}", counter);
+        let data = vec![
+            token(0, 8, 7, 0, 0b11),
+            token(1, 0, 7, 0, 0b10),
+            token(0, 14, 7, 1, 0),
+            token(0, 9, 1, 7, 0),
+        ];
+
+        let tokens = decode_semantic_tokens(&legend(), &data);
+
+        // last one has type not present in legend
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].stupid_range, (StupidCursor::new(8, 0), StupidCursor::new(15, 0)));
+        assert_eq!(tokens[0].modifiers.len(), 2);
+        assert_eq!(tokens[1].stupid_range, (StupidCursor::new(0, 1), StupidCursor::new(7, 1)));
+        assert_eq!(tokens[1].modifiers, vec![Arc::new("mutable".to_string())]);
+        assert_eq!(tokens[2].stupid_range, (StupidCursor::new(14, 1), StupidCursor::new(21, 1)));
+        assert_eq!(tokens[2].token_type.as_str(), "macro");
+    }
+
+    #[test]
+    fn semantic_tokens_edits_address_flat_array() {
+        let old = vec![token(0, 1, 2, 0, 0), token(1, 0, 3, 0, 0), token(1, 4, 5, 1, 0)];
+
+        let edits = vec![
+            // changing length of the first token
+            SemanticTokensEdit {
+                start: 2,
+                delete_count: 1,
+                data: None,
+            },
+            // replacing the last one
+            SemanticTokensEdit {
+                start: 10,
+                delete_count: 5,
+                data: Some(vec![token(2, 0, 1, 1, 0)]),
+            },
+        ];
+
+        // data of edit is a list of tokens, so removing a single integer breaks the array
+        assert!(apply_semantic_tokens_edits(&old, edits.clone()).is_none());
+
+        let edits = vec![
+            SemanticTokensEdit {
+                start: 0,
+                delete_count: 5,
+                data: Some(vec![token(0, 1, 4, 0, 0)]),
+            },
+            edits[1].clone(),
+        ];
+        let new = apply_semantic_tokens_edits(&old, edits).unwrap();
+        assert_eq!(new, vec![token(0, 1, 4, 0, 0), token(1, 0, 3, 0, 0), token(2, 0, 1, 1, 0)]);
+
+        let out_of_bounds = SemanticTokensEdit {
+            start: 14,
+            delete_count: 5,
+            data: None,
+        };
+        assert!(apply_semantic_tokens_edits(&old, vec![out_of_bounds]).is_none());
+    }
+
+    #[test]
+    fn bad_semantic_tokens_delta_is_not_an_empty_answer() {
+        let old = vec![token(0, 1, 2, 0, 0)];
+
+        let bad_delta = SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+            result_id: Some("2".to_string()),
+            edits: vec![SemanticTokensEdit {
+                start: 7,
+                delete_count: 1,
+                data: None,
+            }],
+        });
+        assert!(semantic_tokens_after_delta(&old, Some(bad_delta)).is_none());
+        assert!(semantic_tokens_after_delta(&old, None).is_none());
+
+        let good_delta = SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+            result_id: Some("3".to_string()),
+            edits: vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 0,
+                data: Some(vec![token(1, 0, 3, 1, 0)]),
+            }],
+        });
+        assert_eq!(
+            semantic_tokens_after_delta(&old, Some(good_delta)),
+            Some((Some("3".to_string()), vec![token(0, 1, 2, 0, 0), token(1, 0, 3, 1, 0)]))
+        );
+    }
+}
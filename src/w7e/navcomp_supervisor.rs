@@ -0,0 +1,338 @@
+use std::sync::{Arc, RwLock, Weak};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use log::{debug, error, warn};
+use ropey::Rope;
+
+use crate::experiments::buffer_register::{BufferRegister, BufferRegisterRef};
+use crate::fs::path::SPath;
+use crate::tsw::lang_id::LangId;
+use crate::w7e::handler::NavCompRef;
+use crate::w7e::navcomp_group::{NavCompGroup, NavCompHealth, NavCompTick};
+
+/*
+Watches navcomps and restarts the ones that died (see the rant at the top of navcomp_provider.rs).
+After a successful restart, all open buffers belonging to given navcomp are opened again, since
+the new server knows nothing about them. If restarts keep failing, I give up and the status bar
+says so. A server that dies shortly after each restart counts as failing too, otherwise it would be
+restarted forever.
+
+Supervisor holds only weak references, so it terminates together with the editor (or a test).
+ */
+pub struct NavCompSupervisor {
+    navcomp_group: Weak<RwLock<NavCompGroup>>,
+    buffer_register: Weak<RwLock<BufferRegister>>,
+
+    watched: Vec<Watched>,
+}
+
+struct Watched {
+    navcomp: NavCompRef,
+    state: WatchedState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WatchedState {
+    // failures count restarts too, they are forgotten only after navcomp stays healthy for MIN_HEALTHY_UPTIME
+    Healthy { failures: u32, since: Instant },
+    Restarting { failures: u32, next_attempt: Instant },
+    Down,
+}
+
+impl WatchedState {
+    fn new_healthy(now: Instant) -> Self {
+        WatchedState::Healthy { failures: 0, since: now }
+    }
+
+    fn health(&self) -> Option<NavCompHealth> {
+        match self {
+            WatchedState::Healthy { .. } => None,
+            WatchedState::Restarting { .. } => Some(NavCompHealth::Restarting),
+            WatchedState::Down => Some(NavCompHealth::Down),
+        }
+    }
+
+    /*
+    Returns new state, if it changed. Restart is attempted only when it's due, and is expected to
+    return whether it succeeded.
+     */
+    fn next(self, is_healthy: bool, now: Instant, restart: impl FnOnce() -> bool) -> Option<WatchedState> {
+        match self {
+            WatchedState::Healthy { failures, since } if !is_healthy => {
+                // crashing right after restart is as bad as failing to restart
+                let failures = if now < since + NavCompSupervisor::MIN_HEALTHY_UPTIME {
+                    failures
+                } else {
+                    0
+                };
+
+                if failures >= NavCompSupervisor::MAX_FAILURES {
+                    Some(WatchedState::Down)
+                } else {
+                    Some(WatchedState::Restarting {
+                        failures,
+                        next_attempt: now + NavCompSupervisor::BASE_BACKOFF * 2u32.pow(failures),
+                    })
+                }
+            }
+            WatchedState::Healthy { failures, since } if failures > 0 && now >= since + NavCompSupervisor::MIN_HEALTHY_UPTIME => {
+                Some(WatchedState::new_healthy(since))
+            }
+            WatchedState::Restarting { failures, next_attempt } if now >= next_attempt => {
+                if restart() {
+                    Some(WatchedState::Healthy {
+                        failures: failures + 1,
+                        since: now,
+                    })
+                } else if failures + 1 >= NavCompSupervisor::MAX_FAILURES {
+                    Some(WatchedState::Down)
+                } else {
+                    Some(WatchedState::Restarting {
+                        failures: failures + 1,
+                        next_attempt: now + NavCompSupervisor::BASE_BACKOFF * 2u32.pow(failures + 1),
+                    })
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl NavCompSupervisor {
+    pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+    // doubled with each failure
+    pub const BASE_BACKOFF: Duration = Duration::from_millis(500);
+    pub const MAX_FAILURES: u32 = 5;
+    // restarted navcomp has to stay alive this long before its failures are forgotten
+    pub const MIN_HEALTHY_UPTIME: Duration = Duration::from_secs(30);
+
+    pub fn start(navcomp_group: &Arc<RwLock<NavCompGroup>>, buffer_register: &BufferRegisterRef) -> JoinHandle<()> {
+        let mut supervisor = NavCompSupervisor {
+            navcomp_group: Arc::downgrade(navcomp_group),
+            buffer_register: Arc::downgrade(buffer_register),
+            watched: vec![],
+        };
+
+        thread::spawn(move || supervisor.run())
+    }
+
+    fn run(&mut self) {
+        loop {
+            thread::sleep(Self::POLL_INTERVAL);
+
+            let navcomp_group = match self.navcomp_group.upgrade() {
+                Some(group) => group,
+                None => break,
+            };
+            let buffer_register = match self.buffer_register.upgrade() {
+                Some(register) => register,
+                None => break,
+            };
+
+            self.check(&navcomp_group, &buffer_register);
+        }
+
+        debug!("navcomp supervisor terminates");
+    }
+
+    fn check(&mut self, navcomp_group: &RwLock<NavCompGroup>, buffer_register: &RwLock<BufferRegister>) {
+        let navcomps = match navcomp_group.try_read() {
+            Ok(lock) => lock.navcomps(),
+            Err(_) => {
+                // someone is adding navcomps, I'll check next time
+                return;
+            }
+        };
+
+        self.watched
+            .retain(|watched| navcomps.iter().any(|navcomp| navcomp.ptr_eq(&watched.navcomp)));
+
+        for navcomp in navcomps {
+            let idx = match self.watched.iter().position(|watched| watched.navcomp.ptr_eq(&navcomp)) {
+                Some(idx) => idx,
+                None => {
+                    self.watched.push(Watched {
+                        navcomp,
+                        state: WatchedState::new_healthy(Instant::now()),
+                    });
+                    self.watched.len() - 1
+                }
+            };
+
+            let watched = &mut self.watched[idx];
+            let old_state = watched.state;
+            let is_healthy = watched.navcomp.is_healthy();
+            let navcomp = watched.navcomp.clone();
+
+            let new_state = match old_state.next(is_healthy, Instant::now(), || navcomp.restart()) {
+                Some(new_state) => new_state,
+                None => continue,
+            };
+
+            match (&old_state, &new_state) {
+                (WatchedState::Healthy { .. }, WatchedState::Restarting { .. }) => {
+                    warn!("navcomp {:?} is not healthy, scheduling restart", navcomp);
+                }
+                (WatchedState::Restarting { .. }, WatchedState::Healthy { .. }) => {
+                    debug!("navcomp {:?} restarted", navcomp);
+                    reopen_files(navcomp_group, buffer_register, &navcomp);
+                }
+                (WatchedState::Restarting { .. }, WatchedState::Restarting { .. }) => {
+                    warn!("navcomp {:?} failed to restart, will try again", navcomp);
+                }
+                (_, WatchedState::Down) => {
+                    error!("navcomp {:?} keeps failing, giving up", navcomp);
+                }
+                _ => {}
+            }
+
+            watched.state = new_state;
+            if old_state.health() != new_state.health() {
+                report_health(navcomp_group, &navcomp, new_state.health());
+            }
+        }
+    }
+}
+
+fn report_health(navcomp_group: &RwLock<NavCompGroup>, navcomp: &NavCompRef, health_op: Option<NavCompHealth>) {
+    let (langs, health, sender) = match navcomp_group.read() {
+        Ok(lock) => (lock.langs_of(navcomp), lock.health().clone(), lock.todo_sender().clone()),
+        Err(e) => {
+            error!("failed acquiring navcomp group lock: {:?}", e);
+            return;
+        }
+    };
+
+    match health.write() {
+        Ok(mut lock) => {
+            for lang_id in langs.iter() {
+                match health_op {
+                    Some(health) => lock.insert(*lang_id, health),
+                    None => lock.remove(lang_id),
+                };
+            }
+        }
+        Err(e) => {
+            error!("failed acquiring health lock: {:?}", e);
+            return;
+        }
+    }
+
+    for lang_id in langs {
+        if let Err(e) = sender.try_send(NavCompTick::HealthChanged(lang_id)) {
+            error!("non-fatal: failed to send navcomp tick: {:?}", e);
+        }
+    }
+}
+
+fn reopen_files(navcomp_group: &RwLock<NavCompGroup>, buffer_register: &RwLock<BufferRegister>, navcomp: &NavCompRef) {
+    // contents are collected first, so buffers are not locked while navcomp is busy
+    let mut files: Vec<(SPath, Rope)> = Vec::new();
+    match buffer_register.read() {
+        Ok(register) => {
+            for (_, buffer_ref) in register.iter() {
+                let buffer = match buffer_ref.lock() {
+                    Some(buffer) => buffer,
+                    None => {
+                        warn!("failed locking buffer, it will not be reopened");
+                        continue;
+                    }
+                };
+
+                if let Some(path) = buffer.get_path() {
+                    files.push((path.clone(), buffer.text().rope().clone()));
+                }
+            }
+        }
+        Err(e) => {
+            error!("failed acquiring buffer register lock: {:?}", e);
+            return;
+        }
+    }
+
+    // same rule as NavCompGroup::get_navcomp_for
+    match navcomp_group.read() {
+        Ok(lock) => {
+            let langs: Vec<LangId> = lock.langs_of(navcomp);
+            files.retain(|(path, _)| lock.lang_for(path).map(|lang_id| langs.contains(&lang_id)).unwrap_or(false));
+        }
+        Err(e) => {
+            error!("failed acquiring navcomp group lock: {:?}", e);
+            return;
+        }
+    }
+
+    debug!("reopening {} files in {:?}", files.len(), navcomp);
+    for (path, rope) in files {
+        navcomp.file_open_for_edition(&path, rope);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::w7e::navcomp_supervisor::{NavCompSupervisor, WatchedState};
+
+    // runs state machine until a restart is due, then restarts with given result
+    fn restart(state: WatchedState, now: &mut Instant, succeeds: bool) -> WatchedState {
+        if let WatchedState::Restarting { next_attempt, .. } = state {
+            *now = next_attempt;
+        }
+        state.next(false, *now, || succeeds).unwrap()
+    }
+
+    #[test]
+    fn supervisor_gives_up_on_navcomp_crashing_right_after_restart_test() {
+        let mut now = Instant::now();
+        let mut state = WatchedState::new_healthy(now);
+
+        for _ in 0..NavCompSupervisor::MAX_FAILURES {
+            state = state.next(false, now, || panic!("restart is not due yet")).unwrap();
+            assert!(matches!(state, WatchedState::Restarting { .. }), "{:?}", state);
+
+            state = restart(state, &mut now, true);
+            assert!(matches!(state, WatchedState::Healthy { .. }), "{:?}", state);
+        }
+
+        assert_eq!(state.next(false, now, || true), Some(WatchedState::Down));
+    }
+
+    #[test]
+    fn supervisor_forgets_failures_after_uptime_test() {
+        let mut now = Instant::now();
+        let mut state = WatchedState::new_healthy(now);
+
+        state = state.next(false, now, || true).unwrap();
+        state = restart(state, &mut now, true);
+        assert!(matches!(state, WatchedState::Healthy { failures: 1, .. }), "{:?}", state);
+
+        // nothing happens while it's fresh
+        assert_eq!(state.next(true, now, || true), None);
+
+        now += NavCompSupervisor::MIN_HEALTHY_UPTIME;
+        state = state.next(true, now, || true).unwrap();
+        assert!(matches!(state, WatchedState::Healthy { failures: 0, .. }), "{:?}", state);
+
+        // so next crash is treated like the first one
+        assert!(matches!(
+            state.next(false, now, || true),
+            Some(WatchedState::Restarting { failures: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn supervisor_gives_up_after_failed_restarts_test() {
+        let mut now = Instant::now();
+        let mut state = WatchedState::new_healthy(now).next(false, now, || true).unwrap();
+
+        for _ in 0..NavCompSupervisor::MAX_FAILURES - 1 {
+            state = restart(state, &mut now, false);
+            assert!(matches!(state, WatchedState::Restarting { .. }), "{:?}", state);
+        }
+
+        assert_eq!(restart(state, &mut now, false), WatchedState::Down);
+    }
+}
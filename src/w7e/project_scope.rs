@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use log::debug;
 use serde::{Deserialize, Serialize};
 
+use crate::config::custom_lsp_config::CustomLspConfig;
 use crate::experiments::pretty_ron::ToPrettyRonString;
 use crate::fs::path::SPath;
 use crate::tsw::lang_id::LangId;
@@ -17,6 +18,17 @@ pub struct ProjectScope {
      */
     pub handler_id: Option<String>,
     pub handler: Option<Box<dyn Handler>>,
+
+    /*
+    Free-form settings passed to handler. LSP based ones serve them as answers to
+    workspace/configuration, so it's like {"rust-analyzer": {"cargo": {"features": "all"}}}.
+     */
+    pub handler_settings: Option<serde_json::Value>,
+
+    /*
+    Language server declared right here, for this scope only. handler_id has to match its name.
+     */
+    pub custom_lsp: Option<CustomLspConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,6 +36,10 @@ pub struct SerializableProjectScope {
     pub lang_id: LangId,
     pub path: PathBuf,
     pub handler_id_op: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler_settings: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_lsp: Option<CustomLspConfig>,
 }
 
 impl ToPrettyRonString for SerializableProjectScope {}
@@ -39,6 +55,8 @@ impl ProjectScope {
             lang_id: self.lang_id,
             path: self.path.relative_path(),
             handler_id_op: self.handler.as_ref().map(|h| h.handler_id().to_string()),
+            handler_settings: self.handler_settings.clone(),
+            custom_lsp: self.custom_lsp.clone(),
         }
     }
 
@@ -55,6 +73,8 @@ impl ProjectScope {
             path: ff,
             handler_id: sps.handler_id_op,
             handler: None,
+            handler_settings: sps.handler_settings,
+            custom_lsp: sps.custom_lsp,
         })
     }
 }
@@ -5,6 +5,9 @@ use crate::fs::path::SPath;
 use crate::tsw::lang_id::LangId;
 use crate::w7e::handler::{Handler, NavCompRef};
 use crate::w7e::handler_load_error::HandlerLoadError;
+use crate::w7e::inspector::LangInspector;
+use crate::w7e::python::inspector_python::PythonLangInspector;
+use crate::w7e::task::Task;
 
 pub const INIT_TIMEOUT: Duration = Duration::from_millis(2000);
 
@@ -19,7 +22,7 @@ impl Handler for PythonHandler {
         LangId::PYTHON3
     }
 
-    fn handler_id(&self) -> &'static str {
+    fn handler_id(&self) -> &str {
         "python3"
     }
 
@@ -30,6 +33,10 @@ impl Handler for PythonHandler {
     fn navcomp(&self) -> Option<NavCompRef> {
         self.navcomp.clone()
     }
+
+    fn tasks(&self) -> Vec<Task> {
+        vec![Task::new("pytest", &[], self.root.clone())]
+    }
 }
 
 /*
@@ -40,13 +47,10 @@ impl PythonHandler {
             return Err(HandlerLoadError::NotAProject);
         }
 
-        let compile_commands_file = ff.descendant_checked("requirements.txt").ok_or(HandlerLoadError::NotAProject)?;
-        if !compile_commands_file.is_file() {
+        if !PythonLangInspector::new().is_project_dir(&ff) {
             return Err(HandlerLoadError::NotAProject);
         }
 
-        // let contents = compile_commands_file.read_entire_file()?;
-
         Ok(PythonHandler {
             root: ff,
             navcomp: navcomp_op,
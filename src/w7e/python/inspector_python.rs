@@ -2,24 +2,31 @@ use crate::fs::path::SPath;
 use crate::tsw::lang_id::LangId;
 use crate::w7e::inspector::LangInspector;
 
-pub struct CppLangInspector {}
+// any of these in a directory makes it a python project
+pub const PYTHON_PROJECT_FILES: [&str; 3] = ["requirements.txt", "pyproject.toml", "setup.py"];
 
-impl LangInspector for CppLangInspector {
+#[derive(Default)]
+pub struct PythonLangInspector {}
+
+impl LangInspector for PythonLangInspector {
     fn lang_id(&self) -> LangId {
-        LangId::CPP
+        LangId::PYTHON3
+    }
+
+    fn handler_id(&self) -> &'static str {
+        "python3"
     }
 
     fn is_project_dir(&self, ff: &SPath) -> bool {
         ff.is_dir()
-            && ff
-                .descendant_checked("compile_commands.json")
-                .map(|desc| desc.is_file())
-                .unwrap_or(false)
+            && PYTHON_PROJECT_FILES
+                .iter()
+                .any(|file| ff.descendant_checked(file).map(|desc| desc.is_file()).unwrap_or(false))
     }
 }
 
-impl CppLangInspector {
+impl PythonLangInspector {
     pub fn new() -> Self {
-        CppLangInspector {}
+        PythonLangInspector {}
     }
 }
@@ -1 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use crate::fs::filesystem_front::FilesystemFront;
+    use crate::fs::mock_fs::MockFS;
+    use crate::spath;
+    use crate::tsw::lang_id::LangId;
+    use crate::w7e::inspector::inspect_workspace;
 
+    #[test]
+    fn test_inspect_python_project_files() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file("repo/with_requirements/requirements.txt", "requests\n")
+            .with_file("repo/with_pyproject/pyproject.toml", "[project]\nname = \"tools\"\n")
+            .with_file("repo/with_setup/setup.py", "from setuptools import setup\n")
+            .with_file("repo/just_scripts/script.py", "print('hello')\n")
+            .to_fsf();
+
+        let repo = spath!(mock_fs, "repo").unwrap();
+        let scopes = inspect_workspace(&repo).unwrap();
+
+        assert!(scopes.iter().all(|scope| scope.lang_id == LangId::PYTHON3));
+        assert_eq!(
+            scopes.iter().map(|scope| scope.path.label().to_string()).collect::<Vec<_>>(),
+            vec!["with_pyproject", "with_requirements", "with_setup"]
+        );
+    }
+}
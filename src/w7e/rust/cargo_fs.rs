@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::PathBuf;
+
+use cargo_toml::AbstractFilesystem;
+
+use crate::fs::path::SPath;
+use crate::fs::read_error::ListError;
+
+/*
+Lets cargo_toml discover implicit targets (src/main.rs, files in src/bin, examples, tests...)
+through our filesystem abstraction, instead of going straight to disk. Rooted at package directory.
+ */
+pub struct CargoFs<'a> {
+    package_root: &'a SPath,
+}
+
+impl<'a> CargoFs<'a> {
+    pub fn new(package_root: &'a SPath) -> Self {
+        CargoFs { package_root }
+    }
+}
+
+impl<'a> AbstractFilesystem for CargoFs<'a> {
+    fn file_names_in(&self, rel_path: &str) -> io::Result<HashSet<Box<str>>> {
+        let dir = if rel_path.is_empty() || rel_path == "." {
+            self.package_root.clone()
+        } else {
+            self.package_root
+                .descendant_checked(rel_path)
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?
+        };
+
+        let items = dir.blocking_list().map_err(|e| match e {
+            ListError::PathNotFound => io::Error::from(io::ErrorKind::NotFound),
+            other => io::Error::other(format!("{:?}", other)),
+        })?;
+
+        Ok(items.filter_map(|item| item.file_name_str().map(|name| name.into())).collect())
+    }
+
+    /*
+    Only the "search up the tree" variant is supported, explicit package.workspace paths (like
+    "../") are not something SPath does.
+     */
+    fn read_root_workspace(&self, rel_path_hint: Option<&str>) -> io::Result<(Vec<u8>, PathBuf)> {
+        if rel_path_hint.is_some() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+
+        let mut ancestor_op = self.package_root.parent_ref();
+        while let Some(ancestor) = ancestor_op {
+            if let Some(manifest) = ancestor.descendant_checked("Cargo.toml") {
+                if manifest.is_file() {
+                    let contents = manifest.read_entire_file().map_err(|e| io::Error::other(format!("{:?}", e)))?;
+                    return Ok((contents, ancestor.absolute_path()));
+                }
+            }
+            ancestor_op = ancestor.parent_ref();
+        }
+
+        Err(io::ErrorKind::NotFound.into())
+    }
+}
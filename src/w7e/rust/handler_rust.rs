@@ -1,16 +1,80 @@
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use log::{debug, warn};
+
 use crate::config::config::ConfigRef;
 use crate::fs::path::SPath;
 use crate::tsw::lang_id::LangId;
 use crate::w7e::handler::{Handler, NavCompRef};
 use crate::w7e::handler_load_error::HandlerLoadError;
+use crate::w7e::rust::cargo_fs::CargoFs;
+use crate::w7e::target::{Target, TargetKind};
+use crate::w7e::task::Task;
 
 pub const INIT_TIMEOUT: Duration = Duration::from_millis(2000);
 
+/*
+A single package of a cargo project. Manifest can be both a workspace and a package at the same
+time, so the root one can be listed here too.
+ */
+pub struct CargoPackage {
+    pub name: String,
+    pub root: SPath,
+    pub manifest: cargo_toml::Manifest,
+}
+
+impl CargoPackage {
+    fn target(&self, kind: TargetKind, product: &cargo_toml::Product) -> Option<Target> {
+        let name = match product.name.as_ref() {
+            Some(name) => name.clone(),
+            None => {
+                warn!("{} target with no name in package {}, skipping", kind, self.name);
+                return None;
+            }
+        };
+
+        Some(Target {
+            kind,
+            name,
+            package: self.name.clone(),
+            package_root: self.root.clone(),
+            path: product.path.as_ref().map(PathBuf::from),
+        })
+    }
+
+    pub fn run_targets(&self) -> Vec<Target> {
+        let bins = self.manifest.bin.iter().filter_map(|p| self.target(TargetKind::Binary, p));
+        let examples = self.manifest.example.iter().filter_map(|p| self.target(TargetKind::Example, p));
+
+        bins.chain(examples).collect()
+    }
+
+    // unit tests of lib and bins (unless disabled with test = false) and integration tests
+    pub fn test_targets(&self) -> Vec<Target> {
+        let lib = self
+            .manifest
+            .lib
+            .iter()
+            .filter(|p| p.test)
+            .filter_map(|p| self.target(TargetKind::Library, p));
+        let bins = self
+            .manifest
+            .bin
+            .iter()
+            .filter(|p| p.test)
+            .filter_map(|p| self.target(TargetKind::Binary, p));
+        let tests = self.manifest.test.iter().filter_map(|p| self.target(TargetKind::Test, p));
+
+        lib.chain(bins).chain(tests).collect()
+    }
+}
+
 pub struct RustHandler {
     root: SPath,
     cargo_file: cargo_toml::Manifest,
+    // root package (if any) first, then workspace members in order they were declared
+    packages: Vec<CargoPackage>,
 
     navcomp: Option<NavCompRef>,
 }
@@ -20,20 +84,71 @@ impl Handler for RustHandler {
         LangId::RUST
     }
 
-    fn handler_id(&self) -> &'static str {
+    fn handler_id(&self) -> &str {
         "rust"
     }
 
     fn project_name(&self) -> &str {
-        "todo"
+        self.cargo_file
+            .package
+            .as_ref()
+            .map(|p| p.name.as_str())
+            .or_else(|| self.root.file_name_str())
+            .unwrap_or("todo")
     }
 
     fn navcomp(&self) -> Option<NavCompRef> {
         self.navcomp.clone()
     }
+
+    fn run_targets(&self) -> Vec<Target> {
+        self.packages.iter().flat_map(|p| p.run_targets()).collect()
+    }
+
+    fn test_targets(&self) -> Vec<Target> {
+        self.packages.iter().flat_map(|p| p.test_targets()).collect()
+    }
+
+    // whole project first, then each target on its own
+    fn tasks(&self) -> Vec<Task> {
+        let mut tasks = vec![
+            Task::new("cargo", &["build"], self.root.clone()),
+            Task::new("cargo", &["test"], self.root.clone()),
+        ];
+
+        for target in self.run_targets() {
+            let kind_flag = format!("--{}", target.kind);
+            tasks.push(Task::new(
+                "cargo",
+                &["run", "-p", &target.package, &kind_flag, &target.name],
+                self.root.clone(),
+            ));
+        }
+
+        for target in self.test_targets() {
+            let args: Vec<&str> = match target.kind {
+                TargetKind::Library => vec!["test", "-p", &target.package, "--lib"],
+                _ => vec![
+                    "test",
+                    "-p",
+                    &target.package,
+                    if target.kind == TargetKind::Test { "--test" } else { "--bin" },
+                    &target.name,
+                ],
+            };
+            tasks.push(Task::new("cargo", &args, self.root.clone()));
+        }
+
+        tasks
+    }
 }
 
 /*
+Reads Cargo.toml in root, and if it's a workspace, Cargo.toml of each of its members. Targets not
+listed in manifests (src/main.rs, files in src/bin, examples, tests...) are discovered the way
+cargo does it.
+
+Broken members are skipped with a warning, broken root is an error.
  */
 impl RustHandler {
     pub fn load(_config: &ConfigRef, ff: SPath, navcomp_op: Option<NavCompRef>) -> Result<RustHandler, HandlerLoadError> {
@@ -41,18 +156,136 @@ impl RustHandler {
             return Err(HandlerLoadError::NotAProject);
         }
 
-        let cargo_file = ff.descendant_checked("Cargo.toml").ok_or(HandlerLoadError::NotAProject)?;
-        if !cargo_file.is_file() {
-            return Err(HandlerLoadError::NotAProject);
+        let cargo = Self::read_manifest(&ff, None)?;
+
+        let mut packages: Vec<CargoPackage> = Vec::new();
+        if let Some(package) = cargo.package.as_ref() {
+            packages.push(CargoPackage {
+                name: package.name.clone(),
+                root: ff.clone(),
+                manifest: cargo.clone(),
+            });
         }
 
-        let contents = cargo_file.read_entire_file()?;
-        let cargo = cargo_toml::Manifest::from_slice(&contents).map_err(|e| HandlerLoadError::DeserializationError(e.to_string()))?;
+        if let Some(workspace) = cargo.workspace.as_ref() {
+            let workspace_path = ff.absolute_path();
+
+            for member_root in Self::workspace_members(&ff, workspace) {
+                match Self::read_manifest(&member_root, Some((&cargo, &workspace_path))) {
+                    Ok(manifest) => match manifest.package.as_ref() {
+                        Some(package) => {
+                            debug!("found workspace member {} at {:?}", package.name, member_root.relative_path());
+                            packages.push(CargoPackage {
+                                name: package.name.clone(),
+                                root: member_root,
+                                manifest,
+                            });
+                        }
+                        None => {
+                            warn!("workspace member at {:?} is not a package, skipping", member_root.relative_path());
+                        }
+                    },
+                    Err(e) => {
+                        warn!("failed loading workspace member at {:?}: {}", member_root.relative_path(), e);
+                    }
+                }
+            }
+        }
 
         Ok(RustHandler {
             root: ff,
             cargo_file: cargo,
+            packages,
             navcomp: navcomp_op,
         })
     }
+
+    pub fn packages(&self) -> &Vec<CargoPackage> {
+        &self.packages
+    }
+
+    fn read_manifest(dir: &SPath, workspace: Option<(&cargo_toml::Manifest, &Path)>) -> Result<cargo_toml::Manifest, HandlerLoadError> {
+        let cargo_file = dir.descendant_checked("Cargo.toml").ok_or(HandlerLoadError::NotAProject)?;
+        if !cargo_file.is_file() {
+            return Err(HandlerLoadError::NotAProject);
+        }
+
+        let contents = cargo_file.read_entire_file()?;
+        let mut cargo = cargo_toml::Manifest::from_slice(&contents).map_err(|e| HandlerLoadError::DeserializationError(e.to_string()))?;
+
+        // Without it, only explicitly listed targets are known. Not a reason to fail.
+        if let Err(e) = cargo.complete_from_abstract_filesystem::<cargo_toml::Value, _>(CargoFs::new(dir), workspace) {
+            warn!("failed discovering targets of {:?}: {}", dir.relative_path(), e);
+        }
+
+        Ok(cargo)
+    }
+
+    // Members are paths relative to workspace root, with "*" allowed in any segment, like "crates/*".
+    fn workspace_members(root: &SPath, workspace: &cargo_toml::Workspace) -> Vec<SPath> {
+        let excluded: Vec<SPath> = workspace.exclude.iter().filter_map(|e| root.descendant_checked(e)).collect();
+
+        let mut members: Vec<SPath> = Vec::new();
+        for pattern in workspace.members.iter() {
+            let mut matched: Vec<SPath> = vec![root.clone()];
+
+            for segment in pattern.split('/').filter(|s| !s.is_empty() && *s != ".") {
+                matched = if segment.contains('*') {
+                    matched
+                        .iter()
+                        .flat_map(|dir| match dir.blocking_list() {
+                            Ok(items) => items
+                                .filter(|item| item.is_dir() && item.file_name_str().map(|n| glob_matches(segment, n)).unwrap_or(false))
+                                .collect::<Vec<_>>(),
+                            Err(e) => {
+                                warn!("failed listing {:?}: {:?}", dir.relative_path(), e);
+                                Vec::new()
+                            }
+                        })
+                        .collect()
+                } else {
+                    matched.iter().filter_map(|dir| dir.descendant_checked(segment)).collect()
+                };
+            }
+
+            if matched.is_empty() {
+                warn!("workspace member pattern {} matched nothing", pattern);
+            }
+
+            matched.sort();
+            for member in matched {
+                // globs happily match directories that are not crates, cargo ignores them too
+                let is_crate = member.descendant_checked("Cargo.toml").map(|f| f.is_file()).unwrap_or(false);
+                if is_crate && member != *root && !excluded.contains(&member) && !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+
+        members
+    }
+}
+
+// "*" matches any (possibly empty) sequence of characters, everything else matches itself.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in parts[1..parts.len() - 1].iter() {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
 }
@@ -9,6 +9,10 @@ impl LangInspector for RustLangInspector {
         LangId::RUST
     }
 
+    fn handler_id(&self) -> &'static str {
+        "rust"
+    }
+
     fn is_project_dir(&self, ff: &SPath) -> bool {
         ff.is_dir() && ff.descendant_checked("Cargo.toml").map(|desc| desc.is_file()).unwrap_or(false)
     }
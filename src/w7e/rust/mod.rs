@@ -1,4 +1,5 @@
 // all stuff that helps reading Rust projects
+pub mod cargo_fs;
 pub mod handler_rust;
 pub mod inspector_rust;
 mod workspace_test;
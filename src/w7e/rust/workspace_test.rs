@@ -1,13 +1,19 @@
 #[cfg(test)]
 mod tests {
     use std::path::{Path, PathBuf};
+    use std::sync::Arc;
 
+    use crate::config::config::Config;
     use crate::experiments::pretty_ron::ToPrettyRonString;
     use crate::fs::filesystem_front::FilesystemFront;
     use crate::fs::mock_fs::MockFS;
     use crate::spath;
     use crate::tsw::lang_id::LangId;
+    use crate::w7e::handler::Handler;
+    use crate::w7e::inspector::inspect_workspace;
     use crate::w7e::project_scope::SerializableProjectScope;
+    use crate::w7e::rust::handler_rust::RustHandler;
+    use crate::w7e::target::TargetKind;
     use crate::w7e::workspace::{ScopeLoadErrors, SerializableWorkspace, Workspace};
 
     #[test]
@@ -17,7 +23,10 @@ mod tests {
                 lang_id: LangId::RUST,
                 path: PathBuf::from("rust_repo"),
                 handler_id_op: Some("rust".to_string()),
+                handler_settings: None,
+                custom_lsp: None,
             }],
+            tasks: vec![],
         };
 
         let item = workspace_pill.to_pretty_ron_string().unwrap();
@@ -57,6 +66,242 @@ mod tests {
         assert_eq!(workspace_pill.scopes[0].handler_id_op, Some("rust".to_string()));
     }
 
+    #[test]
+    fn test_read_rust_workspace_with_handler_settings() {
+        let workspace = r#"(
+    scopes: [
+        (
+            lang_id: RUST,
+            path: "rust_repo",
+            handler_id_op: Some("rust"),
+            handler_settings: Some({
+                "rust-analyzer": {
+                    "cargo": {
+                        "features": "all",
+                    },
+                    "checkOnSave": false,
+                },
+            }),
+        ),
+    ],
+)
+        "#;
+
+        let workspace_pill = ron::from_str::<SerializableWorkspace>(workspace).unwrap();
+
+        assert_eq!(
+            workspace_pill.scopes[0].handler_settings,
+            Some(serde_json::json!({
+                "rust-analyzer": {
+                    "cargo": { "features": "all" },
+                    "checkOnSave": false,
+                }
+            }))
+        );
+
+        let written = workspace_pill.to_pretty_ron_string().unwrap();
+        let reread = ron::from_str::<SerializableWorkspace>(&written).unwrap();
+        assert_eq!(reread.scopes[0].handler_settings, workspace_pill.scopes[0].handler_settings);
+    }
+
+    #[test]
+    fn test_inspect_rust_workspace_with_members() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file("repo/Cargo.toml", "[workspace]\nmembers = [\"core\", \"cli\"]\n")
+            .with_file("repo/core/Cargo.toml", "[package]\nname = \"core\"\n")
+            .with_file("repo/cli/Cargo.toml", "[package]\nname = \"cli\"\n")
+            .with_file("repo/.hidden/go.mod", "module example.com/hidden\n")
+            .to_fsf();
+
+        let repo = spath!(mock_fs, "repo").unwrap();
+        let scopes = inspect_workspace(&repo).unwrap();
+
+        // members are covered by workspace at the top, hidden dirs are not looked into
+        assert_eq!(scopes.len(), 1);
+        assert_eq!(scopes[0].lang_id, LangId::RUST);
+        assert_eq!(scopes[0].path, repo);
+    }
+
+    fn targets_summary(targets: Vec<crate::w7e::target::Target>) -> Vec<String> {
+        targets.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rust_handler_single_package() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file("hello/Cargo.toml", "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n")
+            .with_file("hello/src/main.rs", "fn main() {}")
+            .with_file("hello/src/lib.rs", "")
+            .with_file("hello/src/bin/helper.rs", "fn main() {}")
+            .with_file("hello/examples/demo.rs", "fn main() {}")
+            .with_file("hello/tests/smoke.rs", "")
+            .to_fsf();
+
+        let ff = spath!(mock_fs, "hello").unwrap();
+        let handler = RustHandler::load(&Arc::new(Config::default()), ff.clone(), None).unwrap();
+
+        assert_eq!(handler.project_name(), "hello-world");
+        assert_eq!(handler.packages().len(), 1);
+
+        assert_eq!(
+            targets_summary(handler.run_targets()),
+            vec![
+                "bin hello-world (hello-world)",
+                "bin helper (hello-world)",
+                "example demo (hello-world)"
+            ]
+        );
+        assert_eq!(
+            targets_summary(handler.test_targets()),
+            vec![
+                "lib hello_world (hello-world)",
+                "bin hello-world (hello-world)",
+                "bin helper (hello-world)",
+                "test smoke (hello-world)",
+            ]
+        );
+
+        let demo = handler.run_targets().into_iter().find(|t| t.kind == TargetKind::Example).unwrap();
+        assert_eq!(demo.package_root, ff);
+        assert_eq!(demo.path, Some(PathBuf::from("examples/demo.rs")));
+    }
+
+    #[test]
+    fn test_rust_handler_workspace_members() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file(
+                "repo/Cargo.toml",
+                r#"
+[workspace]
+members = ["crates/*", "tools/gen-*", "app"]
+exclude = ["crates/experimental"]
+
+[workspace.package]
+version = "0.3.0"
+"#,
+            )
+            .with_file("repo/app/Cargo.toml", "[package]\nname = \"app\"\nversion.workspace = true\n")
+            .with_file("repo/app/src/main.rs", "fn main() {}")
+            .with_file("repo/crates/core/Cargo.toml", "[package]\nname = \"core\"\nversion = \"0.1.0\"\n")
+            .with_file("repo/crates/core/src/lib.rs", "")
+            .with_file("repo/crates/core/tests/props.rs", "")
+            .with_file(
+                "repo/crates/experimental/Cargo.toml",
+                "[package]\nname = \"experimental\"\nversion = \"0.1.0\"\n",
+            )
+            .with_file("repo/crates/experimental/src/lib.rs", "")
+            // not a crate, matched by glob but skipped
+            .with_file("repo/crates/assets/logo.txt", "")
+            .with_file(
+                "repo/tools/gen-docs/Cargo.toml",
+                "[package]\nname = \"gen-docs\"\nversion = \"0.1.0\"\n",
+            )
+            .with_file("repo/tools/gen-docs/src/main.rs", "fn main() {}")
+            .with_file("repo/tools/lint/Cargo.toml", "[package]\nname = \"lint\"\nversion = \"0.1.0\"\n")
+            .to_fsf();
+
+        let ff = spath!(mock_fs, "repo").unwrap();
+        let handler = RustHandler::load(&Arc::new(Config::default()), ff, None).unwrap();
+
+        // virtual manifest, no package of its own
+        assert_eq!(handler.project_name(), "repo");
+        assert_eq!(
+            handler.packages().iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["core", "gen-docs", "app"]
+        );
+
+        let app = handler.packages().iter().find(|p| p.name == "app").unwrap();
+        assert_eq!(app.manifest.package.as_ref().unwrap().version(), "0.3.0");
+
+        assert_eq!(
+            targets_summary(handler.run_targets()),
+            vec!["bin gen-docs (gen-docs)", "bin app (app)"]
+        );
+        assert_eq!(
+            targets_summary(handler.test_targets()),
+            vec!["lib core (core)", "test props (core)", "bin gen-docs (gen-docs)", "bin app (app)"]
+        );
+    }
+
+    #[test]
+    fn test_rust_handler_tasks() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file("hello/Cargo.toml", "[package]\nname = \"hello\"\nversion = \"0.1.0\"\n")
+            .with_file("hello/src/main.rs", "fn main() {}")
+            .with_file("hello/examples/demo.rs", "fn main() {}")
+            .with_file("hello/tests/smoke.rs", "")
+            .to_fsf();
+
+        let ff = spath!(mock_fs, "hello").unwrap();
+        let handler = RustHandler::load(&Arc::new(Config::default()), ff.clone(), None).unwrap();
+
+        let tasks = handler.tasks();
+        assert_eq!(
+            tasks.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+            vec![
+                "cargo build",
+                "cargo test",
+                "cargo run -p hello --bin hello",
+                "cargo run -p hello --example demo",
+                "cargo test -p hello --bin hello",
+                "cargo test -p hello --test smoke",
+            ]
+        );
+        assert!(tasks.iter().all(|t| t.cwd == ff));
+    }
+
+    #[test]
+    fn test_read_workspace_with_tasks() {
+        let mock_fs = MockFS::new("/tmp")
+            .with_file(
+                "workspace/.gladius_workspace.ron",
+                r#"(
+    scopes: [],
+    tasks: [
+        (
+            name: "lint",
+            command: "cargo",
+            args: ["clippy", "--", "-D", "warnings"],
+            env: {
+                "RUST_LOG": "debug",
+            },
+        ),
+        (
+            name: "docs",
+            command: "make",
+            cwd: Some("docs"),
+        ),
+        (
+            name: "broken",
+            command: "true",
+            cwd: Some("nonexistent"),
+        ),
+    ],
+)"#,
+            )
+            .with_dir("workspace/docs")
+            .to_fsf();
+
+        let root = spath!(mock_fs, "workspace").unwrap();
+        let (workspace, errors) = Workspace::try_load(root.clone()).unwrap();
+        assert_eq!(errors, ScopeLoadErrors::default());
+
+        // task with missing directory is skipped
+        let tasks = workspace.tasks();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].name, "lint");
+        assert_eq!(tasks[0].command_line(), "cargo clippy -- -D warnings");
+        assert_eq!(tasks[0].env.get("RUST_LOG"), Some(&"debug".to_string()));
+        assert_eq!(tasks[0].cwd, root);
+        assert_eq!(tasks[1].cwd, spath!(mock_fs, "workspace", "docs").unwrap());
+
+        // and they survive a save
+        let written = workspace.serializable().to_pretty_ron_string().unwrap();
+        let reread = ron::from_str::<SerializableWorkspace>(&written).unwrap();
+        assert_eq!(reread.tasks, workspace.serializable().tasks);
+        assert_eq!(reread.tasks.len(), 3);
+    }
+
     fn test_read_workspace() {
         let repo_folder = Path::new("workspace");
         let mock_fs = MockFS::new("/tmp")
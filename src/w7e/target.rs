@@ -0,0 +1,54 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use crate::fs::path::SPath;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TargetKind {
+    Library,
+    Binary,
+    Example,
+    Test,
+    Bench,
+}
+
+impl TargetKind {
+    pub fn is_runnable(&self) -> bool {
+        matches!(self, TargetKind::Binary | TargetKind::Example)
+    }
+}
+
+impl Display for TargetKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TargetKind::Library => "lib",
+            TargetKind::Binary => "bin",
+            TargetKind::Example => "example",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/*
+Something a handler knows how to build and run, or test. It's language agnostic on purpose, it's up
+to handler to translate it into actual command (cargo, go, pytest...).
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub kind: TargetKind,
+    pub name: String,
+    // package (crate, module...) this target belongs to
+    pub package: String,
+    // directory of package, that's where commands are run from
+    pub package_root: SPath,
+    // entry point, relative to package_root, if known
+    pub path: Option<PathBuf>,
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} ({})", self.kind, self.name, self.package)
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::experiments::pretty_ron::ToPrettyRonString;
+use crate::fs::path::SPath;
+
+/*
+Something that can be run as a child process, with output displayed in gladius. Handlers
+contribute the obvious ones (cargo build, go test...), users can add their own in workspace file.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Task {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    // added to environment inherited from gladius
+    pub env: BTreeMap<String, String>,
+    pub cwd: SPath,
+}
+
+impl Task {
+    // named after command line, which is what most handler contributed tasks are known by
+    pub fn new(command: &str, args: &[&str], cwd: SPath) -> Self {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+
+        Task {
+            name: Self::format_command_line(command, &args),
+            command: command.to_string(),
+            args,
+            env: BTreeMap::new(),
+            cwd,
+        }
+    }
+
+    pub fn command_line(&self) -> String {
+        Self::format_command_line(&self.command, &self.args)
+    }
+
+    fn format_command_line(command: &str, args: &[String]) -> String {
+        let mut result = command.to_string();
+        for arg in args {
+            result.push(' ');
+            result.push_str(arg);
+        }
+        result
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableTask {
+    pub name: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    // relative to workspace root, root itself if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+}
+
+impl ToPrettyRonString for SerializableTask {}
+
+impl SerializableTask {
+    pub fn to_task(&self, workspace_root: &SPath) -> Option<Task> {
+        let cwd = match self.cwd.as_ref() {
+            None => workspace_root.clone(),
+            Some(path) if path.as_os_str().is_empty() => workspace_root.clone(),
+            Some(path) => match workspace_root.descendant_checked(path) {
+                Some(cwd) => cwd,
+                None => {
+                    warn!("directory {:?} of task [{}] not found, skipping it", path, self.name);
+                    return None;
+                }
+            },
+        };
+
+        Some(Task {
+            name: self.name.clone(),
+            command: self.command.clone(),
+            args: self.args.clone(),
+            env: self.env.clone(),
+            cwd,
+        })
+    }
+}
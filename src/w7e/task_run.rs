@@ -0,0 +1,427 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use log::{debug, error, warn};
+
+use crate::unpack_unit;
+use crate::w7e::task::Task;
+
+/*
+Sent whenever task produces output or finishes, so the frame gets redrawn. It's just a wake up, so
+a bounded channel is fine: if it's full, a redraw is already coming.
+ */
+pub type TaskTickSender = crossbeam_channel::Sender<()>;
+pub type TaskTickRecv = crossbeam_channel::Receiver<()>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskStatus {
+    Running,
+    // None exit code means process was killed by a signal
+    Finished(Option<i32>),
+    Cancelled,
+    FailedToStart(String),
+}
+
+impl TaskStatus {
+    pub fn is_running(&self) -> bool {
+        *self == TaskStatus::Running
+    }
+}
+
+impl Display for TaskStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskStatus::Running => write!(f, "running"),
+            TaskStatus::Finished(Some(0)) => write!(f, "finished"),
+            TaskStatus::Finished(Some(code)) => write!(f, "failed with exit code {}", code),
+            TaskStatus::Finished(None) => write!(f, "killed"),
+            TaskStatus::Cancelled => write!(f, "cancelled"),
+            TaskStatus::FailedToStart(reason) => write!(f, "failed to start: {}", reason),
+        }
+    }
+}
+
+/*
+stdout and stderr are interleaved in order lines arrived in. Only the last MAX_LINES are kept,
+nobody is going to scroll through more.
+ */
+#[derive(Debug)]
+pub struct TaskOutput {
+    pub lines: VecDeque<String>,
+    pub status: TaskStatus,
+}
+
+pub type TaskOutputRef = Arc<RwLock<TaskOutput>>;
+
+/*
+A single run of a task. Process is watched by a separate thread, so UI never waits for it. It can
+be cancelled at any moment (mission.md: ESC always works).
+
+Process is started in its own process group, so whatever it spawns (think rustc run by cargo) can
+be stopped along with it.
+ */
+pub struct TaskRun {
+    task: Task,
+    output: TaskOutputRef,
+
+    // None once process is gone
+    child: Arc<Mutex<Option<Child>>>,
+    // None if process failed to start
+    process_group: Option<u32>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskRun {
+    pub const MAX_LINES: usize = 10_000;
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+    const READERS_GRACE: Duration = Duration::from_millis(1000);
+
+    pub fn start(task: Task, tick_sender: TaskTickSender) -> TaskRun {
+        debug!(
+            "starting task [{}]: {} in {:?}",
+            task.name,
+            task.command_line(),
+            task.cwd.absolute_path()
+        );
+
+        let output: TaskOutputRef = Arc::new(RwLock::new(TaskOutput {
+            lines: VecDeque::new(),
+            status: TaskStatus::Running,
+        }));
+        let child_ref: Arc<Mutex<Option<Child>>> = Arc::new(Mutex::new(None));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let spawn_result = Command::new(&task.command)
+            .args(&task.args)
+            .envs(&task.env)
+            .current_dir(task.cwd.absolute_path())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn();
+
+        let mut process_group: Option<u32> = None;
+        match spawn_result {
+            Ok(mut child) => {
+                // process_group(0) makes child a group leader, so group id is its pid
+                process_group = Some(child.id());
+
+                let mut readers = Vec::new();
+                if let Some(stdout) = child.stdout.take() {
+                    readers.push(Self::spawn_reader(stdout, output.clone(), tick_sender.clone()));
+                }
+                if let Some(stderr) = child.stderr.take() {
+                    readers.push(Self::spawn_reader(stderr, output.clone(), tick_sender.clone()));
+                }
+
+                match child_ref.lock() {
+                    Ok(mut lock) => *lock = Some(child),
+                    Err(e) => error!("failed acquiring child lock: {:?}", e),
+                }
+
+                let child_clone = child_ref.clone();
+                let output_clone = output.clone();
+                let cancelled_clone = cancelled.clone();
+                thread::spawn(move || Self::watcher_thread(child_clone, readers, output_clone, cancelled_clone, tick_sender));
+            }
+            Err(e) => {
+                warn!("failed to start task [{}]: {}", task.name, e);
+                Self::set_status(&output, TaskStatus::FailedToStart(e.to_string()));
+                let _ = tick_sender.try_send(());
+            }
+        }
+
+        TaskRun {
+            task,
+            output,
+            child: child_ref,
+            process_group,
+            cancelled,
+        }
+    }
+
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+
+    pub fn output(&self) -> &TaskOutputRef {
+        &self.output
+    }
+
+    pub fn status(&self) -> TaskStatus {
+        match self.output.read() {
+            Ok(lock) => lock.status.clone(),
+            Err(e) => {
+                error!("failed acquiring output lock: {:?}", e);
+                TaskStatus::Running
+            }
+        }
+    }
+
+    // returns whether there was anything to cancel
+    pub fn cancel(&self) -> bool {
+        let mut lock = match self.child.lock() {
+            Ok(lock) => lock,
+            Err(e) => {
+                error!("failed acquiring child lock: {:?}", e);
+                return false;
+            }
+        };
+
+        match lock.as_mut() {
+            Some(child) => {
+                debug!("cancelling task [{}]", self.task.name);
+                self.cancelled.store(true, Ordering::SeqCst);
+                // the rest of the group gets a chance to clean up, the task itself does not.
+                self.signal_process_group("TERM");
+                if let Err(e) = child.kill() {
+                    warn!("failed killing task [{}]: {}", self.task.name, e);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /*
+    There's no libc in dependencies, so it's the kill binary. Negative pid addresses the whole
+    process group. Failing is fine if group is already gone.
+     */
+    fn signal_process_group(&self, signal: &str) {
+        let process_group = unpack_unit!(self.process_group, "no process group to signal",);
+
+        match Command::new("kill")
+            .args([format!("-{}", signal), "--".to_string(), format!("-{}", process_group)])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+        {
+            Ok(status) => {
+                if !status.success() {
+                    debug!("kill -{} of task [{}] group did nothing: {}", signal, self.task.name, status);
+                }
+            }
+            Err(e) => warn!("failed running kill for task [{}]: {}", self.task.name, e),
+        }
+    }
+
+    fn set_status(output: &TaskOutputRef, status: TaskStatus) {
+        match output.write() {
+            Ok(mut lock) => lock.status = status,
+            Err(e) => error!("failed acquiring output lock: {:?}", e),
+        }
+    }
+
+    fn spawn_reader<R: Read + Send + 'static>(source: R, output: TaskOutputRef, tick_sender: TaskTickSender) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let mut reader = BufReader::new(source);
+            let mut buf: Vec<u8> = Vec::new();
+
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        let line = String::from_utf8_lossy(&buf).trim_end_matches(&['\n', '\r'][..]).to_string();
+                        match output.write() {
+                            Ok(mut lock) => {
+                                lock.lines.push_back(line);
+                                while lock.lines.len() > Self::MAX_LINES {
+                                    lock.lines.pop_front();
+                                }
+                            }
+                            Err(e) => {
+                                error!("failed acquiring output lock: {:?}", e);
+                                break;
+                            }
+                        }
+                        let _ = tick_sender.try_send(());
+                    }
+                    Err(e) => {
+                        warn!("failed reading task output: {}", e);
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /*
+    Polls instead of waiting, so the child lock is free for cancel() in between.
+     */
+    fn watcher_thread(
+        child: Arc<Mutex<Option<Child>>>,
+        readers: Vec<thread::JoinHandle<()>>,
+        output: TaskOutputRef,
+        cancelled: Arc<AtomicBool>,
+        tick_sender: TaskTickSender,
+    ) {
+        let exit_code: Option<i32> = loop {
+            {
+                let mut lock = match child.lock() {
+                    Ok(lock) => lock,
+                    Err(e) => {
+                        error!("failed acquiring child lock: {:?}", e);
+                        return;
+                    }
+                };
+
+                let child_process = match lock.as_mut() {
+                    Some(child_process) => child_process,
+                    None => {
+                        error!("watching a task with no process");
+                        return;
+                    }
+                };
+
+                match child_process.try_wait() {
+                    Ok(Some(exit_status)) => {
+                        *lock = None;
+                        break exit_status.code();
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        error!("failed waiting for task: {}", e);
+                        *lock = None;
+                        break None;
+                    }
+                }
+            }
+
+            thread::sleep(Self::POLL_INTERVAL);
+        };
+
+        /*
+        So status comes after all the output. But grandchildren (think rustc run by cargo) can
+        keep pipes open after process is gone, so I don't wait for readers forever, and not at all
+        if user asked to stop.
+         */
+        if !cancelled.load(Ordering::SeqCst) {
+            let mut waited = Duration::ZERO;
+            while readers.iter().any(|reader| !reader.is_finished()) && waited < Self::READERS_GRACE {
+                thread::sleep(Self::POLL_INTERVAL);
+                waited += Self::POLL_INTERVAL;
+            }
+        }
+
+        let status = if cancelled.load(Ordering::SeqCst) {
+            TaskStatus::Cancelled
+        } else {
+            TaskStatus::Finished(exit_code)
+        };
+
+        debug!("task finished: {}", status);
+        Self::set_status(&output, status);
+        let _ = tick_sender.try_send(());
+    }
+}
+
+impl Drop for TaskRun {
+    // closing the display is as good as saying "don't bother". Also to everything task left behind.
+    fn drop(&mut self) {
+        self.cancel();
+        self.signal_process_group("KILL");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::fs::filesystem_front::FilesystemFront;
+    use crate::fs::mock_fs::MockFS;
+    use crate::w7e::task::Task;
+    use crate::w7e::task_run::{TaskRun, TaskStatus};
+
+    fn wait_for_end(run: &TaskRun) -> TaskStatus {
+        let start = Instant::now();
+        while run.status().is_running() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        run.status()
+    }
+
+    #[test]
+    fn test_task_output_and_exit_code() {
+        let cwd = MockFS::new("/tmp").to_fsf().root();
+        let (tick_sender, tick_recv) = crossbeam_channel::unbounded();
+        let task = Task::new("sh", &["-c", "echo first; echo second >&2; exit 3"], cwd);
+
+        let run = TaskRun::start(task, tick_sender);
+
+        assert_eq!(wait_for_end(&run), TaskStatus::Finished(Some(3)));
+        let output = run.output().read().unwrap();
+        assert!(output.lines.contains(&"first".to_string()));
+        assert!(output.lines.contains(&"second".to_string()));
+        assert!(tick_recv.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_task_cancel() {
+        let cwd = MockFS::new("/tmp").to_fsf().root();
+        let (tick_sender, _tick_recv) = crossbeam_channel::unbounded();
+        let run = TaskRun::start(Task::new("sleep", &["10"], cwd), tick_sender);
+
+        assert!(run.status().is_running());
+        assert!(run.cancel());
+        assert_eq!(wait_for_end(&run), TaskStatus::Cancelled);
+        assert!(!run.cancel());
+    }
+
+    // Linux only, sorry
+    fn is_alive(pid: &str) -> bool {
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            // state follows process name in parens, Z is a zombie
+            Ok(stat) => stat
+                .rsplit(')')
+                .next()
+                .map(|rest| !rest.trim_start().starts_with('Z'))
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    #[test]
+    fn test_task_cancel_stops_whole_process_group() {
+        let cwd = MockFS::new("/tmp").to_fsf().root();
+        let (tick_sender, _tick_recv) = crossbeam_channel::unbounded();
+        let run = TaskRun::start(Task::new("sh", &["-c", "sleep 10 & echo $!; wait"], cwd), tick_sender);
+
+        let start = Instant::now();
+        let grandchild_pid = loop {
+            if let Some(line) = run.output().read().unwrap().lines.front().cloned() {
+                break line;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "no pid of grandchild");
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert!(is_alive(&grandchild_pid));
+
+        assert!(run.cancel());
+        assert_eq!(wait_for_end(&run), TaskStatus::Cancelled);
+
+        let start = Instant::now();
+        while is_alive(&grandchild_pid) && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!is_alive(&grandchild_pid));
+    }
+
+    #[test]
+    fn test_task_failed_to_start() {
+        let cwd = MockFS::new("/tmp").to_fsf().root();
+        let (tick_sender, _tick_recv) = crossbeam_channel::unbounded();
+        let run = TaskRun::start(Task::new("surely-there-is-no-such-binary", &[], cwd), tick_sender);
+
+        assert!(matches!(run.status(), TaskStatus::FailedToStart(_)));
+    }
+}
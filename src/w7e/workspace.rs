@@ -11,6 +11,7 @@ use crate::gladius::providers::Providers;
 use crate::w7e::handler_load_error::HandlerLoadError;
 use crate::w7e::project_scope;
 use crate::w7e::project_scope::{ProjectScope, SerializableProjectScope};
+use crate::w7e::task::{SerializableTask, Task};
 
 /*
 So a funny finding while adding NavCompTick channel is that it seems like I could immediately add
@@ -30,11 +31,15 @@ pub type ScopeLoadErrors = Vec<(PathBuf, project_scope::LoadError)>;
 pub struct Workspace {
     root_path: SPath,
     scopes: Vec<ProjectScope>,
+    // kept as they were written, so saving the workspace does not mangle them
+    custom_tasks: Vec<SerializableTask>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SerializableWorkspace {
     pub scopes: Vec<SerializableProjectScope>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<SerializableTask>,
 }
 
 impl ToPrettyRonString for SerializableWorkspace {}
@@ -53,7 +58,11 @@ impl From<fs::read_error::ReadError> for LoadError {
 
 impl Workspace {
     pub fn new(root_path: SPath, scopes: Vec<ProjectScope>) -> Workspace {
-        Workspace { root_path, scopes }
+        Workspace {
+            root_path,
+            scopes,
+            custom_tasks: Vec::new(),
+        }
     }
 
     pub fn try_load(root_path: SPath) -> Result<(Workspace, ScopeLoadErrors), LoadError> {
@@ -82,13 +91,21 @@ impl Workspace {
             }
         }
 
-        Ok((Workspace { root_path, scopes }, scope_errors))
+        Ok((
+            Workspace {
+                root_path,
+                scopes,
+                custom_tasks: sw.tasks,
+            },
+            scope_errors,
+        ))
     }
 
     pub fn serializable(&self) -> SerializableWorkspace {
         let serializable_scopes: Vec<_> = self.scopes.iter().map(|scope| scope.serializable()).collect();
         SerializableWorkspace {
             scopes: serializable_scopes,
+            tasks: self.custom_tasks.clone(),
         }
     }
 
@@ -108,6 +125,7 @@ impl Workspace {
 
                     let mut has_navcomp = false;
                     scope.handler.as_ref().map(|h| {
+                        nav_comp_group.add_custom_extensions(h.lang_id(), &h.custom_extensions());
                         if let Some(navcomp) = h.navcomp() {
                             has_navcomp = true;
                             nav_comp_group.add_option(h.lang_id(), navcomp);
@@ -134,4 +152,16 @@ impl Workspace {
     pub fn scopes(&self) -> &Vec<ProjectScope> {
         &self.scopes
     }
+
+    // user defined tasks first, then whatever handlers (initialized ones only) came up with
+    pub fn tasks(&self) -> Vec<Task> {
+        let custom = self.custom_tasks.iter().filter_map(|task| task.to_task(&self.root_path));
+        let from_handlers = self
+            .scopes
+            .iter()
+            .filter_map(|scope| scope.handler.as_ref())
+            .flat_map(|handler| handler.tasks());
+
+        custom.chain(from_handlers).collect()
+    }
 }
@@ -1,18 +1,23 @@
 use std::borrow::Cow;
 use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
 
 use crate::io::keys::Key;
 use crate::primitives::tree::tree_node::TreeNode;
+use crate::w7e::navcomp_provider::HierarchyKind;
 use crate::widget::any_msg::{AnyMsg, AsAny};
 use crate::widgets::editor_widget::msg::EditorWidgetMsg;
 
 pub type Action = fn() -> Box<dyn AnyMsg>;
+// for actions that have to carry data, like code actions offered by LSP
+pub type ClosureAction = Rc<dyn Fn() -> Box<dyn AnyMsg>>;
 pub type TreeKey = Cow<'static, str>;
 
 #[derive(Clone)]
 pub enum NodeType {
     // by key here I mean "keycode" not "hash key"
     Leaf { action: Action, key: Option<Key> },
+    ClosureLeaf { action: ClosureAction },
     InternalNode(Vec<ContextBarItem>),
 }
 
@@ -40,6 +45,14 @@ impl ContextBarItem {
         }
     }
 
+    pub fn new_closure_leaf_node(title: Cow<'static, str>, action: ClosureAction) -> ContextBarItem {
+        ContextBarItem {
+            title,
+            node_type: NodeType::ClosureLeaf { action },
+            depth: 0,
+        }
+    }
+
     pub fn set_depth_recursively(&mut self, depth: usize) {
         self.depth = depth;
 
@@ -50,7 +63,7 @@ impl ContextBarItem {
 
     fn child_iter_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self> + '_> {
         match &mut self.node_type {
-            NodeType::Leaf { .. } => Box::new(std::iter::Empty::default()),
+            NodeType::Leaf { .. } | NodeType::ClosureLeaf { .. } => Box::new(std::iter::empty()),
             NodeType::InternalNode(items) => Box::new(items.iter_mut()),
         }
     }
@@ -59,6 +72,13 @@ impl ContextBarItem {
         self.depth
     }
 
+    // Actions can't be compared, so trees with the same titles in the same places are considered equal.
+    pub fn has_same_titles(&self, other: &ContextBarItem) -> bool {
+        self.title == other.title
+            && self.child_iter().count() == other.child_iter().count()
+            && self.child_iter().zip(other.child_iter()).all(|(a, b)| a.has_same_titles(&b))
+    }
+
     pub const GO_TO_DEFINITION: ContextBarItem = ContextBarItem {
         title: Cow::Borrowed("go to definition"),
         node_type: NodeType::Leaf {
@@ -67,6 +87,82 @@ impl ContextBarItem {
         },
         depth: 0,
     };
+    pub const GO_TO_TYPE_DEFINITION: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("go to type definition"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::GoToTypeDefinition.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const SHOW_IMPLEMENTATIONS: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("show implementations"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::ShowImplementations.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const GO_TO_DECLARATION: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("go to declaration"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::GoToDeclaration.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const SHOW_INCOMING_CALLS: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("incoming calls"),
+        node_type: NodeType::Leaf {
+            action: || {
+                EditorWidgetMsg::ShowHierarchy {
+                    kind: HierarchyKind::IncomingCalls,
+                }
+                .boxed()
+            },
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const SHOW_OUTGOING_CALLS: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("outgoing calls"),
+        node_type: NodeType::Leaf {
+            action: || {
+                EditorWidgetMsg::ShowHierarchy {
+                    kind: HierarchyKind::OutgoingCalls,
+                }
+                .boxed()
+            },
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const SHOW_SUPERTYPES: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("supertypes"),
+        node_type: NodeType::Leaf {
+            action: || {
+                EditorWidgetMsg::ShowHierarchy {
+                    kind: HierarchyKind::Supertypes,
+                }
+                .boxed()
+            },
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const SHOW_SUBTYPES: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("subtypes"),
+        node_type: NodeType::Leaf {
+            action: || {
+                EditorWidgetMsg::ShowHierarchy {
+                    kind: HierarchyKind::Subtypes,
+                }
+                .boxed()
+            },
+            key: None,
+        },
+        depth: 0,
+    };
     pub const REFORMAT_FILE: ContextBarItem = ContextBarItem {
         title: Cow::Borrowed("reformat file"),
         node_type: NodeType::Leaf {
@@ -75,6 +171,30 @@ impl ContextBarItem {
         },
         depth: 0,
     };
+    pub const REFORMAT_SELECTION: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("reformat selection"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::ReformatSelection.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const SHOW_HOVER: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("show hover"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::RequestHover.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const RENAME_SYMBOL: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("rename symbol"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::RequestRename.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
     pub const SHOW_USAGES: ContextBarItem = ContextBarItem {
         title: Cow::Borrowed("show usages"),
         node_type: NodeType::Leaf {
@@ -83,12 +203,28 @@ impl ContextBarItem {
         },
         depth: 0,
     };
+    pub const SHOW_OUTLINE: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("show outline"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::RequestOutline.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
+    pub const SHOW_HISTORY: ContextBarItem = ContextBarItem {
+        title: Cow::Borrowed("show edit history"),
+        node_type: NodeType::Leaf {
+            action: || EditorWidgetMsg::RequestHistory.boxed(),
+            key: None,
+        },
+        depth: 0,
+    };
 }
 
 impl Debug for ContextBarItem {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let desc = match &self.node_type {
-            NodeType::Leaf { .. } => "leaf".to_string(),
+            NodeType::Leaf { .. } | NodeType::ClosureLeaf { .. } => "leaf".to_string(),
             NodeType::InternalNode(vec) => format!("node({})", vec.len()),
         };
 
@@ -107,21 +243,21 @@ impl TreeNode<TreeKey> for ContextBarItem {
 
     fn keyboard_shortcut(&self) -> Option<Key> {
         match self.node_type {
-            NodeType::InternalNode(_) => None,
+            NodeType::InternalNode(_) | NodeType::ClosureLeaf { .. } => None,
             NodeType::Leaf { action, key } => key,
         }
     }
 
     fn is_leaf(&self) -> bool {
         match self.node_type {
-            NodeType::Leaf { .. } => true,
+            NodeType::Leaf { .. } | NodeType::ClosureLeaf { .. } => true,
             NodeType::InternalNode(_) => false,
         }
     }
 
     fn child_iter(&self) -> Box<dyn Iterator<Item = Self>> {
         match &self.node_type {
-            NodeType::Leaf { .. } => Box::new(std::iter::Empty::default()),
+            NodeType::Leaf { .. } | NodeType::ClosureLeaf { .. } => Box::new(std::iter::empty()),
             NodeType::InternalNode(items) => Box::new(items.clone().into_iter()),
         }
     }
@@ -133,8 +269,9 @@ impl TreeNode<TreeKey> for ContextBarItem {
 
 impl ContextBarItem {
     pub fn on_hit(&self) -> Option<Box<dyn AnyMsg>> {
-        match self.node_type {
+        match &self.node_type {
             NodeType::Leaf { action, key } => Some(action()),
+            NodeType::ClosureLeaf { action } => Some(action()),
             NodeType::InternalNode(_) => None,
         }
     }
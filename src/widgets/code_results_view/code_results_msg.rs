@@ -5,6 +5,8 @@ use crate::widget::any_msg::AnyMsg;
 #[derive(Debug)]
 pub enum CodeResultsMsg {
     Hit,
+    // applies changes listed in results, if provider supports it
+    Apply,
 }
 
 impl AnyMsg for CodeResultsMsg {}
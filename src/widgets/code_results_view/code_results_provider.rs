@@ -1,6 +1,7 @@
 use crate::io::loading_state::LoadingState;
 use crate::primitives::printable::Printable;
 use crate::primitives::symbol_usage::SymbolUsage;
+use crate::widget::any_msg::AnyMsg;
 
 /*
 Invariant: if items becomes longer, the initial items DO NOT CHANGE.
@@ -13,4 +14,12 @@ pub trait CodeResultsProvider {
     fn loading_state(&self) -> LoadingState;
 
     fn items(&self) -> Box<dyn Iterator<Item = &SymbolUsage> + '_>;
+
+    /*
+    Some results describe changes that can be applied, like rename preview. Returns message that
+    applies them, or None if there is nothing to apply.
+     */
+    fn apply(&mut self) -> Option<Box<dyn AnyMsg>> {
+        None
+    }
 }
@@ -172,9 +172,11 @@ impl Widget for CodeResultsView {
         debug!("{} input {:?}", self.typename(), input_event);
 
         let has_item = self.item_list.internal().get_selected_item().is_some();
+        let apply_key = self.providers.config().keyboard_config.global.apply;
 
         match input_event {
             InputEvent::KeyInput(key) if has_item && key == Keycode::Enter.to_key() => CodeResultsMsg::Hit.someboxed(),
+            InputEvent::KeyInput(key) if has_item && key == apply_key => CodeResultsMsg::Apply.someboxed(),
             _ => None,
         }
     }
@@ -188,6 +190,7 @@ impl Widget for CodeResultsView {
 
         match our_msg.unwrap() {
             CodeResultsMsg::Hit => self.on_hit(),
+            CodeResultsMsg::Apply => self.data_provider.apply(),
         }
     }
 
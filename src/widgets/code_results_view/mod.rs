@@ -9,4 +9,5 @@ pub mod code_results_msg;
 pub mod code_results_provider;
 pub mod code_results_widget;
 pub mod full_text_search_code_results_provider;
+pub mod rename_code_results_provider;
 pub mod stupid_symbol_usage_code_results_provider;
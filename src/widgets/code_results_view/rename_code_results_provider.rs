@@ -0,0 +1,188 @@
+use std::iter;
+
+use log::{debug, error};
+
+use crate::fs::path::SPath;
+use crate::gladius::providers::Providers;
+use crate::io::loading_state::LoadingState;
+use crate::primitives::printable::Printable;
+use crate::primitives::symbol_usage::SymbolUsage;
+use crate::promise::promise::PromiseState;
+use crate::text::text_buffer::TextBuffer;
+use crate::unpack_unit_e;
+use crate::w7e::navcomp_provider::{NavCompWorkspaceEdit, RenamePromise, StupidSubstituteMessage};
+use crate::widget::any_msg::{AnyMsg, AsAny};
+use crate::widgets::code_results_view::code_results_provider::CodeResultsProvider;
+use crate::widgets::code_results_view::stupid_symbol_usage_code_results_provider::url_to_spath;
+use crate::widgets::main_view::msg::MainViewMsg;
+
+/*
+Preview of rename: lists every place that will change, and on "apply" hands the edits to MainView.
+
+CodeResultsView opens every listed file in BufferRegister to display it. I remember which files
+were opened just for the preview, so they can be closed before applying and written to disk
+directly, as opposed to files user had already open, which get undoable edits.
+ */
+#[derive(Debug)]
+pub struct RenameCodeResultsProvider {
+    providers: Providers,
+    desc: String,
+    promise: Option<RenamePromise>,
+    broken: bool,
+
+    file_edits: Vec<(SPath, Vec<StupidSubstituteMessage>)>,
+    opened_for_preview: Vec<SPath>,
+    resolved_symbols: Vec<SymbolUsage>,
+    applied: bool,
+}
+
+impl RenameCodeResultsProvider {
+    pub fn new(providers: Providers, desc: String, promise: RenamePromise) -> Self {
+        RenameCodeResultsProvider {
+            providers,
+            desc,
+            promise: Some(promise),
+            broken: false,
+            file_edits: vec![],
+            opened_for_preview: vec![],
+            resolved_symbols: vec![],
+            applied: false,
+        }
+    }
+
+    fn edits_count(&self) -> usize {
+        self.file_edits.iter().map(|(_, edits)| edits.len()).sum()
+    }
+
+    fn add_workspace_edit(&mut self, workspace_edit: &NavCompWorkspaceEdit) {
+        let mut buffer_register_lock = unpack_unit_e!(
+            self.providers.buffer_register().try_write().ok(),
+            "failed to acquire buffer register",
+        );
+
+        for (url, edits) in workspace_edit.file_edits.iter() {
+            let spath = match url_to_spath(self.providers.fsf(), url) {
+                None => continue,
+                Some(s) => s,
+            };
+
+            let open_result = buffer_register_lock.open_file(&self.providers, &spath);
+            let buffer_state_ref = match open_result.buffer_shared_ref {
+                Err(e) => {
+                    error!("failed to load buffer {} because {}", spath, e);
+                    continue;
+                }
+                Ok(bsr) => bsr,
+            };
+
+            if open_result.opened {
+                self.opened_for_preview.push(spath.clone());
+            }
+
+            let lock = match buffer_state_ref.lock() {
+                None => {
+                    error!("failed locking buffer {}", spath);
+                    continue;
+                }
+                Some(lock) => lock,
+            };
+
+            for edit in edits.iter() {
+                let cursor = if edit.stupid_range.0 == edit.stupid_range.1 {
+                    lock.stupid_cursor_to_cursor(edit.stupid_range.0, None)
+                } else {
+                    lock.stupid_cursor_to_cursor(edit.stupid_range.0, Some(edit.stupid_range.1))
+                };
+
+                match cursor {
+                    None => error!("failed cursor conversion"),
+                    Some(c) => self.resolved_symbols.push(SymbolUsage {
+                        path: spath.clone(),
+                        range: c,
+                    }),
+                }
+            }
+
+            self.file_edits.push((spath, edits.clone()));
+        }
+    }
+}
+
+impl CodeResultsProvider for RenameCodeResultsProvider {
+    fn description(&self) -> Box<dyn Printable> {
+        let desc = if self.promise.is_some() {
+            format!("{}: loading...", self.desc)
+        } else if self.broken {
+            format!("{}: failed", self.desc)
+        } else if self.applied {
+            format!("{}: applied", self.desc)
+        } else if self.file_edits.is_empty() {
+            format!("{}: nothing to rename", self.desc)
+        } else {
+            format!(
+                "{}: {} changes in {} files, press {} to apply",
+                self.desc,
+                self.edits_count(),
+                self.file_edits.len(),
+                self.providers.config().keyboard_config.global.apply.to_string(),
+            )
+        };
+
+        Box::new(desc)
+    }
+
+    fn poll(&mut self) {
+        if let Some(promise) = self.promise.as_mut() {
+            let update_result = promise.update();
+            if update_result.state.is_broken() {
+                error!("broken promise of rename");
+                self.broken = true;
+            }
+
+            if update_result.state.is_resolved() {
+                if let Some(Some(workspace_edit)) = self.promise.as_ref().unwrap().read() {
+                    // TODO unnecessary clone
+                    self.add_workspace_edit(&workspace_edit.clone());
+                    debug!("rename touches {} files", self.file_edits.len());
+                }
+                self.promise = None;
+            }
+        }
+    }
+
+    fn loading_state(&self) -> LoadingState {
+        if let Some(promise) = self.promise.as_ref() {
+            match promise.state() {
+                PromiseState::Unresolved => LoadingState::InProgress,
+                PromiseState::Ready => LoadingState::Complete,
+                PromiseState::Broken => LoadingState::Error,
+            }
+        } else if self.broken {
+            LoadingState::Error
+        } else {
+            LoadingState::Complete
+        }
+    }
+
+    fn items(&self) -> Box<dyn Iterator<Item = &SymbolUsage> + '_> {
+        if self.promise.is_some() {
+            Box::new(iter::empty()) as Box<dyn Iterator<Item = &SymbolUsage>>
+        } else {
+            Box::new(self.resolved_symbols.iter())
+        }
+    }
+
+    fn apply(&mut self) -> Option<Box<dyn AnyMsg>> {
+        if self.promise.is_some() || self.applied || self.file_edits.is_empty() {
+            debug!("nothing to apply");
+            return None;
+        }
+
+        self.applied = true;
+        MainViewMsg::ApplyWorkspaceEdit {
+            file_edits: std::mem::take(&mut self.file_edits),
+            opened_for_preview: std::mem::take(&mut self.opened_for_preview),
+        }
+        .someboxed()
+    }
+}
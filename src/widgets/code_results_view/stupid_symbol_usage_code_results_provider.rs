@@ -3,15 +3,17 @@ use std::sync::Arc;
 
 use log::{debug, error};
 
+use crate::fs::fsf_ref::FsfRef;
+use crate::fs::path::SPath;
 use crate::gladius::providers::Providers;
 use crate::io::loading_state::LoadingState;
 use crate::primitives::printable::Printable;
 use crate::primitives::symbol_usage::SymbolUsage;
 use crate::promise::promise::PromiseState;
 use crate::text::text_buffer::TextBuffer;
-use crate::unpack_unit_e;
 use crate::w7e::navcomp_provider::{StupidSymbolUsage, SymbolUsagesPromise};
 use crate::widgets::code_results_view::code_results_provider::CodeResultsProvider;
+use crate::{unpack_or_e, unpack_unit_e};
 
 #[derive(Debug)]
 pub struct StupidSymbolUsageCodeResultsProvider {
@@ -21,6 +23,29 @@ pub struct StupidSymbolUsageCodeResultsProvider {
     resolved_symbols: Vec<SymbolUsage>,
 }
 
+/*
+Navcomp returns paths as urls, this converts them to SPath, as long as they are within workspace.
+ */
+pub fn url_to_spath(fsf: &FsfRef, url: &str) -> Option<SPath> {
+    let root_path_buf = fsf.root_path_buf().to_string_lossy().to_string() + "/";
+
+    let no_prefix = unpack_or_e!(url.strip_prefix("file://"), None, "failed stripping prefix file:// from {}", url);
+    let in_workspace = unpack_or_e!(
+        no_prefix.strip_prefix(&root_path_buf),
+        None,
+        "failed stripping prefix root_path from {}",
+        no_prefix
+    );
+
+    let spath = unpack_or_e!(
+        fsf.descendant_checked(in_workspace),
+        None,
+        "failed to get spath from {}",
+        in_workspace
+    );
+    Some(spath)
+}
+
 impl StupidSymbolUsageCodeResultsProvider {
     pub fn new(providers: Providers, desc: String, promise: SymbolUsagesPromise) -> Self {
         StupidSymbolUsageCodeResultsProvider {
@@ -37,30 +62,9 @@ impl StupidSymbolUsageCodeResultsProvider {
             "failed to acquire buffer register",
         );
 
-        let root_path_buf = self.providers.fsf().root_path_buf().to_string_lossy().to_string() + "/";
-
         for ss in stupid_symbols.iter() {
-            let no_prefix = match ss.path.strip_prefix("file://") {
-                None => {
-                    error!("failed stripping prefix file:// from {}", &ss.path);
-                    continue;
-                }
-                Some(np) => np,
-            };
-
-            let in_workspace = match no_prefix.strip_prefix(&root_path_buf) {
-                None => {
-                    error!("failed stripping prefix root_path from {}", &no_prefix);
-                    continue;
-                }
-                Some(iw) => iw,
-            };
-
-            let spath = match self.providers.fsf().descendant_checked(&in_workspace) {
-                None => {
-                    error!("failed to get spath from {}", &in_workspace);
-                    continue;
-                }
+            let spath = match url_to_spath(self.providers.fsf(), &ss.path) {
+                None => continue,
                 Some(s) => s,
             };
 
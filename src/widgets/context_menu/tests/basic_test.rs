@@ -1,7 +1,10 @@
 use crate::io::keys::Keycode;
 use crate::mocks::mock_tree_item::get_mock_data_set_1;
 use crate::mocks::with_wait_for::WithWaitFor;
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::widget::any_msg::AnyMsg;
 use crate::widgets::context_menu::tests::context_menu_testbed::{AdditionalData, ContextMenuTestbed, ContextMenuTestbedBuilder};
+use crate::widgets::context_menu::widget::ContextMenuWidget;
 
 pub fn get_setup_1() -> ContextMenuTestbed {
     let setup = ContextMenuTestbedBuilder::new(AdditionalData {
@@ -61,3 +64,44 @@ fn context_menu_2_letters_filter() {
     assert!(testbed.has_items(["menu1", "submenu", "child1", "child2"].into_iter()));
     assert!(testbed.has_none_of_items(["option1", "option2"].into_iter()));
 }
+
+#[derive(Debug)]
+struct HitMsg(String);
+
+impl AnyMsg for HitMsg {}
+
+#[test]
+fn context_menu_3_lazy_children_enter_hits_arrows_expand() {
+    let mut testbed = get_setup_1();
+    testbed.widget = ContextMenuWidget::new(testbed.providers.clone(), get_mock_data_set_1())
+        .with_lazy_children()
+        .with_on_hit(Box::new(|w| Some(Box::new(HitMsg(w.get_highlighted().1.label().to_string())))));
+
+    testbed.next_frame();
+    assert_eq!(testbed.context_menu().unwrap().tree_view().items().len(), 1);
+
+    testbed.push_input(Keycode::ArrowRight.to_key().to_input_event());
+    assert!(testbed.has_items(["menu1", "option1", "submenu"].into_iter()));
+
+    // filter does not look into collapsed submenu
+    testbed.push_text("child");
+    assert!(testbed.has_none_of_items(["child1", "child2"].into_iter()));
+    for _ in 0.."child".len() {
+        testbed.push_input(Keycode::Backspace.to_key().to_input_event());
+    }
+
+    for _ in 0..3 {
+        testbed.push_input(Keycode::ArrowDown.to_key().to_input_event());
+    }
+    testbed.push_input(Keycode::Enter.to_key().to_input_event());
+
+    // enter over internal node hits instead of expanding
+    assert_eq!(testbed.last_msg_as::<HitMsg>().map(|msg| msg.0.as_str()), Some("submenu"));
+    assert!(testbed.has_none_of_items(["child1"].into_iter()));
+
+    testbed.push_input(Keycode::ArrowRight.to_key().to_input_event());
+    assert!(testbed.has_items(["child1", "child2"].into_iter()));
+
+    testbed.push_input(Keycode::ArrowLeft.to_key().to_input_event());
+    assert!(testbed.has_none_of_items(["child1", "child2"].into_iter()));
+}
@@ -104,6 +104,17 @@ impl<Key: Hash + Eq + Debug + Clone, Item: TreeNode<Key>> ContextMenuWidget<Key,
         self.tree_view.internal_mut().expand_root();
     }
 
+    /*
+    For trees whose children are expensive to get (or infinite, like recursive calls). Filter
+    applies only to what's already expanded, enter hits any item, and arrows expand and collapse.
+     */
+    pub fn with_lazy_children(mut self) -> Self {
+        let tree_view = self.tree_view.internal_mut();
+        tree_view.set_filter_overrides_expanded(false);
+        tree_view.set_enter_hits_internal_nodes(true);
+        self
+    }
+
     pub fn set_on_shortcut_hit(&mut self, on_shortcut_hit: WidgetActionParam<TreeViewWidget<Key, Item>, Item>) {
         self.tree_view.internal_mut().set_on_shortcut_hit(on_shortcut_hit)
     }
@@ -201,7 +212,11 @@ impl<Key: Hash + Eq + Debug + Clone, Item: TreeNode<Key>> Widget for ContextMenu
 
                     let tree_view = self.tree_view.internal_mut();
                     tree_view.set_filter_op(
-                        Some(ClosureFilter::new(move |item: &Item| query_fuzz.matches(item.label().as_ref())).arc_box()),
+                        Some(
+                            ClosureFilter::new(move |item: &Item| query_fuzz.matches(item.label().as_ref()))
+                                .with_query(query.clone())
+                                .arc_box(),
+                        ),
                         FilterPolicy::MatchNodeOrAncestors,
                     );
 
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::time::Duration;
 
 use log::{debug, error, warn};
 use unicode_width::UnicodeWidthStr;
@@ -19,11 +20,11 @@ use crate::layout::split_layout::{SplitDirection, SplitLayout, SplitRule};
 use crate::primitives::common_edit_msgs::CommonEditMsg;
 use crate::primitives::has_invariant::HasInvariant;
 use crate::primitives::rect::Rect;
-use crate::primitives::scroll::ScrollDirection;
 use crate::primitives::search_pattern::SearchPattern;
 use crate::primitives::xy::XY;
 use crate::text::buffer_state::{BufferState, SetFilePathResult};
 use crate::text::text_buffer::TextBuffer;
+use crate::text::undo_cache;
 use crate::w7e::buffer_state_shared_ref::BufferSharedRef;
 use crate::widget::any_msg::{AnyMsg, AsAny};
 use crate::widget::complex_widget::{ComplexWidget, DisplayState};
@@ -36,8 +37,7 @@ use crate::widgets::editor_widget::editor_widget::EditorWidget;
 use crate::widgets::main_view::msg::MainViewMsg;
 use crate::widgets::save_file_dialog::save_file_dialog::SaveFileDialogWidget;
 use crate::widgets::text_widget::TextWidget;
-use crate::widgets::with_scroll::with_scroll::WithScroll;
-use crate::{subwidget, unpack_or, unpack_or_e};
+use crate::{subwidget, unpack_or, unpack_or_e, unpack_unit, unpack_unit_e};
 
 const PATTERN: &str = "pattern: ";
 const REPLACE: &str = "replace: ";
@@ -57,7 +57,7 @@ pub struct EditorView {
 
     display_state: Option<DisplayState<EditorView>>,
 
-    editor: WithScroll<EditorWidget>,
+    editor: EditorWidget,
     find_box: EditBoxWidget,
     find_label: TextWidget,
     replace_box: EditBoxWidget,
@@ -78,6 +78,7 @@ pub struct EditorView {
 
 impl EditorView {
     pub const TYPENAME: &'static str = "editor_view";
+    pub const FORMAT_ON_SAVE_TIMEOUT: Duration = Duration::from_secs(1);
 
     pub fn new(
         providers: Providers, // TODO(#17) now navcomp is language specific, and editor can be "recycled" from say yaml to rs, requiring change of navcomp.
@@ -101,7 +102,7 @@ impl EditorView {
             wid: get_new_widget_id(),
             providers,
             display_state: None,
-            editor: WithScroll::new(ScrollDirection::Both, editor).with_line_no(),
+            editor,
             find_box,
             find_label,
             replace_box,
@@ -133,17 +134,17 @@ impl EditorView {
     }
 
     pub fn with_readonly(mut self) -> Self {
-        self.editor.internal_mut().set_readonly(true);
+        self.editor.set_readonly(true);
         self
     }
 
     pub fn with_ignore_input_altogether(mut self) -> Self {
-        self.editor.internal_mut().set_ignore_input_altogether(true);
+        self.editor.set_ignore_input_altogether(true);
         self
     }
 
     pub fn get_buffer_ref(&self) -> &BufferSharedRef {
-        self.editor.internal().get_buffer()
+        self.editor.get_buffer()
     }
 
     // copy-pasted from main view, TODO move to layout?
@@ -162,11 +163,23 @@ impl EditorView {
     This attempts to save current file, but in case that's not possible (filename unknown) proceeds to open_save_as_dialog() below
      */
     fn save_or_save_as(&mut self, buffer: &mut BufferState) {
-        if let Some(ff) = buffer.get_path() {
+        if let Some(ff) = buffer.get_path().cloned() {
+            let format_on_save = self
+                .providers
+                .lang_for(&ff)
+                .map(|lang_id| self.providers.config().global.format_on_save.contains(&lang_id))
+                .unwrap_or(false);
+
+            // if formatter does not answer in time, file is saved as it is
+            if format_on_save && !self.editor.format(buffer, false, Self::FORMAT_ON_SAVE_TIMEOUT) {
+                warn!("failed to format {} before save, saving unformatted", ff);
+            }
+
             // TODO do something wih thi
             match ff.overwrite_with_stream(&mut buffer.streaming_iterator(), false) {
                 Ok(_) => {
                     buffer.mark_as_saved();
+                    self.store_undo_history(buffer);
                 }
                 Err(e) => {
                     error!("failed to save file {} because {:?}", ff, e);
@@ -177,6 +190,19 @@ impl EditorView {
         }
     }
 
+    // persists history of just saved buffer, so it can be undone after reopening
+    fn store_undo_history(&self, buffer: &BufferState) {
+        let cache_dir = unpack_unit!(undo_cache::cache_dir(self.providers.config()));
+        let snapshot = unpack_unit_e!(
+            buffer.undo_snapshot(undo_cache::MAX_ENTRIES, undo_cache::MAX_BYTES),
+            "failed to snapshot history of saved buffer"
+        );
+
+        if let Err(e) = undo_cache::store(&cache_dir, &snapshot) {
+            error!("failed to store undo history of {:?}, because {}", buffer.get_path(), e);
+        }
+    }
+
     fn open_save_as_dialog_and_focus(&mut self, buffer: &BufferState) {
         match self.state {
             EditorViewState::Simple => {}
@@ -206,6 +232,7 @@ impl EditorView {
         let set_path_result = self.set_file_name(buffer_mut, path);
 
         buffer_mut.mark_as_saved();
+        self.store_undo_history(buffer_mut);
 
         if set_path_result.path_changed {
             // updating the "save as dialog" starting position
@@ -250,7 +277,7 @@ impl EditorView {
 
     fn hit_find_once(&mut self, buffer_mut: &mut BufferState) -> bool {
         let phrase = self.find_box.get_buffer().to_string();
-        match self.editor.internal_mut().find_once(buffer_mut, &phrase) {
+        match self.editor.find_once(buffer_mut, &phrase) {
             Ok(changed) => changed,
             Err(_e) => {
                 // TODO handle?
@@ -267,12 +294,12 @@ impl EditorView {
     fn hit_replace_once(&mut self, buffer_mut: &mut BufferState) -> bool {
         let phrase = unpack_or!(self.get_pattern(), false, "hit_replace_once with empty phrase - ignoring");
         let curr_text = buffer_mut.text();
-        let editor_widget_id = self.editor.internal().id();
+        let editor_widget_id = self.editor.id();
         let cursor_set = unpack_or!(curr_text.get_cursor_set(editor_widget_id), false, "no cursors for editor");
 
         if cursor_set.is_single() && curr_text.do_cursors_match_regex(editor_widget_id, &phrase) {
             let with_what = self.replace_box.get_buffer().to_string();
-            let page_height = self.editor.internal().page_height() as usize;
+            let page_height = self.editor.page_height() as usize;
             buffer_mut.apply_common_edit_message(
                 CommonEditMsg::Block(with_what),
                 editor_widget_id,
@@ -302,7 +329,6 @@ impl EditorView {
 
     pub fn get_path(&self) -> Option<SPath> {
         self.editor
-            .internal()
             .get_buffer()
             .lock()
             .map(|buffer_lock| buffer_lock.get_path().map(|c| c.clone()))
@@ -310,7 +336,7 @@ impl EditorView {
     }
 
     pub fn override_cursor_set(&mut self, cursor_set: CursorSet) -> bool {
-        let widget: &mut EditorWidget = self.editor.internal_mut();
+        let widget: &mut EditorWidget = &mut self.editor;
         let wid = widget.id();
         widget.set_cursors(cursor_set);
 
@@ -318,11 +344,11 @@ impl EditorView {
     }
 
     pub fn get_internal_widget(&self) -> &EditorWidget {
-        self.editor.internal()
+        &self.editor
     }
 
     pub fn get_internal_widget_mut(&mut self) -> &mut EditorWidget {
-        self.editor.internal_mut()
+        &mut self.editor
     }
 }
 
@@ -383,7 +409,7 @@ impl Widget for EditorView {
                 Some(msg) //passthrough
             }
             Some(msg) => {
-                if let Some(mut buffer_lock) = self.editor.internal_mut().get_buffer().clone().lock_rw() {
+                if let Some(mut buffer_lock) = self.editor.get_buffer().clone().lock_rw() {
                     match msg {
                         EditorViewMsg::Save => {
                             self.save_or_save_as(&mut buffer_lock);
@@ -583,6 +609,6 @@ impl ComplexWidget for EditorView {
 
 impl HasInvariant for EditorView {
     fn check_invariant(&self) -> bool {
-        self.editor.internal().check_invariant()
+        self.editor.check_invariant()
     }
 }
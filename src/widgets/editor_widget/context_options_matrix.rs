@@ -1,26 +1,48 @@
+use std::rc::Rc;
+
 use log::debug;
 
 use crate::cursor::cursor::Cursor;
 use crate::cursor::cursor_set::CursorSet;
 use crate::primitives::stupid_cursor::StupidCursor;
-use crate::w7e::navcomp_provider::NavCompSymbol;
+use crate::w7e::navcomp_provider::{NavCompCodeAction, NavCompSymbol};
+use crate::widget::any_msg::AsAny;
 use crate::widget::context_bar_item::ContextBarItem;
 use crate::widgets::editor_widget::editor_widget::EditorState;
+use crate::widgets::editor_widget::msg::EditorWidgetMsg;
 
 /*
 I am preemptively moving this code away from EditorWidget, because I expect it to be big
 */
 
-pub fn get_context_options(
-    state: &EditorState,
-    single_cursor: Option<Cursor>,
-    multiple_cursors: &CursorSet,
-    single_stupid_cursor: Option<StupidCursor>,
-    lsp_available: bool,
-    lsp_symbol: Option<&NavCompSymbol>,
-    can_reformat: bool,
-    tree_sitter_symbol: Option<&str>,
-) -> Vec<ContextBarItem> {
+// Everything the options depend on: editor and cursor state, what navcomp can do and what's under cursor.
+pub struct ContextOptionsInput<'a> {
+    pub state: &'a EditorState,
+    pub single_cursor: Option<Cursor>,
+    pub multiple_cursors: &'a CursorSet,
+    pub single_stupid_cursor: Option<StupidCursor>,
+
+    pub lsp_available: bool,
+    pub can_reformat: bool,
+
+    pub lsp_symbol: Option<&'a NavCompSymbol>,
+    pub tree_sitter_symbol: Option<&'a str>,
+    pub code_actions: &'a [NavCompCodeAction],
+}
+
+pub fn get_context_options(input: &ContextOptionsInput) -> Vec<ContextBarItem> {
+    let ContextOptionsInput {
+        state,
+        single_cursor,
+        multiple_cursors,
+        single_stupid_cursor,
+        lsp_available,
+        can_reformat,
+        lsp_symbol,
+        tree_sitter_symbol,
+        code_actions,
+    } = *input;
+
     let mut code_results: Vec<ContextBarItem> = Vec::new();
 
     debug!(target: "context_matrix", "hit lsp_symbol, tree_sitter_symbol: {:?} {:?}", &lsp_symbol, &tree_sitter_symbol);
@@ -37,23 +59,77 @@ pub fn get_context_options(
     ) {
         (EditorState::Editing, true, Some(_), _, _, _, Some("function")) => {
             code_results.push(ContextBarItem::GO_TO_DEFINITION);
+            code_results.push(ContextBarItem::GO_TO_DECLARATION);
+            // trait methods and interface methods are highlighted as functions too
+            code_results.push(ContextBarItem::SHOW_IMPLEMENTATIONS);
             code_results.push(ContextBarItem::SHOW_USAGES);
+            code_results.push(ContextBarItem::new_internal_node(
+                "call hierarchy".into(),
+                vec![ContextBarItem::SHOW_INCOMING_CALLS, ContextBarItem::SHOW_OUTGOING_CALLS],
+            ));
+            code_results.push(ContextBarItem::SHOW_HOVER);
+            code_results.push(ContextBarItem::RENAME_SYMBOL);
         }
         (EditorState::Editing, true, Some(_), _, _, _, Some("function.builtin")) => {
             code_results.push(ContextBarItem::GO_TO_DEFINITION);
+            code_results.push(ContextBarItem::GO_TO_DECLARATION);
             code_results.push(ContextBarItem::SHOW_USAGES);
+            code_results.push(ContextBarItem::SHOW_HOVER);
+            code_results.push(ContextBarItem::RENAME_SYMBOL);
         }
         (EditorState::Editing, true, Some(_), _, _, _, Some("property")) => {
+            code_results.push(ContextBarItem::GO_TO_TYPE_DEFINITION);
+            code_results.push(ContextBarItem::SHOW_USAGES);
+            code_results.push(ContextBarItem::SHOW_HOVER);
+            code_results.push(ContextBarItem::RENAME_SYMBOL);
+        }
+        (EditorState::Editing, true, Some(_), _, _, _, Some("variable")) => {
+            code_results.push(ContextBarItem::GO_TO_DEFINITION);
+            code_results.push(ContextBarItem::GO_TO_TYPE_DEFINITION);
             code_results.push(ContextBarItem::SHOW_USAGES);
+            code_results.push(ContextBarItem::SHOW_HOVER);
+            code_results.push(ContextBarItem::RENAME_SYMBOL);
         }
         (EditorState::Editing, true, Some(_), _, _, _, Some("type")) => {
             code_results.push(ContextBarItem::GO_TO_DEFINITION);
+            code_results.push(ContextBarItem::SHOW_IMPLEMENTATIONS);
             code_results.push(ContextBarItem::SHOW_USAGES);
+            code_results.push(ContextBarItem::new_internal_node(
+                "type hierarchy".into(),
+                vec![ContextBarItem::SHOW_SUPERTYPES, ContextBarItem::SHOW_SUBTYPES],
+            ));
+            code_results.push(ContextBarItem::SHOW_HOVER);
+            code_results.push(ContextBarItem::RENAME_SYMBOL);
         }
         _ => {}
     }
 
+    // these come from LSP and do not depend on tree-sitter, so they are outside the matrix above.
+    if !code_actions.is_empty() {
+        let items: Vec<ContextBarItem> = code_actions
+            .iter()
+            .map(|action| {
+                let action = action.clone();
+                ContextBarItem::new_closure_leaf_node(
+                    action.title.clone().into(),
+                    Rc::new(move || EditorWidgetMsg::ApplyCodeAction { action: action.clone() }.boxed()),
+                )
+            })
+            .collect();
+
+        code_results.push(ContextBarItem::new_internal_node("code actions".into(), items));
+    }
+
+    // outline falls back to tree-sitter, so it's available without LSP too.
+    if matches!(state, EditorState::Editing) {
+        code_results.push(ContextBarItem::SHOW_OUTLINE);
+        code_results.push(ContextBarItem::SHOW_HISTORY);
+    }
+
     if can_reformat {
+        if single_cursor.map(|cursor| cursor.s.is_some()).unwrap_or(false) {
+            code_results.push(ContextBarItem::REFORMAT_SELECTION);
+        }
         code_results.push(ContextBarItem::REFORMAT_FILE);
     }
 
@@ -1,8 +1,9 @@
 use std::borrow::Cow;
 use std::cmp::{max, min};
 use std::collections::BTreeMap;
+use std::ops::Range;
 use std::path::Path;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use log::{debug, error, info, warn};
 use matches::debug_assert_matches;
@@ -17,6 +18,7 @@ use crate::cursor::cursor_set_rect::{cursor_set_to_rect, cursor_to_xy, cursor_to
 use crate::experiments::regex_search::FindError;
 use crate::experiments::screenspace::Screenspace;
 use crate::experiments::subwidget_pointer::SubwidgetPointer;
+use crate::fs::path::SPath;
 use crate::gladius::providers::Providers;
 use crate::io::input_event::InputEvent;
 use crate::io::keys::Keycode;
@@ -24,6 +26,7 @@ use crate::io::output::Output;
 use crate::io::style::TextStyle;
 use crate::io::sub_output::SubOutput;
 use crate::primitives::arrow::Arrow;
+use crate::primitives::buffer_xy::BufferXY;
 use crate::primitives::common_edit_msgs::{apply_common_edit_message, cme_to_direction, key_to_edit_msg, CommonEditMsg};
 use crate::primitives::has_invariant::HasInvariant;
 use crate::primitives::helpers;
@@ -37,20 +40,32 @@ use crate::text::buffer_state::BufferState;
 use crate::text::text_buffer::TextBuffer;
 use crate::w7e::buffer_state_shared_ref::BufferSharedRef;
 use crate::w7e::handler::NavCompRef;
-use crate::w7e::navcomp_provider::CompletionAction;
+use crate::w7e::navcomp_provider::{
+    CodeActionsPromise, CompletionAction, DocumentHighlightKind, DocumentHighlightsPromise, HierarchyKind, InlayHintsPromise,
+    NavCompCodeAction, NavCompDocumentSymbol, NavCompSymbolContextActions, SemanticTokensPromise, SymbolUsagesPromise,
+};
 use crate::widget::any_msg::{AnyMsg, AsAny};
 use crate::widget::context_bar_item::ContextBarItem;
 use crate::widget::fill_policy::SizePolicy;
 use crate::widget::widget::{get_new_widget_id, Widget, WID};
-use crate::widgets::code_results_view::stupid_symbol_usage_code_results_provider::StupidSymbolUsageCodeResultsProvider;
+use crate::widgets::code_results_view::rename_code_results_provider::RenameCodeResultsProvider;
+use crate::widgets::code_results_view::stupid_symbol_usage_code_results_provider::{url_to_spath, StupidSymbolUsageCodeResultsProvider};
 use crate::widgets::context_bar::widget::ContextBarWidget;
 use crate::widgets::editor_widget::completion::completion_widget::CompletionWidget;
-use crate::widgets::editor_widget::context_options_matrix::get_context_options;
-use crate::widgets::editor_widget::helpers::{find_trigger_and_substring, CursorScreenPosition};
-use crate::widgets::editor_widget::label::label::Label;
+use crate::widgets::editor_widget::context_options_matrix::{get_context_options, ContextOptionsInput};
+use crate::widgets::editor_widget::helpers::{
+    find_trigger_and_substring, trigger_before_char_idx, word_around_char_idx, CursorScreenPosition,
+};
+use crate::widgets::editor_widget::hover::hover_widget::HoverWidget;
+use crate::widgets::editor_widget::label::label::{Label, LabelPos};
 use crate::widgets::editor_widget::label::labels_provider::LabelsProvider;
 use crate::widgets::editor_widget::msg::EditorWidgetMsg;
+use crate::widgets::editor_widget::rename::rename_widget::RenameWidget;
+use crate::widgets::editor_widget::signature_help::signature_help_widget::SignatureHelpWidget;
+use crate::widgets::hierarchy::hierarchy_item::HierarchyItem;
+use crate::widgets::history_browser::history_item::HistoryItem;
 use crate::widgets::main_view::msg::MainViewMsg;
+use crate::widgets::outline::outline_item::OutlineItem;
 use crate::{unpack_or, unpack_or_e, unpack_unit, unpack_unit_e};
 
 const MIN_EDITOR_SIZE: XY = XY::new(10, 3);
@@ -76,7 +91,6 @@ each widget is given their own "output" to render at.
 /*
 TODO:
 - display save error (and write tests)
-- support for files with more than u16 lines
 - I should probably remember "milestones" every several mb of file for big files.
 - we have a lot of places where we check for Some on path, single cursor, navcomp and cast from cursor to stupid cursor.
     they need unification.
@@ -152,18 +166,27 @@ impl HoverSettings {
 
 enum EditorHover {
     Completion(CompletionWidget),
+    Hover(HoverWidget),
+    SignatureHelp(SignatureHelpWidget),
+    Rename(RenameWidget),
 }
 
 impl EditorHover {
     fn get_widget(&self) -> &dyn Widget {
         match self {
             EditorHover::Completion(cw) => cw,
+            EditorHover::Hover(hw) => hw,
+            EditorHover::SignatureHelp(sw) => sw,
+            EditorHover::Rename(rw) => rw,
         }
     }
 
     fn get_widget_mut(&mut self) -> &mut dyn Widget {
         match self {
             EditorHover::Completion(cw) => cw,
+            EditorHover::Hover(hw) => hw,
+            EditorHover::SignatureHelp(sw) => sw,
+            EditorHover::Rename(rw) => rw,
         }
     }
 }
@@ -176,14 +199,20 @@ pub struct EditorWidget {
     // I'd prefer to have "constrain cursors to visible part", but since it's non-trivial, I don't do it now.
     ignore_input_altogether: bool,
 
+    // this is screenspace of text area, so it does NOT include line numbers margin.
     layout_res: Option<Screenspace>,
+    margin_width: u16,
 
     // to be constructed in layout step based on HoverSettings
     last_hover_rect: Option<Rect>,
 
     buffer: BufferSharedRef,
 
-    kite: XY,
+    // Kite and viewport are in buffer space. Editor scrolls itself instead of relying on WithScroll,
+    // because screen space (XY) is u16 and files are often way longer than that.
+    kite: BufferXY,
+    // first visible column and line
+    viewport: BufferXY,
 
     // navcomp is to submit edit messages, suggestion display will probably be somewhere else
     navcomp: Option<NavCompRef>,
@@ -198,13 +227,42 @@ pub struct EditorWidget {
     // These are label providers. Their order is important.
     // todo_lable_providers: Vec<LabelsProviderRef>, // moved to providers
     autoindent: bool,
+
+    // Inlay hints are requested for visible lines only (plus a margin), and re-requested on scroll
+    // and edit. Old hints are kept until new ones arrive, so they don't blink while typing. Only
+    // one request is in flight at a time, so typing fast does not flood LSP.
+    inlay_hints: Vec<Label>,
+    inlay_hints_promise: Option<(InlayHintsPromise, Instant)>,
+    // lines covered by last request, None means "outdated"
+    inlay_hints_lines: Option<Range<usize>>,
+
+    // Semantic tokens cover entire file and are kept in buffer. Only one request is in flight at
+    // a time, since next one may be just a delta to the previous answer.
+    semantic_tokens_promise: Option<SemanticTokensPromise>,
+    semantic_tokens_outdated: bool,
+
+    // Occurrences of the symbol under cursor as char ranges, sorted. Dropped on edit, and when
+    // cursor leaves them.
+    document_highlights: Vec<(Range<usize>, DocumentHighlightKind)>,
+    document_highlights_promise: Option<DocumentHighlightsPromise>,
+
+    // Code actions for the char range of single cursor (or its selection). Requested whenever the
+    // range changes, so context bar never waits for them. None range means "outdated".
+    code_actions: Vec<NavCompCodeAction>,
+    code_actions_promise: Option<CodeActionsPromise>,
+    code_actions_range: Option<Range<usize>>,
 }
 
 impl EditorWidget {
     pub const TYPENAME: &'static str = "editor_widget";
 
+    pub const TYPENAME_FOR_MARGIN: &'static str = "editor_widget_margin";
+
     const MAX_HOVER_WIDTH: u16 = 45;
     const MIN_HOVER_WIDTH: u16 = 15;
+    // how many lines above and below visible part are covered by inlay hints request
+    const INLAY_HINTS_MARGIN: usize = 20;
+    const INLAY_HINTS_TIMEOUT: Duration = Duration::from_secs(5);
 
     pub fn new(providers: Providers, buffer: BufferSharedRef) -> EditorWidget {
         let buffer_named: bool = buffer.lock().map(|lock| lock.get_path().is_some()).unwrap_or_else(|| {
@@ -218,13 +276,25 @@ impl EditorWidget {
             readonly: false,
             ignore_input_altogether: false,
             layout_res: None,
+            margin_width: 0,
             last_hover_rect: None,
             buffer: buffer.clone(),
-            kite: XY::ZERO,
+            kite: BufferXY::ZERO,
+            viewport: BufferXY::ZERO,
             state: EditorState::Editing,
             navcomp: None,
             requested_hover: None,
             autoindent: false,
+            inlay_hints: Vec::new(),
+            inlay_hints_promise: None,
+            inlay_hints_lines: None,
+            semantic_tokens_promise: None,
+            semantic_tokens_outdated: true,
+            document_highlights: Vec::new(),
+            document_highlights_promise: None,
+            code_actions: Vec::new(),
+            code_actions_promise: None,
+            code_actions_range: None,
         };
 
         if buffer_named {
@@ -357,43 +427,43 @@ impl EditorWidget {
             EditorState::DroppingCursor { special_cursor } => &CursorSet::singleton(special_cursor),
         };
 
-        let cursor_rect = cursor_set_to_rect(cursor_set, buffer);
+        let (upper_left, lower_right) = cursor_set_to_rect(cursor_set, buffer);
 
         match last_move_direction {
             //When cursor is at the end of the line end we press up or down,
             //We may go to the end of shorter line, hence we need also updating kite.x
             Arrow::Up => {
-                if self.kite.y > cursor_rect.upper_left().y {
-                    self.kite.y = cursor_rect.upper_left().y;
+                if self.kite.y > upper_left.y {
+                    self.kite.y = upper_left.y;
                 }
-                if self.kite.x > cursor_rect.upper_left().x {
-                    self.kite.x = cursor_rect.upper_left().x;
+                if self.kite.x > upper_left.x {
+                    self.kite.x = upper_left.x;
                 }
             }
             Arrow::Down => {
-                if self.kite.y < cursor_rect.lower_right().y {
-                    self.kite.y = cursor_rect.lower_right().y;
+                if self.kite.y < lower_right.y {
+                    self.kite.y = lower_right.y;
                 }
-                if self.kite.x > cursor_rect.upper_left().x {
-                    self.kite.x = cursor_rect.upper_left().x;
+                if self.kite.x > upper_left.x {
+                    self.kite.x = upper_left.x;
                 }
             }
             Arrow::Left => {
-                if self.kite.x > cursor_rect.upper_left().x {
-                    self.kite.x = cursor_rect.upper_left().x;
+                if self.kite.x > upper_left.x {
+                    self.kite.x = upper_left.x;
                 }
-                if self.kite.y > cursor_rect.upper_left().y {
-                    self.kite.y = cursor_rect.upper_left().y;
+                if self.kite.y > upper_left.y {
+                    self.kite.y = upper_left.y;
                 }
             }
             //When going right at the end of a line, we change cursors y,
             //hence we need to update kite.y. Analogically in Arrow::Left
             Arrow::Right => {
-                if self.kite.x < cursor_rect.lower_right().x {
-                    self.kite.x = cursor_rect.lower_right().x;
+                if self.kite.x < lower_right.x {
+                    self.kite.x = lower_right.x;
                 }
-                if self.kite.y < cursor_rect.lower_right().y {
-                    self.kite.y = cursor_rect.lower_right().y;
+                if self.kite.y < lower_right.y {
+                    self.kite.y = lower_right.y;
                 }
             }
         }
@@ -432,7 +502,7 @@ impl EditorWidget {
             None,
             "failed mapping cursor to lsp-cursor"
         );
-        let lsp_cursor_xy = unpack_or_e!(lsp_cursor.to_xy(buffer), None, "lsp cursor beyond XY max");
+        let lsp_cursor_xy = unpack_or_e!(lsp_cursor.to_xy(buffer), None, "lsp cursor beyond end of buffer");
 
         let layout_res = unpack_or!(
             self.layout_res.as_ref(),
@@ -440,24 +510,21 @@ impl EditorWidget {
             "single_cursor_screen_pos called before first layout"
         );
 
-        // So I get here the visible rect, but ignoring the offset (as if 1st line was in upper right corner)
-        // I think this is due to a bug in scroll.
         let visible_rect = layout_res.visible_rect();
 
-        if !visible_rect.contains(lsp_cursor_xy) {
-            warn!("cursor seems to be outside visible hint {:?}", layout_res.visible_rect());
-            return Some(CursorScreenPosition {
-                cursor,
-                visible_rect_space: None,
-                text_space: lsp_cursor_xy,
-            });
-        }
-
-        let local_pos = lsp_cursor_xy - visible_rect.upper_left();
+        let local_pos = match lsp_cursor_xy.relative_to(self.viewport) {
+            Some(local_pos) if local_pos < visible_rect.size => local_pos,
+            _ => {
+                warn!("cursor {} seems to be outside viewport {}", lsp_cursor_xy, self.viewport);
+                return Some(CursorScreenPosition {
+                    cursor,
+                    visible_rect_space: None,
+                    text_space: lsp_cursor_xy,
+                });
+            }
+        };
 
         debug!("cursor {:?} converted to {:?} positioned at {:?}", cursor, lsp_cursor, local_pos);
-        debug_assert!(local_pos >= XY::ZERO);
-        debug_assert!(local_pos < visible_rect.size);
 
         Some(CursorScreenPosition {
             cursor,
@@ -541,25 +608,200 @@ impl EditorWidget {
 
         let can_reformat = self.navcomp.as_ref().map(|navcomp| navcomp.can_reformat()).unwrap_or(false);
 
-        let items = get_context_options(
-            &self.state,
+        // ones that are not there yet are added to context bar when they arrive (see MainView::refresh_main_context_menu)
+        let code_actions: &[NavCompCodeAction] = if single_cursor.map(Self::code_actions_range) == self.code_actions_range {
+            &self.code_actions
+        } else {
+            &[]
+        };
+
+        let items = get_context_options(&ContextOptionsInput {
+            state: &self.state,
             single_cursor,
-            &cursor_set,
-            stupid_cursor_op,
-            self.navcomp.is_some(),
-            None,
+            multiple_cursors: &cursor_set,
+            single_stupid_cursor: stupid_cursor_op,
+            lsp_available: self.navcomp.is_some(),
             can_reformat,
-            tree_sitter_highlight.as_ref().map(|c| c.as_str()),
-        );
+            lsp_symbol: None,
+            tree_sitter_symbol: tree_sitter_highlight.as_ref().map(|c| c.as_str()),
+            code_actions,
+        });
 
         items
     }
 
-    // TODO add test to reformat
+    fn code_actions_range(cursor: Cursor) -> Range<usize> {
+        match cursor.s {
+            None => cursor.a..cursor.a,
+            Some(sel) => sel.b..sel.e,
+        }
+    }
+
+    /*
+    Called from prelayout, not layout like the other LSP answers: main view refreshes an open
+    context bar in its prelayout, so actions that just arrived make it there within the same frame.
+    Polls pending code actions and asks for new ones if cursor moved or contents changed.
+     */
+    fn update_code_actions(&mut self) {
+        if let Some(promise) = self.code_actions_promise.as_mut() {
+            match promise.update().state {
+                PromiseState::Unresolved => {}
+                PromiseState::Ready => {
+                    self.code_actions = promise.read().cloned().unwrap_or_default();
+                    self.code_actions_promise = None;
+                }
+                PromiseState::Broken => {
+                    debug!("code actions promise broken");
+                    self.code_actions_promise = None;
+                }
+            }
+        }
+
+        if self.readonly {
+            return;
+        }
+
+        let navcomp = unpack_unit!(self.navcomp.as_ref());
+        let buffer = unpack_unit_e!(self.buffer.lock(), "failed to lock buffer");
+        let path = unpack_unit!(buffer.get_path());
+        let cursor = unpack_unit!(buffer.cursors(self.wid).and_then(|c| c.as_single()));
+
+        let range = Self::code_actions_range(cursor);
+        if self.code_actions_range.as_ref() == Some(&range) {
+            return;
+        }
+
+        self.code_actions.clear();
+        self.code_actions_promise = None;
+        self.code_actions_range = Some(range.clone());
+
+        let rope = buffer.text().rope();
+        let stupid_range = unpack_unit_e!(
+            StupidCursor::from_char_idx_utf16(rope, range.start).zip(StupidCursor::from_char_idx_utf16(rope, range.end)),
+            "failed to convert cursor to stupid cursor",
+        );
+
+        // If there's no promise (navcomp busy), there are no actions until cursor moves or text changes.
+        self.code_actions_promise = navcomp.code_actions(path, stupid_range);
+    }
+
+    /*
+    LSP gets DEFAULT_EDITOR_TIMEOUT to answer. If it does not (or there is no LSP at all), outline is
+    built from tree-sitter parse.
+     */
+    fn get_document_symbols(&self, buffer: &BufferState) -> Vec<NavCompDocumentSymbol> {
+        let lsp_symbols: Option<Vec<NavCompDocumentSymbol>> = match (self.navcomp.as_ref(), buffer.get_path()) {
+            (Some(navcomp), Some(path)) => navcomp.document_symbols(path).and_then(|mut promise| {
+                if promise.wait(Some(DEFAULT_EDITOR_TIMEOUT)) == PromiseState::Ready {
+                    promise.read().cloned()
+                } else {
+                    debug!("document symbols promise not ready");
+                    None
+                }
+            }),
+            _ => None,
+        };
+
+        match lsp_symbols {
+            Some(symbols) if !symbols.is_empty() => symbols,
+            _ => {
+                debug!("falling back to tree-sitter outline");
+                buffer.tree_sitter_document_symbols().unwrap_or_default()
+            }
+        }
+    }
+
+    pub fn show_outline(&self, buffer: &BufferState) -> Option<Box<dyn AnyMsg>> {
+        let symbols = self.get_document_symbols(buffer);
+
+        let title = buffer
+            .get_path()
+            .map(|path| path.label().to_string())
+            .unwrap_or_else(|| "[unnamed]".to_string());
+
+        MainViewMsg::OpenOutline {
+            document: buffer.get_document_identifier().clone(),
+            root: OutlineItem::new_root(title, &symbols, buffer),
+        }
+        .someboxed()
+    }
+
+    pub fn show_history(&self, buffer: &BufferState) -> Option<Box<dyn AnyMsg>> {
+        if self.readonly {
+            debug!("not showing history of readonly editor");
+            return None;
+        }
+
+        let title = buffer
+            .get_path()
+            .map(|path| path.label().to_string())
+            .unwrap_or_else(|| "[unnamed]".to_string());
+
+        MainViewMsg::OpenHistory {
+            document: buffer.get_document_identifier().clone(),
+            root: HistoryItem::new_root(title, buffer.history(), buffer.last_saved_history_node()),
+            current_node: buffer.history().current_id(),
+            widget_id: self.wid,
+        }
+        .someboxed()
+    }
+
+    /*
+    Waits up to DEFAULT_EDITOR_TIMEOUT for items the hierarchy starts with. Everything below them
+    is requested as user expands the tree, see HierarchyItem.
+     */
+    pub fn show_hierarchy(&self, buffer: &BufferState, kind: HierarchyKind) -> Option<Box<dyn AnyMsg>> {
+        let navcomp = unpack_or_e!(&self.navcomp, None, "can't show hierarchy without navcomp");
+        let cursor = unpack_or!(
+            buffer.cursors(self.wid).and_then(|c| c.as_single()),
+            None,
+            "not showing hierarchy - cursor not single."
+        );
+        let path = unpack_or!(buffer.get_path(), None, "no path set");
+        let stupid_cursor = unpack_or!(
+            StupidCursor::from_real_cursor(buffer, cursor).ok(),
+            None,
+            "failed conversion to stupid cursor"
+        );
+
+        let mut promise = unpack_or_e!(
+            navcomp.prepare_hierarchy(path, stupid_cursor, kind),
+            None,
+            "failed to acquire hierarchy promise from navcomp"
+        );
+
+        if promise.wait(Some(DEFAULT_EDITOR_TIMEOUT)) != PromiseState::Ready {
+            debug!("hierarchy promise not ready");
+            return None;
+        }
+
+        let items = promise.read().cloned().unwrap_or_default();
+        if items.is_empty() {
+            debug!("no {} at cursor", kind.description());
+            return None;
+        }
+
+        MainViewMsg::OpenHierarchy {
+            root: HierarchyItem::new_root(navcomp.clone(), self.providers.fsf().clone(), kind, items),
+        }
+        .someboxed()
+    }
+
     pub fn reformat(&mut self, buffer: &mut BufferState) -> bool {
+        self.format(buffer, false, DEFAULT_EDITOR_TIMEOUT)
+    }
+
+    pub fn reformat_selection(&mut self, buffer: &mut BufferState) -> bool {
+        self.format(buffer, true, DEFAULT_EDITOR_TIMEOUT)
+    }
+
+    /*
+    Formats current contents of the buffer, saved or not. If navcomp does not answer within timeout,
+    nothing changes, so a stuck formatter can't block whoever called it (like saving).
+     */
+    pub fn format(&mut self, buffer: &mut BufferState, selection_only: bool, timeout: Duration) -> bool {
         let navcomp = unpack_or!(self.navcomp.as_ref(), false, "can't reformat: navcomp not available");
         let path = unpack_or!(buffer.get_path(), false, "can't reformat: unsaved file");
-        let mut promise = unpack_or!(navcomp.todo_reformat(path), false, "can't reformat: no promise for reformat");
         let cursor_set = unpack_or!(buffer.cursors(self.wid), false, "no cursor for wid");
 
         if !cursor_set.are_simple() {
@@ -567,24 +809,53 @@ impl EditorWidget {
             return false;
         }
 
-        if promise.wait(Some(DEFAULT_EDITOR_TIMEOUT)) == PromiseState::Ready {
-            // invariant : promise ready => take.is_some()
-            let edits = unpack_or!(promise.read().unwrap(), false, "can't reformat: promise empty");
+        let stupid_range_op = if selection_only {
+            let selection = unpack_or!(
+                cursor_set.as_single().and_then(|c| c.s),
+                false,
+                "can't format selection: no single selection"
+            );
+            let rope = buffer.text().rope();
+            let stupid_range = unpack_or_e!(
+                StupidCursor::from_char_idx_utf16(rope, selection.b).zip(StupidCursor::from_char_idx_utf16(rope, selection.e)),
+                false,
+                "failed to convert selection to stupid cursors"
+            );
+            Some(stupid_range)
+        } else {
+            None
+        };
 
-            let page_height = self.page_height();
+        let mut promise = unpack_or!(
+            navcomp.format(path, stupid_range_op),
+            false,
+            "can't reformat: no promise for reformat"
+        );
 
-            let _res = buffer.apply_stupid_substitute_messages(self.wid, edits, page_height as usize);
+        match promise.wait(Some(timeout)) {
+            PromiseState::Ready => {
+                // invariant : promise ready => take.is_some()
+                let edits = unpack_or!(promise.read().unwrap(), false, "can't reformat: promise empty");
 
-            // This theoretically could be optimised out, but maybe it's not worth it, it leads to
-            // a new category of bugs if statement above turns out to be false, and it rarely is,
-            // so it's very very hard to test. So I keep this here for peace of mind.
-            self.after_content_changed(buffer);
+                let page_height = self.page_height();
 
-            //res
-            true
-        } else {
-            warn!("reformat promise broken");
-            false
+                let _res = buffer.apply_stupid_substitute_messages(self.wid, edits, page_height as usize);
+
+                // This theoretically could be optimised out, but maybe it's not worth it, it leads to
+                // a new category of bugs if statement above turns out to be false, and it rarely is,
+                // so it's very very hard to test. So I keep this here for peace of mind.
+                self.after_content_changed(buffer);
+
+                true
+            }
+            PromiseState::Unresolved => {
+                warn!("reformat promise not resolved within {:?}, giving up", timeout);
+                false
+            }
+            PromiseState::Broken => {
+                warn!("reformat promise broken");
+                false
+            }
         }
     }
 
@@ -639,6 +910,70 @@ impl EditorWidget {
         }
     }
 
+    /*
+    Moves viewport the least possible distance, so kite is visible on page of given size.
+     */
+    fn follow_kite(&mut self, page_size: XY) {
+        let page_size = BufferXY::from(page_size);
+
+        if self.kite.x < self.viewport.x {
+            self.viewport.x = self.kite.x;
+        }
+        if page_size.x > 0 && self.kite.x >= self.viewport.x + page_size.x {
+            self.viewport.x = self.kite.x + 1 - page_size.x;
+        }
+
+        if self.kite.y < self.viewport.y {
+            self.viewport.y = self.kite.y;
+        }
+        if page_size.y > 0 && self.kite.y >= self.viewport.y + page_size.y {
+            self.viewport.y = self.kite.y + 1 - page_size.y;
+        }
+    }
+
+    // first visible column and line, in buffer space
+    pub fn viewport(&self) -> BufferXY {
+        self.viewport
+    }
+
+    fn get_margin_width_for_height(height: usize) -> u16 {
+        format!("{}", height).width() as u16 + 2
+    }
+
+    fn render_line_no(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        let layout_res = unpack_unit!(self.layout_res.as_ref(), "render before layout",);
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            output.emit_metadata(crate::io::output::Metadata {
+                id: self.wid,
+                typename: Self::TYPENAME_FOR_MARGIN.to_string(),
+                rect: Rect::from_zero(XY::new(self.margin_width, output.size().y)),
+                focused,
+            });
+        }
+
+        let style = if focused { theme.ui.header } else { theme.ui.header.half() }.with_background(theme.default_text(focused).background);
+
+        let visible_rect = layout_res.visible_rect();
+        for idx in visible_rect.pos.y..visible_rect.lower_right().y {
+            let line_no_base_0 = self.viewport.y + (idx - visible_rect.pos.y) as usize;
+            let item = format!("{} ", line_no_base_0 + 1);
+            let num_digits_plus_one = item.len() as u16;
+            let offset = if num_digits_plus_one <= self.margin_width {
+                self.margin_width - num_digits_plus_one
+            } else {
+                error!("num_digits > margin_width, hardcoding safe fix");
+                0
+            };
+
+            for px in 0..offset {
+                output.print_at(XY::new(px, idx), style, " ")
+            }
+
+            output.print_at(XY::new(offset, idx), style, &item);
+        }
+    }
+
     pub fn set_cursors(&mut self, cursor_set: CursorSet) -> bool {
         if cursor_set.len() == 0 {
             error!("empty cursor set!");
@@ -679,26 +1014,52 @@ impl EditorWidget {
     fn get_hover_subwidget(&self) -> Option<SubwidgetPointer<Self>> {
         self.requested_hover.as_ref()?;
 
+        // signature help never takes focus, so user can keep typing arguments
+        if self.has_signature_help() {
+            return None;
+        }
+
         Some(SubwidgetPointer::<Self>::new(
             Box::new(|s: &EditorWidget| match s.requested_hover.as_ref().unwrap() {
                 (_, EditorHover::Completion(comp)) => comp as &dyn Widget,
+                (_, EditorHover::Hover(hover)) => hover as &dyn Widget,
+                (_, EditorHover::SignatureHelp(sh)) => sh as &dyn Widget,
+                (_, EditorHover::Rename(rw)) => rw as &dyn Widget,
             }),
             Box::new(|s: &mut EditorWidget| match s.requested_hover.as_mut().unwrap() {
                 (_, EditorHover::Completion(comp)) => comp as &mut dyn Widget,
+                (_, EditorHover::Hover(hover)) => hover as &mut dyn Widget,
+                (_, EditorHover::SignatureHelp(sh)) => sh as &mut dyn Widget,
+                (_, EditorHover::Rename(rw)) => rw as &mut dyn Widget,
             }),
         ))
     }
 
+    /*
+    Inline labels push the text to the right, so they don't cover anything but their anchor
+    position (think parameter name hints in "foo(a, b)").
+     */
+    fn label_collision_width(label: &Label) -> usize {
+        match label.pos {
+            LabelPos::Inline { .. } | LabelPos::InlineStupid { .. } => 1,
+            LabelPos::LineAfter { .. } => label.screen_width() as usize,
+        }
+    }
+
     // Returns first colliding label
-    fn can_add_label<'a>(labels: &'a mut BTreeMap<XY, Label>, new_label: (XY, &Label)) -> Option<(XY, &'a Label)> {
-        let width = new_label.1.screen_width();
-        let new_rect = Rect::new(new_label.0, XY::new(width, 1));
+    fn can_add_label<'a>(labels: &'a mut BTreeMap<BufferXY, Label>, new_label: (BufferXY, &Label)) -> Option<(BufferXY, &'a Label)> {
+        let width = Self::label_collision_width(new_label.1);
+        let new_range = new_label.0.x..new_label.0.x + width;
 
         for (pos, label) in labels.iter() {
-            let width = label.screen_width();
-            let old_rect = Rect::new(*pos, XY::new(width, 1));
+            if pos.y != new_label.0.y {
+                continue;
+            }
 
-            if old_rect.intersect(new_rect).is_some() {
+            let width = Self::label_collision_width(label);
+            let old_range = pos.x..pos.x + width;
+
+            if old_range.start < new_range.end && new_range.start < old_range.end {
                 return Some((*pos, label));
             }
         }
@@ -730,47 +1091,63 @@ impl EditorWidget {
             Some(cs) => cs.clone(),
         };
 
+        // output is in screen space, viewport says where it is in the buffer.
         let visible_rect = output.visible_rect();
+        let first_column = self.viewport.x;
+        let beyond_last_column = first_column + visible_rect.size.x as usize;
+        let lines_to_skip = self.viewport.y;
+        let beyond_last_line = lines_to_skip + visible_rect.size.y as usize;
+
+        let buffer_to_screen = |x: usize, y: usize| -> XY {
+            debug_assert!(first_column <= x && x < beyond_last_column);
+            debug_assert!(lines_to_skip <= y && y < beyond_last_line);
+            visible_rect.pos + XY::new((x - first_column) as u16, (y - lines_to_skip) as u16)
+        };
 
-        let char_range_op = buffer.get_visible_chars_range(output);
+        let char_range_op = buffer.get_visible_chars_range(lines_to_skip..beyond_last_line);
         // highlights are actually just code coloring
         let highlights = buffer.highlight(char_range_op.clone());
 
         let mut highlight_iter = highlights.iter().peekable();
-        let lines_to_skip = visible_rect.upper_left().y as usize;
+        // sorted and not overlapping, so just like highlights, passed only once
+        let mut document_highlights_iter = self.document_highlights.iter().peekable();
 
         let mut lines_it = buffer.lines().skip(lines_to_skip);
-        // skipping lines that cannot be visible, because they are before hint()
+        // skipping lines that cannot be visible, because they are before viewport
         let mut line_idx = lines_to_skip;
 
         // preparing labels
         // Right now labels "chain" one after another. Provided priority does not change, they should not
         // glitter.
-        let mut labels: BTreeMap<XY, Label> = BTreeMap::new();
+        let mut labels: BTreeMap<BufferXY, Label> = BTreeMap::new();
         let mut last_x_offset = 0;
 
         // TODO add test
+        let mut candidate_labels: Vec<Label> = Vec::new();
         if self.providers.config().global.inline_warnings_and_errors {
-            // if we don't have a char_range, that means the "visible rect" is empty, so we don't draw anything
-            if let Some(char_range) = char_range_op {
-                for label in self.providers.todo_get_aggegated_labels(buffer.get_path()) {
-                    if label
-                        .pos
-                        .maybe_should_draw(char_range.clone(), lines_to_skip..visible_rect.lower_right().y as usize)
-                    {
-                        if let Some(xy) = label.pos.into_position(&*buffer) {
-                            if (xy.y as usize) < lines_to_skip {
-                                continue;
-                            }
+            candidate_labels = self.providers.todo_get_aggegated_labels(buffer.get_path());
+        }
+        // inlay hints go last, so in case of collision warnings and errors win.
+        if self.providers.config().global.inlay_hints {
+            candidate_labels.extend(self.inlay_hints.iter().cloned());
+        }
 
-                            if let Some((_collision_xy, old_label)) = Self::can_add_label(&mut labels, (xy, &label)) {
-                                warn!(
-                                    "Discarding a label because of collision. Discarded label [{:?}], colliding label [{:?}]",
-                                    label, old_label
-                                );
-                            } else {
-                                labels.insert(xy, label.clone());
-                            }
+        // if we don't have a char_range, that means the "visible rect" is empty, so we don't draw anything
+        if let Some(char_range) = char_range_op {
+            for label in candidate_labels {
+                if label.pos.maybe_should_draw(char_range.clone(), lines_to_skip..beyond_last_line) {
+                    if let Some(xy) = label.pos.into_position(&*buffer) {
+                        if xy.y < lines_to_skip {
+                            continue;
+                        }
+
+                        if let Some((_collision_xy, old_label)) = Self::can_add_label(&mut labels, (xy, &label)) {
+                            warn!(
+                                "Discarding a label because of collision. Discarded label [{:?}], colliding label [{:?}]",
+                                label, old_label
+                            );
+                        } else {
+                            labels.insert(xy, label);
                         }
                     }
                 }
@@ -781,8 +1158,8 @@ impl EditorWidget {
         //     // skipping lines that cannot be visible, because they are before hint()
         //     .skip(output.size_constraint().visible_hint().upper_left().y as usize)
         while let Some(line) = lines_it.next() {
-            // skipping lines that cannot be visible, because the are after the hint()
-            if line_idx >= visible_rect.lower_right().y as usize {
+            // skipping lines that cannot be visible, because the are after the viewport
+            if line_idx >= beyond_last_line {
                 // debug!("early exit 7");
                 break;
             }
@@ -793,10 +1170,10 @@ impl EditorWidget {
             };
 
             // let's generate the "combined line" of actual file and labels.
-            let mut filtered_labels: Vec<(XY, &Label)> = Vec::new();
+            let mut filtered_labels: Vec<(BufferXY, &Label)> = Vec::new();
 
             for (label_pos, label) in labels.iter() {
-                if label_pos.y as usize != line_idx {
+                if label_pos.y != line_idx {
                     continue;
                 }
 
@@ -815,7 +1192,7 @@ impl EditorWidget {
                 let mut x_offset: usize = 0;
 
                 for (c_idx, c) in line.graphemes().enumerate() {
-                    let text_pos = XY::new(x_offset as u16, line_idx as u16);
+                    let text_pos = BufferXY::new(x_offset, line_idx);
 
                     while let Some((label_pos, label)) = label_it.peek() {
                         if *label_pos == text_pos {
@@ -841,6 +1218,14 @@ impl EditorWidget {
                         }
                     }
 
+                    while let Some((range, _)) = document_highlights_iter.peek() {
+                        if char_idx >= range.end {
+                            document_highlights_iter.next();
+                        } else {
+                            break;
+                        }
+                    }
+
                     // TODO optimise
                     let tr: String;
                     let whitespace: bool;
@@ -864,27 +1249,37 @@ impl EditorWidget {
                         false
                     };
 
-                    let mut style = Self::get_cell_style(
-                        theme,
-                        cursor_set_copy.get_cursor_status_for_char(char_idx),
-                        is_dropping_cursor,
-                        is_special_cursor,
-                        focused,
-                    );
+                    let cursor_status = cursor_set_copy.get_cursor_status_for_char(char_idx);
+                    let mut style = Self::get_cell_style(theme, cursor_status, is_dropping_cursor, is_special_cursor, focused);
+
+                    // occurrences don't override cursors and selections
+                    if cursor_status == CursorStatus::None && !is_special_cursor {
+                        if let Some((range, kind)) = document_highlights_iter.peek() {
+                            if range.contains(&char_idx) {
+                                style.set_background(theme.occurrence_background(*kind));
+                            }
+                        }
+                    }
 
                     if !whitespace {
                         // TODO cleanup
                         if let Some(item) = highlight_iter.peek() {
-                            if let Some(color) = theme.name_to_color(&item.identifier) {
+                            if let Some(color) = theme.highlight_color(item) {
                                 style.set_foreground(color);
                             }
+                            // unlike color, effect of the next item must not leak onto characters before it
+                            if item.char_begin <= char_idx {
+                                if let Some(effect) = theme.highlight_effect(item) {
+                                    style.set_effect(effect);
+                                }
+                            }
                         }
                     }
 
                     x_offset += tr.width();
                     combined_line.push((style, tr));
 
-                    if x_offset as u16 >= visible_rect.lower_right().x {
+                    if x_offset >= beyond_last_column {
                         debug!("early exit 6: character after visible rect");
                         break;
                     }
@@ -903,7 +1298,7 @@ impl EditorWidget {
                     // I follow up on not drawn labels
                     for (label_pos, label) in label_it {
                         // moving cursor to right place
-                        while x_offset < label_pos.x as usize {
+                        while x_offset < label_pos.x {
                             combined_line.push((local_style, " ".to_string()));
                             x_offset += 1;
                         }
@@ -916,19 +1311,20 @@ impl EditorWidget {
                 }
             }
 
-            // ok, at this point I consume the combined line
+            // ok, at this point I consume the combined line, drawing only the part within viewport
             let mut x_offset: usize = 0;
             for (style, grapheme) in combined_line.styled_graphemes() {
-                let pos = XY::new(x_offset as u16, line_idx as u16);
+                let width = grapheme.width();
 
-                output.print_at(pos, *style, grapheme);
-                x_offset += grapheme.width();
+                if x_offset >= first_column && x_offset + width <= beyond_last_column {
+                    output.print_at(buffer_to_screen(x_offset, line_idx), *style, grapheme);
+                }
+                x_offset += width;
             }
             last_x_offset = x_offset;
 
             line_idx += 1;
-            // TODO u16 overflow
-            if line_idx as u16 >= visible_rect.lower_right().y {
+            if line_idx >= beyond_last_line {
                 // debug!("early exit 5 : osc : {:?}, output : {:?}", output.size_constraint(), output);
                 break;
             }
@@ -941,9 +1337,7 @@ impl EditorWidget {
 
         // let x_beyond_last = one_beyond_limit - buffer.line_to_char(last_line).unwrap(); //TODO
 
-        let one_beyond_last_pos = XY::new(x as u16, last_line as u16);
-
-        if one_beyond_last_pos < visible_rect.lower_right() {
+        if (lines_to_skip..beyond_last_line).contains(&last_line) && (first_column..beyond_last_column).contains(&x) {
             let cursor_status = cursor_set_copy.get_cursor_status_for_char(one_beyond_limit);
             let is_special_cursor: bool = if let EditorState::DroppingCursor { special_cursor } = &self.state {
                 special_cursor.get_cursor_status_for_char(one_beyond_limit) == CursorStatus::UnderCursor
@@ -953,7 +1347,7 @@ impl EditorWidget {
 
             let style = Self::get_cell_style(&theme, cursor_status, is_dropping_cursor, is_special_cursor, focused);
 
-            output.print_at(one_beyond_last_pos, style, BEYOND);
+            output.print_at(buffer_to_screen(x, last_line), style, BEYOND);
         }
     }
 
@@ -963,6 +1357,9 @@ impl EditorWidget {
             let mut sub_output = SubOutput::new(output, rect);
             match hover {
                 EditorHover::Completion(completion) => completion.render(theme, focused, &mut sub_output),
+                EditorHover::Hover(hover) => hover.render(theme, focused, &mut sub_output),
+                EditorHover::SignatureHelp(sh) => sh.render(theme, focused, &mut sub_output),
+                EditorHover::Rename(rw) => rw.render(theme, focused, &mut sub_output),
             }
         }
     }
@@ -987,6 +1384,10 @@ impl EditorWidget {
         }
     }
 
+    pub fn has_signature_help(&self) -> bool {
+        matches!(self.requested_hover, Some((_, EditorHover::SignatureHelp(_))))
+    }
+
     pub fn close_completions(&mut self) -> bool {
         if !self.has_completions() {
             error!("not closing completions - they are not open");
@@ -1044,6 +1445,115 @@ impl EditorWidget {
         }
     }
 
+    pub fn request_hover(&mut self, buffer: &BufferState) {
+        let cursor = unpack_unit!(
+            buffer.cursors(self.wid).and_then(|c| c.as_single()),
+            "not opening hover - cursor not single.",
+        );
+        let navcomp = unpack_unit!(self.navcomp.clone(), "not opening hover - navcomp not available.",);
+        let stupid_cursor = unpack_unit_e!(
+            StupidCursor::from_real_cursor(buffer, cursor).ok(),
+            "failed converting cursor to lsp_cursor",
+        );
+        let path = unpack_unit_e!(buffer.get_path(), "path not available",);
+        let visible_rect = unpack_unit_e!(
+            self.layout_res.as_ref().map(|lr| lr.visible_rect()),
+            "can't request hover before layout"
+        );
+
+        let hover_settings = unpack_unit!(
+            self.get_cursor_related_hover_settings(buffer, None),
+            "not opening hover - no hover settings"
+        );
+        let promise = unpack_unit!(navcomp.hover(path, stupid_cursor), "not opening hover - no promise");
+        let max_size = unpack_unit_e!(hover_settings.get_max_hover_size(&visible_rect), "failed spacing hover");
+
+        debug!("created hover: settings [{:?}]", &hover_settings);
+        self.requested_hover = Some((hover_settings, EditorHover::Hover(HoverWidget::new(promise, max_size))));
+    }
+
+    /*
+    If signature help is already displayed, it's kept (with updated position) until new results arrive.
+     */
+    pub fn request_signature_help(&mut self, buffer: &BufferState) {
+        let cursor = unpack_unit!(
+            buffer.cursors(self.wid).and_then(|c| c.as_single()),
+            "not opening signature help - cursor not single.",
+        );
+        let navcomp = unpack_unit!(self.navcomp.clone(), "not opening signature help - navcomp not available.",);
+        let stupid_cursor = unpack_unit_e!(
+            StupidCursor::from_real_cursor(buffer, cursor).ok(),
+            "failed converting cursor to lsp_cursor",
+        );
+        let path = unpack_unit_e!(buffer.get_path(), "path not available",);
+        let visible_rect = unpack_unit_e!(
+            self.layout_res.as_ref().map(|lr| lr.visible_rect()),
+            "can't request signature help before layout"
+        );
+
+        let hover_settings = match self.get_cursor_related_hover_settings(buffer, None) {
+            Some(hs) => hs,
+            None => {
+                debug!("closing signature help - no hover settings");
+                self.close_signature_help();
+                return;
+            }
+        };
+        let max_size = unpack_unit_e!(hover_settings.get_max_hover_size(&visible_rect), "failed spacing hover");
+        let promise = match navcomp.signature_help(path, stupid_cursor) {
+            Some(promise) => promise,
+            None => {
+                debug!("closing signature help - no promise");
+                self.close_signature_help();
+                return;
+            }
+        };
+
+        if let Some((settings, EditorHover::SignatureHelp(sw))) = self.requested_hover.as_mut() {
+            *settings = hover_settings;
+            sw.set_max_size(max_size);
+            sw.set_promise(promise);
+        } else {
+            debug!("created signature help: settings [{:?}]", &hover_settings);
+            self.requested_hover = Some((
+                hover_settings,
+                EditorHover::SignatureHelp(SignatureHelpWidget::new(promise, max_size)),
+            ));
+        }
+    }
+
+    pub fn close_signature_help(&mut self) -> bool {
+        if !self.has_signature_help() {
+            return false;
+        }
+
+        self.requested_hover = None;
+        true
+    }
+
+    /*
+    Called after every edit message. Signature help is opened when one of triggers was just typed, and
+    once open it follows the cursor until navcomp says we're no longer within a call.
+     */
+    fn update_signature_help(&mut self, buffer: &BufferState, content_changed: bool) {
+        if self.has_signature_help() {
+            self.request_signature_help(buffer);
+            return;
+        }
+
+        if !content_changed || self.requested_hover.is_some() {
+            return;
+        }
+
+        let cursor = unpack_unit!(buffer.cursors(self.wid).and_then(|c| c.as_single()));
+        let navcomp = unpack_unit!(self.navcomp.as_ref());
+        let path = unpack_unit!(buffer.get_path());
+
+        if trigger_before_char_idx(navcomp.signature_help_triggers(path), buffer, cursor.a).is_some() {
+            self.request_signature_help(buffer);
+        }
+    }
+
     // TODO merge with function above
     // TODO if cursor became non-single while waiting for completions - we need to close hover
     pub fn update_completions(&mut self, buffer: &BufferState) {
@@ -1153,23 +1663,227 @@ impl EditorWidget {
             "not opening completions - cursor not single.",
         );
 
-        let _path = unpack_unit!(buffer.get_path(), "no path set",);
-        let _stupid_cursor = unpack_unit!(
-            StupidCursor::from_real_cursor(buffer, cursor).ok(),
-            "failed conversion to stupid cursor",
-        );
+        self.update_document_highlights(buffer, cursor);
+    }
+
+    /*
+    Asks for occurrences of the symbol under cursor, unless cursor is still within one of the
+    current ones. Without navcomp, tree-sitter is asked for identifiers of the same name.
+     */
+    fn update_document_highlights(&mut self, buffer: &BufferState, cursor: Cursor) {
+        if self
+            .document_highlights
+            .iter()
+            .any(|(range, _)| range.start <= cursor.a && cursor.a <= range.end)
+        {
+            return;
+        }
+
+        self.document_highlights.clear();
+        self.document_highlights_promise = None;
 
-        // TODO add support for scrachpad (path == None)
+        match self.navcomp.as_ref() {
+            Some(navcomp) => {
+                let path = unpack_unit!(buffer.get_path());
+                let stupid_cursor = unpack_unit!(
+                    StupidCursor::from_real_cursor(buffer, cursor).ok(),
+                    "failed conversion to stupid cursor",
+                );
 
-        // self.navcomp_symbol = self.navcomp.as_ref().map(|navcomp|
-        //     navcomp.todo_get_symbol_at(path, stupid_cursor)
-        // ).flatten();
+                self.document_highlights_promise = navcomp.document_highlights(path, stupid_cursor);
+            }
+            None => {
+                self.document_highlights = buffer
+                    .identifier_occurrences(cursor.a)
+                    .into_iter()
+                    .map(|range| (range, DocumentHighlightKind::Text))
+                    .collect();
+            }
+        }
     }
 
-    fn after_content_changed(&self, buffer: &BufferState) {
-        if let (Some(navcomp), Some(path)) = (&self.navcomp, buffer.get_path()) {
-            let contents = buffer.text().rope().clone();
-            navcomp.submit_edit_event(path, contents);
+    // Called from layout.
+    fn poll_document_highlights(&mut self) {
+        let promise = unpack_unit!(self.document_highlights_promise.as_mut());
+
+        match promise.update().state {
+            PromiseState::Unresolved => return,
+            PromiseState::Ready => {
+                let highlights = promise.read().cloned().unwrap_or_default();
+                let buffer = unpack_unit_e!(self.buffer.lock(), "failed to lock buffer");
+
+                self.document_highlights = highlights
+                    .into_iter()
+                    .filter_map(|highlight| {
+                        StupidCursor::to_real_cursor_range(highlight.stupid_range, &*buffer)
+                            .map(|selection| (selection.b..selection.e, highlight.kind))
+                    })
+                    .collect();
+                self.document_highlights.sort_by_key(|(range, _)| range.start);
+            }
+            PromiseState::Broken => {
+                debug!("document highlights promise broken");
+            }
+        }
+
+        self.document_highlights_promise = None;
+    }
+
+    /*
+    Moves cursor to next (or previous) occurrence of the symbol under cursor, wrapping around.
+     */
+    fn jump_to_usage(&mut self, buffer: &mut BufferState, forward: bool) -> bool {
+        let cursor = unpack_or!(buffer.cursors(self.wid).and_then(|c| c.as_single()), false, "cursor not single");
+        let count = self.document_highlights.len();
+        if count == 0 {
+            debug!("no usages to jump to");
+            return false;
+        }
+
+        let current_idx_op = self
+            .document_highlights
+            .iter()
+            .position(|(range, _)| range.start <= cursor.a && cursor.a <= range.end);
+
+        let target_idx = match (current_idx_op, forward) {
+            (Some(idx), true) => (idx + 1) % count,
+            (Some(idx), false) => (idx + count - 1) % count,
+            (None, true) => self
+                .document_highlights
+                .iter()
+                .position(|(range, _)| range.start > cursor.a)
+                .unwrap_or(0),
+            (None, false) => self
+                .document_highlights
+                .iter()
+                .rposition(|(range, _)| range.end < cursor.a)
+                .unwrap_or(count - 1),
+        };
+
+        let target = self.document_highlights[target_idx].0.start;
+        buffer.text_mut().set_cursor_set(self.wid, Cursor::new(target).as_cursor_set());
+        self.update_kite(buffer, if forward { Arrow::Down } else { Arrow::Up });
+
+        true
+    }
+
+    fn after_content_changed(&mut self, buffer: &mut BufferState) {
+        // edit events are taken even if there is no navcomp, so they don't pile up.
+        let edit_event_op = buffer.take_edit_event();
+
+        if let (Some(navcomp), Some(path), Some(edit_event)) = (&self.navcomp, buffer.get_path(), edit_event_op) {
+            navcomp.submit_edit_event(path, edit_event);
+        }
+
+        self.inlay_hints_lines = None;
+        self.semantic_tokens_outdated = true;
+        self.document_highlights.clear();
+        self.document_highlights_promise = None;
+        self.code_actions_range = None;
+    }
+
+    /*
+    Called from layout. Polls pending semantic tokens and asks for new ones if contents changed
+    since the last request.
+     */
+    fn update_semantic_tokens(&mut self) {
+        if let Some(promise) = self.semantic_tokens_promise.as_mut() {
+            match promise.update().state {
+                PromiseState::Unresolved => return,
+                PromiseState::Ready => match promise.read().cloned().flatten() {
+                    Some(tokens) => match self.buffer.lock_rw() {
+                        Some(mut buffer) => buffer.set_semantic_tokens(tokens),
+                        None => error!("failed to lock buffer for writing semantic tokens"),
+                    },
+                    // old tokens are better than none, until the new ones come
+                    None => {
+                        debug!("no usable semantic tokens, asking again");
+                        self.semantic_tokens_outdated = true;
+                    }
+                },
+                PromiseState::Broken => {
+                    debug!("semantic tokens promise broken");
+                }
+            }
+            self.semantic_tokens_promise = None;
+        }
+
+        if !self.semantic_tokens_outdated {
+            return;
+        }
+
+        let navcomp = unpack_unit!(self.navcomp.as_ref());
+        let buffer = unpack_unit_e!(self.buffer.lock(), "failed to lock buffer");
+        let path = unpack_unit!(buffer.get_path());
+
+        // If there's no promise (navcomp busy or not supporting semantic tokens), I will retry
+        // after next edit.
+        self.semantic_tokens_promise = navcomp.semantic_tokens(path);
+        self.semantic_tokens_outdated = false;
+    }
+
+    /*
+    Called from layout. Polls pending inlay hints and requests new ones if visible part of the file
+    is not covered by the last request, or the contents changed since. Answer to a request in flight
+    is awaited even if it's outdated, it's followed by a new request on the frame it arrives.
+     */
+    fn update_inlay_hints(&mut self, visible_lines: Range<usize>) {
+        if !self.providers.config().global.inlay_hints {
+            self.inlay_hints.clear();
+            self.inlay_hints_promise = None;
+            self.inlay_hints_lines = None;
+            return;
+        }
+
+        if let Some((promise, requested_at)) = self.inlay_hints_promise.as_mut() {
+            match promise.update().state {
+                // LSP that never answers should not stop hints forever
+                PromiseState::Unresolved if requested_at.elapsed() > Self::INLAY_HINTS_TIMEOUT => {
+                    debug!("inlay hints promise timed out");
+                    self.inlay_hints_promise = None;
+                }
+                PromiseState::Unresolved => {}
+                PromiseState::Ready => {
+                    self.inlay_hints = promise.read().cloned().unwrap_or_default();
+                    self.inlay_hints_promise = None;
+                }
+                PromiseState::Broken => {
+                    debug!("inlay hints promise broken");
+                    self.inlay_hints_promise = None;
+                }
+            }
+        }
+
+        if self.inlay_hints_promise.is_some() {
+            return;
+        }
+
+        let first_line = visible_lines.start;
+        let last_line = visible_lines.end;
+
+        if let Some(lines) = self.inlay_hints_lines.as_ref() {
+            if lines.start <= first_line && last_line <= lines.end {
+                return;
+            }
+        }
+
+        let navcomp = unpack_unit!(self.navcomp.as_ref());
+        let buffer = unpack_unit_e!(self.buffer.lock(), "failed to lock buffer");
+        let path = unpack_unit!(buffer.get_path());
+
+        // lines range is not clamped to file length, so visible rect reaching past the end of file
+        // does not trigger a new request on every layout. Growing the file is an edit anyway.
+        let lines = first_line.saturating_sub(Self::INLAY_HINTS_MARGIN)..last_line + Self::INLAY_HINTS_MARGIN;
+        let stupid_range = (
+            StupidCursor::new(0, lines.start as u32),
+            // end of range is exclusive, so it's the beginning of first line not covered.
+            StupidCursor::new(0, min(lines.end, buffer.len_lines()) as u32),
+        );
+
+        // If there's no promise (for instance navcomp was busy), I will retry on next layout.
+        if let Some(promise) = navcomp.inlay_hints(path, stupid_range) {
+            self.inlay_hints_promise = Some((promise, Instant::now()));
+            self.inlay_hints_lines = Some(lines);
         }
     }
 
@@ -1203,12 +1917,16 @@ impl EditorWidget {
     fn layout_hover(&mut self, visible_rect: Rect) {
         let (hover_settings, hover) = unpack_unit!(self.requested_hover.as_mut());
 
-        if let EditorHover::Completion(cw) = hover {
-            if !cw.poll_results_should_draw() {
-                debug!("withdrawing completion widget");
-                self.requested_hover = None;
-                return;
-            }
+        let should_draw = match hover {
+            EditorHover::Completion(cw) => cw.poll_results_should_draw(),
+            EditorHover::Hover(hw) => hw.poll_results_should_draw(),
+            EditorHover::SignatureHelp(sw) => sw.poll_results_should_draw(),
+            EditorHover::Rename(_) => true,
+        };
+        if !should_draw {
+            debug!("withdrawing hover widget");
+            self.requested_hover = None;
+            return;
         }
 
         // TODO this check should be moved somewhere else
@@ -1219,11 +1937,9 @@ impl EditorWidget {
         }
 
         let mut hover_size = unpack_unit_e!(hover_settings.get_max_hover_size(&visible_rect), "failed get max hover size");
-        if let EditorHover::Completion(cw) = hover {
-            let full_size = cw.full_size();
-            hover_size.x = max(10, min(hover_size.x, full_size.x));
-            hover_size.y = max(1, min(hover_size.y, full_size.y));
-        };
+        let full_size = hover.get_widget().full_size();
+        hover_size.x = max(10, min(hover_size.x, full_size.x));
+        hover_size.y = max(1, min(hover_size.y, full_size.y));
 
         let mut rect_assuming_below = Rect::new(visible_rect.pos + hover_settings.anchor_in_visible_rect + (0, 1), hover_size);
 
@@ -1285,6 +2001,22 @@ impl EditorWidget {
         .someboxed()
     }
 
+    // Used as title of code results, like: Definition of function "foo"
+    fn describe_symbol_at(buffer: &BufferState, cursor: Cursor, what: &str) -> String {
+        let highlight = buffer.smallest_highlight(cursor.a);
+
+        let symbol_op: Option<String> = highlight
+            .as_ref()
+            .and_then(|item| buffer.get_selected_chars(Selection::new(item.char_begin, item.char_end)).0);
+
+        match (highlight, symbol_op) {
+            (Some(type_), Some(item)) => {
+                format!("{} {} \"{}\"", what, type_.identifier.as_ref(), item)
+            }
+            _ => format!("{} symbol:", what),
+        }
+    }
+
     /*
     The BufferState is passed to avoid double-locking
      */
@@ -1301,18 +2033,7 @@ impl EditorWidget {
             None,
             "failed conversion to stupid cursor"
         );
-        let highlight = buffer.smallest_highlight(cursor.a);
-
-        let symbol_op: Option<String> = highlight
-            .as_ref()
-            .and_then(|item| buffer.get_selected_chars(Selection::new(item.char_begin, item.char_end)).0);
-
-        let symbol_desc: String = match (highlight, symbol_op) {
-            (Some(type_), Some(item)) => {
-                format!("Definition of {} \"{}\"", type_.identifier.as_ref(), item)
-            }
-            _ => "Definition of symbol:".to_string(),
-        };
+        let symbol_desc = Self::describe_symbol_at(buffer, cursor, "Definition of");
 
         let promise = unpack_or_e!(
             navcomp.go_to_definition(path, stupid_cursor),
@@ -1329,6 +2050,144 @@ impl EditorWidget {
         }
         .someboxed()
     }
+
+    /*
+    Used for type definition, implementations and declaration. This blocks up to DEFAULT_EDITOR_TIMEOUT:
+    if there is exactly one target, I just jump there. Otherwise (or if navcomp is slow) targets are
+    listed the same way as definitions.
+     */
+    fn go_to_targets(
+        &mut self,
+        buffer: &BufferState,
+        what: &str,
+        request: fn(&NavCompRef, &SPath, StupidCursor) -> Option<SymbolUsagesPromise>,
+    ) -> Option<Box<dyn AnyMsg>> {
+        let navcomp = unpack_or_e!(&self.navcomp, None, "can't jump without navcomp");
+        let cursor = unpack_or!(
+            buffer.cursors(self.wid).and_then(|c| c.as_single()),
+            None,
+            "not jumping - cursor not single."
+        );
+        let path = unpack_or!(buffer.get_path(), None, "no path set");
+        let stupid_cursor = unpack_or!(
+            StupidCursor::from_real_cursor(buffer, cursor).ok(),
+            None,
+            "failed conversion to stupid cursor"
+        );
+
+        let mut promise = unpack_or_e!(
+            request(navcomp, path, stupid_cursor),
+            None,
+            "failed to acquire promise from navcomp"
+        );
+
+        match promise.wait(Some(DEFAULT_EDITOR_TIMEOUT)) {
+            PromiseState::Ready => {
+                let targets = promise.read().cloned().unwrap_or_default();
+                match targets.as_slice() {
+                    [] => {
+                        debug!("{}: no targets", what);
+                        return None;
+                    }
+                    [target] => {
+                        let spath = unpack_or_e!(
+                            url_to_spath(self.providers.fsf(), &target.path),
+                            None,
+                            "failed converting {} to spath",
+                            target.path
+                        );
+
+                        return MainViewMsg::OpenFileBySpath {
+                            spath,
+                            position_op: Some(target.stupid_range.0),
+                        }
+                        .someboxed();
+                    }
+                    _ => {}
+                }
+            }
+            PromiseState::Unresolved => {
+                debug!("{}: promise not ready, listing targets as they come", what);
+            }
+            PromiseState::Broken => {
+                warn!("{}: promise broken", what);
+                return None;
+            }
+        }
+
+        MainViewMsg::GoToDefinition {
+            promise_op: Some(StupidSymbolUsageCodeResultsProvider::new(
+                self.providers.clone(),
+                Self::describe_symbol_at(buffer, cursor, what),
+                promise,
+            )),
+        }
+        .someboxed()
+    }
+
+    /*
+    Opens a box asking for the new name, pre-filled with the old one.
+     */
+    pub fn request_rename(&mut self, buffer: &BufferState) {
+        let cursor = unpack_unit!(
+            buffer.cursors(self.wid).and_then(|c| c.as_single()),
+            "not opening rename - cursor not single.",
+        );
+        unpack_unit!(self.navcomp.as_ref(), "not opening rename - navcomp not available.",);
+        unpack_unit!(buffer.get_path(), "not opening rename - no path set",);
+        let visible_rect = unpack_unit_e!(
+            self.layout_res.as_ref().map(|lr| lr.visible_rect()),
+            "can't request rename before layout"
+        );
+
+        let hover_settings = unpack_unit!(
+            self.get_cursor_related_hover_settings(buffer, None),
+            "not opening rename - no hover settings"
+        );
+        let max_size = unpack_unit_e!(hover_settings.get_max_hover_size(&visible_rect), "failed spacing hover");
+        let old_name_op = word_around_char_idx(buffer, cursor.a);
+
+        debug!("created rename: settings [{:?}]", &hover_settings);
+        self.requested_hover = Some((
+            hover_settings,
+            EditorHover::Rename(RenameWidget::new(self.providers.config().clone(), old_name_op, max_size)),
+        ));
+    }
+
+    /*
+    The BufferState is passed to avoid double-locking.
+    Nothing is changed here, edits are applied only after user accepts the preview.
+     */
+    pub fn rename(&self, buffer: &BufferState, new_name: String) -> Option<Box<dyn AnyMsg>> {
+        let navcomp = unpack_or_e!(&self.navcomp, None, "can't rename without navcomp");
+        let cursor = unpack_or!(
+            buffer.cursors(self.wid).and_then(|c| c.as_single()),
+            None,
+            "not renaming - cursor not single."
+        );
+        let path = unpack_or!(buffer.get_path(), None, "no path set");
+        let stupid_cursor = unpack_or!(
+            StupidCursor::from_real_cursor(buffer, cursor).ok(),
+            None,
+            "failed conversion to stupid cursor"
+        );
+
+        let symbol_desc: String = match word_around_char_idx(buffer, cursor.a) {
+            Some(old_name) => format!("Rename \"{}\" to \"{}\"", old_name, new_name),
+            None => format!("Rename to \"{}\"", new_name),
+        };
+
+        let promise = unpack_or_e!(
+            navcomp.rename(path, stupid_cursor, new_name),
+            None,
+            "failed to acquire RenamePromise from navcomp"
+        );
+
+        MainViewMsg::RenamePreview {
+            promise_op: Some(RenameCodeResultsProvider::new(self.providers.clone(), symbol_desc, promise)),
+        }
+        .someboxed()
+    }
 }
 
 pub fn build_tabs_string(tab_count: usize) -> String {
@@ -1376,13 +2235,21 @@ impl Widget for EditorWidget {
         Self::TYPENAME
     }
 
+    fn prelayout(&mut self) {
+        self.update_code_actions();
+    }
+
     fn size_policy(&self) -> SizePolicy {
         SizePolicy::MATCH_LAYOUT
     }
 
     fn full_size(&self) -> XY {
         if let Some(lock) = self.buffer.lock() {
-            lock.size()
+            let size = lock.size();
+            let margin_width = Self::get_margin_width_for_height(size.y);
+            let size = size.to_xy_saturating();
+
+            XY::new(size.x.saturating_add(margin_width), size.y).max_both_axis(MIN_EDITOR_SIZE)
         } else {
             error!("couldn't lock buffer to count");
             MIN_EDITOR_SIZE
@@ -1390,7 +2257,30 @@ impl Widget for EditorWidget {
     }
 
     fn layout(&mut self, screenspace: Screenspace) {
-        if self.layout_res != Some(screenspace) {
+        let len_lines = unpack_unit_e!(self.buffer.lock().map(|lock| lock.len_lines()), "failed to lock buffer for layout");
+        let output_size = screenspace.output_size();
+
+        let mut margin_width = Self::get_margin_width_for_height(max(len_lines, output_size.y as usize));
+        if margin_width >= output_size.x {
+            error!(
+                "margin_width = {} >= {} = output_size.x, not drawing line numbers",
+                margin_width, output_size.x
+            );
+            margin_width = 0;
+        }
+
+        let margin = XY::new(margin_width, 0);
+        let text_rect = Rect::new(margin, output_size - margin);
+        let text_visible_rect = unpack_unit!(
+            screenspace
+                .visible_rect()
+                .intersect(text_rect)
+                .and_then(|rect| rect.minus_shift(margin)),
+            "no part of text area is visible, not laying out further"
+        );
+        let text_screenspace = Screenspace::new(text_rect.size, text_visible_rect);
+
+        if self.layout_res != Some(text_screenspace) {
             debug!("changed size");
 
             if self.requested_hover.is_some() {
@@ -1399,20 +2289,36 @@ impl Widget for EditorWidget {
             }
         }
 
+        self.follow_kite(text_visible_rect.size);
+
         self.last_hover_rect = None;
-        self.layout_res = Some(screenspace);
-        self.layout_hover(screenspace.visible_rect());
+        self.margin_width = margin_width;
+        self.layout_res = Some(text_screenspace);
+        self.layout_hover(text_visible_rect);
+        self.update_inlay_hints(self.viewport.y..self.viewport.y + text_visible_rect.size.y as usize);
+        self.update_semantic_tokens();
+        self.poll_document_highlights();
     }
 
     fn on_input(&self, input_event: InputEvent) -> Option<Box<dyn AnyMsg>> {
         let c = &self.providers.config().keyboard_config.editor;
         let edit_msgs_keybindings = &self.providers.config().keyboard_config.edit_msgs;
 
-        match (&self.state, &self.requested_hover, input_event) {
+        // hovers that don't take focus (like signature help) don't block editing
+        let focused_hover = if self.get_hover_subwidget().is_some() {
+            self.requested_hover.as_ref()
+        } else {
+            None
+        };
+
+        match (&self.state, focused_hover, input_event) {
             (_, _, _) if self.ignore_input_altogether => {
                 debug!("ignoring input because ignore_input_altogether = true");
                 None
             }
+            (&EditorState::Editing, None, InputEvent::KeyInput(key)) if key.keycode == Keycode::Esc && self.has_signature_help() => {
+                EditorWidgetMsg::HoverClose.someboxed()
+            }
             (&EditorState::Editing, None, InputEvent::KeyInput(key)) if key == c.enter_cursor_drop_mode => {
                 EditorWidgetMsg::ToCursorDropMode.someboxed()
             }
@@ -1427,8 +2333,32 @@ impl Widget for EditorWidget {
             (&EditorState::Editing, None, InputEvent::KeyInput(key)) if !self.readonly && key == c.request_completions => {
                 EditorWidgetMsg::RequestCompletions.someboxed()
             }
+            (&EditorState::Editing, None, InputEvent::KeyInput(key)) if key == c.show_hover => EditorWidgetMsg::RequestHover.someboxed(),
+            (&EditorState::Editing, None, InputEvent::KeyInput(key)) if !self.readonly && key == c.rename => {
+                EditorWidgetMsg::RequestRename.someboxed()
+            }
+            (&EditorState::Editing, None, InputEvent::KeyInput(key)) if key == c.outline => EditorWidgetMsg::RequestOutline.someboxed(),
+            (&EditorState::Editing, None, InputEvent::KeyInput(key)) if !self.readonly && key == c.history => {
+                EditorWidgetMsg::RequestHistory.someboxed()
+            }
+            (&EditorState::Editing, None, InputEvent::KeyInput(key)) if key == c.next_usage => {
+                EditorWidgetMsg::SymbolContextAction(NavCompSymbolContextActions::NextUsage).someboxed()
+            }
+            (&EditorState::Editing, None, InputEvent::KeyInput(key)) if key == c.prev_usage => {
+                EditorWidgetMsg::SymbolContextAction(NavCompSymbolContextActions::PrevUsage).someboxed()
+            }
             (&EditorState::Editing, None, InputEvent::KeyInput(key)) if !self.readonly && key == c.reformat => {
-                EditorWidgetMsg::Reformat.someboxed()
+                let has_selection = self
+                    .get_buffer()
+                    .lock()
+                    .and_then(|buffer| buffer.cursors(self.wid).and_then(|c| c.as_single()).map(|c| c.s.is_some()))
+                    .unwrap_or(false);
+
+                if has_selection {
+                    EditorWidgetMsg::ReformatSelection.someboxed()
+                } else {
+                    EditorWidgetMsg::Reformat.someboxed()
+                }
             }
             // TODO change to if let Some() when it's stabilized
             (&EditorState::DroppingCursor { .. }, None, InputEvent::KeyInput(key))
@@ -1480,7 +2410,7 @@ impl Widget for EditorWidget {
 
                             // TODO this needs to happen only if CONTENTS changed, not if cursor positions changed
                             if changed.modified_buffer {
-                                self.after_content_changed(&buffer);
+                                self.after_content_changed(&mut buffer);
 
                                 if self.has_completions() {
                                     self.update_completions(&buffer);
@@ -1494,6 +2424,7 @@ impl Widget for EditorWidget {
                             };
 
                             self.todo_after_cursor_moved(&buffer);
+                            self.update_signature_help(&buffer, changed.modified_buffer);
 
                             None
                         }
@@ -1564,26 +2495,95 @@ impl Widget for EditorWidget {
                             self.request_completions(&buffer);
                             None
                         }
+                        (&EditorState::Editing, EditorWidgetMsg::RequestHover) => {
+                            self.request_hover(&buffer);
+                            None
+                        }
                         (&EditorState::Editing, EditorWidgetMsg::HoverClose) => {
                             self.requested_hover = None;
                             None
                         }
                         (&EditorState::Editing, EditorWidgetMsg::CompletionWidgetSelected(completion)) => {
-                            self.apply_completion_action(&mut buffer, completion);
+                            if self.apply_completion_action(&mut buffer, completion) {
+                                self.after_content_changed(&mut buffer);
+                            }
                             None
                         }
                         (&EditorState::Editing, EditorWidgetMsg::Reformat) => {
                             self.reformat(&mut buffer);
                             None
                         }
+                        (&EditorState::Editing, EditorWidgetMsg::ReformatSelection) => {
+                            self.reformat_selection(&mut buffer);
+                            None
+                        }
                         (&EditorState::Editing, EditorWidgetMsg::ShowUsages) => {
                             self.requested_hover = None;
                             self.show_usages(&buffer)
                         }
+                        (&EditorState::Editing, EditorWidgetMsg::SymbolContextAction(action)) => {
+                            match action {
+                                NavCompSymbolContextActions::NextUsage => self.jump_to_usage(&mut buffer, true),
+                                NavCompSymbolContextActions::PrevUsage => self.jump_to_usage(&mut buffer, false),
+                                other => {
+                                    warn!("symbol context action {:?} is not handled here", other);
+                                    false
+                                }
+                            };
+                            None
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::RequestOutline) => {
+                            self.requested_hover = None;
+                            self.show_outline(&buffer)
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::RequestHistory) => {
+                            self.requested_hover = None;
+                            self.show_history(&buffer)
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::ShowHierarchy { kind }) => {
+                            self.requested_hover = None;
+                            self.show_hierarchy(&buffer, *kind)
+                        }
                         (&EditorState::Editing, EditorWidgetMsg::GoToDefinition) => {
                             self.requested_hover = None;
                             self.go_to_definition(&buffer)
                         }
+                        (&EditorState::Editing, EditorWidgetMsg::GoToTypeDefinition) => {
+                            self.requested_hover = None;
+                            self.go_to_targets(&buffer, "Type definition of", |navcomp, path, cursor| {
+                                navcomp.go_to_type_definition(path, cursor)
+                            })
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::ShowImplementations) => {
+                            self.requested_hover = None;
+                            self.go_to_targets(&buffer, "Implementations of", |navcomp, path, cursor| {
+                                navcomp.go_to_implementations(path, cursor)
+                            })
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::GoToDeclaration) => {
+                            self.requested_hover = None;
+                            self.go_to_targets(&buffer, "Declaration of", |navcomp, path, cursor| {
+                                navcomp.go_to_declaration(path, cursor)
+                            })
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::RequestRename) => {
+                            self.request_rename(&buffer);
+                            None
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::Rename { new_name }) => {
+                            self.requested_hover = None;
+                            self.rename(&buffer, new_name.clone())
+                        }
+                        (&EditorState::Editing, EditorWidgetMsg::ApplyCodeAction { action }) => {
+                            // edits can span multiple files, so it's main view's job
+                            buffer.get_path().and_then(|path| {
+                                MainViewMsg::ApplyCodeAction {
+                                    path: path.clone(),
+                                    action: action.clone(),
+                                }
+                                .someboxed()
+                            })
+                        }
                         (editor_state, msg) => {
                             error!("Unhandled combination of editor state {:?} and msg {:?}", editor_state, msg);
                             None
@@ -1608,25 +2608,40 @@ impl Widget for EditorWidget {
     }
 
     fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        debug_assert!(self.last_hover_rect.is_some() == self.requested_hover.is_some());
+
+        let layout_res = unpack_unit!(self.layout_res.as_ref(), "render before layout");
+
+        if self.margin_width > 0 {
+            self.render_line_no(theme, focused, output);
+        }
+
+        let mut text_output = SubOutput::new(output, Rect::new(XY::new(self.margin_width, 0), layout_res.output_size()));
+
+        // metadata covers text area only, line numbers have their own.
         #[cfg(any(test, feature = "fuzztest"))]
         {
-            output.emit_metadata(crate::io::output::Metadata {
+            text_output.emit_metadata(crate::io::output::Metadata {
                 id: self.wid,
                 typename: self.typename().to_string(),
-                // TODO I am not sure of that
-                rect: Rect::from_zero(output.size()),
+                rect: Rect::from_zero(layout_res.output_size()),
                 focused,
             });
         }
 
-        debug_assert!(self.last_hover_rect.is_some() == self.requested_hover.is_some());
-
-        self.internal_render(theme, focused, output);
-        self.render_hover(theme, focused, output);
+        self.internal_render(theme, focused, &mut text_output);
+        self.render_hover(theme, focused, &mut text_output);
     }
 
     fn kite(&self) -> XY {
-        self.kite
+        let layout_res = unpack_or!(self.layout_res.as_ref(), XY::ZERO, "kite requested before layout");
+        let visible_rect = layout_res.visible_rect();
+
+        // between update and layout, kite can be outside viewport. It's ok, next layout will follow it.
+        let local_kite = self.kite.relative_to(self.viewport).unwrap_or(XY::ZERO);
+        let local_kite = local_kite.min_both_axis(visible_rect.size - XY::ONE);
+
+        XY::new(self.margin_width, 0) + visible_rect.pos + local_kite
     }
 
     fn get_status_description(&self) -> Option<Cow<'_, str>> {
@@ -1655,16 +2670,16 @@ impl Widget for EditorWidget {
             }
         } else {
             let num_cursors = cursor_set.len();
-            let rect = cursor_set_to_rect(cursor_set, lock.text().rope());
+            let (upper_left, lower_right) = cursor_set_to_rect(cursor_set, lock.text().rope());
 
             Some(Cow::Owned(format!(
                 "{} | {} cursors in rect [{}-{})x[{}-{})",
                 name,
                 num_cursors,
-                rect.upper_left().x,
-                rect.upper_left().y,
-                rect.lower_right().x,
-                rect.lower_right().y
+                upper_left.x,
+                upper_left.y,
+                lower_right.x + 1,
+                lower_right.y + 1
             )))
         }
     }
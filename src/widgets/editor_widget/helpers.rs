@@ -3,6 +3,7 @@ use streaming_iterator::StreamingIterator;
 use unicode_width::UnicodeWidthStr;
 
 use crate::cursor::cursor::Cursor;
+use crate::primitives::buffer_xy::BufferXY;
 use crate::primitives::helpers::copy_last_n_columns;
 use crate::primitives::xy::XY;
 use crate::text::text_buffer::TextBuffer;
@@ -14,7 +15,7 @@ pub struct CursorScreenPosition {
     //  can be outside of visible part of widget.
     pub visible_rect_space: Option<XY>,
     // This is position in space of text rendered from (0, 0)
-    pub text_space: XY,
+    pub text_space: BufferXY,
 }
 
 //TODO tests
@@ -34,13 +35,13 @@ pub fn find_trigger_and_substring<'a>(
     let how_many_columns_visible = cursor_screen_pos.x;
     let how_many_columns_total = cursor_pos.text_space.x;
 
-    debug_assert!(how_many_columns_visible <= how_many_columns_total);
+    debug_assert!(how_many_columns_visible as usize <= how_many_columns_total);
     if how_many_columns_visible == 0 {
         debug!("no columns visible");
         return None;
     }
 
-    let entire_line = match buffer.lines().skip(cursor_pos.text_space.y as usize).next() {
+    let entire_line = match buffer.lines().skip(cursor_pos.text_space.y).next() {
         None => {
             error!("couldn't find line {} (drawn as +1) to harvest substring", cursor_pos.text_space.y);
             return None;
@@ -79,3 +80,47 @@ pub fn find_trigger_and_substring<'a>(
         None
     }
 }
+
+/*
+Returns the trigger that ends exactly at char_idx (so "was just typed"), if any.
+ */
+pub fn trigger_before_char_idx<'a>(triggers: &'a [String], buffer: &dyn TextBuffer, char_idx: usize) -> Option<&'a str> {
+    triggers
+        .iter()
+        .find(|trigger| {
+            let len = trigger.chars().count();
+            if len == 0 || len > char_idx {
+                return false;
+            }
+
+            (char_idx - len..char_idx)
+                .map(|idx| buffer.char_at(idx))
+                .zip(trigger.chars())
+                .all(|(buffer_char, trigger_char)| buffer_char == Some(trigger_char))
+        })
+        .map(|trigger| trigger.as_str())
+}
+
+/*
+Returns identifier-like word (alphanumerics and underscores) that contains or ends at char_idx.
+Used to pre-fill rename box with old name of a symbol.
+ */
+pub fn word_around_char_idx(buffer: &dyn TextBuffer, char_idx: usize) -> Option<String> {
+    let is_word_char = |idx: usize| buffer.char_at(idx).map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false);
+
+    let mut begin = char_idx;
+    while begin > 0 && is_word_char(begin - 1) {
+        begin -= 1;
+    }
+
+    let mut end = char_idx;
+    while is_word_char(end) {
+        end += 1;
+    }
+
+    if begin == end {
+        None
+    } else {
+        Some((begin..end).filter_map(|idx| buffer.char_at(idx)).collect())
+    }
+}
@@ -0,0 +1,233 @@
+/*
+Displays results of "hover" request (documentation, signature etc.) of symbol under cursor.
+Markdown is displayed as-is, I might render it one day.
+ */
+
+use log::{debug, error, warn};
+use unicode_width::UnicodeWidthChar;
+
+use crate::config::theme::Theme;
+use crate::experiments::screenspace::Screenspace;
+use crate::experiments::subwidget_pointer::SubwidgetPointer;
+use crate::io::input_event::InputEvent;
+use crate::io::keys::Keycode;
+use crate::io::output::Output;
+use crate::layout::layout::Layout;
+use crate::layout::leaf_layout::LeafLayout;
+use crate::primitives::xy::XY;
+use crate::promise::promise::PromiseState;
+use crate::subwidget;
+use crate::w7e::navcomp_provider::HoverPromise;
+use crate::widget::any_msg::{AnyMsg, AsAny};
+use crate::widget::complex_widget::{ComplexWidget, DisplayState};
+use crate::widget::widget::{get_new_widget_id, Widget, WID};
+use crate::widgets::editor_widget::hover::msg::HoverWidgetMsg;
+use crate::widgets::editor_widget::msg::EditorWidgetMsg;
+use crate::widgets::text_widget::TextWidget;
+
+pub struct HoverWidget {
+    wid: WID,
+    /*
+    Same as in CompletionWidget: I keep the promise until it's resolved, and then move it's contents
+    to text_widget. Broken or empty promise means EditorWidget should discard entire HoverWidget.
+     */
+    hover_promise: Option<HoverPromise>,
+
+    text_widget: TextWidget,
+    display_state: Option<DisplayState<Self>>,
+
+    max_size: XY,
+}
+
+impl HoverWidget {
+    pub const LOADING: &'static str = "loading...";
+    pub const TYPENAME: &'static str = "hover_widget";
+
+    pub fn new(hover_promise: HoverPromise, max_size: XY) -> Self {
+        HoverWidget {
+            wid: get_new_widget_id(),
+            hover_promise: Some(hover_promise),
+            text_widget: TextWidget::new(Box::new(Self::LOADING)),
+            display_state: None,
+            max_size,
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        self.text_widget.get_text()
+    }
+
+    /*
+    Updates the state of HoverPromise, and returns whether we should proceed to draw or discard the widget.
+     */
+    pub fn poll_results_should_draw(&mut self) -> bool {
+        let promise = match self.hover_promise.as_mut() {
+            // results already moved to text_widget
+            None => return true,
+            Some(promise) => promise,
+        };
+
+        match promise.update().state {
+            PromiseState::Unresolved => true,
+            PromiseState::Ready => {
+                let text_op = promise.read().cloned().flatten();
+                self.hover_promise = None;
+
+                match text_op {
+                    Some(text) => {
+                        let wrapped = wrap_lines(&text, self.max_size.x as usize);
+                        self.text_widget.set_text(Box::new(wrapped));
+                        debug!("resolved hover promise");
+                        true
+                    }
+                    None => {
+                        debug!("nothing to show in hover, discarding");
+                        false
+                    }
+                }
+            }
+            PromiseState::Broken => {
+                error!("discarding hover widget due to broken promise : {:?}", promise);
+                false
+            }
+        }
+    }
+}
+
+/*
+Breaks lines longer than max_width, so they are not just cut off at the edge of hover.
+ */
+fn wrap_lines(text: &str, max_width: usize) -> String {
+    if max_width == 0 {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+
+    for (idx, line) in text.lines().enumerate() {
+        if idx > 0 {
+            result.push('\n');
+        }
+
+        let mut width = 0;
+        for c in line.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if width + char_width > max_width {
+                result.push('\n');
+                width = 0;
+            }
+            result.push(c);
+            width += char_width;
+        }
+    }
+
+    result
+}
+
+impl Widget for HoverWidget {
+    fn id(&self) -> WID {
+        self.wid
+    }
+    fn static_typename() -> &'static str
+    where
+        Self: Sized,
+    {
+        Self::TYPENAME
+    }
+    fn typename(&self) -> &'static str {
+        Self::TYPENAME
+    }
+
+    fn prelayout(&mut self) {
+        self.complex_prelayout();
+    }
+
+    fn full_size(&self) -> XY {
+        let text_size = self.text_widget.full_size();
+        XY::new(
+            std::cmp::min(text_size.x, self.max_size.x),
+            std::cmp::min(text_size.y, self.max_size.y),
+        )
+    }
+
+    fn layout(&mut self, screenspace: Screenspace) {
+        self.complex_layout(screenspace)
+    }
+
+    fn on_input(&self, input_event: InputEvent) -> Option<Box<dyn AnyMsg>> {
+        match input_event {
+            InputEvent::KeyInput(key) if key.keycode == Keycode::Esc => HoverWidgetMsg::Close.someboxed(),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, msg: Box<dyn AnyMsg>) -> Option<Box<dyn AnyMsg>> {
+        match msg.as_msg::<HoverWidgetMsg>() {
+            None => {
+                warn!("expected HoverWidgetMsg, got {:?}", msg);
+                None
+            }
+            Some(msg) => match msg {
+                HoverWidgetMsg::Close => EditorWidgetMsg::HoverClose.someboxed(),
+            },
+        }
+    }
+
+    fn get_focused(&self) -> Option<&dyn Widget> {
+        self.complex_get_focused()
+    }
+
+    fn get_focused_mut(&mut self) -> Option<&mut dyn Widget> {
+        self.complex_get_focused_mut()
+    }
+
+    fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            if let Some(ds) = self.get_display_state_op() {
+                output.emit_metadata(crate::io::output::Metadata {
+                    id: self.wid,
+                    typename: self.typename().to_string(),
+                    rect: crate::primitives::rect::Rect::new(XY::ZERO, ds.total_size),
+                    focused,
+                });
+            }
+        }
+
+        self.complex_render(theme, focused, output)
+    }
+}
+
+impl ComplexWidget for HoverWidget {
+    fn get_layout(&self) -> Box<dyn Layout<Self>> {
+        Box::new(LeafLayout::new(subwidget!(Self.text_widget)))
+    }
+
+    fn get_default_focused(&self) -> SubwidgetPointer<Self> {
+        subwidget!(Self.text_widget)
+    }
+
+    fn set_display_state(&mut self, display_state: DisplayState<Self>) {
+        self.display_state = Some(display_state)
+    }
+
+    fn get_display_state_op(&self) -> Option<&DisplayState<Self>> {
+        self.display_state.as_ref()
+    }
+
+    fn get_display_state_mut_op(&mut self) -> Option<&mut DisplayState<Self>> {
+        self.display_state.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::editor_widget::hover::hover_widget::wrap_lines;
+
+    #[test]
+    fn wrap_lines_test() {
+        assert_eq!(wrap_lines("abcdef\nab", 4), "abcd\nef\nab");
+        assert_eq!(wrap_lines("abc", 0), "abc");
+        assert_eq!(wrap_lines("", 4), "");
+    }
+}
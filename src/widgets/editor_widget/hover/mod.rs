@@ -0,0 +1,2 @@
+pub mod hover_widget;
+mod msg;
@@ -0,0 +1,8 @@
+use crate::widget::any_msg::AnyMsg;
+
+#[derive(Clone, Debug)]
+pub enum HoverWidgetMsg {
+    Close,
+}
+
+impl AnyMsg for HoverWidgetMsg {}
@@ -1,14 +1,14 @@
 use std::ops::Range;
 
-use log::warn;
+use unicode_width::UnicodeWidthStr;
 
 use crate::config::theme::Theme;
 use crate::cursor::cursor::NEWLINE_WIDTH;
 use crate::io::style::TextStyle;
+use crate::primitives::buffer_xy::BufferXY;
 use crate::primitives::printable::Printable;
 use crate::primitives::stupid_cursor::StupidCursor;
 use crate::primitives::styled_printable::{StyleBorrowedPrintable, StyledPrintable};
-use crate::primitives::xy::XY;
 use crate::text::text_buffer::TextBuffer;
 
 #[derive(Debug, Clone)]
@@ -18,7 +18,7 @@ pub enum LabelPos {
      */
     Inline { char_idx: usize },
     /*
-       both line and column are 1-based
+    Same as above, but position comes from LSP, so column is in UTF-16 code units (see StupidCursor)
     */
     InlineStupid { stupid_cursor: StupidCursor },
 
@@ -49,7 +49,7 @@ impl LabelPos {
         }
     }
 
-    pub fn into_position(&self, text_buffer: &dyn TextBuffer) -> Option<XY> {
+    pub fn into_position(&self, text_buffer: &dyn TextBuffer) -> Option<BufferXY> {
         match self {
             LabelPos::Inline { char_idx } => {
                 if let Some(line_no_0b) = text_buffer.char_to_line(*char_idx) {
@@ -59,32 +59,25 @@ impl LabelPos {
 
                     let added_from_tabs = text_buffer.get_tabs_expansion_for_line(line_no_0b);
 
-                    debug_assert!(line_no_0b <= u16::MAX as usize);
-                    debug_assert!(in_line_char_idx <= u16::MAX as usize);
-
-                    Some(XY::new(in_line_char_idx as u16 + added_from_tabs, line_no_0b as u16))
+                    Some(BufferXY::new(in_line_char_idx + added_from_tabs, line_no_0b))
                 } else {
                     None
                 }
             }
             LabelPos::InlineStupid { stupid_cursor } => {
-                // TODO add get_tabs_expansion_for_line
-                stupid_cursor.to_xy(text_buffer)
+                let cursor = stupid_cursor.to_real_cursor(text_buffer)?;
+                LabelPos::Inline { char_idx: cursor.a }.into_position(text_buffer)
             }
             LabelPos::LineAfter { line_no_1b } => {
                 debug_assert!(*line_no_1b >= 1);
                 if text_buffer.len_lines() + 1 > *line_no_1b {
                     let line_no_0b = line_no_1b - 1;
-                    if line_no_0b > u16::MAX as usize {
-                        warn!("line too far");
-                        return None;
-                    }
 
                     let line = text_buffer.get_line(line_no_0b)?;
                     let added_from_tabs = text_buffer.get_tabs_expansion_for_line(line_no_0b);
 
                     // I add NEWLINE_WIDTH to cover the "⏎" char
-                    Some(XY::new(line.screen_width() + NEWLINE_WIDTH + added_from_tabs, line_no_0b as u16))
+                    Some(BufferXY::new(line.width() + NEWLINE_WIDTH as usize + added_from_tabs, line_no_0b))
                 } else {
                     None
                 }
@@ -2,8 +2,11 @@ pub mod completion;
 mod context_options_matrix;
 pub mod editor_widget;
 mod helpers;
+pub mod hover;
 pub mod label;
 pub mod msg;
+pub mod rename;
+pub mod signature_help;
 
 #[cfg(test)]
 mod tests;
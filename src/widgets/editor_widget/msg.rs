@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use crate::cursor::cursor::Cursor;
 use crate::primitives::common_edit_msgs::CommonEditMsg;
-use crate::w7e::navcomp_provider::CompletionAction;
+use crate::w7e::navcomp_provider::{CompletionAction, HierarchyKind, NavCompCodeAction, NavCompSymbolContextActions};
 use crate::widget::any_msg::AnyMsg;
 
 #[derive(Clone, Debug)]
@@ -20,14 +20,26 @@ pub enum EditorWidgetMsg {
     ContextMenuClose,
 
     RequestCompletions,
+    RequestHover,
     HoverClose,
     CompletionWidgetSelected(CompletionAction),
 
     RequestContextBar,
 
     Reformat,
+    ReformatSelection,
     GoToDefinition,
+    GoToTypeDefinition,
+    ShowImplementations,
+    GoToDeclaration,
     ShowUsages,
+    RequestRename,
+    Rename { new_name: String },
+    ApplyCodeAction { action: NavCompCodeAction },
+    RequestOutline,
+    RequestHistory,
+    ShowHierarchy { kind: HierarchyKind },
+    SymbolContextAction(NavCompSymbolContextActions),
 }
 
 impl AnyMsg for EditorWidgetMsg {}
@@ -0,0 +1,2 @@
+mod msg;
+pub mod rename_widget;
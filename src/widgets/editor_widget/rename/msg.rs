@@ -0,0 +1,9 @@
+use crate::widget::any_msg::AnyMsg;
+
+#[derive(Clone, Debug)]
+pub enum RenameWidgetMsg {
+    Hit,
+    Close,
+}
+
+impl AnyMsg for RenameWidgetMsg {}
@@ -0,0 +1,175 @@
+/*
+A tiny box asking for the new name of symbol under cursor. It does not talk to navcomp itself,
+on Enter it just hands the new name to EditorWidget, which requests the rename and opens preview.
+ */
+
+use log::warn;
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::config::ConfigRef;
+use crate::config::theme::Theme;
+use crate::experiments::screenspace::Screenspace;
+use crate::experiments::subwidget_pointer::SubwidgetPointer;
+use crate::io::input_event::InputEvent;
+use crate::io::keys::Keycode;
+use crate::io::output::Output;
+use crate::layout::layout::Layout;
+use crate::layout::leaf_layout::LeafLayout;
+use crate::layout::split_layout::{SplitDirection, SplitLayout, SplitRule};
+use crate::primitives::xy::XY;
+use crate::subwidget;
+use crate::widget::any_msg::{AnyMsg, AsAny};
+use crate::widget::complex_widget::{ComplexWidget, DisplayState};
+use crate::widget::fill_policy::SizePolicy;
+use crate::widget::widget::{get_new_widget_id, Widget, WID};
+use crate::widgets::edit_box::EditBoxWidget;
+use crate::widgets::editor_widget::msg::EditorWidgetMsg;
+use crate::widgets::editor_widget::rename::msg::RenameWidgetMsg;
+use crate::widgets::text_widget::TextWidget;
+
+pub struct RenameWidget {
+    wid: WID,
+
+    label: TextWidget,
+    edit_box: EditBoxWidget,
+    display_state: Option<DisplayState<Self>>,
+
+    max_size: XY,
+}
+
+impl RenameWidget {
+    pub const LABEL: &'static str = "Rename to:";
+    pub const MIN_WIDTH: u16 = 24;
+    pub const TYPENAME: &'static str = "rename_widget";
+
+    pub fn new(config: ConfigRef, old_name_op: Option<String>, max_size: XY) -> Self {
+        let mut edit_box = EditBoxWidget::new(config)
+            .with_size_policy(SizePolicy::MATCH_LAYOUTS_WIDTH)
+            .with_on_hit(Box::new(|_| RenameWidgetMsg::Hit.someboxed()));
+
+        if let Some(old_name) = old_name_op {
+            edit_box.set_text(old_name);
+            edit_box.set_cursor_end();
+        }
+
+        RenameWidget {
+            wid: get_new_widget_id(),
+            label: TextWidget::new(Box::new(Self::LABEL)),
+            edit_box,
+            display_state: None,
+            max_size,
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        self.edit_box.get_text()
+    }
+}
+
+impl Widget for RenameWidget {
+    fn id(&self) -> WID {
+        self.wid
+    }
+    fn static_typename() -> &'static str
+    where
+        Self: Sized,
+    {
+        Self::TYPENAME
+    }
+    fn typename(&self) -> &'static str {
+        Self::TYPENAME
+    }
+
+    fn prelayout(&mut self) {
+        self.complex_prelayout();
+    }
+
+    fn full_size(&self) -> XY {
+        let width = std::cmp::max(Self::MIN_WIDTH as usize, self.edit_box.get_text().width() + 1);
+        XY::new(
+            std::cmp::min(width, self.max_size.x as usize) as u16,
+            std::cmp::min(2, self.max_size.y),
+        )
+    }
+
+    fn layout(&mut self, screenspace: Screenspace) {
+        self.complex_layout(screenspace)
+    }
+
+    fn on_input(&self, input_event: InputEvent) -> Option<Box<dyn AnyMsg>> {
+        match input_event {
+            InputEvent::KeyInput(key) if key.keycode == Keycode::Esc => RenameWidgetMsg::Close.someboxed(),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, msg: Box<dyn AnyMsg>) -> Option<Box<dyn AnyMsg>> {
+        match msg.as_msg::<RenameWidgetMsg>() {
+            None => {
+                warn!("expected RenameWidgetMsg, got {:?}", msg);
+                None
+            }
+            Some(msg) => match msg {
+                RenameWidgetMsg::Hit => {
+                    let new_name = self.edit_box.get_text();
+                    if new_name.trim().is_empty() {
+                        warn!("ignoring rename to empty name");
+                        return None;
+                    }
+
+                    EditorWidgetMsg::Rename { new_name }.someboxed()
+                }
+                RenameWidgetMsg::Close => EditorWidgetMsg::HoverClose.someboxed(),
+            },
+        }
+    }
+
+    fn get_focused(&self) -> Option<&dyn Widget> {
+        self.complex_get_focused()
+    }
+
+    fn get_focused_mut(&mut self) -> Option<&mut dyn Widget> {
+        self.complex_get_focused_mut()
+    }
+
+    fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            if let Some(ds) = self.get_display_state_op() {
+                output.emit_metadata(crate::io::output::Metadata {
+                    id: self.wid,
+                    typename: self.typename().to_string(),
+                    rect: crate::primitives::rect::Rect::new(XY::ZERO, ds.total_size),
+                    focused,
+                });
+            }
+        }
+
+        self.complex_render(theme, focused, output)
+    }
+}
+
+impl ComplexWidget for RenameWidget {
+    fn get_layout(&self) -> Box<dyn Layout<Self>> {
+        SplitLayout::new(SplitDirection::Vertical)
+            .with(SplitRule::Fixed(1), LeafLayout::new(subwidget!(Self.label)).boxed())
+            .with(SplitRule::Fixed(1), LeafLayout::new(subwidget!(Self.edit_box)).boxed())
+            .boxed()
+    }
+
+    fn get_default_focused(&self) -> SubwidgetPointer<Self> {
+        subwidget!(Self.edit_box)
+    }
+
+    fn set_display_state(&mut self, display_state: DisplayState<Self>) {
+        self.display_state = Some(display_state)
+    }
+
+    fn get_display_state_op(&self) -> Option<&DisplayState<Self>> {
+        self.display_state.as_ref()
+    }
+
+    fn get_display_state_mut_op(&mut self) -> Option<&mut DisplayState<Self>> {
+        self.display_state.as_mut()
+    }
+}
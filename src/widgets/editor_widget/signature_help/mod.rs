@@ -0,0 +1 @@
+pub mod signature_help_widget;
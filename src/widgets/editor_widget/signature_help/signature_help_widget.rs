@@ -0,0 +1,164 @@
+/*
+A single line showing signature of a function being called, with the active parameter highlighted.
+
+Unlike CompletionWidget and HoverWidget, this one never takes focus: user is supposed to keep
+typing arguments while looking at it. EditorWidget re-requests signature help as cursor moves, and
+I keep displaying the previous signature until the new one arrives, so it doesn't flicker.
+ */
+
+use log::{debug, error};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::config::theme::Theme;
+use crate::experiments::screenspace::Screenspace;
+use crate::io::input_event::InputEvent;
+use crate::io::output::Output;
+use crate::primitives::helpers::fill_output;
+use crate::primitives::xy::XY;
+use crate::promise::promise::PromiseState;
+use crate::w7e::navcomp_provider::{NavCompSignature, SignatureHelpPromise};
+use crate::widget::any_msg::AnyMsg;
+use crate::widget::widget::{get_new_widget_id, Widget, WID};
+
+pub struct SignatureHelpWidget {
+    wid: WID,
+    signature_promise: Option<SignatureHelpPromise>,
+    signature: Option<NavCompSignature>,
+
+    max_size: XY,
+}
+
+impl SignatureHelpWidget {
+    pub const LOADING: &'static str = "loading...";
+    pub const TYPENAME: &'static str = "signature_help_widget";
+
+    pub fn new(signature_promise: SignatureHelpPromise, max_size: XY) -> Self {
+        SignatureHelpWidget {
+            wid: get_new_widget_id(),
+            signature_promise: Some(signature_promise),
+            signature: None,
+            max_size,
+        }
+    }
+
+    // Replaces pending promise, the current signature is displayed until the new one arrives.
+    pub fn set_promise(&mut self, signature_promise: SignatureHelpPromise) {
+        self.signature_promise = Some(signature_promise);
+    }
+
+    pub fn set_max_size(&mut self, max_size: XY) {
+        self.max_size = max_size;
+    }
+
+    pub fn get_signature(&self) -> Option<&NavCompSignature> {
+        self.signature.as_ref()
+    }
+
+    /*
+    Updates the state of SignatureHelpPromise, and returns whether we should proceed to draw or discard the widget.
+     */
+    pub fn poll_results_should_draw(&mut self) -> bool {
+        let promise = match self.signature_promise.as_mut() {
+            None => return true,
+            Some(promise) => promise,
+        };
+
+        match promise.update().state {
+            PromiseState::Unresolved => true,
+            PromiseState::Ready => {
+                let signature_op = promise.read().cloned().flatten();
+                self.signature_promise = None;
+
+                if signature_op.is_none() {
+                    debug!("no signature to show, discarding");
+                    return false;
+                }
+
+                self.signature = signature_op;
+                true
+            }
+            PromiseState::Broken => {
+                error!("discarding signature help widget due to broken promise : {:?}", promise);
+                false
+            }
+        }
+    }
+}
+
+impl Widget for SignatureHelpWidget {
+    fn id(&self) -> WID {
+        self.wid
+    }
+
+    fn static_typename() -> &'static str
+    where
+        Self: Sized,
+    {
+        Self::TYPENAME
+    }
+
+    fn typename(&self) -> &'static str {
+        Self::TYPENAME
+    }
+
+    fn full_size(&self) -> XY {
+        let width = match self.signature.as_ref() {
+            Some(signature) => signature.label.width(),
+            None => Self::LOADING.width(),
+        };
+
+        XY::new(std::cmp::min(width, self.max_size.x as usize) as u16, 1)
+    }
+
+    fn layout(&mut self, _screenspace: Screenspace) {}
+
+    fn on_input(&self, _input_event: InputEvent) -> Option<Box<dyn AnyMsg>> {
+        None
+    }
+
+    fn update(&mut self, _msg: Box<dyn AnyMsg>) -> Option<Box<dyn AnyMsg>> {
+        None
+    }
+
+    fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            output.emit_metadata(crate::io::output::Metadata {
+                id: self.wid,
+                typename: self.typename().to_string(),
+                rect: crate::primitives::rect::Rect::from_zero(output.size()),
+                focused,
+            });
+        }
+
+        let default_style = theme.default_text(focused);
+        fill_output(default_style.background, output);
+
+        let signature = match self.signature.as_ref() {
+            Some(signature) => signature,
+            None => {
+                output.print_at(XY::ZERO, default_style, Self::LOADING);
+                return;
+            }
+        };
+
+        let visible_rect = output.visible_rect();
+        let highlighted_style = theme.highlighted(focused);
+
+        let mut x: usize = 0;
+        for (char_idx, c) in signature.label.chars().enumerate() {
+            let width = c.width().unwrap_or(0);
+            if x + width > visible_rect.lower_right().x as usize {
+                break;
+            }
+
+            let style = match signature.active_parameter {
+                Some((begin, end)) if begin <= char_idx && char_idx < end => highlighted_style,
+                _ => default_style,
+            };
+
+            output.print_at(XY::new(x as u16, 0), style, c.encode_utf8(&mut [0; 4]));
+            x += width;
+        }
+    }
+}
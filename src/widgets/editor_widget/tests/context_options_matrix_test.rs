@@ -0,0 +1,81 @@
+use crate::cursor::cursor::{Cursor, Selection};
+use crate::cursor::cursor_set::CursorSet;
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::widgets::editor_widget::context_options_matrix::{get_context_options, ContextOptionsInput};
+use crate::widgets::editor_widget::editor_widget::EditorState;
+
+fn labels_for(tree_sitter_symbol: &str) -> Vec<String> {
+    get_context_options(&ContextOptionsInput {
+        state: &EditorState::Editing,
+        single_cursor: Some(Cursor::single()),
+        multiple_cursors: &CursorSet::single(),
+        single_stupid_cursor: None,
+        lsp_available: true,
+        can_reformat: false,
+        lsp_symbol: None,
+        tree_sitter_symbol: Some(tree_sitter_symbol),
+        code_actions: &[],
+    })
+    .iter()
+    .map(|item| item.label().to_string())
+    .collect()
+}
+
+#[test]
+fn context_options_type_offers_implementations() {
+    let labels = labels_for("type");
+
+    assert!(labels.contains(&"go to definition".to_string()));
+    assert!(labels.contains(&"show implementations".to_string()));
+}
+
+#[test]
+fn context_options_function_offers_declaration_and_implementations() {
+    let labels = labels_for("function");
+
+    assert!(labels.contains(&"go to declaration".to_string()));
+    assert!(labels.contains(&"show implementations".to_string()));
+}
+
+#[test]
+fn context_options_variable_offers_type_definition() {
+    let labels = labels_for("variable");
+
+    assert!(labels.contains(&"go to type definition".to_string()));
+    assert!(!labels.contains(&"show implementations".to_string()));
+}
+
+#[test]
+fn context_options_hierarchies() {
+    assert!(labels_for("function").contains(&"call hierarchy".to_string()));
+    assert!(labels_for("type").contains(&"type hierarchy".to_string()));
+    assert!(!labels_for("variable").contains(&"call hierarchy".to_string()));
+}
+
+#[test]
+fn context_options_reformat_selection_only_with_selection() {
+    let options = |cursor: Cursor| -> Vec<String> {
+        get_context_options(&ContextOptionsInput {
+            state: &EditorState::Editing,
+            single_cursor: Some(cursor),
+            multiple_cursors: &CursorSet::single(),
+            single_stupid_cursor: None,
+            lsp_available: true,
+            can_reformat: true,
+            lsp_symbol: None,
+            tree_sitter_symbol: None,
+            code_actions: &[],
+        })
+        .iter()
+        .map(|item| item.label().to_string())
+        .collect()
+    };
+
+    let without_selection = options(Cursor::single());
+    assert!(without_selection.contains(&"reformat file".to_string()));
+    assert!(!without_selection.contains(&"reformat selection".to_string()));
+
+    let with_selection = options(Cursor::new(5).with_selection(Selection::new(0, 5)));
+    assert!(with_selection.contains(&"reformat file".to_string()));
+    assert!(with_selection.contains(&"reformat selection".to_string()));
+}
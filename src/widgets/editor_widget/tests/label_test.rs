@@ -1,5 +1,6 @@
 use crate::experiments::screen_shot::screenshot;
 use crate::mocks::mock_labels_provider::MockLabelsProvider;
+use crate::primitives::buffer_xy::BufferXY;
 use crate::primitives::stupid_cursor::StupidCursor;
 use crate::widgets::editor_view::test::editor_view_testbed::EditorViewTestbed;
 use crate::widgets::editor_view::test::editor_view_testbed_builder::EditorViewTestbedBuilder;
@@ -107,3 +108,20 @@ fn editor_label_error_stupid_cursor() {
         "// srandom error annotationome comment to avoid formatting collapse⏎"
     );
 }
+
+#[test]
+fn editor_label_stupid_cursor_column_is_utf16() {
+    // emoji is a single char, but 2 UTF-16 code units
+    let rope = ropey::Rope::from_str("ab\n😀x = 1;");
+
+    let pos = LabelPos::InlineStupid {
+        stupid_cursor: StupidCursor::new(3, 1),
+    };
+    assert_eq!(pos.into_position(&rope), Some(BufferXY::new(2, 1)));
+
+    // in the middle of emoji
+    let pos = LabelPos::InlineStupid {
+        stupid_cursor: StupidCursor::new(1, 1),
+    };
+    assert_eq!(pos.into_position(&rope), None);
+}
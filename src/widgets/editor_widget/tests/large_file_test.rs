@@ -0,0 +1,105 @@
+use crate::cursor::cursor::Cursor;
+use crate::cursor::cursor_set::CursorSet;
+use crate::io::keys::Keycode;
+use crate::primitives::buffer_xy::BufferXY;
+use crate::widget::widget::Widget;
+use crate::widgets::editor_view::test::editor_view_testbed::EditorViewTestbed;
+use crate::widgets::editor_view::test::editor_view_testbed_builder::EditorViewTestbedBuilder;
+
+const NUM_LINES: usize = 100_000;
+
+fn get_setup(text: &str) -> EditorViewTestbed {
+    let editor_view_testbed = EditorViewTestbedBuilder::default().build();
+
+    {
+        let editor_wid = editor_view_testbed.widget().get_internal_widget().id();
+        let mut buffer_lock = editor_view_testbed.widget().get_buffer_ref().lock_rw().unwrap();
+        buffer_lock.set_text(text);
+        // set_text drops cursor sets
+        assert!(buffer_lock.initialize_for_widget(editor_wid, None));
+    }
+
+    editor_view_testbed
+}
+
+fn long_text() -> String {
+    (0..NUM_LINES).map(|idx| format!("line {}", idx)).collect::<Vec<_>>().join("\n")
+}
+
+fn set_cursor_at_line(setup: &mut EditorViewTestbed, line_idx: usize, column: usize) {
+    let char_idx = {
+        let buffer_lock = setup.widget().get_buffer_ref().lock().unwrap();
+        buffer_lock.text().rope().line_to_char(line_idx) + column
+    };
+
+    assert!(setup.widget_mut().override_cursor_set(CursorSet::singleton(Cursor::new(char_idx))));
+}
+
+#[test]
+fn editor_scrolls_beyond_u16_lines() {
+    let mut setup = get_setup(&long_text());
+    setup.next_frame();
+
+    set_cursor_at_line(&mut setup, 99_990, 3);
+    setup.next_frame();
+
+    assert_eq!(setup.widget().get_internal_widget().viewport().x, 0);
+    assert!(setup.widget().get_internal_widget().viewport().y > u16::MAX as usize);
+
+    let interpreter = setup.interpreter().unwrap();
+    let cursor_lines: Vec<_> = interpreter.get_visible_cursor_lines().collect();
+    assert_eq!(cursor_lines.len(), 1);
+    assert_eq!(cursor_lines[0].visible_idx, 99_991);
+    assert_eq!(cursor_lines[0].contents.text.trim(), "line 99990⏎");
+}
+
+#[test]
+fn editor_moves_cursor_beyond_u16_lines() {
+    let mut setup = get_setup(&long_text());
+    setup.next_frame();
+
+    set_cursor_at_line(&mut setup, NUM_LINES - 2, 0);
+    setup.next_frame();
+
+    setup.send_input(Keycode::ArrowDown.to_key().to_input_event());
+    setup.next_frame();
+
+    let interpreter = setup.interpreter().unwrap();
+    let cursor_lines: Vec<_> = interpreter.get_visible_cursor_lines().collect();
+    assert_eq!(cursor_lines[0].visible_idx, NUM_LINES);
+    assert_eq!(cursor_lines[0].contents.text.trim(), format!("line {}⇱", NUM_LINES - 1));
+
+    setup.send_input(Keycode::PageUp.to_key().to_input_event());
+    setup.next_frame();
+
+    let viewport = setup.widget().get_internal_widget().viewport();
+    let interpreter = setup.interpreter().unwrap();
+    let cursor_lines: Vec<_> = interpreter.get_visible_cursor_lines().collect();
+    assert_eq!(cursor_lines[0].visible_idx, viewport.y + 1);
+}
+
+#[test]
+fn editor_scrolls_beyond_u16_columns() {
+    let text = format!("short\n{}needle\nshort", "a".repeat(70_000));
+    let mut setup = get_setup(&text);
+    setup.next_frame();
+
+    set_cursor_at_line(&mut setup, 1, 0);
+    setup.next_frame();
+
+    setup.send_input(Keycode::End.to_key().to_input_event());
+
+    let viewport = setup.widget().get_internal_widget().viewport();
+    assert!(viewport.x > u16::MAX as usize);
+    assert_eq!(viewport.y, 0);
+
+    let interpreter = setup.interpreter().unwrap();
+    let cursor_lines: Vec<_> = interpreter.get_visible_cursor_lines().collect();
+    assert_eq!(cursor_lines.len(), 1);
+    assert_eq!(cursor_lines[0].visible_idx, 2);
+    assert!(cursor_lines[0].contents.text.trim().ends_with("aaneedle⏎"));
+
+    // lines shorter than viewport offset are just empty
+    assert_eq!(interpreter.get_line_by_y(0).unwrap().text.trim(), "");
+    assert_eq!(setup.widget().get_internal_widget().viewport(), BufferXY::new(viewport.x, 0));
+}
@@ -1,2 +1,4 @@
 mod basic_tests;
+mod context_options_matrix_test;
 mod label_test;
+mod large_file_test;
@@ -0,0 +1,198 @@
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+
+use log::{debug, error};
+
+use crate::fs::fsf_ref::FsfRef;
+use crate::fs::path::SPath;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::promise::promise::PromiseState;
+use crate::w7e::handler::NavCompRef;
+use crate::w7e::navcomp_provider::{HierarchyKind, HierarchyPromise, NavCompHierarchyItem};
+use crate::widgets::code_results_view::stupid_symbol_usage_code_results_provider::url_to_spath;
+use crate::widgets::context_menu::widget::ContextMenuWidget;
+
+pub type HierarchyWidget = ContextMenuWidget<usize, Rc<HierarchyItem>>;
+
+/*
+A node of call or type hierarchy. Children are not known upfront: first time the node is expanded,
+navcomp is asked for them, and until it answers the node just looks empty. This is the only way,
+since hierarchy of a recursive function is infinite.
+ */
+pub struct HierarchyItem {
+    id: usize,
+    label: String,
+    // None for root gathering several items
+    item_op: Option<NavCompHierarchyItem>,
+    // None for root, or if item is outside of workspace
+    location_op: Option<(SPath, StupidCursor)>,
+    context: Rc<HierarchyContext>,
+    children: RefCell<HierarchyChildren>,
+}
+
+// common to all nodes of given tree
+struct HierarchyContext {
+    navcomp: NavCompRef,
+    fsf: FsfRef,
+    kind: HierarchyKind,
+    next_id: Cell<usize>,
+}
+
+enum HierarchyChildren {
+    NotRequested,
+    Pending(HierarchyPromise),
+    Ready(Vec<Rc<HierarchyItem>>),
+    Failed,
+}
+
+impl HierarchyItem {
+    /*
+    Items are the ones navcomp started the hierarchy with. If there's just one, it becomes the
+    root, otherwise they are gathered under a root of their own.
+     */
+    pub fn new_root(navcomp: NavCompRef, fsf: FsfRef, kind: HierarchyKind, mut items: Vec<NavCompHierarchyItem>) -> Rc<HierarchyItem> {
+        let context = Rc::new(HierarchyContext {
+            navcomp,
+            fsf,
+            kind,
+            next_id: Cell::new(0),
+        });
+
+        if items.len() == 1 {
+            return Self::from_item(&context, items.remove(0));
+        }
+
+        let id = context.get_next_id();
+        let children: Vec<Rc<HierarchyItem>> = items.into_iter().map(|item| Self::from_item(&context, item)).collect();
+
+        Rc::new(HierarchyItem {
+            id,
+            label: kind.description().to_string(),
+            item_op: None,
+            location_op: None,
+            context,
+            children: RefCell::new(HierarchyChildren::Ready(children)),
+        })
+    }
+
+    fn from_item(context: &Rc<HierarchyContext>, item: NavCompHierarchyItem) -> Rc<HierarchyItem> {
+        let spath_op = url_to_spath(&context.fsf, &item.location.path);
+
+        let label = match spath_op.as_ref() {
+            Some(spath) => format!("{}: {} ({})", item.name, item.symbol_type.description(), spath),
+            None => format!("{}: {}", item.name, item.symbol_type.description()),
+        };
+
+        Rc::new(HierarchyItem {
+            id: context.get_next_id(),
+            label,
+            location_op: spath_op.map(|spath| (spath, item.location.stupid_range.0)),
+            item_op: Some(item),
+            context: context.clone(),
+            children: RefCell::new(HierarchyChildren::NotRequested),
+        })
+    }
+
+    pub fn location(&self) -> Option<(SPath, StupidCursor)> {
+        self.location_op.clone()
+    }
+
+    // Asks for children if that was not done yet, and collects them if they arrived.
+    fn poll_children(&self) {
+        let mut children = match self.children.try_borrow_mut() {
+            Ok(children) => children,
+            Err(e) => {
+                error!("failed to borrow children of {:?}: {:?}", self, e);
+                return;
+            }
+        };
+
+        if let HierarchyChildren::NotRequested = *children {
+            let item = match self.item_op.as_ref() {
+                Some(item) => item,
+                None => {
+                    error!("root without children set, this should not happen");
+                    *children = HierarchyChildren::Failed;
+                    return;
+                }
+            };
+
+            *children = match self.context.navcomp.hierarchy_children(item, self.context.kind) {
+                Some(promise) => HierarchyChildren::Pending(promise),
+                None => {
+                    debug!("navcomp did not answer {} of {}", self.context.kind.description(), item.name);
+                    HierarchyChildren::Failed
+                }
+            };
+        }
+
+        if let HierarchyChildren::Pending(promise) = &mut *children {
+            promise.update();
+            let new_children = match promise.state() {
+                PromiseState::Unresolved => return,
+                PromiseState::Ready => match promise.read() {
+                    Some(items) => {
+                        HierarchyChildren::Ready(items.iter().map(|item| Self::from_item(&self.context, item.clone())).collect())
+                    }
+                    None => HierarchyChildren::Failed,
+                },
+                PromiseState::Broken => HierarchyChildren::Failed,
+            };
+
+            *children = new_children;
+        }
+    }
+}
+
+impl HierarchyContext {
+    fn get_next_id(&self) -> usize {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+}
+
+impl Debug for HierarchyItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[HierarchyItem {} \"{}\"]", self.id, self.label)
+    }
+}
+
+impl TreeNode<usize> for Rc<HierarchyItem> {
+    fn id(&self) -> &usize {
+        &self.id
+    }
+
+    fn label(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    // Until navcomp answers, I don't know, so I assume there is something to expand.
+    fn is_leaf(&self) -> bool {
+        match &*self.children.borrow() {
+            HierarchyChildren::NotRequested | HierarchyChildren::Pending(_) => false,
+            HierarchyChildren::Ready(children) => children.is_empty(),
+            HierarchyChildren::Failed => true,
+        }
+    }
+
+    // called only for expanded nodes, so this is where children get requested.
+    fn child_iter(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        self.poll_children();
+
+        match &*self.children.borrow() {
+            HierarchyChildren::Ready(children) => Box::new(children.clone().into_iter()),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        !matches!(
+            &*self.children.borrow(),
+            HierarchyChildren::NotRequested | HierarchyChildren::Pending(_)
+        )
+    }
+}
@@ -0,0 +1,4 @@
+pub mod hierarchy_item;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,188 @@
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Receiver;
+
+use crate::fs::filesystem_front::FilesystemFront;
+use crate::fs::fsf_ref::FsfRef;
+use crate::fs::mock_fs::MockFS;
+use crate::mocks::mock_navcomp_provider::{MockHierarchy, MockNavCompEvent, MockNavCompHealth, MockNavCompProvider, MockSymbolMatcher};
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::spath;
+use crate::w7e::handler::NavCompRef;
+use crate::w7e::navcomp_group::NavCompTick;
+use crate::w7e::navcomp_provider::{HierarchyKind, NavCompHierarchyItem, NavCompSymbol, StupidSymbolUsage, SymbolType};
+use crate::widgets::hierarchy::hierarchy_item::HierarchyItem;
+
+struct Setup {
+    fsf: FsfRef,
+    navcomp: NavCompRef,
+    symbols: Arc<RwLock<Vec<MockSymbolMatcher>>>,
+    // mock promises fail if nobody listens
+    _tick_receiver: Receiver<NavCompTick>,
+    _event_receiver: Receiver<MockNavCompEvent>,
+}
+
+fn get_setup() -> Setup {
+    let fsf = MockFS::new("/tmp/hierarchy").with_file("src/main.rs", "").to_fsf();
+    let (tick_sender, tick_receiver) = crossbeam_channel::unbounded::<NavCompTick>();
+    let (event_sender, event_receiver) = crossbeam_channel::unbounded::<MockNavCompEvent>();
+    let symbols: Arc<RwLock<Vec<MockSymbolMatcher>>> = Arc::new(RwLock::new(Vec::new()));
+
+    let navcomp = NavCompRef::new(Box::new(MockNavCompProvider::new(
+        tick_sender,
+        event_sender,
+        Arc::new(RwLock::new(Vec::new())),
+        symbols.clone(),
+        Arc::new(RwLock::new(Vec::new())),
+        Arc::new(MockNavCompHealth::default()),
+        Arc::new(RwLock::new(Vec::new())),
+    )));
+
+    Setup {
+        fsf,
+        navcomp,
+        symbols,
+        _tick_receiver: tick_receiver,
+        _event_receiver: event_receiver,
+    }
+}
+
+fn item(path: &str, name: &str, line: u32) -> NavCompHierarchyItem {
+    NavCompHierarchyItem {
+        name: name.to_string(),
+        symbol_type: SymbolType::Function,
+        detail_op: None,
+        location: StupidSymbolUsage {
+            path: path.to_string(),
+            stupid_range: (StupidCursor::new(3, line), StupidCursor::new(3 + name.len() as u32, line)),
+        },
+        raw: serde_json::Value::Null,
+    }
+}
+
+fn main_rs_item(name: &str, line: u32) -> NavCompHierarchyItem {
+    item("file:///tmp/hierarchy/src/main.rs", name, line)
+}
+
+fn add_matcher(setup: &Setup, item: NavCompHierarchyItem, children: Vec<(HierarchyKind, Vec<NavCompHierarchyItem>)>) {
+    let line = item.location.stupid_range.0.line_0b;
+
+    setup.symbols.write().unwrap().push(MockSymbolMatcher {
+        path: spath!(setup.fsf, "src", "main.rs"),
+        symbol: NavCompSymbol {
+            symbol_type: SymbolType::Function,
+            stupid_range: (StupidCursor::new(0, line), StupidCursor::new(20, line)),
+        },
+        hierarchy: Some(MockHierarchy { item, children }),
+        ..Default::default()
+    });
+}
+
+// this is what tree view does, asking for children of expanded node with every frame
+fn wait_for_children(node: &Rc<HierarchyItem>) -> Vec<Rc<HierarchyItem>> {
+    let deadline = Instant::now() + Duration::from_secs(3);
+
+    while !node.is_complete() && Instant::now() < deadline {
+        let _ = node.child_iter().count();
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    node.child_iter().collect()
+}
+
+#[test]
+fn hierarchy_item_children_are_requested_on_expand() {
+    let setup = get_setup();
+    add_matcher(
+        &setup,
+        main_rs_item("helper", 1),
+        vec![(HierarchyKind::IncomingCalls, vec![main_rs_item("main", 7)])],
+    );
+    add_matcher(&setup, main_rs_item("main", 6), vec![]);
+
+    let root = HierarchyItem::new_root(
+        setup.navcomp.clone(),
+        setup.fsf.clone(),
+        HierarchyKind::IncomingCalls,
+        vec![main_rs_item("helper", 1)],
+    );
+
+    assert_eq!(root.label(), "helper: function (src/main.rs)");
+    assert!(!root.is_leaf());
+    assert!(!root.is_complete());
+
+    let children = wait_for_children(&root);
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].label(), "main: function (src/main.rs)");
+    assert_eq!(
+        children[0].location(),
+        Some((spath!(setup.fsf, "src", "main.rs").unwrap(), StupidCursor::new(3, 7)))
+    );
+
+    // main is not called by anything
+    assert!(!children[0].is_leaf());
+    assert!(wait_for_children(&children[0]).is_empty());
+    assert!(children[0].is_leaf());
+}
+
+#[test]
+fn hierarchy_item_kind_decides_children() {
+    let setup = get_setup();
+    add_matcher(
+        &setup,
+        main_rs_item("main", 6),
+        vec![
+            (HierarchyKind::IncomingCalls, vec![]),
+            (
+                HierarchyKind::OutgoingCalls,
+                vec![main_rs_item("helper", 1), main_rs_item("println", 2)],
+            ),
+        ],
+    );
+
+    let incoming = HierarchyItem::new_root(
+        setup.navcomp.clone(),
+        setup.fsf.clone(),
+        HierarchyKind::IncomingCalls,
+        vec![main_rs_item("main", 6)],
+    );
+    assert!(wait_for_children(&incoming).is_empty());
+
+    let outgoing = HierarchyItem::new_root(
+        setup.navcomp.clone(),
+        setup.fsf.clone(),
+        HierarchyKind::OutgoingCalls,
+        vec![main_rs_item("main", 6)],
+    );
+    assert_eq!(wait_for_children(&outgoing).len(), 2);
+}
+
+#[test]
+fn hierarchy_item_several_items_get_common_root() {
+    let setup = get_setup();
+
+    let root = HierarchyItem::new_root(
+        setup.navcomp.clone(),
+        setup.fsf.clone(),
+        HierarchyKind::Supertypes,
+        vec![
+            main_rs_item("Point", 1),
+            item("file:///usr/lib/rustlib/src/core/fmt/mod.rs", "Debug", 10),
+        ],
+    );
+
+    assert_eq!(root.label(), "supertypes");
+    assert_eq!(root.location(), None);
+    assert!(root.is_complete());
+
+    let children: Vec<Rc<HierarchyItem>> = root.child_iter().collect();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0].label(), "Point: function (src/main.rs)");
+    // outside of workspace, so it can't be opened
+    assert_eq!(children[1].label(), "Debug: function");
+    assert_eq!(children[1].location(), None);
+}
@@ -0,0 +1 @@
+mod hierarchy_item_test;
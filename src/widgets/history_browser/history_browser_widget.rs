@@ -0,0 +1,226 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use log::{debug, warn};
+
+use crate::config::config::ConfigRef;
+use crate::config::theme::Theme;
+use crate::experiments::screenspace::Screenspace;
+use crate::experiments::subwidget_pointer::SubwidgetPointer;
+use crate::gladius::providers::Providers;
+use crate::io::input_event::InputEvent;
+use crate::io::output::Output;
+use crate::layout::layout::{Layout, LayoutResult};
+use crate::layout::leaf_layout::LeafLayout;
+use crate::layout::split_layout::{SplitDirection, SplitLayout, SplitRule};
+use crate::primitives::scroll::ScrollDirection;
+use crate::primitives::xy::XY;
+use crate::widget::any_msg::{AnyMsg, AsAny};
+use crate::widget::combined_widget::CombinedWidget;
+use crate::widget::fill_policy::SizePolicy;
+use crate::widget::widget::{get_new_widget_id, Widget, WidgetAction, WID};
+use crate::widgets::history_browser::history_item::HistoryItem;
+use crate::widgets::history_browser::msg::HistoryBrowserMsg;
+use crate::widgets::text_widget::TextWidget;
+use crate::widgets::tree_view::tree_view::TreeViewWidget;
+use crate::widgets::with_scroll::with_scroll::WithScroll;
+use crate::{subwidget, unpack_unit_e};
+
+pub const HISTORY_BROWSER_WIDGET_NAME: &str = "history_browser";
+
+pub type HistoryTreeView = TreeViewWidget<usize, Rc<HistoryItem>>;
+
+/*
+Lists states of buffer's undo tree (see HistoryItem for how it's laid out) next to a preview of the
+highlighted one. Arrows left/right fold and unfold branches, Enter jumps to the state (what exactly that
+means is up to on_hit).
+ */
+pub struct HistoryBrowserWidget {
+    id: WID,
+    config: ConfigRef,
+
+    tree_view: WithScroll<HistoryTreeView>,
+    preview: TextWidget,
+
+    layout_res: Option<LayoutResult<Self>>,
+
+    on_close: Option<WidgetAction<HistoryBrowserWidget>>,
+}
+
+impl HistoryBrowserWidget {
+    const PREVIEW_LINES: usize = 40;
+
+    pub fn new(providers: Providers, root: Rc<HistoryItem>, current_node: usize) -> Self {
+        let mut tree_view = TreeViewWidget::new(root)
+            .with_size_policy(SizePolicy::MATCH_LAYOUT)
+            .with_on_highlighted_changed(Box::new(|_| HistoryBrowserMsg::HighlightChanged.someboxed()));
+
+        // states that have abandoned branches are internal nodes, but one should be able to jump to them too
+        tree_view.set_enter_hits_internal_nodes(true);
+        tree_view.expand_root();
+        tree_view.set_selected(&current_node);
+
+        let mut res = HistoryBrowserWidget {
+            id: get_new_widget_id(),
+            config: providers.config().clone(),
+            tree_view: WithScroll::new(ScrollDirection::Both, tree_view),
+            preview: TextWidget::new(Box::new(String::new())).with_size_policy(SizePolicy::MATCH_LAYOUT),
+            layout_res: None,
+            on_close: None,
+        };
+
+        res.update_preview();
+        res
+    }
+
+    pub fn with_on_hit(mut self, on_hit: WidgetAction<HistoryTreeView>) -> Self {
+        self.tree_view.internal_mut().set_on_hit_op(Some(on_hit));
+        self
+    }
+
+    pub fn with_on_close(self, on_close: WidgetAction<HistoryBrowserWidget>) -> Self {
+        Self {
+            on_close: Some(on_close),
+            ..self
+        }
+    }
+
+    pub fn tree_view(&self) -> &HistoryTreeView {
+        self.tree_view.internal()
+    }
+
+    pub fn preview_text(&self) -> String {
+        self.preview.get_text()
+    }
+
+    fn update_preview(&mut self) {
+        let preview = self.tree_view.internal().get_highlighted().1.preview(Self::PREVIEW_LINES);
+        self.preview.set_text(Box::new(preview));
+    }
+}
+
+impl Widget for HistoryBrowserWidget {
+    fn id(&self) -> WID {
+        self.id
+    }
+
+    fn static_typename() -> &'static str
+    where
+        Self: Sized,
+    {
+        HISTORY_BROWSER_WIDGET_NAME
+    }
+
+    fn typename(&self) -> &'static str {
+        HISTORY_BROWSER_WIDGET_NAME
+    }
+
+    fn prelayout(&mut self) {
+        self.combined_prelayout()
+    }
+
+    fn full_size(&self) -> XY {
+        let tree_size = self.tree_view.full_size();
+        let preview_size = self.preview.full_size();
+
+        XY::new(tree_size.x + preview_size.x, tree_size.y.max(preview_size.y))
+    }
+
+    fn size_policy(&self) -> SizePolicy {
+        SizePolicy::MATCH_LAYOUT
+    }
+
+    fn layout(&mut self, screenspace: Screenspace) {
+        self.combined_layout(screenspace);
+    }
+
+    fn on_input(&self, input_event: InputEvent) -> Option<Box<dyn AnyMsg>> {
+        match input_event {
+            InputEvent::KeyInput(key) if key == self.config.keyboard_config.global.close_context_menu => {
+                HistoryBrowserMsg::Close.someboxed()
+            }
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, msg: Box<dyn AnyMsg>) -> Option<Box<dyn AnyMsg>> {
+        let our_msg = msg.as_msg::<HistoryBrowserMsg>();
+        if our_msg.is_none() {
+            debug!("expecetd HistoryBrowserMsg, got {:?}, passing through", msg);
+            return Some(msg);
+        }
+
+        match our_msg.unwrap() {
+            HistoryBrowserMsg::HighlightChanged => {
+                self.update_preview();
+                None
+            }
+            HistoryBrowserMsg::Close => {
+                if let Some(on_close) = self.on_close.as_ref() {
+                    on_close(self)
+                } else {
+                    warn!("received close message, but no on_close is defined");
+                    None
+                }
+            }
+        }
+    }
+
+    fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        let _size = unpack_unit_e!(self.get_layout_res().map(|lr| lr.total_size), "render before layout",);
+
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            output.emit_metadata(crate::io::output::Metadata {
+                id: self.id(),
+                typename: HISTORY_BROWSER_WIDGET_NAME.to_string(),
+                rect: crate::primitives::rect::Rect::from_zero(_size),
+                focused,
+            });
+        }
+
+        self.combined_render(theme, focused, output)
+    }
+
+    // everything goes to the list, preview is not interactive
+    fn act_on(&mut self, input_event: InputEvent) -> (bool, Option<Box<dyn AnyMsg>>) {
+        let mut act_result = self.tree_view.act_on(input_event);
+
+        if !act_result.0 {
+            if let Some(msg_to_self) = self.on_input(input_event) {
+                act_result = (true, Some(msg_to_self));
+            }
+        }
+
+        if let Some(msg_to_myself) = act_result.1 {
+            (true, self.update(msg_to_myself))
+        } else {
+            act_result
+        }
+    }
+
+    fn get_status_description(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed("edit history"))
+    }
+}
+
+impl CombinedWidget for HistoryBrowserWidget {
+    fn get_layout(&self) -> Box<dyn Layout<Self>> {
+        SplitLayout::new(SplitDirection::Horizontal)
+            .with(SplitRule::Proportional(1.0f32), LeafLayout::new(subwidget!(Self.tree_view)).boxed())
+            .with(SplitRule::Proportional(1.0f32), LeafLayout::new(subwidget!(Self.preview)).boxed())
+            .boxed()
+    }
+
+    fn save_layout_res(&mut self, result: LayoutResult<Self>) {
+        self.layout_res = Some(result);
+    }
+
+    fn get_layout_res(&self) -> Option<&LayoutResult<Self>> {
+        self.layout_res.as_ref()
+    }
+
+    fn get_subwidgets_for_input(&self) -> impl Iterator<Item = SubwidgetPointer<Self>> {
+        [subwidget!(Self.tree_view)].into_iter()
+    }
+}
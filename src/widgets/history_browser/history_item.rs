@@ -0,0 +1,256 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use ropey::Rope;
+
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::text::undo_tree::UndoTree;
+
+/*
+A node of history browser. States on the "main line" (the one undo and redo walk through current
+state) are children of root, in chronological order. States with more than one child get abandoned
+branches as their children, each of them being a line of states again. So the tree gets deeper only
+where history forks.
+
+Ropes are cheap to clone, so each state carries its contents and contents of its parent (as
+reconstructed by history), which is all it takes to render a preview.
+ */
+#[derive(Debug)]
+pub struct HistoryItem {
+    id: usize,
+    label: String,
+    state_op: Option<HistoryState>,
+    children: Vec<Rc<HistoryItem>>,
+}
+
+#[derive(Debug)]
+struct HistoryState {
+    node_id: usize,
+    rope: Rope,
+    parent_rope_op: Option<Rope>,
+}
+
+// number of lines shown above the first changed one
+const PREVIEW_CONTEXT: usize = 3;
+
+impl HistoryItem {
+    /*
+    Item ids: states are identified by their node ids, root gets history.next_id() and branches
+    history.next_id() + 1 + id of their first state.
+     */
+    pub fn new_root(title: String, history: &UndoTree, saved_op: Option<usize>) -> Rc<HistoryItem> {
+        let now = SystemTime::now();
+        let current = history.current_id();
+
+        let mut main_line = history.path_from_root(current);
+        main_line.extend(history.redo_chain(current));
+
+        Rc::new(HistoryItem {
+            id: history.next_id(),
+            label: title,
+            state_op: None,
+            children: Self::line(history, &main_line, current, saved_op, now),
+        })
+    }
+
+    fn line(history: &UndoTree, ids: &[usize], current: usize, saved_op: Option<usize>, now: SystemTime) -> Vec<Rc<HistoryItem>> {
+        ids.iter()
+            .enumerate()
+            .map(|(idx, id)| Self::state(history, *id, ids.get(idx + 1).copied(), current, saved_op, now))
+            .collect()
+    }
+
+    fn state(
+        history: &UndoTree,
+        node_id: usize,
+        next_on_line: Option<usize>,
+        current: usize,
+        saved_op: Option<usize>,
+        now: SystemTime,
+    ) -> Rc<HistoryItem> {
+        let node = history.node(node_id).unwrap();
+        let rope = history.rope_at(node_id).unwrap_or_default();
+        let parent_rope_op = node.parent().and_then(|parent| history.rope_at(parent));
+
+        let mut label = format!("#{} {}", node_id, format_age(node.created(), now));
+        if let Some(parent_rope) = parent_rope_op.as_ref() {
+            let delta = rope.len_chars() as isize - parent_rope.len_chars() as isize;
+            label += &format!(", {:+} chars", delta);
+        }
+        if node_id == current {
+            label += " [current]";
+        }
+        if saved_op == Some(node_id) {
+            label += " [saved]";
+        }
+
+        let children = node
+            .children()
+            .iter()
+            .filter(|child| Some(**child) != next_on_line)
+            .map(|child| Self::branch(history, *child, current, saved_op, now))
+            .collect();
+
+        Rc::new(HistoryItem {
+            id: node_id,
+            label,
+            state_op: Some(HistoryState {
+                node_id,
+                rope,
+                parent_rope_op,
+            }),
+            children,
+        })
+    }
+
+    fn branch(history: &UndoTree, head: usize, current: usize, saved_op: Option<usize>, now: SystemTime) -> Rc<HistoryItem> {
+        let mut ids = vec![head];
+        ids.extend(history.redo_chain(head));
+
+        let last_created = ids
+            .iter()
+            .filter_map(|id| history.node(*id))
+            .map(|node| node.created())
+            .max()
+            .unwrap_or(now);
+
+        Rc::new(HistoryItem {
+            id: history.next_id() + 1 + head,
+            label: format!("branch of {} states, {}", ids.len(), format_age(last_created, now)),
+            state_op: None,
+            children: Self::line(history, &ids, current, saved_op, now),
+        })
+    }
+
+    // None for root and branches
+    pub fn node_id(&self) -> Option<usize> {
+        self.state_op.as_ref().map(|state| state.node_id)
+    }
+
+    // see change_preview
+    pub fn preview(&self, max_lines: usize) -> String {
+        let state = match self.state_op.as_ref() {
+            Some(state) => state,
+            None => return String::new(),
+        };
+
+        change_preview(&state.rope, state.parent_rope_op.as_ref(), max_lines)
+    }
+}
+
+/*
+Up to max_lines lines of rope, starting a couple of lines above the first one that differs from
+previous version, with line numbers. The changed line is marked with ">". Without previous version
+it's just the beginning of rope.
+ */
+pub fn change_preview(rope: &Rope, previous_op: Option<&Rope>, max_lines: usize) -> String {
+    let first_changed = match previous_op {
+        Some(previous) => first_different_line(rope, previous),
+        None => 0,
+    };
+
+    let mut result = String::new();
+    let begin = first_changed.saturating_sub(PREVIEW_CONTEXT);
+    let end = (begin + max_lines).min(rope.len_lines());
+
+    for line_idx in begin..end {
+        let line = rope.line(line_idx).to_string();
+        let marker = if line_idx == first_changed { '>' } else { ' ' };
+        result += &format!("{}{:>5} {}\n", marker, line_idx + 1, line.trim_end_matches(&['\n', '\r'][..]));
+    }
+
+    result
+}
+
+fn first_different_line(rope: &Rope, other: &Rope) -> usize {
+    let common = rope.len_lines().min(other.len_lines());
+
+    for line_idx in 0..common {
+        if rope.line(line_idx) != other.line(line_idx) {
+            return line_idx;
+        }
+    }
+
+    common.saturating_sub(1)
+}
+
+fn format_age(created: SystemTime, now: SystemTime) -> String {
+    let secs = now.duration_since(created).map(|d| d.as_secs()).unwrap_or(0);
+
+    if secs < 60 {
+        format!("{}s ago", secs)
+    } else if secs < 60 * 60 {
+        format!("{}m ago", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("{}h ago", secs / (60 * 60))
+    } else {
+        format!("{}d ago", secs / (24 * 60 * 60))
+    }
+}
+
+impl TreeNode<usize> for Rc<HistoryItem> {
+    fn id(&self) -> &usize {
+        &self.id
+    }
+
+    fn label(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn child_iter(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(self.children.iter().cloned())
+    }
+
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::common_edit_msgs::CommonEditMsg;
+    use crate::primitives::tree::tree_node::TreeNode;
+    use crate::text::buffer_state::BufferState;
+    use crate::text::text_buffer::TextBuffer;
+    use crate::widget::widget::get_new_widget_id;
+    use crate::widgets::history_browser::history_item::HistoryItem;
+    use crate::widgets::main_view::main_view::DocumentIdentifier;
+
+    #[test]
+    fn history_item_branches_test() {
+        let wid = get_new_widget_id();
+        let mut bf = BufferState::full(None, DocumentIdentifier::new_unique(), None, None).with_text("a\n");
+        bf.initialize_for_widget(wid, None);
+
+        for c in ['b', 'c'] {
+            bf.apply_common_edit_message(CommonEditMsg::Block(format!("{}\n", c)), wid, 10, None, false);
+        }
+        bf.undo();
+        bf.undo();
+        bf.apply_common_edit_message(CommonEditMsg::Char('x'), wid, 10, None, false);
+
+        let root = HistoryItem::new_root("file".to_string(), bf.history(), Some(0));
+
+        // main line goes through current state
+        let main_line: Vec<usize> = root.child_iter().filter_map(|item| item.node_id()).collect();
+        assert_eq!(main_line, vec![0, 3]);
+        assert!(root.child_iter().last().unwrap().label().contains("[current]"));
+
+        let first = root.child_iter().next().unwrap();
+        assert!(first.label().contains("[saved]"));
+
+        // abandoned states hang from where they forked
+        let branch = first.child_iter().next().unwrap();
+        assert_eq!(branch.node_id(), None);
+        let abandoned: Vec<usize> = branch.child_iter().filter_map(|item| item.node_id()).collect();
+        assert_eq!(abandoned, vec![1, 2]);
+
+        let last = branch.child_iter().last().unwrap();
+        assert_eq!(last.preview(10), "     1 b\n>    2 c\n     3 a\n     4 \n");
+    }
+}
@@ -0,0 +1,3 @@
+pub mod history_browser_widget;
+pub mod history_item;
+pub mod msg;
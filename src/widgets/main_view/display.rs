@@ -2,24 +2,32 @@ use crate::widget::widget::Widget;
 use crate::widgets::code_results_view::code_results_widget::CodeResultsView;
 use crate::widgets::editor_view::editor_view::EditorView;
 use crate::widgets::main_view::main_view::DocumentIdentifier;
+use crate::widgets::recovery_view::recovery_view_widget::RecoveryView;
+use crate::widgets::task_view::task_view_widget::TaskView;
 
 pub enum MainViewDisplay {
-    Editor(EditorView),
-    ResultsView(CodeResultsView),
+    Editor(Box<EditorView>),
+    ResultsView(Box<CodeResultsView>),
+    Task(TaskView),
+    Recovery(RecoveryView),
 }
 
 impl MainViewDisplay {
     pub fn get_widget(&self) -> &dyn Widget {
         match self {
-            MainViewDisplay::Editor(e) => e,
-            MainViewDisplay::ResultsView(r) => r,
+            MainViewDisplay::Editor(e) => e.as_ref(),
+            MainViewDisplay::ResultsView(r) => r.as_ref(),
+            MainViewDisplay::Task(t) => t,
+            MainViewDisplay::Recovery(r) => r,
         }
     }
 
     pub fn get_widget_mut(&mut self) -> &mut dyn Widget {
         match self {
-            MainViewDisplay::Editor(e) => e,
-            MainViewDisplay::ResultsView(r) => r,
+            MainViewDisplay::Editor(e) => e.as_mut(),
+            MainViewDisplay::ResultsView(r) => r.as_mut(),
+            MainViewDisplay::Task(t) => t,
+            MainViewDisplay::Recovery(r) => r,
         }
     }
 
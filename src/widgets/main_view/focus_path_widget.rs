@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use log::{error, warn};
 
 use crate::config::theme::Theme;
@@ -7,7 +9,9 @@ use crate::io::output::Output;
 use crate::io::style::TextStyle;
 use crate::primitives::printable::Printable;
 use crate::primitives::xy::XY;
+use crate::tsw::lang_id::LangId;
 use crate::unpack_or_e;
+use crate::w7e::navcomp_group::NavCompHealth;
 use crate::widget::any_msg::AnyMsg;
 use crate::widget::fill_policy::SizePolicy;
 use crate::widget::widget::{get_new_widget_id, Widget, WID};
@@ -16,6 +20,8 @@ use crate::widget::widget::{get_new_widget_id, Widget, WID};
 pub struct FocusPathWidget {
     id: WID,
     focus_path_items: Vec<String>,
+    // right-aligned, things like "rust lsp restarting"
+    status_items: Vec<String>,
 
     layout_res: Option<XY>,
 }
@@ -28,6 +34,7 @@ impl FocusPathWidget {
         Self {
             id: get_new_widget_id(),
             focus_path_items: vec![],
+            status_items: vec![],
             layout_res: None,
         }
     }
@@ -51,6 +58,21 @@ impl FocusPathWidget {
     pub fn set_focus_path(&mut self, focus_path: Vec<String>) {
         self.focus_path_items = focus_path;
     }
+
+    pub fn set_navcomp_health(&mut self, health: &HashMap<LangId, NavCompHealth>) {
+        self.status_items = health
+            .iter()
+            .map(|(lang_id, health)| {
+                let state = match health {
+                    NavCompHealth::Restarting => "restarting",
+                    NavCompHealth::Down => "down",
+                };
+                format!("{} lsp {}", lang_id.to_lsp_lang_id_string(), state)
+            })
+            .collect();
+        // HashMap order is random, and I don't want the bar to flicker
+        self.status_items.sort();
+    }
 }
 
 impl Widget for FocusPathWidget {
@@ -88,6 +110,16 @@ impl Widget for FocusPathWidget {
     fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
         let size = unpack_or_e!(self.layout_res, (), "render before layout");
 
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            output.emit_metadata(crate::io::output::Metadata {
+                id: self.id(),
+                typename: self.typename().to_string(),
+                rect: crate::primitives::rect::Rect::new(XY::ZERO, XY::new(size.x, 1)),
+                focused,
+            });
+        }
+
         if self.focus_path_items.is_empty() {
             warn!("focus path empty");
             return;
@@ -118,6 +150,20 @@ impl Widget for FocusPathWidget {
             }
             output.print_at(XY::new(idx as u16, 0), theme.default_text(false), grapheme);
         }
+
+        // status goes over focus path, it's more important
+        if !self.status_items.is_empty() {
+            let status = format!(" {} ", self.status_items.join(", "));
+            let status_width = status.graphemes().count();
+            let begin = (available_width as usize).saturating_sub(status_width);
+
+            for (idx, grapheme) in status.graphemes().enumerate() {
+                if begin + idx >= available_width as usize {
+                    break;
+                }
+                output.print_at(XY::new((begin + idx) as u16, 0), theme.editor_label_warning(), grapheme);
+            }
+        }
     }
 
     fn size_policy(&self) -> SizePolicy {
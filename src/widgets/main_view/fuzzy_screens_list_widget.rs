@@ -19,6 +19,9 @@ pub enum Type {
     BufferList(Vec<Arc<DisplayRegistryItem>>),
     CodeResults { description: String },
     CodeResultsList(Vec<Arc<DisplayRegistryItem>>),
+    Task { description: String },
+    TaskList(Vec<Arc<DisplayRegistryItem>>),
+    Recovery { description: String },
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +48,9 @@ impl TreeNode<usize> for DisplayRegistryTreeNode {
             Type::CodeResults { description } => Cow::Borrowed(description.as_ref()),
             Type::BufferList(_) => Cow::Borrowed("buffers:"),
             Type::CodeResultsList(_) => Cow::Borrowed("search/code results views:"),
+            Type::Task { description } => Cow::Borrowed(description.as_ref()),
+            Type::TaskList(_) => Cow::Borrowed("tasks:"),
+            Type::Recovery { description } => Cow::Borrowed(description.as_ref()),
         }
     }
 
@@ -53,6 +59,7 @@ impl TreeNode<usize> for DisplayRegistryTreeNode {
             Type::Root(_) => false,
             Type::BufferList(_) => false,
             Type::CodeResultsList(_) => false,
+            Type::TaskList(_) => false,
             _ => true,
         }
     }
@@ -64,6 +71,9 @@ impl TreeNode<usize> for DisplayRegistryTreeNode {
             Type::BufferList(items) => Box::new(items.clone().into_iter()),
             Type::CodeResults { .. } => Box::new(std::iter::empty()),
             Type::CodeResultsList(items) => Box::new(items.clone().into_iter()),
+            Type::Task { .. } => Box::new(std::iter::empty()),
+            Type::TaskList(items) => Box::new(items.clone().into_iter()),
+            Type::Recovery { .. } => Box::new(std::iter::empty()),
         }
     }
 
@@ -77,6 +87,9 @@ pub type FuzzyScreensList = ContextMenuWidget<usize, DisplayRegistryTreeNode>;
 pub fn get_fuzzy_screen_list(displays: &Vec<MainViewDisplay>, display_idx: usize) -> DisplayRegistryTreeNode {
     let mut buffer_list: Vec<Arc<DisplayRegistryItem>> = Vec::new();
     let mut results_view_list: Vec<Arc<DisplayRegistryItem>> = Vec::new();
+    let mut task_list: Vec<Arc<DisplayRegistryItem>> = Vec::new();
+    // there is at most one, so it goes directly under root
+    let mut recovery_op: Option<Arc<DisplayRegistryItem>> = None;
 
     let len = displays.len();
 
@@ -118,6 +131,24 @@ pub fn get_fuzzy_screen_list(displays: &Vec<MainViewDisplay>, display_idx: usize
 
                 results_view_list.push(Arc::new(code_view));
             }
+            MainViewDisplay::Task(task_view) => {
+                let task = DisplayRegistryItem {
+                    id: idx,
+                    t: Type::Task {
+                        description: task_view.get_description(),
+                    },
+                };
+
+                task_list.push(Arc::new(task));
+            }
+            MainViewDisplay::Recovery(recovery_view) => {
+                recovery_op = Some(Arc::new(DisplayRegistryItem {
+                    id: idx,
+                    t: Type::Recovery {
+                        description: recovery_view.get_description(),
+                    },
+                }));
+            }
         }
     }
 
@@ -134,6 +165,15 @@ pub fn get_fuzzy_screen_list(displays: &Vec<MainViewDisplay>, display_idx: usize
             t: Type::CodeResultsList(results_view_list),
         }));
     }
+    if !task_list.is_empty() {
+        items.push(Arc::new(DisplayRegistryItem {
+            id: len + 3,
+            t: Type::TaskList(task_list),
+        }));
+    }
+    if let Some(recovery) = recovery_op {
+        items.push(recovery);
+    }
 
     Arc::new(DisplayRegistryItem {
         id: len,
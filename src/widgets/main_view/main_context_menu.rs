@@ -25,9 +25,23 @@ pub fn get_focus_path_w<'a>(root: &'a dyn Widget) -> Vec<&'a dyn Widget> {
 }
 
 pub fn aggregate_actions(widget: &dyn Widget) -> Vec<ContextBarItem> {
+    aggregate_actions_of_path(get_focus_path_w(widget))
+}
+
+/*
+Same as above, but focus path goes from root through given widget, no matter what root has focused
+at the moment (like the context menu itself).
+ */
+pub fn aggregate_actions_through<'a>(root: &'a dyn Widget, focused: &'a dyn Widget) -> Vec<ContextBarItem> {
+    let mut focus_path = vec![root];
+    focus_path.append(&mut get_focus_path_w(focused));
+    aggregate_actions_of_path(focus_path)
+}
+
+fn aggregate_actions_of_path(focus_path: Vec<&dyn Widget>) -> Vec<ContextBarItem> {
     let mut result: Vec<ContextBarItem> = Vec::new();
 
-    for (idx, item) in get_focus_path_w(widget).iter().enumerate() {
+    for (idx, item) in focus_path.iter().enumerate() {
         if let Some(mut action) = item.get_widget_actions() {
             action.set_depth_recursively(idx);
             result.push(action);
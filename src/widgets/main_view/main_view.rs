@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fmt::{format, Display};
+use std::rc::Rc;
 use std::sync::Arc;
 
 use jsonrpc_core::futures::SinkExt;
@@ -10,7 +11,6 @@ use uuid::Uuid;
 use crate::config::theme::Theme;
 use crate::cursor::cursor::Cursor;
 use crate::experiments::buffer_register::OpenResult;
-use crate::experiments::filename_to_language::filename_to_language;
 use crate::experiments::screenspace::Screenspace;
 use crate::experiments::subwidget_pointer::SubwidgetPointer;
 use crate::fs::path::SPath;
@@ -28,11 +28,19 @@ use crate::primitives::border::SINGLE_BORDER_STYLE;
 use crate::primitives::common_query::CommonQuery;
 use crate::primitives::rect::Rect;
 use crate::primitives::scroll::ScrollDirection;
+use crate::primitives::stupid_cursor::StupidCursor;
 use crate::primitives::symbol_usage::SymbolUsage;
 use crate::primitives::tree::tree_node::TreeNode;
 use crate::primitives::xy::XY;
 use crate::promise::streaming_promise::StreamingPromise;
+use crate::text::recovery;
+use crate::text::recovery::RecoverySession;
 use crate::text::text_buffer::TextBuffer;
+use crate::w7e::navcomp_provider::{
+    NavCompApplyEditRequest, NavCompCodeAction, NavCompProgress, NavCompWorkspaceEdit, StupidSubstituteMessage,
+};
+use crate::w7e::task::Task;
+use crate::w7e::task_run::{TaskTickRecv, TaskTickSender};
 use crate::widget::any_msg::{AnyMsg, AsAny};
 use crate::widget::complex_widget::{ComplexWidget, DisplayState};
 use crate::widget::context_bar_item::ContextBarItem;
@@ -41,23 +49,33 @@ use crate::widgets::button::ButtonWidget;
 use crate::widgets::code_results_view::code_results_provider::CodeResultsProvider;
 use crate::widgets::code_results_view::code_results_widget::CodeResultsView;
 use crate::widgets::code_results_view::full_text_search_code_results_provider::FullTextSearchCodeResultsProvider;
+use crate::widgets::code_results_view::stupid_symbol_usage_code_results_provider::url_to_spath;
 use crate::widgets::editor_view::editor_view::EditorView;
 use crate::widgets::file_tree_view::file_tree_view::FileTreeViewWidget;
 use crate::widgets::find_in_files_widget::find_in_files_widget::FindInFilesWidget;
 use crate::widgets::generic_dialog::generic_dialog::GenericDialog;
+use crate::widgets::hierarchy::hierarchy_item::{HierarchyItem, HierarchyWidget};
+use crate::widgets::history_browser::history_browser_widget::HistoryBrowserWidget;
+use crate::widgets::history_browser::history_item::HistoryItem;
 use crate::widgets::main_view::display::MainViewDisplay;
 use crate::widgets::main_view::focus_path_widget::FocusPathWidget;
 use crate::widgets::main_view::fuzzy_file_search_widget::FuzzyFileSearchWidget;
 use crate::widgets::main_view::fuzzy_screens_list_widget::{get_fuzzy_screen_list, FuzzyScreensList};
-use crate::widgets::main_view::main_context_menu::{aggregate_actions, get_focus_path_w, MainContextMenuWidget};
+use crate::widgets::main_view::main_context_menu::{aggregate_actions, aggregate_actions_through, MainContextMenuWidget};
 use crate::widgets::main_view::msg::MainViewMsg;
+use crate::widgets::main_view::task_list_widget::{get_task_list, TaskListWidget};
 use crate::widgets::main_view::util;
 use crate::widgets::main_view::util::get_focus_path;
+use crate::widgets::main_view::workspace_symbol_search_widget::{WorkspaceSymbolItem, WorkspaceSymbolSearchWidget};
 use crate::widgets::no_editor::NoEditorWidget;
+use crate::widgets::outline::outline_item::{OutlineItem, OutlineWidget};
+use crate::widgets::recovery_view::recovered_buffer::RecoveredBuffer;
+use crate::widgets::recovery_view::recovery_view_widget::RecoveryView;
 use crate::widgets::spath_tree_view_node::FileTreeNode;
+use crate::widgets::task_view::task_view_widget::TaskView;
 use crate::widgets::tree_view::tree_view::TreeViewWidget;
 use crate::widgets::with_scroll::with_scroll::WithScroll;
-use crate::{subwidget, unpack_or, unpack_or_e};
+use crate::{subwidget, unpack_or, unpack_or_e, unpack_unit_e};
 
 pub type BufferId = Uuid;
 
@@ -66,13 +84,26 @@ pub enum HoverItem {
     FuzzySearch(FuzzyScreensList),
     // used in fuzzy file list
     FuzzySearch2(FuzzyFileSearchWidget),
+    WorkspaceSymbols(WorkspaceSymbolSearchWidget),
 
     // search in files
-    SearchInFiles(FindInFilesWidget),
+    SearchInFiles(Box<FindInFilesWidget>),
 
     //
     QuitUnsavedWarning(GenericDialog),
 
+    // symbols of current file
+    Outline(OutlineWidget),
+
+    // call or type hierarchy
+    Hierarchy(HierarchyWidget),
+
+    // tasks to run
+    Tasks(TaskListWidget),
+
+    // undo tree of current buffer
+    History(HistoryBrowserWidget),
+
     // Context menu
     ContextMain {
         anchor: XY,
@@ -162,6 +193,18 @@ pub struct MainView {
     display_idx: usize,
 
     status_bar: FocusPathWidget,
+    // like "indexing 47%", refreshed each frame
+    navcomp_progress: Option<String>,
+
+    // from workspace, run as MainViewDisplay::Task
+    tasks: Vec<Task>,
+    task_tick_sender: TaskTickSender,
+    task_tick_receiver: TaskTickRecv,
+
+    // left by crashed session, shown as MainViewDisplay::Recovery until restored or discarded
+    recovered: Vec<RecoveredBuffer>,
+    // crashed sessions whose buffers are offered, locked till they are dealt with
+    recovery_sessions: Vec<RecoverySession>,
 
     hover: Option<HoverItem>,
 }
@@ -171,10 +214,10 @@ impl MainView {
     pub const TYPENAME: &'static str = "main_view";
 
     pub fn create_new_display_for_code_results(&mut self, data_provider: Box<dyn CodeResultsProvider>) -> Result<usize, ()> {
-        self.displays.push(MainViewDisplay::ResultsView(CodeResultsView::new(
+        self.displays.push(MainViewDisplay::ResultsView(Box::new(CodeResultsView::new(
             self.providers.clone(),
             data_provider,
-        )));
+        ))));
 
         let res = self.displays.len() - 1;
 
@@ -195,12 +238,12 @@ impl MainView {
         let buffer_shared_ref = buffer_shared_ref?;
 
         if let Some(mut buffer_lock) = buffer_shared_ref.lock_rw() {
-            buffer_lock.set_lang(filename_to_language(&ff));
+            buffer_lock.set_lang(self.providers.lang_for(ff));
         }
 
-        self.displays.push(MainViewDisplay::Editor(
+        self.displays.push(MainViewDisplay::Editor(Box::new(
             EditorView::new(self.providers.clone(), buffer_shared_ref).with_path_op(ff.parent()),
-        ));
+        )));
 
         let res = self.displays.len() - 1;
 
@@ -237,6 +280,8 @@ impl MainView {
                     }
                 }
                 MainViewDisplay::ResultsView(_) => {}
+                MainViewDisplay::Task(_) => {}
+                MainViewDisplay::Recovery(_) => {}
             }
         }
         None
@@ -259,6 +304,8 @@ impl MainView {
         let tree_widget = FileTreeViewWidget::new(providers.config().clone(), root)
             .with_hidden_files_filter(providers.config().file_tree_view_options.show_hidden_files == false);
 
+        let (task_tick_sender, task_tick_receiver) = crossbeam_channel::bounded(1);
+
         MainView {
             wid: get_new_widget_id(),
             providers: providers.clone(),
@@ -268,17 +315,36 @@ impl MainView {
             no_editor: NoEditorWidget::default(),
             display_idx: 0,
             status_bar: FocusPathWidget::new(),
+            navcomp_progress: None,
+            tasks: Vec::new(),
+            task_tick_sender,
+            task_tick_receiver,
+            recovered: Vec::new(),
+            recovery_sessions: Vec::new(),
             hover: None,
         }
     }
 
+    pub fn set_tasks(&mut self, tasks: Vec<Task>) {
+        self.tasks = tasks;
+    }
+
+    pub fn tasks(&self) -> &Vec<Task> {
+        &self.tasks
+    }
+
+    // ticks whenever a running task has something new to show
+    pub fn task_tick_receiver(&self) -> &TaskTickRecv {
+        &self.task_tick_receiver
+    }
+
     fn open_find_in_files(&mut self) {
         if self.hover.is_some() {
             debug!("ignoring 'open find everywhere', because there is already a hover");
             return;
         }
 
-        self.hover = Some(HoverItem::SearchInFiles(
+        self.hover = Some(HoverItem::SearchInFiles(Box::new(
             FindInFilesWidget::new(self.providers.fsf().root(), self.providers.config().clone())
                 .with_on_hit(Some(Box::new(|widget| {
                     MainViewMsg::FindInFilesQuery {
@@ -289,7 +355,7 @@ impl MainView {
                     .someboxed()
                 })))
                 .with_on_cancel(Some(Box::new(|_| MainViewMsg::CloseHover.someboxed()))),
-        ));
+        )));
         self.set_focus_to_hover();
     }
 
@@ -325,7 +391,7 @@ impl MainView {
         };
 
         self.displays
-            .push(MainViewDisplay::Editor(EditorView::new(self.providers.clone(), buffer)));
+            .push(MainViewDisplay::Editor(Box::new(EditorView::new(self.providers.clone(), buffer))));
 
         self.display_idx = self.displays.len() - 1;
         self.set_focus_to_default();
@@ -365,19 +431,90 @@ impl MainView {
             .with_on_close(Box::new(|_| MainViewMsg::CloseHover.someboxed()))
             .with_expanded_root();
 
-        // I use "len +1" and "len +2" for "buffers" and "searches"
+        // I use "len +1", "len +2" and "len +3" for "buffers", "searches" and "tasks"
         fsl.tree_view_mut().set_expanded(len + 1, true);
         fsl.tree_view_mut().set_expanded(len + 2, true);
+        fsl.tree_view_mut().set_expanded(len + 3, true);
 
         self.hover = Some(HoverItem::FuzzySearch(fsl));
         self.set_focus_to_hover();
     }
 
+    fn open_outline_and_focus(&mut self, document: DocumentIdentifier, root: Rc<OutlineItem>) {
+        let mut widget = OutlineWidget::new(self.providers.clone(), root)
+            .with_on_hit(Box::new(move |w| {
+                let cursor = w.get_highlighted().1.cursor()?;
+                MainViewMsg::OpenFile {
+                    file: document.clone(),
+                    position_op: Some(cursor),
+                }
+                .someboxed()
+            }))
+            .with_on_close(Box::new(|_| MainViewMsg::CloseHover.someboxed()));
+
+        // outlines are rarely deep, so I show everything right away
+        widget.tree_view_mut().expand_all_internal_nodes();
+
+        self.hover = Some(HoverItem::Outline(widget));
+        self.set_focus_to_hover();
+    }
+
+    fn open_hierarchy_and_focus(&mut self, root: Rc<HierarchyItem>) {
+        let widget = HierarchyWidget::new(self.providers.clone(), root)
+            .with_lazy_children()
+            .with_on_hit(Box::new(|w| {
+                let (spath, stupid_cursor) = w.get_highlighted().1.location()?;
+                MainViewMsg::OpenFileBySpath {
+                    spath,
+                    position_op: Some(stupid_cursor),
+                }
+                .someboxed()
+            }))
+            .with_on_close(Box::new(|_| MainViewMsg::CloseHover.someboxed()))
+            .with_expanded_root();
+
+        self.hover = Some(HoverItem::Hierarchy(widget));
+        self.set_focus_to_hover();
+    }
+
+    fn open_history_and_focus(&mut self, document: DocumentIdentifier, root: Rc<HistoryItem>, current_node: usize, widget_id: WID) {
+        let widget = HistoryBrowserWidget::new(self.providers.clone(), root, current_node)
+            .with_on_hit(Box::new(move |w| {
+                let node_id = w.get_highlighted().1.node_id()?;
+                MainViewMsg::JumpToHistory {
+                    document: document.clone(),
+                    node_id,
+                    widget_id,
+                }
+                .someboxed()
+            }))
+            .with_on_close(Box::new(|_| MainViewMsg::CloseHover.someboxed()));
+
+        self.hover = Some(HoverItem::History(widget));
+        self.set_focus_to_hover();
+    }
+
+    fn jump_to_history(&mut self, document: &DocumentIdentifier, node_id: usize, widget_id: WID) -> bool {
+        let bsr = unpack_or_e!(
+            self.providers
+                .buffer_register()
+                .read()
+                .ok()
+                .and_then(|buffer_register| buffer_register.get_buffer_ref_from_id(document)),
+            false,
+            "failed to find buffer {}",
+            document
+        );
+        let mut buffer = unpack_or_e!(bsr.lock_rw(), false, "failed to lock buffer {}", document);
+
+        buffer.jump_to_history(node_id, widget_id)
+    }
+
     fn open_fuzzy_search_in_files_and_focus(&mut self) {
         let mut widget = FuzzyFileSearchWidget::new(self.providers.clone(), FileTreeNode::new(self.providers.fsf().root().clone()))
             .with_on_hit(Box::new(|w| {
                 let spath = w.get_highlighted().1.spath().clone();
-                MainViewMsg::OpenFileBySpath { spath }.someboxed()
+                MainViewMsg::OpenFileBySpath { spath, position_op: None }.someboxed()
             }))
             .with_on_close(Box::new(|_| MainViewMsg::CloseHover.someboxed()))
             .with_expanded_root();
@@ -386,12 +523,248 @@ impl MainView {
         self.set_focus_to_hover();
     }
 
+    fn open_workspace_symbols_and_focus(&mut self) {
+        let root = WorkspaceSymbolItem::new_root(self.providers.navcomp_group().clone(), self.providers.fsf().clone());
+
+        let widget = WorkspaceSymbolSearchWidget::new(self.providers.clone(), root)
+            .with_on_hit(Box::new(|w| {
+                let (spath, stupid_cursor) = w.get_highlighted().1.location()?;
+                MainViewMsg::OpenFileBySpath {
+                    spath,
+                    position_op: Some(stupid_cursor),
+                }
+                .someboxed()
+            }))
+            .with_on_close(Box::new(|_| MainViewMsg::CloseHover.someboxed()))
+            .with_expanded_root();
+
+        self.hover = Some(HoverItem::WorkspaceSymbols(widget));
+        self.set_focus_to_hover();
+    }
+
+    fn open_task_list_and_focus(&mut self) {
+        if self.tasks.is_empty() {
+            debug!("ignoring 'open tasks', because there are none");
+            return;
+        }
+
+        let len = self.tasks.len();
+
+        let widget = TaskListWidget::new(self.providers.clone(), get_task_list(&self.tasks))
+            .with_on_hit(Box::new(move |w| {
+                // root has id equal to len
+                let task_idx = *w.get_highlighted().1.id();
+                if task_idx >= len {
+                    None
+                } else {
+                    MainViewMsg::RunTask { task_idx }.someboxed()
+                }
+            }))
+            .with_on_close(Box::new(|_| MainViewMsg::CloseHover.someboxed()))
+            .with_expanded_root();
+
+        self.hover = Some(HoverItem::Tasks(widget));
+        self.set_focus_to_hover();
+    }
+
+    /*
+    A task is not run twice at the same time (think two cargo builds fighting for lock). If it's
+    already running, its display is brought up, if it ended, it's rerun in the same display.
+     */
+    fn run_task_and_focus(&mut self, task_idx: usize) -> bool {
+        let task = unpack_or_e!(self.tasks.get(task_idx), false, "no task with index {}", task_idx).clone();
+
+        let existing_idx = self.displays.iter().position(|display| match display {
+            MainViewDisplay::Task(task_view) => *task_view.task() == task,
+            _ => false,
+        });
+
+        match existing_idx {
+            Some(idx) => {
+                if let Some(MainViewDisplay::Task(task_view)) = self.displays.get_mut(idx) {
+                    if !task_view.run().status().is_running() {
+                        task_view.rerun();
+                    }
+                }
+                self.display_idx = idx;
+            }
+            None => {
+                self.displays
+                    .push(MainViewDisplay::Task(TaskView::new(task, self.task_tick_sender.clone())));
+                self.display_idx = self.displays.len() - 1;
+            }
+        }
+
+        self.set_focus_to_default();
+        true
+    }
+
+    /*
+    Looks for buffers left by sessions that did not end cleanly (see recovery::orphaned_sessions), and
+    if there are any, lists them in a display (not a hover, so one can look at the files before
+    deciding). Sessions with nothing worth offering are removed right away.
+     */
+    pub fn offer_recovered_buffers(&mut self, sessions: Vec<RecoverySession>) {
+        self.recovered = RecoveredBuffer::find_all(&self.providers, &sessions);
+
+        for session in sessions {
+            if self.recovered.iter().any(|recovered| recovered.dir.as_path() == session.dir()) {
+                self.recovery_sessions.push(session);
+            } else {
+                session.remove();
+            }
+        }
+
+        if self.recovered.is_empty() {
+            return;
+        }
+
+        debug!("found {} recovered buffers", self.recovered.len());
+        self.displays.push(MainViewDisplay::Recovery(self.new_recovery_view()));
+        self.display_idx = self.displays.len() - 1;
+        self.set_focus_to_default();
+    }
+
+    pub fn recovered_buffers(&self) -> &Vec<RecoveredBuffer> {
+        &self.recovered
+    }
+
+    fn new_recovery_view(&self) -> RecoveryView {
+        RecoveryView::new(&self.recovered)
+            .with_on_hit(Box::new(|w| {
+                let idx = w.get_highlighted().1.recovered_idx()?;
+                MainViewMsg::RestoreRecovered { idx }.someboxed()
+            }))
+            .with_on_discard(Box::new(|w| {
+                let idx = w.highlighted()?;
+                MainViewMsg::DiscardRecovered { idx }.someboxed()
+            }))
+    }
+
+    // removes entry both from the list and from disk, and rebuilds (or closes) the recovery display
+    fn forget_recovered(&mut self, idx: usize) {
+        if idx >= self.recovered.len() {
+            error!("no recovered buffer with index {}", idx);
+            return;
+        }
+
+        let recovered = self.recovered.remove(idx);
+        if let Err(e) = recovery::remove(&recovered.dir, &recovered.entry.buffer_id) {
+            error!("failed to remove recovery entry {}, because {}", recovered.entry.buffer_id, e);
+        }
+
+        // last entry of a session is gone, so is the session
+        if !self.recovered.iter().any(|other| other.dir == recovered.dir) {
+            if let Some(pos) = self
+                .recovery_sessions
+                .iter()
+                .position(|session| session.dir() == recovered.dir.as_path())
+            {
+                self.recovery_sessions.remove(pos).remove();
+            }
+        }
+
+        let display_pos = unpack_unit_e!(
+            self.displays
+                .iter()
+                .position(|display| matches!(display, MainViewDisplay::Recovery(_))),
+            "no recovery display",
+        );
+
+        if self.recovered.is_empty() {
+            self.displays.remove(display_pos);
+            if self.display_idx > display_pos || (self.display_idx == display_pos && self.display_idx > 0) {
+                self.display_idx -= 1;
+            }
+        } else {
+            self.displays[display_pos] = MainViewDisplay::Recovery(self.new_recovery_view());
+        }
+    }
+
+    fn discard_recovered(&mut self, idx: usize) {
+        self.forget_recovered(idx);
+        self.set_focus_to_default();
+    }
+
+    /*
+    Opens the file (or a new buffer, if it was never saved or is gone from disk) and replaces its text
+    with the recovered one in a single edit, so undo gets back to what's on disk. Buffer is left unsaved.
+     */
+    fn restore_recovered_and_focus(&mut self, idx: usize) -> bool {
+        let recovered = unpack_or_e!(self.recovered.get(idx), false, "no recovered buffer with index {}", idx).clone();
+
+        let opened = match (recovered.spath.as_ref(), recovered.disk_rope_op.as_ref()) {
+            (Some(spath), Some(_)) => self.open_file_with_path_and_focus(spath.clone()),
+            _ => {
+                self.open_empty_editor_and_focus();
+                true
+            }
+        };
+
+        if !opened {
+            error!("failed to open buffer for recovered {}", recovered.label());
+            return false;
+        }
+
+        let providers = self.providers.clone();
+        let editor_view = unpack_or_e!(self.get_currently_focused_editor_view_mut(), false, "no editor focused");
+        let widget_id = editor_view.get_internal_widget().id();
+
+        {
+            let mut buffer = unpack_or_e!(editor_view.get_buffer_ref().lock_rw(), false, "failed to lock buffer");
+
+            if buffer.to_string() != recovered.entry.text {
+                let end = unpack_or_e!(
+                    StupidCursor::from_char_idx_utf16(buffer.text().rope(), buffer.len_chars()),
+                    false,
+                    "failed to find end of buffer"
+                );
+                let substitute = StupidSubstituteMessage {
+                    substitute: recovered.entry.text.clone(),
+                    stupid_range: (StupidCursor::new(0, 0), end),
+                };
+
+                if !buffer.apply_stupid_substitute_messages(widget_id, &vec![substitute], 1) {
+                    error!("failed to apply recovered text of {}", recovered.label());
+                    return false;
+                }
+
+                let edit_event_op = buffer.take_edit_event();
+                if let (Some(path), Some(edit_event)) = (buffer.get_path(), edit_event_op) {
+                    if let Ok(navcomp_group) = providers.navcomp_group().try_read() {
+                        if let Some(navcomp) = navcomp_group.get_navcomp_for(path) {
+                            navcomp.submit_edit_event(path, edit_event);
+                        }
+                    }
+                }
+            }
+        }
+
+        // editor checks if they fit
+        if let Some(cursor_set) = recovered.entry.cursor_set {
+            editor_view.override_cursor_set(cursor_set);
+        }
+
+        // finding the editor again, since removing recovery display can move it
+        let document = editor_view.get_buffer_ref().document_identifier().clone();
+        self.forget_recovered(idx);
+        let editor_idx_op = self.get_opened_views_for_document_id(document).next().map(|pair| pair.0);
+        if let Some(editor_idx) = editor_idx_op {
+            self.display_idx = editor_idx;
+        }
+        self.set_focus_to_default();
+
+        true
+    }
+
     fn get_opened_views_for_document_id(
         &self,
         document_identifier: DocumentIdentifier,
     ) -> impl Iterator<Item = (usize, &MainViewDisplay)> + '_ {
         self.displays.iter().enumerate().filter_map(move |(idx, item)| match item {
             MainViewDisplay::ResultsView(_) => None,
+            MainViewDisplay::Task(_) => None,
+            MainViewDisplay::Recovery(_) => None,
             MainViewDisplay::Editor(editor) => {
                 if editor.get_buffer_ref().document_identifier() == &document_identifier {
                     Some((idx, item))
@@ -410,9 +783,14 @@ impl MainView {
                         match mv.hover.as_ref().unwrap() {
                             HoverItem::FuzzySearch(fs) => fs as &dyn Widget,
                             HoverItem::FuzzySearch2(fs) => fs as &dyn Widget,
-                            HoverItem::SearchInFiles(fs) => fs as &dyn Widget,
+                            HoverItem::WorkspaceSymbols(ws) => ws as &dyn Widget,
+                            HoverItem::SearchInFiles(fs) => fs.as_ref() as &dyn Widget,
                             HoverItem::ContextMain { anchor, widget, old_focus } => widget as &dyn Widget,
                             HoverItem::QuitUnsavedWarning(gd) => gd as &dyn Widget,
+                            HoverItem::Outline(ow) => ow as &dyn Widget,
+                            HoverItem::Hierarchy(hw) => hw as &dyn Widget,
+                            HoverItem::Tasks(tw) => tw as &dyn Widget,
+                            HoverItem::History(hw) => hw as &dyn Widget,
                         }
                     } else {
                         error!("no hover found, this subwidget pointer should have been overriden by now.");
@@ -426,9 +804,14 @@ impl MainView {
                         match mv.hover.as_mut().unwrap() {
                             HoverItem::FuzzySearch(fs) => fs as &mut dyn Widget,
                             HoverItem::FuzzySearch2(fs) => fs as &mut dyn Widget,
-                            HoverItem::SearchInFiles(fs) => fs as &mut dyn Widget,
+                            HoverItem::WorkspaceSymbols(ws) => ws as &mut dyn Widget,
+                            HoverItem::SearchInFiles(fs) => fs.as_mut() as &mut dyn Widget,
                             HoverItem::ContextMain { anchor, widget, old_focus } => widget as &mut dyn Widget,
                             HoverItem::QuitUnsavedWarning(gd) => gd as &mut dyn Widget,
+                            HoverItem::Outline(ow) => ow as &mut dyn Widget,
+                            HoverItem::Hierarchy(hw) => hw as &mut dyn Widget,
+                            HoverItem::Tasks(tw) => tw as &mut dyn Widget,
+                            HoverItem::History(hw) => hw as &mut dyn Widget,
                         }
                     } else {
                         error!("no hover found, this subwidget pointer should have been overriden by now.");
@@ -479,6 +862,22 @@ impl MainView {
         true
     }
 
+    fn set_stupid_cursor_in_focused_editor(&mut self, stupid_cursor: StupidCursor) -> bool {
+        let editor_view = unpack_or_e!(self.get_currently_focused_editor_view_mut(), false, "no editor focused");
+
+        let cursor = {
+            let buffer = unpack_or_e!(editor_view.get_buffer_ref().lock(), false, "failed to lock buffer");
+            unpack_or_e!(
+                stupid_cursor.to_real_cursor(&*buffer),
+                false,
+                "position {:?} is outside of buffer",
+                stupid_cursor
+            )
+        };
+
+        editor_view.override_cursor_set(cursor.as_cursor_set())
+    }
+
     fn open_unsaved_warning_and_focus(&mut self) {
         if self.hover.is_some() {
             warn!("closing old hover to put new one on top");
@@ -579,6 +978,29 @@ impl MainView {
         self.set_focus_to_hover();
     }
 
+    /*
+    Some actions (like code actions offered by LSP) arrive after context menu was opened, so it's
+    rebuilt whenever widgets below it offer something different.
+     */
+    fn refresh_main_context_menu(&mut self) {
+        let old_focus = match self.hover.as_ref() {
+            Some(HoverItem::ContextMain { old_focus, .. }) => old_focus.clone(),
+            _ => return,
+        };
+
+        let options = aggregate_actions_through(self, old_focus.get(self));
+        let item = ContextBarItem::new_internal_node(Cow::Borrowed("†"), options);
+
+        if let Some(HoverItem::ContextMain { widget, .. }) = self.hover.as_mut() {
+            if !widget.tree_view().get_root_node().has_same_titles(&item) {
+                debug!("refreshing main context menu");
+                let tree_view = widget.tree_view_mut();
+                tree_view.set_root_node(item);
+                tree_view.expand_all_internal_nodes();
+            }
+        }
+    }
+
     // TODO this piece of code would be great to unify with act_on, but I have no idea how to do it now.
     fn context_menu_process_hit(&mut self, msg: Box<dyn AnyMsg>, depth: usize) -> Option<Box<dyn AnyMsg>> {
         fn rec_process_msg(widget: &mut dyn Widget, depth: usize, msg: Box<dyn AnyMsg>) -> Option<Box<dyn AnyMsg>> {
@@ -649,6 +1071,119 @@ impl MainView {
         success
     }
 
+    /*
+    Closes the preview (that's where the request comes from), and applies edits through BufferRegister.
+     */
+    fn apply_workspace_edit(&mut self, file_edits: Vec<(SPath, Vec<StupidSubstituteMessage>)>, opened_for_preview: Vec<SPath>) -> bool {
+        if let Some(MainViewDisplay::ResultsView(_)) = self.displays.get(self.display_idx) {
+            self.displays.remove(self.display_idx);
+            if !self.do_prev_display() && !self.do_next_display() {
+                self.display_idx = 0;
+                self.set_focus_to_default();
+            }
+        } else {
+            warn!("expected rename preview to be the current display");
+        }
+
+        // user might have jumped from preview to one of these files in the meantime
+        let to_close: Vec<SPath> = opened_for_preview
+            .into_iter()
+            .filter(|path| self.get_editor_idx_for(path).is_none())
+            .collect();
+
+        let mut buffer_register = unpack_or_e!(
+            self.providers.buffer_register().write().ok(),
+            false,
+            "failed to lock buffer register"
+        );
+
+        for path in to_close.iter() {
+            if let Some(id) = buffer_register.get_id_from_path(path) {
+                if !buffer_register.close_buffer(&id) {
+                    error!("failed to close buffer {} opened for preview", path);
+                }
+            }
+        }
+
+        buffer_register.apply_workspace_edit(&self.providers, &file_edits)
+    }
+
+    fn apply_code_action(&mut self, path: &SPath, action: NavCompCodeAction) -> bool {
+        let mut success = true;
+
+        if let Some(edit) = action.edit {
+            if !self.apply_navcomp_workspace_edit(edit) {
+                success = false;
+            }
+        }
+
+        // as LSP spec says, command goes after the edit
+        if let Some(command) = action.command {
+            let navcomp_group = unpack_or_e!(
+                self.providers.navcomp_group().try_read().ok(),
+                false,
+                "failed to lock navcomp group"
+            );
+            let navcomp = unpack_or_e!(navcomp_group.get_navcomp_for(path), false, "no navcomp for {}", path);
+
+            if !navcomp.execute_command(&command) {
+                debug!("command {} was not executed", command.title);
+            }
+        }
+
+        success
+    }
+
+    // Edits of files outside of workspace are skipped, and then result is false.
+    fn apply_navcomp_workspace_edit(&self, edit: NavCompWorkspaceEdit) -> bool {
+        let mut success = true;
+
+        let mut file_edits: Vec<(SPath, Vec<StupidSubstituteMessage>)> = Vec::new();
+        for (url, edits) in edit.file_edits {
+            match url_to_spath(self.providers.fsf(), &url) {
+                Some(spath) => file_edits.push((spath, edits)),
+                None => {
+                    error!("workspace edit touches file outside of workspace: {}", url);
+                    success = false;
+                }
+            }
+        }
+
+        let mut buffer_register = unpack_or_e!(
+            self.providers.buffer_register().write().ok(),
+            false,
+            "failed to lock buffer register"
+        );
+
+        if !buffer_register.apply_workspace_edit(&self.providers, &file_edits) {
+            success = false;
+        }
+
+        success
+    }
+
+    /*
+    Navcomps can ask for edits on their own (LSP servers do that when executing commands). These are
+    applied without preview, and navcomp is told whether it worked.
+     */
+    fn apply_navcomp_edit_requests(&mut self) {
+        let requests: Vec<NavCompApplyEditRequest> = match self.providers.navcomp_group().try_read() {
+            Ok(navcomp_group) => navcomp_group
+                .navcomps()
+                .iter()
+                .flat_map(|navcomp| navcomp.take_apply_edit_requests())
+                .collect(),
+            // requests will wait for the next frame
+            Err(_) => return,
+        };
+
+        for request in requests {
+            debug!("applying edit requested by navcomp: {:?}", request);
+            let applied = self.apply_navcomp_workspace_edit(request.edit.clone());
+            request.respond(applied);
+        }
+    }
+
     fn do_next_display(&mut self) -> bool {
         if self.hover.is_some() {
             return false;
@@ -720,8 +1255,21 @@ impl Widget for MainView {
     }
 
     fn prelayout(&mut self) {
+        self.apply_navcomp_edit_requests();
         self.complex_prelayout();
+        self.refresh_main_context_menu();
 
+        // if locks are taken, status bar just shows what it showed last time
+        if let Ok(navcomp_group) = self.providers.navcomp_group().try_read() {
+            if let Ok(health) = navcomp_group.health().try_read() {
+                self.status_bar.set_navcomp_health(&health);
+            }
+
+            let progress: Vec<NavCompProgress> = navcomp_group.navcomps().iter().flat_map(|navcomp| navcomp.progress()).collect();
+            self.navcomp_progress = util::progress_description(&progress);
+        }
+
+        // goes after progress, since it's part of my description
         let focus_path = get_focus_path(self);
         self.status_bar.set_focus_path(focus_path);
     }
@@ -747,6 +1295,9 @@ impl Widget for MainView {
             InputEvent::KeyInput(key) if key == config.keyboard_config.global.quit => MainViewMsg::QuitGladius.someboxed(),
             InputEvent::KeyInput(key) if key == config.keyboard_config.global.new_buffer => MainViewMsg::OpenNewFile.someboxed(),
             InputEvent::KeyInput(key) if key == config.keyboard_config.global.fuzzy_file => MainViewMsg::OpenFuzzyFiles.someboxed(),
+            InputEvent::KeyInput(key) if key == config.keyboard_config.global.workspace_symbol => {
+                MainViewMsg::OpenWorkspaceSymbols.someboxed()
+            }
             InputEvent::KeyInput(key) if key == config.keyboard_config.global.browse_buffers => {
                 if self.displays.is_empty() {
                     debug!("ignoring browse_buffers request - no displays open.");
@@ -759,6 +1310,14 @@ impl Widget for MainView {
             InputEvent::KeyInput(key) if key == config.keyboard_config.global.next_display => MainViewMsg::NextDisplay.someboxed(),
             InputEvent::KeyInput(key) if key == config.keyboard_config.global.prev_display => MainViewMsg::PrevDisplay.someboxed(),
             InputEvent::KeyInput(key) if key == config.keyboard_config.global.close_buffer => MainViewMsg::CloseBuffer.someboxed(),
+            InputEvent::KeyInput(key) if key == config.keyboard_config.global.run_task => {
+                if self.tasks.is_empty() {
+                    debug!("ignoring run_task request - no tasks known.");
+                    None
+                } else {
+                    MainViewMsg::OpenTasks.someboxed()
+                }
+            }
             InputEvent::EverythingBarTrigger => MainViewMsg::OpenContextMenu.someboxed(),
             // InputEvent::KeyInput(key) if key == config.keyboard_config.global.everything_bar => MainViewMsg::OpenContextMenu.someboxed(),
             _ => {
@@ -792,6 +1351,10 @@ impl Widget for MainView {
                     self.open_fuzzy_search_in_files_and_focus();
                     None
                 }
+                MainViewMsg::OpenWorkspaceSymbols => {
+                    self.open_workspace_symbols_and_focus();
+                    None
+                }
                 MainViewMsg::CloseHover => {
                     if let Some(HoverItem::ContextMain { anchor, widget, old_focus }) = self.hover.take() {
                         self.set_focused(old_focus);
@@ -842,12 +1405,18 @@ impl Widget for MainView {
                     None
                 }
                 MainViewMsg::OpenFile { file, position_op } => {
+                    // in case it came from outline
+                    self.hover = None;
                     self.open_document_and_focus(file.clone(), position_op.clone());
                     None
                 }
-                MainViewMsg::OpenFileBySpath { spath } => {
+                MainViewMsg::OpenFileBySpath { spath, position_op } => {
                     self.hover = None;
-                    self.open_file_with_path_and_focus(spath.clone());
+                    if self.open_file_with_path_and_focus(spath.clone()) {
+                        if let Some(stupid_cursor) = position_op {
+                            self.set_stupid_cursor_in_focused_editor(*stupid_cursor);
+                        }
+                    }
                     None
                 }
                 MainViewMsg::GoToDefinition { promise_op } => {
@@ -866,6 +1435,68 @@ impl Widget for MainView {
                     }
                     None
                 }
+                MainViewMsg::RenamePreview { promise_op } => {
+                    if let Some(promise) = promise_op.take() {
+                        match self.create_new_display_for_code_results(Box::new(promise)) {
+                            Ok(idx) => {
+                                self.display_idx = idx;
+                                self.set_focus_to_default();
+                            }
+                            Err(_) => {
+                                error!("failed to open rename preview");
+                            }
+                        }
+                    } else {
+                        warn!("rename preview with empty promise")
+                    }
+                    None
+                }
+                MainViewMsg::ApplyWorkspaceEdit {
+                    file_edits,
+                    opened_for_preview,
+                } => {
+                    let file_edits = std::mem::take(file_edits);
+                    let opened_for_preview = std::mem::take(opened_for_preview);
+                    if !self.apply_workspace_edit(file_edits, opened_for_preview) {
+                        error!("failed to apply some of workspace edits");
+                    }
+                    None
+                }
+                MainViewMsg::ApplyCodeAction { path, action } => {
+                    if !self.apply_code_action(path, action.clone()) {
+                        error!("failed to apply code action {}", action.title);
+                    }
+                    None
+                }
+                MainViewMsg::OpenOutline { document, root } => {
+                    self.open_outline_and_focus(document.clone(), root.clone());
+                    None
+                }
+                MainViewMsg::OpenHierarchy { root } => {
+                    self.open_hierarchy_and_focus(root.clone());
+                    None
+                }
+                MainViewMsg::OpenHistory {
+                    document,
+                    root,
+                    current_node,
+                    widget_id,
+                } => {
+                    self.open_history_and_focus(document.clone(), root.clone(), *current_node, *widget_id);
+                    None
+                }
+                MainViewMsg::JumpToHistory {
+                    document,
+                    node_id,
+                    widget_id,
+                } => {
+                    self.hover = None;
+                    if !self.jump_to_history(document, *node_id, *widget_id) {
+                        error!("failed to jump to history node {} of {}", node_id, document);
+                    }
+                    self.set_focus_to_default();
+                    None
+                }
                 MainViewMsg::OpenFindInFiles => {
                     self.open_find_in_files();
                     None
@@ -897,6 +1528,17 @@ impl Widget for MainView {
                     self.do_prune_unchanged_buffers();
                     None
                 }
+                MainViewMsg::OpenTasks => {
+                    self.open_task_list_and_focus();
+                    None
+                }
+                MainViewMsg::RunTask { task_idx } => {
+                    self.hover = None;
+                    if !self.run_task_and_focus(*task_idx) {
+                        error!("failed to run task {}", task_idx);
+                    }
+                    None
+                }
                 MainViewMsg::NextDisplay => {
                     self.do_next_display();
                     None
@@ -909,6 +1551,16 @@ impl Widget for MainView {
                     self.do_close_buffer();
                     None
                 }
+                MainViewMsg::RestoreRecovered { idx } => {
+                    if !self.restore_recovered_and_focus(*idx) {
+                        error!("failed to restore recovered buffer {}", idx);
+                    }
+                    None
+                }
+                MainViewMsg::DiscardRecovered { idx } => {
+                    self.discard_recovered(*idx);
+                    None
+                }
                 MainViewMsg::QuitGladius => {
                     // TODO I should perhaps NOT close, if I cannot confirm that all files are saved? super unlikely
                     let unsaved = if let Some(br) = self.providers.buffer_register().try_read().ok() {
@@ -949,7 +1601,10 @@ impl Widget for MainView {
     }
 
     fn get_status_description(&self) -> Option<Cow<'_, str>> {
-        Some(Cow::Borrowed("gladius"))
+        match self.navcomp_progress.as_ref() {
+            Some(progress) => Some(Cow::Owned(format!("gladius ({})", progress))),
+            None => Some(Cow::Borrowed("gladius")),
+        }
     }
 
     fn get_widget_actions(&self) -> Option<ContextBarItem> {
@@ -1007,8 +1662,21 @@ impl Widget for MainView {
                 || MainViewMsg::OpenFuzzyFiles.boxed(),
                 Some(config.keyboard_config.global.fuzzy_file),
             ),
+            ContextBarItem::new_leaf_node(
+                Cow::Borrowed("go to symbol in workspace"),
+                || MainViewMsg::OpenWorkspaceSymbols.boxed(),
+                Some(config.keyboard_config.global.workspace_symbol),
+            ),
         ]);
 
+        if !self.tasks.is_empty() {
+            options.push(ContextBarItem::new_leaf_node(
+                Cow::Borrowed("run task"),
+                || MainViewMsg::OpenTasks.boxed(),
+                Some(config.keyboard_config.global.run_task),
+            ));
+        }
+
         Some(ContextBarItem::new_internal_node(Cow::Borrowed("gladius"), options))
     }
 
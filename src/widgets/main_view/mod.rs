@@ -6,7 +6,9 @@ pub mod fuzzy_screens_list_widget;
 mod main_context_menu;
 pub mod main_view;
 pub mod msg;
+pub mod task_list_widget;
 mod util;
+pub mod workspace_symbol_search_widget;
 
 #[cfg(test)]
 mod test;
@@ -1,9 +1,18 @@
+use std::rc::Rc;
+
 use crate::cursor::cursor::Cursor;
 use crate::experiments::focus_group::FocusUpdate;
 use crate::fs::path::SPath;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::w7e::navcomp_provider::{NavCompCodeAction, StupidSubstituteMessage};
 use crate::widget::any_msg::AnyMsg;
+use crate::widget::widget::WID;
+use crate::widgets::code_results_view::rename_code_results_provider::RenameCodeResultsProvider;
 use crate::widgets::code_results_view::stupid_symbol_usage_code_results_provider::StupidSymbolUsageCodeResultsProvider;
+use crate::widgets::hierarchy::hierarchy_item::HierarchyItem;
+use crate::widgets::history_browser::history_item::HistoryItem;
 use crate::widgets::main_view::main_view::DocumentIdentifier;
+use crate::widgets::outline::outline_item::OutlineItem;
 
 #[derive(Debug)]
 pub enum MainViewMsg {
@@ -25,6 +34,7 @@ pub enum MainViewMsg {
 
     // Open fuzzy files
     OpenFuzzyFiles,
+    OpenWorkspaceSymbols,
     OpenContextMenu,
     // depth describes depth of focus path. It's Option so I can "take"
     ContextMenuHit {
@@ -58,10 +68,12 @@ pub enum MainViewMsg {
     },
 
     /*
-    Opens file by path. Reopens existing buffer if it already exists.
+    Opens file by path. Reopens existing buffer if it already exists. Position is given as
+    StupidCursor, because it can't be converted before buffer is loaded.
      */
     OpenFileBySpath {
         spath: SPath,
+        position_op: Option<StupidCursor>,
     },
 
     BufferChangedName {
@@ -79,8 +91,62 @@ pub enum MainViewMsg {
         promise_op: Option<StupidSymbolUsageCodeResultsProvider>,
     },
 
+    RenamePreview {
+        promise_op: Option<RenameCodeResultsProvider>,
+    },
+    /*
+    Files that were opened only to display preview are closed before edits are applied, so they
+    get written to disk instead of becoming unsaved buffers.
+     */
+    ApplyWorkspaceEdit {
+        file_edits: Vec<(SPath, Vec<StupidSubstituteMessage>)>,
+        opened_for_preview: Vec<SPath>,
+    },
+
+    // path is of the file the action was requested for, it determines which navcomp runs the command.
+    ApplyCodeAction {
+        path: SPath,
+        action: NavCompCodeAction,
+    },
+
+    OpenOutline {
+        document: DocumentIdentifier,
+        root: Rc<OutlineItem>,
+    },
+
+    OpenHierarchy {
+        root: Rc<HierarchyItem>,
+    },
+
+    // widget_id is of the EditorWidget that asked, it gets a cursor set in the state we jump to.
+    OpenHistory {
+        document: DocumentIdentifier,
+        root: Rc<HistoryItem>,
+        current_node: usize,
+        widget_id: WID,
+    },
+    JumpToHistory {
+        document: DocumentIdentifier,
+        node_id: usize,
+        widget_id: WID,
+    },
+
     PruneUnchangedBuffers,
 
+    OpenTasks,
+    // index in MainView's task list
+    RunTask {
+        task_idx: usize,
+    },
+
+    // index in MainView's list of buffers recovered after crash
+    RestoreRecovered {
+        idx: usize,
+    },
+    DiscardRecovered {
+        idx: usize,
+    },
+
     QuitGladius,
     QuitGladiusConfirmed,
 }
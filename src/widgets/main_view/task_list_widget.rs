@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::w7e::task::Task;
+use crate::widgets::context_menu::widget::ContextMenuWidget;
+
+/*
+Flat list of tasks to choose from. Ids of tasks are their indices in MainView's task list, root
+gets id equal to number of tasks.
+ */
+#[derive(Debug, Clone)]
+pub enum TaskListItem {
+    Root { id: usize, items: Vec<Arc<TaskListItem>> },
+    Task { idx: usize, label: String },
+}
+
+pub type TaskListTreeNode = Arc<TaskListItem>;
+
+impl TreeNode<usize> for TaskListTreeNode {
+    fn id(&self) -> &usize {
+        match self.as_ref() {
+            TaskListItem::Root { id, .. } => id,
+            TaskListItem::Task { idx, .. } => idx,
+        }
+    }
+
+    fn label(&self) -> Cow<'_, str> {
+        match self.as_ref() {
+            TaskListItem::Root { .. } => Cow::Borrowed("tasks"),
+            TaskListItem::Task { label, .. } => Cow::Borrowed(label.as_str()),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        matches!(self.as_ref(), TaskListItem::Task { .. })
+    }
+
+    fn child_iter(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        match self.as_ref() {
+            TaskListItem::Root { items, .. } => Box::new(items.clone().into_iter()),
+            TaskListItem::Task { .. } => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
+
+pub type TaskListWidget = ContextMenuWidget<usize, TaskListTreeNode>;
+
+pub fn get_task_list(tasks: &[Task]) -> TaskListTreeNode {
+    let items: Vec<Arc<TaskListItem>> = tasks
+        .iter()
+        .enumerate()
+        .map(|(idx, task)| {
+            // custom tasks are known by name, so command line is worth showing too
+            let command_line = task.command_line();
+            let label = if task.name == command_line {
+                command_line
+            } else {
+                format!("{} ({})", task.name, command_line)
+            };
+
+            Arc::new(TaskListItem::Task { idx, label })
+        })
+        .collect();
+
+    Arc::new(TaskListItem::Root { id: tasks.len(), items })
+}
@@ -1,5 +1,6 @@
 use crate::primitives::xy::XY;
-use crate::widgets::main_view::util::get_rect_for_context_menu;
+use crate::w7e::navcomp_provider::NavCompProgress;
+use crate::widgets::main_view::util::{get_rect_for_context_menu, progress_description};
 
 #[test]
 fn util_get_rect_for_context_menu_test_1() {
@@ -25,3 +26,22 @@ fn util_get_rect_for_context_menu_test_1() {
         }
     }
 }
+
+#[test]
+fn util_progress_description_test() {
+    let indexing = NavCompProgress {
+        title: "Indexing".to_string(),
+        message: Some("47/100 (core)".to_string()),
+        percentage: Some(47),
+    };
+    let fetching = NavCompProgress {
+        title: "Fetching".to_string(),
+        message: None,
+        percentage: None,
+    };
+
+    assert_eq!(progress_description(&[]), None);
+    assert_eq!(progress_description(&[indexing.clone()]), Some("indexing 47%".to_string()));
+    assert_eq!(progress_description(&[fetching.clone()]), Some("fetching".to_string()));
+    assert_eq!(progress_description(&[indexing, fetching]), Some("indexing 47% (+1)".to_string()));
+}
@@ -3,6 +3,7 @@ use log::warn;
 use crate::primitives::rect::Rect;
 use crate::primitives::xy::XY;
 use crate::unpack_or_e;
+use crate::w7e::navcomp_provider::NavCompProgress;
 use crate::widget::widget::Widget;
 
 pub fn get_focus_path(root: &dyn Widget) -> Vec<String> {
@@ -23,6 +24,24 @@ pub fn get_focus_path(root: &dyn Widget) -> Vec<String> {
     result
 }
 
+/*
+Compact, to fit in status bar: title of the first progress, with percentage if known, like
+"indexing 47%". Other ones are just counted.
+ */
+pub fn progress_description(progress: &[NavCompProgress]) -> Option<String> {
+    let first = progress.first()?;
+
+    let mut result = first.title.to_lowercase();
+    if let Some(percentage) = first.percentage {
+        result += &format!(" {}%", percentage);
+    }
+    if progress.len() > 1 {
+        result += &format!(" (+{})", progress.len() - 1);
+    }
+
+    Some(result)
+}
+
 pub(crate) fn get_rect_for_context_menu(parent_size: XY, pos: XY) -> Option<Rect> {
     if !(pos < parent_size) {
         return None;
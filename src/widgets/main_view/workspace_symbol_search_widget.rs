@@ -0,0 +1,209 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use log::{debug, error};
+
+use crate::fs::fsf_ref::FsfRef;
+use crate::fs::path::SPath;
+use crate::primitives::stupid_cursor::StupidCursor;
+use crate::primitives::tree::filter_policy::FilterPolicy;
+use crate::primitives::tree::tree_node::{FilterRef, TreeNode};
+use crate::promise::joined_promises::JoinedPromises;
+use crate::promise::streaming_promise::{StreamingPromise, StreamingPromiseState, UpdateResult};
+use crate::w7e::navcomp_group::NavCompGroup;
+use crate::w7e::navcomp_provider::{NavCompWorkspaceSymbol, WorkspaceSymbolsPromise};
+use crate::widgets::code_results_view::stupid_symbol_usage_code_results_provider::url_to_spath;
+use crate::widgets::context_menu::widget::ContextMenuWidget;
+
+pub type WorkspaceSymbolSearchWidget = ContextMenuWidget<usize, Rc<WorkspaceSymbolItem>>;
+
+/*
+Root of this tree has no children of its own. Whenever query changes, it asks all navcomps for
+symbols and streams back whatever they answer, so a slow server only delays its own results.
+ */
+pub struct WorkspaceSymbolItem {
+    id: usize,
+    label: String,
+    kind: WorkspaceSymbolItemKind,
+}
+
+enum WorkspaceSymbolItemKind {
+    Root {
+        navcomp_group: Arc<RwLock<NavCompGroup>>,
+        fsf: FsfRef,
+    },
+    Symbol {
+        spath: SPath,
+        stupid_cursor: StupidCursor,
+    },
+}
+
+impl WorkspaceSymbolItem {
+    pub fn new_root(navcomp_group: Arc<RwLock<NavCompGroup>>, fsf: FsfRef) -> Rc<WorkspaceSymbolItem> {
+        Rc::new(WorkspaceSymbolItem {
+            id: 0,
+            label: "workspace symbols".to_string(),
+            kind: WorkspaceSymbolItemKind::Root { navcomp_group, fsf },
+        })
+    }
+
+    // Symbols outside of workspace (like ones from standard library) are skipped, I can't open them anyway.
+    fn from_symbol(id: usize, fsf: &FsfRef, symbol: &NavCompWorkspaceSymbol) -> Option<Rc<WorkspaceSymbolItem>> {
+        let spath = url_to_spath(fsf, &symbol.location.path)?;
+
+        let label = match symbol.container_name_op.as_ref() {
+            Some(container) => format!("{}: {} in {} ({})", symbol.name, symbol.symbol_type.description(), container, spath),
+            None => format!("{}: {} ({})", symbol.name, symbol.symbol_type.description(), spath),
+        };
+
+        Some(Rc::new(WorkspaceSymbolItem {
+            id,
+            label,
+            kind: WorkspaceSymbolItemKind::Symbol {
+                spath,
+                stupid_cursor: symbol.location.stupid_range.0,
+            },
+        }))
+    }
+
+    // None for root
+    pub fn location(&self) -> Option<(SPath, StupidCursor)> {
+        match &self.kind {
+            WorkspaceSymbolItemKind::Root { .. } => None,
+            WorkspaceSymbolItemKind::Symbol { spath, stupid_cursor } => Some((spath.clone(), *stupid_cursor)),
+        }
+    }
+}
+
+impl Debug for WorkspaceSymbolItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[WorkspaceSymbolItem {} \"{}\"]", self.id, self.label)
+    }
+}
+
+impl TreeNode<usize> for Rc<WorkspaceSymbolItem> {
+    fn id(&self) -> &usize {
+        &self.id
+    }
+
+    fn label(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    fn is_leaf(&self) -> bool {
+        match self.kind {
+            WorkspaceSymbolItemKind::Root { .. } => false,
+            WorkspaceSymbolItemKind::Symbol { .. } => true,
+        }
+    }
+
+    fn child_iter(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(std::iter::empty())
+    }
+
+    fn is_complete(&self) -> bool {
+        true
+    }
+
+    /*
+    Results are not filtered locally, navcomps do their own matching and it does not have to agree
+    with fuzzy search (case-insensitivity, matching container names etc.).
+     */
+    fn get_streaming_promise_instead_of_iterator(
+        &self,
+        filter_op: Option<(FilterRef<Self>, FilterPolicy)>,
+        _expanded_op: Option<HashSet<usize>>,
+    ) -> Option<Box<dyn StreamingPromise<(u16, Self)>>> {
+        let (navcomp_group, fsf) = match &self.kind {
+            WorkspaceSymbolItemKind::Root { navcomp_group, fsf } => (navcomp_group, fsf),
+            WorkspaceSymbolItemKind::Symbol { .. } => return None,
+        };
+
+        let query = filter_op.as_ref().and_then(|(filter, _)| filter.query()).unwrap_or("");
+
+        let mut promises: Vec<WorkspaceSymbolsPromise> = Vec::new();
+        if !query.is_empty() {
+            let navcomps = match navcomp_group.try_read() {
+                Ok(lock) => lock.navcomps(),
+                Err(e) => {
+                    error!("failed to lock navcomp group: {:?}", e);
+                    vec![]
+                }
+            };
+
+            for navcomp in navcomps {
+                match navcomp.workspace_symbols(query) {
+                    Some(promise) => promises.push(promise),
+                    None => debug!("navcomp {:?} did not answer workspace symbol query", navcomp),
+                }
+            }
+        }
+
+        Some(Box::new(WorkspaceSymbolStream::new(self.clone(), fsf.clone(), promises)))
+    }
+}
+
+/*
+Translates symbols to items as they come. Root goes first, so the list is never empty.
+ */
+struct WorkspaceSymbolStream {
+    fsf: FsfRef,
+    symbols: JoinedPromises<NavCompWorkspaceSymbol>,
+    symbols_translated: usize,
+    cached: Vec<(u16, Rc<WorkspaceSymbolItem>)>,
+}
+
+impl WorkspaceSymbolStream {
+    fn new(root: Rc<WorkspaceSymbolItem>, fsf: FsfRef, promises: Vec<WorkspaceSymbolsPromise>) -> Self {
+        let mut res = WorkspaceSymbolStream {
+            fsf,
+            symbols: JoinedPromises::new(promises),
+            symbols_translated: 0,
+            cached: vec![(0, root)],
+        };
+
+        res.translate_new_symbols();
+        res
+    }
+
+    // returns whether anything was added
+    fn translate_new_symbols(&mut self) -> bool {
+        let old_len = self.cached.len();
+
+        for symbol in self.symbols.read().iter().skip(self.symbols_translated) {
+            if let Some(item) = WorkspaceSymbolItem::from_symbol(self.cached.len(), &self.fsf, symbol) {
+                self.cached.push((1, item));
+            }
+        }
+        self.symbols_translated = self.symbols.read().len();
+
+        old_len != self.cached.len()
+    }
+}
+
+impl StreamingPromise<(u16, Rc<WorkspaceSymbolItem>)> for WorkspaceSymbolStream {
+    fn state(&self) -> StreamingPromiseState {
+        self.symbols.state()
+    }
+
+    fn drain(&mut self, how_long: Option<Duration>) -> StreamingPromiseState {
+        let state = self.symbols.drain(how_long);
+        self.translate_new_symbols();
+        state
+    }
+
+    fn update(&mut self) -> UpdateResult {
+        let state = self.symbols.update().state;
+        let has_changed = self.translate_new_symbols();
+
+        UpdateResult { state, has_changed }
+    }
+
+    fn read(&self) -> &Vec<(u16, Rc<WorkspaceSymbolItem>)> {
+        &self.cached
+    }
+}
@@ -12,12 +12,17 @@ pub mod editor_widget;
 pub mod file_tree_view;
 
 pub mod generic_dialog;
+pub mod hierarchy;
+pub mod history_browser;
 pub mod list_widget;
 pub mod main_view;
 pub mod no_editor;
+pub mod outline;
+pub mod recovery_view;
 pub mod save_file_dialog;
 pub mod spath_list_widget_item;
 pub mod spath_tree_view_node;
+pub mod task_view;
 pub mod text_widget;
 pub mod tree_view;
 pub mod with_scroll;
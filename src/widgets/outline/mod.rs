@@ -0,0 +1 @@
+pub mod outline_item;
@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use log::debug;
+
+use crate::cursor::cursor::Cursor;
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::text::text_buffer::TextBuffer;
+use crate::w7e::navcomp_provider::NavCompDocumentSymbol;
+use crate::widgets::context_menu::widget::ContextMenuWidget;
+
+pub type OutlineWidget = ContextMenuWidget<usize, Rc<OutlineItem>>;
+
+/*
+A node of file outline. Positions are converted to real cursors upfront, so hitting an item does
+not require access to the buffer.
+ */
+#[derive(Debug)]
+pub struct OutlineItem {
+    id: usize,
+    label: String,
+    // None for root
+    cursor_op: Option<Cursor>,
+    children: Vec<Rc<OutlineItem>>,
+}
+
+impl OutlineItem {
+    pub fn new_root(title: String, symbols: &[NavCompDocumentSymbol], buffer: &dyn TextBuffer) -> Rc<OutlineItem> {
+        let mut next_id: usize = 1;
+
+        Rc::new(OutlineItem {
+            id: 0,
+            label: title,
+            cursor_op: None,
+            children: Self::from_symbols(symbols, buffer, &mut next_id),
+        })
+    }
+
+    fn from_symbols(symbols: &[NavCompDocumentSymbol], buffer: &dyn TextBuffer, next_id: &mut usize) -> Vec<Rc<OutlineItem>> {
+        let mut result: Vec<Rc<OutlineItem>> = Vec::with_capacity(symbols.len());
+
+        for symbol in symbols {
+            let cursor_op = symbol.name_stupid_range.0.to_real_cursor(buffer);
+            if cursor_op.is_none() {
+                debug!("symbol {} is outside of buffer, outline is probably outdated", symbol.name);
+            }
+
+            let id = *next_id;
+            *next_id += 1;
+
+            result.push(Rc::new(OutlineItem {
+                id,
+                label: format!("{}: {}", symbol.name, symbol.symbol_type.description()),
+                cursor_op,
+                children: Self::from_symbols(&symbol.children, buffer, next_id),
+            }));
+        }
+
+        result
+    }
+
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.cursor_op
+    }
+}
+
+impl TreeNode<usize> for Rc<OutlineItem> {
+    fn id(&self) -> &usize {
+        &self.id
+    }
+
+    fn label(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn child_iter(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(self.children.iter().cloned())
+    }
+
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
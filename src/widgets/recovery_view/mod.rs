@@ -0,0 +1,4 @@
+pub mod msg;
+pub mod recovered_buffer;
+pub mod recovery_item;
+pub mod recovery_view_widget;
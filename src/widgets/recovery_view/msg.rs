@@ -0,0 +1,11 @@
+use std::fmt::Debug;
+
+use crate::widget::any_msg::AnyMsg;
+
+#[derive(Debug, Clone)]
+pub enum RecoveryViewMsg {
+    HighlightChanged,
+    Discard,
+}
+
+impl AnyMsg for RecoveryViewMsg {}
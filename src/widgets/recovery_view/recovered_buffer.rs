@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+
+use log::{debug, error};
+use ropey::Rope;
+
+use crate::fs::path::SPath;
+use crate::gladius::providers::Providers;
+use crate::text::recovery;
+use crate::text::recovery::{RecoveryEntry, RecoverySession};
+
+/*
+A buffer found in recovery folder, that is worth offering back: its text is not what's on disk.
+ */
+#[derive(Clone, Debug)]
+pub struct RecoveredBuffer {
+    pub entry: RecoveryEntry,
+    // session folder the entry was found in
+    pub dir: PathBuf,
+    // None for buffers that were never saved
+    pub spath: Option<SPath>,
+    // None if file was never saved, or it's gone
+    pub disk_rope_op: Option<Rope>,
+}
+
+impl RecoveredBuffer {
+    /*
+    Reads what Autosaver left in given sessions and keeps entries worth restoring: never saved buffers
+    with any text in them, and files that differ from what's on disk now. Entries that match disk are
+    removed right away, there's nothing to recover there.
+     */
+    pub fn find_all(providers: &Providers, sessions: &[RecoverySession]) -> Vec<RecoveredBuffer> {
+        sessions
+            .iter()
+            .flat_map(|session| Self::find_in(providers, session.dir()))
+            .collect()
+    }
+
+    fn find_in(providers: &Providers, dir: &Path) -> Vec<RecoveredBuffer> {
+        let entries = match recovery::load_all(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("failed to read recovery folder {:?}, because {}", dir, e);
+                return Vec::new();
+            }
+        };
+
+        let mut result: Vec<RecoveredBuffer> = Vec::new();
+
+        for entry in entries {
+            let (spath, disk_rope_op) = match entry.path.as_ref() {
+                None => (None, None),
+                Some(path) => {
+                    let spath = providers.fsf().descendant_unchecked(path);
+                    let disk_bytes_op = spath
+                        .as_ref()
+                        .and_then(|spath| providers.fsf().blocking_read_entire_file(spath).ok());
+
+                    if disk_bytes_op.as_deref() == Some(entry.text.as_bytes()) {
+                        debug!("recovered {:?} is the same as on disk, dropping it", path);
+                        if let Err(e) = recovery::remove(dir, &entry.buffer_id) {
+                            error!("failed to remove recovery entry {}, because {}", entry.buffer_id, e);
+                        }
+                        continue;
+                    }
+
+                    let disk_rope_op = disk_bytes_op.map(|bytes| Rope::from_str(&String::from_utf8_lossy(&bytes)));
+                    (spath, disk_rope_op)
+                }
+            };
+
+            if entry.path.is_none() && entry.text.is_empty() {
+                continue;
+            }
+
+            result.push(RecoveredBuffer {
+                entry,
+                dir: dir.to_path_buf(),
+                spath,
+                disk_rope_op,
+            });
+        }
+
+        result
+    }
+
+    pub fn label(&self) -> String {
+        match (self.entry.path.as_ref(), self.disk_rope_op.as_ref()) {
+            (Some(path), Some(_)) => format!("{} (modified)", path.to_string_lossy()),
+            (Some(path), None) => format!("{} (not on disk)", path.to_string_lossy()),
+            (None, _) => {
+                let first_line = self.entry.text.lines().next().unwrap_or("").trim();
+                format!("[unnamed] {}", first_line.chars().take(40).collect::<String>())
+            }
+        }
+    }
+}
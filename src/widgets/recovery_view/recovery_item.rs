@@ -0,0 +1,80 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use ropey::Rope;
+
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::widgets::history_browser::history_item::change_preview;
+use crate::widgets::recovery_view::recovered_buffer::RecoveredBuffer;
+
+/*
+Flat list of recovered buffers. Ids of items are their indices in MainView's list of recovered
+buffers, root gets id equal to number of them.
+ */
+#[derive(Debug)]
+pub struct RecoveryItem {
+    id: usize,
+    label: String,
+    // recovered text and what's on disk, for preview
+    ropes_op: Option<(Rope, Option<Rope>)>,
+    children: Vec<Rc<RecoveryItem>>,
+}
+
+impl RecoveryItem {
+    pub fn new_root(recovered: &[RecoveredBuffer]) -> Rc<RecoveryItem> {
+        let children = recovered
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| {
+                Rc::new(RecoveryItem {
+                    id: idx,
+                    label: item.label(),
+                    ropes_op: Some((Rope::from_str(&item.entry.text), item.disk_rope_op.clone())),
+                    children: Vec::new(),
+                })
+            })
+            .collect();
+
+        Rc::new(RecoveryItem {
+            id: recovered.len(),
+            label: "unsaved buffers left by previous session (enter: restore, delete: discard)".to_string(),
+            ropes_op: None,
+            children,
+        })
+    }
+
+    // None for root
+    pub fn recovered_idx(&self) -> Option<usize> {
+        self.ropes_op.as_ref().map(|_| self.id)
+    }
+
+    // lines around the first one that differs from disk
+    pub fn preview(&self, max_lines: usize) -> String {
+        match self.ropes_op.as_ref() {
+            Some((rope, disk_rope_op)) => change_preview(rope, disk_rope_op.as_ref(), max_lines),
+            None => String::new(),
+        }
+    }
+}
+
+impl TreeNode<usize> for Rc<RecoveryItem> {
+    fn id(&self) -> &usize {
+        &self.id
+    }
+
+    fn label(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.ropes_op.is_some()
+    }
+
+    fn child_iter(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(self.children.iter().cloned())
+    }
+
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,225 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use log::{debug, warn};
+
+use crate::config::theme::Theme;
+use crate::experiments::screenspace::Screenspace;
+use crate::experiments::subwidget_pointer::SubwidgetPointer;
+use crate::io::input_event::InputEvent;
+use crate::io::keys::Keycode;
+use crate::io::output::Output;
+use crate::layout::layout::{Layout, LayoutResult};
+use crate::layout::leaf_layout::LeafLayout;
+use crate::layout::split_layout::{SplitDirection, SplitLayout, SplitRule};
+use crate::primitives::scroll::ScrollDirection;
+use crate::primitives::tree::tree_node::TreeNode;
+use crate::primitives::xy::XY;
+use crate::widget::any_msg::{AnyMsg, AsAny};
+use crate::widget::combined_widget::CombinedWidget;
+use crate::widget::fill_policy::SizePolicy;
+use crate::widget::widget::{get_new_widget_id, Widget, WidgetAction, WID};
+use crate::widgets::recovery_view::msg::RecoveryViewMsg;
+use crate::widgets::recovery_view::recovered_buffer::RecoveredBuffer;
+use crate::widgets::recovery_view::recovery_item::RecoveryItem;
+use crate::widgets::text_widget::TextWidget;
+use crate::widgets::tree_view::tree_view::TreeViewWidget;
+use crate::widgets::with_scroll::with_scroll::WithScroll;
+use crate::{subwidget, unpack_unit_e};
+
+pub const RECOVERY_VIEW_WIDGET_NAME: &str = "recovery_view";
+
+pub type RecoveryTreeView = TreeViewWidget<usize, Rc<RecoveryItem>>;
+
+/*
+Lists buffers recovered after a crash, next to a preview of where the highlighted one differs from
+disk. It's a display, not a hover, so one can look around before deciding. Enter restores the buffer,
+Delete discards it (what exactly that means is up to on_hit and on_discard).
+ */
+pub struct RecoveryView {
+    id: WID,
+
+    tree_view: WithScroll<RecoveryTreeView>,
+    preview: TextWidget,
+
+    layout_res: Option<LayoutResult<Self>>,
+
+    on_discard: Option<WidgetAction<RecoveryView>>,
+}
+
+impl RecoveryView {
+    const PREVIEW_LINES: usize = 40;
+
+    pub fn new(recovered: &[RecoveredBuffer]) -> Self {
+        let mut tree_view = TreeViewWidget::new(RecoveryItem::new_root(recovered))
+            .with_size_policy(SizePolicy::MATCH_LAYOUT)
+            .with_on_highlighted_changed(Box::new(|_| RecoveryViewMsg::HighlightChanged.someboxed()));
+
+        tree_view.expand_root();
+        tree_view.set_selected(&0);
+
+        let mut res = RecoveryView {
+            id: get_new_widget_id(),
+            tree_view: WithScroll::new(ScrollDirection::Both, tree_view),
+            preview: TextWidget::new(Box::new(String::new())).with_size_policy(SizePolicy::MATCH_LAYOUT),
+            layout_res: None,
+            on_discard: None,
+        };
+
+        res.update_preview();
+        res
+    }
+
+    pub fn with_on_hit(mut self, on_hit: WidgetAction<RecoveryTreeView>) -> Self {
+        self.tree_view.internal_mut().set_on_hit_op(Some(on_hit));
+        self
+    }
+
+    pub fn with_on_discard(self, on_discard: WidgetAction<RecoveryView>) -> Self {
+        Self {
+            on_discard: Some(on_discard),
+            ..self
+        }
+    }
+
+    // index of highlighted recovered buffer, None if it's the root
+    pub fn highlighted(&self) -> Option<usize> {
+        self.tree_view.internal().get_highlighted().1.recovered_idx()
+    }
+
+    pub fn get_description(&self) -> String {
+        let count = self.tree_view.internal().get_root_node().child_iter().count();
+        format!("{} recovered buffers", count)
+    }
+
+    fn update_preview(&mut self) {
+        let preview = self.tree_view.internal().get_highlighted().1.preview(Self::PREVIEW_LINES);
+        self.preview.set_text(Box::new(preview));
+    }
+}
+
+impl Widget for RecoveryView {
+    fn id(&self) -> WID {
+        self.id
+    }
+
+    fn static_typename() -> &'static str
+    where
+        Self: Sized,
+    {
+        RECOVERY_VIEW_WIDGET_NAME
+    }
+
+    fn typename(&self) -> &'static str {
+        RECOVERY_VIEW_WIDGET_NAME
+    }
+
+    fn prelayout(&mut self) {
+        self.combined_prelayout()
+    }
+
+    fn full_size(&self) -> XY {
+        let tree_size = self.tree_view.full_size();
+        let preview_size = self.preview.full_size();
+
+        XY::new(tree_size.x + preview_size.x, tree_size.y.max(preview_size.y))
+    }
+
+    fn size_policy(&self) -> SizePolicy {
+        SizePolicy::MATCH_LAYOUT
+    }
+
+    fn layout(&mut self, screenspace: Screenspace) {
+        self.combined_layout(screenspace);
+    }
+
+    fn on_input(&self, input_event: InputEvent) -> Option<Box<dyn AnyMsg>> {
+        match input_event {
+            InputEvent::KeyInput(key) if key == Keycode::Delete.to_key() && self.highlighted().is_some() => {
+                RecoveryViewMsg::Discard.someboxed()
+            }
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, msg: Box<dyn AnyMsg>) -> Option<Box<dyn AnyMsg>> {
+        let our_msg = msg.as_msg::<RecoveryViewMsg>();
+        if our_msg.is_none() {
+            debug!("expecetd RecoveryViewMsg, got {:?}, passing through", msg);
+            return Some(msg);
+        }
+
+        match our_msg.unwrap() {
+            RecoveryViewMsg::HighlightChanged => {
+                self.update_preview();
+                None
+            }
+            RecoveryViewMsg::Discard => {
+                if let Some(on_discard) = self.on_discard.as_ref() {
+                    on_discard(self)
+                } else {
+                    warn!("received discard message, but no on_discard is defined");
+                    None
+                }
+            }
+        }
+    }
+
+    fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        let _size = unpack_unit_e!(self.get_layout_res().map(|lr| lr.total_size), "render before layout",);
+
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            output.emit_metadata(crate::io::output::Metadata {
+                id: self.id(),
+                typename: RECOVERY_VIEW_WIDGET_NAME.to_string(),
+                rect: crate::primitives::rect::Rect::from_zero(_size),
+                focused,
+            });
+        }
+
+        self.combined_render(theme, focused, output)
+    }
+
+    // everything goes to the list, preview is not interactive
+    fn act_on(&mut self, input_event: InputEvent) -> (bool, Option<Box<dyn AnyMsg>>) {
+        let mut act_result = self.tree_view.act_on(input_event);
+
+        if !act_result.0 {
+            if let Some(msg_to_self) = self.on_input(input_event) {
+                act_result = (true, Some(msg_to_self));
+            }
+        }
+
+        if let Some(msg_to_myself) = act_result.1 {
+            (true, self.update(msg_to_myself))
+        } else {
+            act_result
+        }
+    }
+
+    fn get_status_description(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed("recovered buffers"))
+    }
+}
+
+impl CombinedWidget for RecoveryView {
+    fn get_layout(&self) -> Box<dyn Layout<Self>> {
+        SplitLayout::new(SplitDirection::Horizontal)
+            .with(SplitRule::Proportional(1.0f32), LeafLayout::new(subwidget!(Self.tree_view)).boxed())
+            .with(SplitRule::Proportional(1.0f32), LeafLayout::new(subwidget!(Self.preview)).boxed())
+            .boxed()
+    }
+
+    fn save_layout_res(&mut self, result: LayoutResult<Self>) {
+        self.layout_res = Some(result);
+    }
+
+    fn get_layout_res(&self) -> Option<&LayoutResult<Self>> {
+        self.layout_res.as_ref()
+    }
+
+    fn get_subwidgets_for_input(&self) -> impl Iterator<Item = SubwidgetPointer<Self>> {
+        [subwidget!(Self.tree_view)].into_iter()
+    }
+}
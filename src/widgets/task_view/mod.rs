@@ -0,0 +1,7 @@
+/*
+Full-page (or editor-size) view of a running or finished task: status line on top, output below,
+following the end of output unless user scrolled up.
+ */
+
+pub mod task_view_msg;
+pub mod task_view_widget;
@@ -0,0 +1,15 @@
+use crate::widget::any_msg::AnyMsg;
+
+#[derive(Debug)]
+pub enum TaskViewMsg {
+    Cancel,
+    Rerun,
+    // in lines
+    ScrollUp(usize),
+    ScrollDown(usize),
+    ScrollToStart,
+    // and keep following new output
+    ScrollToEnd,
+}
+
+impl AnyMsg for TaskViewMsg {}
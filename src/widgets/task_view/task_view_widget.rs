@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+
+use log::{debug, error, warn};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::theme::Theme;
+use crate::experiments::screenspace::Screenspace;
+use crate::io::input_event::InputEvent;
+use crate::io::keys::Keycode;
+use crate::io::output::Output;
+use crate::io::style::TextStyle;
+use crate::primitives::helpers::fill_output;
+use crate::primitives::xy::XY;
+use crate::unpack_unit_e;
+use crate::w7e::task::Task;
+use crate::w7e::task_run::{TaskRun, TaskTickSender};
+use crate::widget::any_msg::{AnyMsg, AsAny};
+use crate::widget::context_bar_item::ContextBarItem;
+use crate::widget::fill_policy::SizePolicy;
+use crate::widget::widget::{get_new_widget_id, Widget, WID};
+use crate::widgets::task_view::task_view_msg::TaskViewMsg;
+
+pub struct TaskView {
+    wid: WID,
+
+    run: TaskRun,
+    // kept for rerun
+    tick_sender: TaskTickSender,
+
+    // index of first visible output line, None means "follow the end"
+    scroll: Option<usize>,
+
+    last_size: Option<XY>,
+}
+
+impl TaskView {
+    pub const TYPENAME: &'static str = "task_view";
+    pub const MIN_SIZE: XY = XY::new(20, 3);
+    pub const CANCEL_HINT: &'static str = " (ESC to cancel)";
+
+    pub fn new(task: Task, tick_sender: TaskTickSender) -> Self {
+        TaskView {
+            wid: get_new_widget_id(),
+            run: TaskRun::start(task, tick_sender.clone()),
+            tick_sender,
+            scroll: None,
+            last_size: None,
+        }
+    }
+
+    pub fn task(&self) -> &Task {
+        self.run.task()
+    }
+
+    pub fn run(&self) -> &TaskRun {
+        &self.run
+    }
+
+    pub fn get_description(&self) -> String {
+        let status = self.run.status();
+        let hint = if status.is_running() { Self::CANCEL_HINT } else { "" };
+        format!("{}: {}{}", self.task().name, status, hint)
+    }
+
+    // old run is dropped, and with it killed if it was still running
+    pub fn rerun(&mut self) {
+        debug!("rerunning task [{}]", self.task().name);
+        let task = self.task().clone();
+        self.run = TaskRun::start(task, self.tick_sender.clone());
+        self.scroll = None;
+    }
+
+    fn page_height(&self) -> usize {
+        // one line goes to the header
+        self.last_size.map(|size| size.y.saturating_sub(1) as usize).unwrap_or(1).max(1)
+    }
+
+    fn lines_count(&self) -> usize {
+        match self.run.output().read() {
+            Ok(lock) => lock.lines.len(),
+            Err(e) => {
+                error!("failed acquiring output lock: {:?}", e);
+                0
+            }
+        }
+    }
+
+    // first line to show when following the end
+    fn tail_start(&self) -> usize {
+        self.lines_count().saturating_sub(self.page_height())
+    }
+
+    fn first_visible_line(&self) -> usize {
+        self.scroll.unwrap_or_else(|| self.tail_start())
+    }
+
+    fn scroll_to(&mut self, first_line: usize) {
+        let tail_start = self.tail_start();
+        self.scroll = if first_line >= tail_start { None } else { Some(first_line) };
+    }
+}
+
+impl Widget for TaskView {
+    fn id(&self) -> WID {
+        self.wid
+    }
+
+    fn static_typename() -> &'static str
+    where
+        Self: Sized,
+    {
+        Self::TYPENAME
+    }
+
+    fn typename(&self) -> &'static str {
+        Self::TYPENAME
+    }
+
+    fn size_policy(&self) -> SizePolicy {
+        SizePolicy::MATCH_LAYOUT
+    }
+
+    fn full_size(&self) -> XY {
+        Self::MIN_SIZE
+    }
+
+    fn layout(&mut self, screenspace: Screenspace) {
+        self.last_size = Some(screenspace.output_size());
+    }
+
+    fn on_input(&self, input_event: InputEvent) -> Option<Box<dyn AnyMsg>> {
+        let key = match input_event {
+            InputEvent::KeyInput(key) if key.no_modifiers() => key,
+            _ => return None,
+        };
+
+        match key.keycode {
+            Keycode::Esc if self.run.status().is_running() => TaskViewMsg::Cancel.someboxed(),
+            Keycode::ArrowUp => TaskViewMsg::ScrollUp(1).someboxed(),
+            Keycode::ArrowDown => TaskViewMsg::ScrollDown(1).someboxed(),
+            Keycode::PageUp => TaskViewMsg::ScrollUp(self.page_height()).someboxed(),
+            Keycode::PageDown => TaskViewMsg::ScrollDown(self.page_height()).someboxed(),
+            Keycode::Home => TaskViewMsg::ScrollToStart.someboxed(),
+            Keycode::End => TaskViewMsg::ScrollToEnd.someboxed(),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, msg: Box<dyn AnyMsg>) -> Option<Box<dyn AnyMsg>> {
+        let our_msg = match msg.as_msg::<TaskViewMsg>() {
+            Some(msg) => msg,
+            None => {
+                warn!("expected TaskViewMsg, got {:?}", msg);
+                return None;
+            }
+        };
+
+        match our_msg {
+            TaskViewMsg::Cancel => {
+                if !self.run.cancel() {
+                    debug!("nothing to cancel, task [{}] already ended", self.task().name);
+                }
+            }
+            TaskViewMsg::Rerun => self.rerun(),
+            TaskViewMsg::ScrollUp(lines) => {
+                let first = self.first_visible_line().saturating_sub(*lines);
+                self.scroll = Some(first);
+            }
+            TaskViewMsg::ScrollDown(lines) => {
+                let first = self.first_visible_line() + *lines;
+                self.scroll_to(first);
+            }
+            TaskViewMsg::ScrollToStart => self.scroll_to(0),
+            TaskViewMsg::ScrollToEnd => self.scroll = None,
+        }
+
+        None
+    }
+
+    fn render(&self, theme: &Theme, focused: bool, output: &mut dyn Output) {
+        let size = unpack_unit_e!(self.last_size, "render before layout");
+
+        #[cfg(any(test, feature = "fuzztest"))]
+        {
+            output.emit_metadata(crate::io::output::Metadata {
+                id: self.wid,
+                typename: self.typename().to_string(),
+                rect: crate::primitives::rect::Rect::from_zero(size),
+                focused,
+            });
+        }
+
+        fill_output(theme.default_text(focused).background, output);
+
+        let header = self.get_description();
+        print_clipped(output, 0, size.x, theme.header(focused), &header);
+
+        // before taking the lock, these take it too
+        let first = self.first_visible_line();
+        let page_height = self.page_height();
+
+        let text_style = theme.default_text(focused);
+        let lock = unpack_unit_e!(self.run.output().read().ok(), "failed acquiring output lock");
+        for (idx, line) in lock.lines.iter().skip(first).take(page_height).enumerate() {
+            print_clipped(output, idx as u16 + 1, size.x, text_style, line);
+        }
+    }
+
+    fn get_status_description(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(self.get_description()))
+    }
+
+    fn get_widget_actions(&self) -> Option<ContextBarItem> {
+        let mut options: Vec<ContextBarItem> = Vec::new();
+
+        if self.run.status().is_running() {
+            options.push(ContextBarItem::new_leaf_node(
+                Cow::Borrowed("cancel task"),
+                || TaskViewMsg::Cancel.boxed(),
+                None,
+            ));
+        } else {
+            options.push(ContextBarItem::new_leaf_node(
+                Cow::Borrowed("rerun task"),
+                || TaskViewMsg::Rerun.boxed(),
+                None,
+            ));
+        }
+
+        Some(ContextBarItem::new_internal_node(Cow::Borrowed("task"), options))
+    }
+}
+
+// tasks print whatever they like, tabs and control characters included, so these are dropped
+fn print_clipped(output: &mut dyn Output, y: u16, width: u16, style: TextStyle, line: &str) {
+    let mut x: usize = 0;
+    for g in line.graphemes(true) {
+        if g.chars().any(|c| c.is_control()) {
+            continue;
+        }
+
+        let g_width = g.width();
+        if x + g_width > width as usize {
+            break;
+        }
+
+        output.print_at(XY::new(x as u16, y), style, g);
+        x += g_width;
+    }
+}
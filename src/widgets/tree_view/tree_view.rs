@@ -70,6 +70,9 @@ pub struct TreeViewWidget<Key: Hash + Eq + Debug + Clone, Item: TreeNode<Key>> {
     // if set to true, all nodes which lead to non-empty subtrees will appear in view, even if not expanded.
     filter_overrides_expanded: bool,
 
+    // if set to true, enter hits internal nodes too, and expanding is done with left/right arrows.
+    enter_hits_internal_nodes: bool,
+
     promise: Option<Box<dyn StreamingPromise<(u16, Item)>>>,
 }
 
@@ -107,6 +110,7 @@ impl<Key: Hash + Eq + Debug + Clone + 'static, Item: TreeNode<Key> + 'static> Tr
             filter_policy: FilterPolicy::MatchNodeOrAncestors,
             size_policy: SizePolicy::MATCH_LAYOUT,
             filter_overrides_expanded: false,
+            enter_hits_internal_nodes: false,
 
             promise: None,
         };
@@ -140,6 +144,15 @@ impl<Key: Hash + Eq + Debug + Clone + 'static, Item: TreeNode<Key> + 'static> Tr
         }
     }
 
+    pub fn set_filter_overrides_expanded(&mut self, filter_overrides_expanded: bool) {
+        self.filter_overrides_expanded = filter_overrides_expanded;
+        self.reset_promise();
+    }
+
+    pub fn set_enter_hits_internal_nodes(&mut self, enter_hits_internal_nodes: bool) {
+        self.enter_hits_internal_nodes = enter_hits_internal_nodes;
+    }
+
     pub fn with_size_policy(self, size_policy: SizePolicy) -> Self {
         Self { size_policy, ..self }
     }
@@ -188,6 +201,23 @@ impl<Key: Hash + Eq + Debug + Clone + 'static, Item: TreeNode<Key> + 'static> Tr
         }
     }
 
+    /*
+    Items matching the filter might arrive after it was set. If so far nothing matched, I move
+    highlight to the first item that does.
+     */
+    fn after_items_streamed(&mut self) {
+        let filter = crate::unpack_unit!(self.filter_op.as_ref());
+
+        if self.items().nth(self.highlighted).map(|item| filter.call(&item.1)) == Some(true) {
+            return;
+        }
+
+        let first_matching = self.items().position(|item| filter.call(&item.1));
+        if let Some(idx) = first_matching {
+            self.highlighted = idx;
+        }
+    }
+
     fn reset_promise(&mut self) {
         let expanded_op: Option<HashSet<Key>> = if self.filter_op.is_some() && self.filter_overrides_expanded {
             None
@@ -368,6 +398,23 @@ impl<Key: Hash + Eq + Debug + Clone + 'static, Item: TreeNode<Key> + 'static> Tr
         &self.root_node
     }
 
+    /*
+    Replaces the entire tree, keeping filter, expanded nodes and (if it's still there) highlighted
+    item.
+     */
+    pub fn set_root_node(&mut self, root_node: Item) {
+        let highlighted_key = self.items().nth(self.highlighted).map(|item| item.1.id().clone());
+
+        self.root_node = root_node;
+        self.promise = None;
+        self.reset_promise();
+
+        match highlighted_key.and_then(|key| self.items().position(|item| *item.1.id() == key)) {
+            Some(idx) => self.highlighted = idx,
+            None => self.after_filter_set(),
+        }
+    }
+
     // Does NOT check for presence of Key in the tree
     pub fn set_expanded(&mut self, key: Key, expanded: bool) {
         if expanded {
@@ -495,7 +542,9 @@ impl<K: Hash + Eq + Debug + Clone + 'static, I: TreeNode<K> + 'static> Widget fo
 
     fn prelayout(&mut self) {
         if let Some(promise) = self.promise.as_mut() {
-            promise.update();
+            if promise.update().has_changed {
+                self.after_items_streamed();
+            }
         }
     }
 
@@ -510,6 +559,8 @@ impl<K: Hash + Eq + Debug + Clone + 'static, I: TreeNode<K> + 'static> Widget fo
             InputEvent::KeyInput(key) => match key.keycode {
                 Keycode::ArrowUp => Some(Box::new(TreeViewMsg::Arrow(Arrow::Up))),
                 Keycode::ArrowDown => Some(Box::new(TreeViewMsg::Arrow(Arrow::Down))),
+                Keycode::ArrowLeft if self.enter_hits_internal_nodes => Some(Box::new(TreeViewMsg::Arrow(Arrow::Left))),
+                Keycode::ArrowRight if self.enter_hits_internal_nodes => Some(Box::new(TreeViewMsg::Arrow(Arrow::Right))),
                 Keycode::Enter => Some(Box::new(TreeViewMsg::HitEnter)),
                 _ => {
                     if let Some(action_trigger) = self.on_keyboard_shortcut_hit.as_ref() {
@@ -559,7 +610,17 @@ impl<K: Hash + Eq + Debug + Clone + 'static, I: TreeNode<K> + 'static> Widget fo
                         self.event_miss()
                     }
                 }
-                _ => None,
+                Arrow::Left | Arrow::Right => {
+                    let node = self.get_highlighted().1;
+                    let expand = matches!(arrow, Arrow::Right);
+
+                    if !node.is_leaf() && self.is_expanded(node.id()) != expand {
+                        self.flip_expanded(node.id());
+                        self.event_flip_expand()
+                    } else {
+                        self.event_miss()
+                    }
+                }
             },
             TreeViewMsg::HitEnter => {
                 let node = {
@@ -573,7 +634,7 @@ impl<K: Hash + Eq + Debug + Clone + 'static, I: TreeNode<K> + 'static> Widget fo
                     highlighted_node
                 };
 
-                if node.is_leaf() {
+                if node.is_leaf() || self.enter_hits_internal_nodes {
                     self.event_hit()
                 } else {
                     self.flip_expanded(node.id());
@@ -0,0 +1,12 @@
+use std::path::PathBuf;
+
+enum Direction {
+    Left,
+    Right,
+}
+
+fn main() {
+    let direction = Direction::Left;
+
+    match direction {}
+}
@@ -0,0 +1,5 @@
+mod some_other_file;
+
+fn main() {
+    some_function("a");
+}
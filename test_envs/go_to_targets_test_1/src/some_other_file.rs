@@ -0,0 +1,7 @@
+pub fn some_function(x: &str) {
+    // nothing
+}
+
+pub fn other_function(x: &str) {
+    // nothing
+}
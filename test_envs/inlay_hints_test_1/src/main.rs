@@ -0,0 +1,8 @@
+fn add(first: i32, second: i32) -> i32 {
+    first + second
+}
+
+fn main() {
+    let sum = add(1, 2);
+    println!("{}", sum);
+}
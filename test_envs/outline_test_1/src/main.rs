@@ -0,0 +1,19 @@
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+
+    fn manhattan_length(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+fn main() {
+    let point = Point::new(3, -4);
+    println!("{}", point.manhattan_length());
+}
@@ -0,0 +1,3 @@
+pub fn some_function(x: &str) {
+    // nothing
+}
\ No newline at end of file
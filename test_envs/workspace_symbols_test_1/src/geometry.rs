@@ -0,0 +1,14 @@
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+
+    pub fn manhattan_length(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+}
@@ -0,0 +1,8 @@
+mod geometry;
+
+use geometry::Point;
+
+fn main() {
+    let point = Point::new(3, -4);
+    println!("{}", point.manhattan_length());
+}